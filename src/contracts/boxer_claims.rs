@@ -1 +1,79 @@
+#[cfg(test)]
+mod tests;
 
+pub mod boxer_claims_error;
+pub mod principal_schema_violation;
+pub mod validator_schema_resolver;
+
+use crate::contracts::boxer_claims::boxer_claims_error::BoxerClaimsError;
+use crate::contracts::boxer_claims::principal_schema_violation::PrincipalSchemaViolation;
+use crate::contracts::boxer_claims::validator_schema_resolver::ValidatorSchemaResolver;
+use crate::contracts::dynamic_claims_collection::DynamicClaims;
+use cedar_policy::{Entity, Schema, SchemaFragment};
+use serde_json::Value;
+
+/// The claim names of the principal and its schema in a version of the internal token.
+pub(crate) struct PrincipalClaimKeys {
+    pub principal: &'static str,
+    pub schema: &'static str,
+    pub schema_id: &'static str,
+    pub validator_schema_id: &'static str,
+}
+
+/// The principal and its schema, read from the claims of any version of the internal token.
+pub(crate) struct PrincipalClaims {
+    pub schema: SchemaFragment,
+    pub schema_id: String,
+    pub validator_schema_id: String,
+    pub principal: Entity,
+}
+
+/// Reads the principal and its schema from the claims. The principal is validated against the
+/// schema embedded in the token, or against the validator schema resolved by the resolver.
+pub(crate) fn read_principal_claims<T: DynamicClaims>(
+    claims: &T,
+    keys: &PrincipalClaimKeys,
+    resolver: Option<&dyn ValidatorSchemaResolver>,
+) -> Result<PrincipalClaims, BoxerClaimsError> {
+    let schema = claims
+        .get_value(keys.schema)
+        .ok_or(BoxerClaimsError::MissingClaim("schema"))?;
+    let principal = claims
+        .get_value(keys.principal)
+        .ok_or(BoxerClaimsError::MissingClaim("principal"))?;
+    let schema_id = claims
+        .get_claim(keys.schema_id)
+        .ok_or(BoxerClaimsError::MissingClaim("schema_id"))?;
+    let validator_schema_id = claims
+        .get_claim(keys.validator_schema_id)
+        .ok_or(BoxerClaimsError::MissingClaim("validator_schema_id"))?;
+
+    let schema = SchemaFragment::from_json_value(schema).map_err(|e| BoxerClaimsError::InvalidSchema(e.to_string()))?;
+    let principal = match resolver {
+        None => parse_principal(principal, &schema)?,
+        Some(resolver) => {
+            let validator_schema = resolver
+                .resolve(&validator_schema_id)
+                .ok_or(BoxerClaimsError::UnknownValidatorSchema(validator_schema_id.clone()))?;
+            parse_principal(principal, &validator_schema)?
+        }
+    };
+
+    Ok(PrincipalClaims {
+        schema,
+        schema_id,
+        validator_schema_id,
+        principal,
+    })
+}
+
+/// Parses the principal entity from the token claims and validates it against the schema
+/// built from the provided fragment.
+pub fn parse_principal(principal: Value, schema: &SchemaFragment) -> Result<Entity, BoxerClaimsError> {
+    let schema =
+        Schema::from_schema_fragments([schema.clone()]).map_err(|e| BoxerClaimsError::InvalidSchema(e.to_string()))?;
+    Entity::from_json_value(principal, Some(&schema)).map_err(|e| match PrincipalSchemaViolation::try_from(&e) {
+        Ok(violation) => BoxerClaimsError::PrincipalSchemaViolation(violation),
+        Err(_) => BoxerClaimsError::InvalidPrincipal(e.to_string()),
+    })
+}
@@ -0,0 +1,44 @@
+use crate::contracts::boxer_claims::principal_schema_violation::PrincipalSchemaViolation;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// The error type returned when the Boxer claims cannot be read from an internal token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoxerClaimsError {
+    /// The claim with the given key is missing from the token.
+    MissingClaim(&'static str),
+
+    /// The embedded schema fragment cannot be parsed or does not form a valid schema.
+    InvalidSchema(String),
+
+    /// The principal is not a valid Cedar entity.
+    InvalidPrincipal(String),
+
+    /// The principal is a valid Cedar entity, but does not conform to the schema.
+    PrincipalSchemaViolation(PrincipalSchemaViolation),
+
+    /// The schema referenced by the validator schema id cannot be resolved.
+    UnknownValidatorSchema(String),
+
+    /// The audit event embedded in the token cannot be parsed.
+    InvalidAuditEvent(String),
+}
+
+impl Display for BoxerClaimsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BoxerClaimsError::MissingClaim(claim) => write!(f, "Missing {}", claim),
+            BoxerClaimsError::InvalidSchema(details) => write!(f, "Invalid schema: {}", details),
+            BoxerClaimsError::InvalidPrincipal(details) => write!(f, "Invalid principal: {}", details),
+            BoxerClaimsError::PrincipalSchemaViolation(violation) => {
+                write!(f, "Invalid principal: {}", violation)
+            }
+            BoxerClaimsError::UnknownValidatorSchema(schema_id) => {
+                write!(f, "Unknown validator schema: {}", schema_id)
+            }
+            BoxerClaimsError::InvalidAuditEvent(details) => write!(f, "Invalid audit event: {}", details),
+        }
+    }
+}
+
+impl Error for BoxerClaimsError {}
@@ -0,0 +1,78 @@
+use cedar_policy::conformance_errors::EntitySchemaConformanceError;
+use cedar_policy::entities_errors::EntitiesError;
+use cedar_policy::entities_json_errors::JsonDeserializationError;
+use std::fmt::{Display, Formatter};
+
+/// Describes how the principal entity violates the schema. The details name the offending
+/// attribute or type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrincipalSchemaViolation {
+    /// The principal type is not declared in the schema.
+    UnexpectedEntityType { entity_type: String },
+
+    /// The principal has an attribute that is not declared in the schema.
+    UnexpectedAttribute(String),
+
+    /// The principal lacks an attribute that is required by the schema.
+    MissingRequiredAttribute(String),
+
+    /// An attribute of the principal has a type different from the schema.
+    AttributeTypeMismatch(String),
+
+    /// A parent of the principal has a type that is not allowed by the schema.
+    InvalidParentType(String),
+
+    /// Any other conformance error reported by Cedar.
+    Other(String),
+}
+
+impl Display for PrincipalSchemaViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrincipalSchemaViolation::UnexpectedEntityType { entity_type } => {
+                write!(f, "entity type `{}` is not declared in the schema", entity_type)
+            }
+            PrincipalSchemaViolation::UnexpectedAttribute(details)
+            | PrincipalSchemaViolation::MissingRequiredAttribute(details)
+            | PrincipalSchemaViolation::AttributeTypeMismatch(details)
+            | PrincipalSchemaViolation::InvalidParentType(details)
+            | PrincipalSchemaViolation::Other(details) => write!(f, "{}", details),
+        }
+    }
+}
+
+impl From<&EntitySchemaConformanceError> for PrincipalSchemaViolation {
+    fn from(error: &EntitySchemaConformanceError) -> Self {
+        let details = error.to_string();
+        match error {
+            EntitySchemaConformanceError::UnexpectedEntityType(e) => PrincipalSchemaViolation::UnexpectedEntityType {
+                entity_type: e.uid.entity_type().to_string(),
+            },
+            EntitySchemaConformanceError::UnexpectedEntityAttr(_) => {
+                PrincipalSchemaViolation::UnexpectedAttribute(details)
+            }
+            EntitySchemaConformanceError::MissingRequiredEntityAttr(_) => {
+                PrincipalSchemaViolation::MissingRequiredAttribute(details)
+            }
+            EntitySchemaConformanceError::TypeMismatch(_) => PrincipalSchemaViolation::AttributeTypeMismatch(details),
+            EntitySchemaConformanceError::InvalidAncestorType(_) => {
+                PrincipalSchemaViolation::InvalidParentType(details)
+            }
+            _ => PrincipalSchemaViolation::Other(details),
+        }
+    }
+}
+
+/// Extracts the schema violation from the error returned by `Entity::from_json_value`.
+/// Fails for errors that are not caused by a schema conformance check.
+impl TryFrom<&EntitiesError> for PrincipalSchemaViolation {
+    type Error = ();
+
+    fn try_from(error: &EntitiesError) -> Result<Self, Self::Error> {
+        match error {
+            EntitiesError::InvalidEntity(e) => Ok(e.into()),
+            EntitiesError::Deserialization(JsonDeserializationError::EntitySchemaConformance(e)) => Ok(e.into()),
+            _ => Err(()),
+        }
+    }
+}
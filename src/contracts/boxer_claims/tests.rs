@@ -0,0 +1,117 @@
+use crate::contracts::boxer_claims::boxer_claims_error::BoxerClaimsError;
+use crate::contracts::boxer_claims::parse_principal;
+use crate::contracts::boxer_claims::principal_schema_violation::PrincipalSchemaViolation;
+use assert_matches::assert_matches;
+use cedar_policy::SchemaFragment;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use serde_json::{Value, json};
+
+#[test]
+fn test_valid_principal() {
+    // Arrange
+    let principal = make_principal(
+        json!({"name": "Alice"}),
+        json!([{"type": "PhotoApp::Group", "id": "admins"}]),
+    );
+
+    // Act
+    let result = parse_principal(principal, &make_schema());
+
+    // Assert
+    assert_matches!(result, Ok(entity) => {
+        assert_eq!(entity.uid().to_string(), r#"PhotoApp::User::"alice""#);
+    });
+}
+
+#[test]
+fn test_unexpected_entity_type() {
+    // Arrange
+    let principal = json!({"uid": {"type": "PhotoApp::Robot", "id": "r2d2"}, "attrs": {}, "parents": []});
+
+    // Act
+    let result = parse_principal(principal, &make_schema());
+
+    // Assert
+    assert_matches!(
+        result,
+        Err(BoxerClaimsError::PrincipalSchemaViolation(PrincipalSchemaViolation::UnexpectedEntityType { entity_type })) => {
+            assert_eq!(entity_type, "PhotoApp::Robot");
+        }
+    );
+}
+
+#[rstest]
+#[case::unexpected_attribute(
+    make_principal(json!({"name": "Alice", "age": 42}), json!([])),
+    PrincipalSchemaViolation::UnexpectedAttribute(String::new()),
+    "age"
+)]
+#[case::missing_attribute(
+    make_principal(json!({}), json!([])),
+    PrincipalSchemaViolation::MissingRequiredAttribute(String::new()),
+    "name"
+)]
+#[case::type_mismatch(
+    make_principal(json!({"name": 42}), json!([])),
+    PrincipalSchemaViolation::AttributeTypeMismatch(String::new()),
+    "name"
+)]
+#[case::invalid_parent_type(
+    make_principal(json!({"name": "Alice"}), json!([{"type": "PhotoApp::Photo", "id": "cat.jpg"}])),
+    PrincipalSchemaViolation::InvalidParentType(String::new()),
+    "PhotoApp::Photo"
+)]
+fn test_schema_violations(
+    #[case] principal: Value,
+    #[case] expected: PrincipalSchemaViolation,
+    #[case] offending_name: &str,
+) {
+    // Act
+    let result = parse_principal(principal, &make_schema());
+
+    // Assert
+    assert_matches!(result, Err(BoxerClaimsError::PrincipalSchemaViolation(violation)) => {
+        assert_eq!(std::mem::discriminant(&violation), std::mem::discriminant(&expected));
+        assert!(violation.to_string().contains(offending_name), "{}", violation);
+    });
+}
+
+#[test]
+fn test_invalid_principal_format() {
+    // Act
+    let result = parse_principal(json!({"bad": "format"}), &make_schema());
+
+    // Assert
+    assert_matches!(result, Err(BoxerClaimsError::InvalidPrincipal(_)));
+}
+
+fn make_principal(attrs: Value, parents: Value) -> Value {
+    json!({
+        "uid": {"type": "PhotoApp::User", "id": "alice"},
+        "attrs": attrs,
+        "parents": parents
+    })
+}
+
+fn make_schema() -> SchemaFragment {
+    SchemaFragment::from_json_value(json!({
+        "PhotoApp": {
+            "entityTypes": {
+                "User": {
+                    "memberOfTypes": ["Group"],
+                    "shape": {
+                        "type": "Record",
+                        "attributes": {
+                            "name": {"type": "String", "required": true}
+                        }
+                    }
+                },
+                "Group": {},
+                "Photo": {}
+            },
+            "actions": {}
+        }
+    }))
+    .unwrap()
+}
@@ -0,0 +1,14 @@
+use cedar_policy::SchemaFragment;
+use std::collections::HashMap;
+
+/// Resolves the schema fragment referenced by the validator schema id of an internal token.
+pub trait ValidatorSchemaResolver {
+    /// Returns the schema fragment with the given id, if known.
+    fn resolve(&self, validator_schema_id: &str) -> Option<SchemaFragment>;
+}
+
+impl ValidatorSchemaResolver for HashMap<String, SchemaFragment> {
+    fn resolve(&self, validator_schema_id: &str) -> Option<SchemaFragment> {
+        self.get(validator_schema_id).cloned()
+    }
+}
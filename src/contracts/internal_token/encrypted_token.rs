@@ -1,5 +1,6 @@
 use crate::http::middleware::extract_external_token::token_with_id::TokenWithId;
-use actix_web::http::header::HeaderValue;
+use crate::http::middleware::token_source::TokenSource;
+use actix_web::http::header::{AUTHORIZATION, HeaderValue};
 
 /// Wraps the raw encrypted token value extracted from request headers.
 #[derive(Clone)]
@@ -16,10 +17,22 @@ impl TryFrom<HeaderValue> for EncryptedToken {
     }
 }
 
+impl From<String> for EncryptedToken {
+    fn from(value: String) -> Self {
+        EncryptedToken(value)
+    }
+}
+
+/// Exposes the raw encrypted token value
+impl AsRef<str> for EncryptedToken {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
 impl TokenWithId for EncryptedToken {
-    /// Returns a stable MD5-based identifier for the encrypted token.
-    fn id(&self) -> String {
-        let token_hash = md5::compute(&self.0);
-        format!("md5:{:x}", token_hash)
+    /// The encrypted token is read from the raw `Authorization` header value by default.
+    fn default_source() -> TokenSource {
+        TokenSource::header(AUTHORIZATION)
     }
 }
@@ -12,7 +12,7 @@ fn test_to_boxer_claims_success() {
     assert!(claims.schema.to_json_string().unwrap().contains("entityTypes"));
     assert_eq!(claims.schema_id, "schema-v1");
     assert_eq!(claims.validator_schema_id, "validator-schema-v1");
-    assert_eq!(claims.principal.uid().to_string(), "User::\"alice\"");
+    assert_eq!(claims.principal.uid().to_string(), "PhotoApp::User::\"alice\"");
 }
 
 #[test]
@@ -26,7 +26,7 @@ fn test_missing_schema() {
 fn test_missing_principal() {
     let mc = MockClaims::base().remove_value(PRINCIPAL_KEY);
     let err = mc.to_boxer_claims().unwrap_err();
-    assert!(err.to_string().contains("Missing schema"));
+    assert!(err.to_string().contains("Missing principal"));
 }
 
 #[test]
@@ -40,7 +40,7 @@ fn test_missing_schema_id() {
 fn test_missing_validator_schema_id() {
     let mc = MockClaims::base().remove_claim(VALIDATOR_SCHEMA_ID_KEY);
     let err = mc.to_boxer_claims().unwrap_err();
-    assert!(err.to_string().contains("Missing schema_id"));
+    assert!(err.to_string().contains("Missing validator_schema_id"));
 }
 
 #[test]
@@ -81,7 +81,7 @@ impl MockClaims {
         values.insert(
             PRINCIPAL_KEY,
             json!({
-                "uid": { "type": "User", "id": "alice" },
+                "uid": { "type": "PhotoApp::User", "id": "alice" },
                 "attrs": {},
                 "parents": []
             }),
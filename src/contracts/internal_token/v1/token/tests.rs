@@ -23,7 +23,7 @@ fn test_serialization_integrity() {
 
     assert_eq!(boxer_claims.schema_id, "schema-v1");
     assert_eq!(boxer_claims.validator_schema_id, "validator-schema-v1");
-    assert_eq!(boxer_claims.principal.uid().to_string(), "User::\"alice\"");
+    assert_eq!(boxer_claims.principal.uid().to_string(), "PhotoApp::User::\"alice\"");
     assert!(
         boxer_claims
             .schema
@@ -54,7 +54,7 @@ fn test_get_version() {
 }
 
 fn make_principal() -> Entity {
-    let uid: EntityUid = r#"User::"alice""#.parse().unwrap();
+    let uid: EntityUid = r#"PhotoApp::User::"alice""#.parse().unwrap();
     Entity::new(uid, Default::default(), Default::default()).expect("to be valid")
 }
 
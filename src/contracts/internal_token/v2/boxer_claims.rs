@@ -1,6 +1,9 @@
 #[cfg(test)]
 mod tests;
 
+use crate::contracts::boxer_claims::boxer_claims_error::BoxerClaimsError;
+use crate::contracts::boxer_claims::validator_schema_resolver::ValidatorSchemaResolver;
+use crate::contracts::boxer_claims::{PrincipalClaimKeys, read_principal_claims};
 use crate::contracts::dynamic_claims_collection::DynamicClaims;
 use crate::contracts::internal_token::v2::{
     AUDIT_EVENT, PRINCIPAL_KEY, SCHEMA_ID_KEY, SCHEMA_KEY, VALIDATOR_SCHEMA_ID_KEY,
@@ -8,6 +11,13 @@ use crate::contracts::internal_token::v2::{
 use crate::services::audit::chained::chained_audit_event::ChainedAuditEvent;
 use cedar_policy::{Entity, SchemaFragment};
 
+const CLAIM_KEYS: PrincipalClaimKeys = PrincipalClaimKeys {
+    principal: PRINCIPAL_KEY,
+    schema: SCHEMA_KEY,
+    schema_id: SCHEMA_ID_KEY,
+    validator_schema_id: VALIDATOR_SCHEMA_ID_KEY,
+};
+
 #[derive(Debug)]
 /// [`BoxerClaims`] represents the claims extracted from an internal token that are necessary for
 /// validation and authorization checks.
@@ -24,37 +34,54 @@ where
     T: DynamicClaims,
 {
     type Error;
+
+    /// Reads the Boxer claims and validates the principal against the schema embedded in the token.
     fn to_boxer_claims(&self) -> Result<BoxerClaims, Self::Error>;
+
+    /// Reads the Boxer claims and validates the principal against the schema referenced by the
+    /// validator schema id of the token.
+    fn to_boxer_claims_with_validator_schema(
+        &self,
+        resolver: &dyn ValidatorSchemaResolver,
+    ) -> Result<BoxerClaims, Self::Error>;
 }
 
 impl<T> ToBoxerClaims<T> for T
 where
     T: DynamicClaims,
 {
-    type Error = anyhow::Error;
+    type Error = BoxerClaimsError;
 
     fn to_boxer_claims(&self) -> Result<BoxerClaims, Self::Error> {
-        let schema = self.get_value(SCHEMA_KEY).ok_or(anyhow::anyhow!("Missing schema"))?;
-        let principal = self.get_value(PRINCIPAL_KEY).ok_or(anyhow::anyhow!("Missing schema"))?;
-        let schema_id = self
-            .get_claim(SCHEMA_ID_KEY)
-            .ok_or(anyhow::anyhow!("Missing schema_id"))?;
-        let validator_schema_id = self
-            .get_claim(VALIDATOR_SCHEMA_ID_KEY)
-            .ok_or(anyhow::anyhow!("Missing schema_id"))?;
-
-        let audit_event = self
-            .get_value(AUDIT_EVENT)
-            .ok_or(anyhow::anyhow!("Missing audit event"))?;
-
-        Ok(BoxerClaims {
-            schema: SchemaFragment::from_json_value(schema.clone())
-                .map_err(|e| anyhow::anyhow!("Invalid schema: {}", e))?,
-            principal: Entity::from_json_value(principal.clone(), None)
-                .map_err(|e| anyhow::anyhow!("Invalid principal: {}", e))?,
-            audit_event: serde_json::from_value(audit_event)?,
-            schema_id,
-            validator_schema_id,
-        })
+        read_boxer_claims(self, None)
+    }
+
+    fn to_boxer_claims_with_validator_schema(
+        &self,
+        resolver: &dyn ValidatorSchemaResolver,
+    ) -> Result<BoxerClaims, Self::Error> {
+        read_boxer_claims(self, Some(resolver))
     }
 }
+
+/// Reads the audit event embedded in the claims of a v2 token without validating the principal.
+pub fn read_audit_event<T: DynamicClaims>(claims: &T) -> Result<ChainedAuditEvent, BoxerClaimsError> {
+    let audit_event = claims
+        .get_value(AUDIT_EVENT)
+        .ok_or(BoxerClaimsError::MissingClaim("audit event"))?;
+    serde_json::from_value(audit_event).map_err(|e| BoxerClaimsError::InvalidAuditEvent(e.to_string()))
+}
+
+fn read_boxer_claims<T: DynamicClaims>(
+    claims: &T,
+    resolver: Option<&dyn ValidatorSchemaResolver>,
+) -> Result<BoxerClaims, BoxerClaimsError> {
+    let principal_claims = read_principal_claims(claims, &CLAIM_KEYS, resolver)?;
+    Ok(BoxerClaims {
+        schema: principal_claims.schema,
+        principal: principal_claims.principal,
+        audit_event: read_audit_event(claims)?,
+        schema_id: principal_claims.schema_id,
+        validator_schema_id: principal_claims.validator_schema_id,
+    })
+}
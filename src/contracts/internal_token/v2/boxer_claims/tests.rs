@@ -1,6 +1,8 @@
 use super::*;
+use crate::contracts::boxer_claims::principal_schema_violation::PrincipalSchemaViolation;
 use crate::contracts::dynamic_claims_collection::DynamicClaims;
 use crate::contracts::internal_token::v2::{PRINCIPAL_KEY, SCHEMA_ID_KEY, SCHEMA_KEY, VALIDATOR_SCHEMA_ID_KEY};
+use assert_matches::assert_matches;
 use pretty_assertions::assert_eq;
 use serde_json::{Value, json};
 use std::collections::HashMap;
@@ -12,7 +14,7 @@ fn test_to_boxer_claims_success() {
     assert!(claims.schema.to_json_string().unwrap().contains("entityTypes"));
     assert_eq!(claims.schema_id, "schema-v1");
     assert_eq!(claims.validator_schema_id, "validator-schema-v1");
-    assert_eq!(claims.principal.uid().to_string(), "User::\"alice\"");
+    assert_eq!(claims.principal.uid().to_string(), "PhotoApp::User::\"alice\"");
 }
 
 #[test]
@@ -26,7 +28,7 @@ fn test_missing_schema() {
 fn test_missing_principal() {
     let mc = MockClaims::base().remove_value(PRINCIPAL_KEY);
     let err = mc.to_boxer_claims().unwrap_err();
-    assert!(err.to_string().contains("Missing schema"));
+    assert!(err.to_string().contains("Missing principal"));
 }
 
 #[test]
@@ -40,7 +42,7 @@ fn test_missing_schema_id() {
 fn test_missing_validator_schema_id() {
     let mc = MockClaims::base().remove_claim(VALIDATOR_SCHEMA_ID_KEY);
     let err = mc.to_boxer_claims().unwrap_err();
-    assert!(err.to_string().contains("Missing schema_id"));
+    assert!(err.to_string().contains("Missing validator_schema_id"));
 }
 
 #[test]
@@ -60,6 +62,75 @@ fn test_invalid_principal() {
     let err = mc.to_boxer_claims().unwrap_err();
     assert!(err.to_string().contains("Invalid principal"));
 }
+#[test]
+fn test_principal_not_in_schema() {
+    let mc = MockClaims::base().with_value(
+        PRINCIPAL_KEY,
+        json!({"uid": { "type": "User", "id": "alice" }, "attrs": {}, "parents": []}),
+    );
+    let err = mc.to_boxer_claims().unwrap_err();
+    assert_matches!(
+        err,
+        BoxerClaimsError::PrincipalSchemaViolation(PrincipalSchemaViolation::UnexpectedEntityType { entity_type }) => {
+            assert_eq!(entity_type, "User");
+        }
+    );
+}
+
+#[test]
+fn test_principal_attribute_not_in_schema() {
+    let mc = MockClaims::base().with_value(
+        PRINCIPAL_KEY,
+        json!({"uid": { "type": "PhotoApp::User", "id": "alice" }, "attrs": {"age": 42}, "parents": []}),
+    );
+    let err = mc.to_boxer_claims().unwrap_err();
+    assert_matches!(
+        err,
+        BoxerClaimsError::PrincipalSchemaViolation(PrincipalSchemaViolation::UnexpectedAttribute(details)) => {
+            assert!(details.contains("age"), "{}", details);
+        }
+    );
+}
+
+#[test]
+fn test_validator_schema() {
+    let validator_schema = SchemaFragment::from_json_value(json!({
+        "PhotoApp": {
+            "entityTypes": {
+                "User": {
+                    "shape": {"type": "Record", "attributes": {"age": {"type": "Long"}}}
+                }
+            },
+            "actions": { }
+        }
+    }))
+    .unwrap();
+    let resolver = HashMap::from([("validator-schema-v1".to_string(), validator_schema)]);
+    let mc = MockClaims::base().with_value(
+        PRINCIPAL_KEY,
+        json!({"uid": { "type": "PhotoApp::User", "id": "alice" }, "attrs": {"age": 42}, "parents": []}),
+    );
+
+    let claims = mc
+        .to_boxer_claims_with_validator_schema(&resolver)
+        .expect("should succeed");
+
+    assert_eq!(claims.principal.uid().to_string(), "PhotoApp::User::\"alice\"");
+}
+
+#[test]
+fn test_unknown_validator_schema() {
+    let resolver: HashMap<String, SchemaFragment> = HashMap::new();
+    let mc = MockClaims::base();
+
+    let err = mc.to_boxer_claims_with_validator_schema(&resolver).unwrap_err();
+
+    assert_eq!(
+        err,
+        BoxerClaimsError::UnknownValidatorSchema("validator-schema-v1".to_string())
+    );
+}
+
 struct MockClaims {
     values: HashMap<&'static str, Value>,
     claims: HashMap<&'static str, String>,
@@ -85,7 +156,7 @@ impl MockClaims {
         values.insert(
             PRINCIPAL_KEY,
             json!({
-                "uid": { "type": "User", "id": "alice" },
+                "uid": { "type": "PhotoApp::User", "id": "alice" },
                 "attrs": {},
                 "parents": []
             }),
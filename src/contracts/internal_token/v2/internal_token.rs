@@ -9,6 +9,7 @@ use crate::contracts::internal_token::v2::{
     USER_ID_KEY, VALIDATOR_SCHEMA_ID_KEY,
 };
 use crate::services::audit::chained::chained_audit_event::ChainedAuditEvent;
+use crate::services::identity::mapped_principal::MappedPrincipal;
 use cedar_policy::{Entity, SchemaFragment};
 use josekit::jwt::JwtPayload;
 use std::time::{Duration, SystemTime};
@@ -52,6 +53,27 @@ impl InternalToken {
             audit_event,
         }
     }
+
+    /// Creates the token for a principal mapped by the identity provider registry.
+    pub fn for_principal(
+        mapped_principal: MappedPrincipal,
+        schema: SchemaFragment,
+        schema_id: String,
+        validity_period: Duration,
+        validator_schema_id: String,
+        audit_event: ChainedAuditEvent,
+    ) -> Self {
+        InternalToken::new(
+            mapped_principal.principal,
+            schema,
+            mapped_principal.user_id,
+            mapped_principal.identity_provider,
+            schema_id,
+            validity_period,
+            validator_schema_id,
+            audit_event,
+        )
+    }
 }
 
 impl TryInto<JwtPayload> for InternalToken {
@@ -1,6 +1,8 @@
 use crate::contracts::dynamic_claims_collection::DynamicClaims;
 use crate::contracts::internal_token::v1::boxer_claims::ToBoxerClaims;
 use crate::contracts::internal_token::v1::token::InternalToken;
+use crate::contracts::internal_token::v2::boxer_claims::read_audit_event;
+use crate::services::audit::chained::chained_audit_event::ChainedAuditEvent;
 use cedar_policy::{Entity, EntityUid, SchemaFragment};
 use josekit::jwt::JwtPayload;
 use serde_json::json;
@@ -23,7 +25,7 @@ fn test_serialization_integrity() {
 
     assert_eq!(boxer_claims.schema_id, "schema-v1");
     assert_eq!(boxer_claims.validator_schema_id, "validator-schema-v1");
-    assert_eq!(boxer_claims.principal.uid().to_string(), "User::\"alice\"");
+    assert_eq!(boxer_claims.principal.uid().to_string(), "PhotoApp::User::\"alice\"");
     assert!(
         boxer_claims
             .schema
@@ -53,8 +55,34 @@ fn test_get_version() {
     assert_eq!(jwt.get_version().expect("has no version claim"), "v1");
 }
 
+#[test]
+fn test_audit_event_chain_identity_serialization() {
+    let audit_event = ChainedAuditEvent {
+        sequence: 3,
+        ..ChainedAuditEvent::begin()
+    };
+    let token = super::InternalToken::new(
+        make_principal(),
+        make_schema(),
+        "alice-ext".to_string(),
+        "github".to_string(),
+        "schema-v1".to_string(),
+        Duration::from_secs(600),
+        "validator-schema-v1".to_string(),
+        audit_event.clone(),
+    );
+
+    let jwt: JwtPayload = token.try_into().expect("to jwt");
+    let resumed = read_audit_event(&jwt).expect("audit event claim");
+
+    assert_eq!(resumed.chain_id, audit_event.chain_id);
+    assert_eq!(resumed.created_at, audit_event.created_at);
+    assert_eq!(resumed.finalized_at, None);
+    assert_eq!(resumed.sequence, 3);
+}
+
 fn make_principal() -> Entity {
-    let uid: EntityUid = r#"User::"alice""#.parse().unwrap();
+    let uid: EntityUid = r#"PhotoApp::User::"alice""#.parse().unwrap();
     Entity::new(uid, Default::default(), Default::default()).expect("to be valid")
 }
 
@@ -1,4 +1,5 @@
 pub mod conversions;
 pub mod extractors;
+pub mod jwks;
 pub mod middleware;
 pub mod readiness;
@@ -1 +1,2 @@
+pub mod audit_context;
 pub mod boxer_claims;
@@ -0,0 +1,104 @@
+#[cfg(test)]
+mod tests;
+
+use crate::http::middleware::audit::audit_pipeline_error::AuditPipelineError;
+use crate::services::audit::chained::audit_event::AuditEvent;
+use crate::services::audit::chained::audit_transition_error::AuditTransitionError;
+use crate::services::audit::events::authorization_audit_event::Reason;
+use actix_web::{FromRequest, HttpMessage, HttpRequest};
+use cedar_policy::Decision;
+use futures_util::future::{Ready, ready};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+/// [`AuditContext`] is a handle for enriching the intermediate audit event of the current request
+/// from the request handlers. The changes are collected in the handle and applied to the audit
+/// event by the audit recorder when the response is recorded.
+///
+/// The handle is shared by all extractions within a request and can be cloned into spawned tasks.
+/// Changes made after the response is recorded are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct AuditContext(Arc<Mutex<AuditContextChanges>>);
+
+#[derive(Debug, Default)]
+struct AuditContextChanges {
+    actor: Option<String>,
+    action: Option<String>,
+    resource: Option<String>,
+    decision: Option<(Decision, Option<Reason>)>,
+    attributes: BTreeMap<String, String>,
+}
+
+impl AuditContext {
+    /// Sets the actor of the request.
+    pub fn set_actor(&self, actor: impl Into<String>) {
+        self.changes().actor = Some(actor.into());
+    }
+
+    /// Sets the action performed by the request.
+    pub fn set_action(&self, action: impl Into<String>) {
+        self.changes().action = Some(action.into());
+    }
+
+    /// Sets the resource accessed by the request.
+    pub fn set_resource(&self, resource: impl Into<String>) {
+        self.changes().resource = Some(resource.into());
+    }
+
+    /// Sets the authorization decision and its reason.
+    pub fn set_decision(&self, decision: Decision, reason: Option<Reason>) {
+        self.changes().decision = Some((decision, reason));
+    }
+
+    /// Sets a custom attribute. An attribute with the same key is replaced.
+    pub fn set_attribute(&self, key: impl Into<String>, value: impl Into<String>) {
+        self.changes().attributes.insert(key.into(), value.into());
+    }
+
+    /// Applies the collected changes to the audit event. The event is left unchanged if no
+    /// changes were made, otherwise it must be an intermediate event.
+    pub(crate) fn apply(&self, event: &mut AuditEvent) -> Result<(), AuditTransitionError> {
+        let changes = self.changes();
+        if let Some(actor) = changes.actor.clone() {
+            event.record_actor(actor)?;
+        }
+        if let Some(action) = changes.action.clone() {
+            event.record_action(action)?;
+        }
+        if let Some(resource) = changes.resource.clone() {
+            event.record_resource(resource)?;
+        }
+        if let Some((decision, reason)) = changes.decision.clone() {
+            event.record_decision(decision, reason)?;
+        }
+        for (key, value) in changes.attributes.iter() {
+            event.record_attribute(key.clone(), value.clone())?;
+        }
+        Ok(())
+    }
+
+    /// Returns the audit context of the request. The context is created on the first extraction.
+    ///
+    /// Returns [`AuditPipelineError::MissingAuditEvent`] if the request does not contain an audit
+    /// event and [`AuditPipelineError::UnexpectedFinalEvent`] if the audit event is final.
+    pub fn of_request(request: &HttpRequest) -> Result<AuditContext, AuditPipelineError> {
+        let mut extensions = request.extensions_mut();
+        match extensions.get::<AuditEvent>() {
+            Some(AuditEvent::Intermediate(_)) => Ok(extensions.get_or_insert_with(AuditContext::default).clone()),
+            Some(AuditEvent::Final(_)) => Err(AuditPipelineError::UnexpectedFinalEvent),
+            None => Err(AuditPipelineError::MissingAuditEvent),
+        }
+    }
+
+    fn changes(&self) -> std::sync::MutexGuard<'_, AuditContextChanges> {
+        self.0.lock().expect("Audit context lock is poisoned")
+    }
+}
+
+impl FromRequest for AuditContext {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+    fn from_request(req: &HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        ready(AuditContext::of_request(req).map_err(actix_web::Error::from))
+    }
+}
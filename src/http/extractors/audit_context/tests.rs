@@ -0,0 +1,106 @@
+use crate::http::extractors::audit_context::AuditContext;
+use crate::http::middleware::audit::audit_pipeline_error::AuditPipelineError;
+use crate::services::audit::chained::audit_event::AuditEvent;
+use crate::services::audit::chained::audit_transition_error::AuditTransitionError;
+use crate::services::audit::chained::chained_audit_event::ChainedAuditEvent;
+use actix_web::test::TestRequest;
+use actix_web::{FromRequest, HttpMessage};
+use assert_matches::assert_matches;
+use cedar_policy::Decision;
+use pretty_assertions::assert_eq;
+
+#[actix_web::test]
+async fn test_extract_audit_context() {
+    // Arrange
+    let request = TestRequest::get().to_http_request();
+    request
+        .extensions_mut()
+        .insert(AuditEvent::Intermediate(ChainedAuditEvent::begin()));
+
+    // Act
+    let first = AuditContext::extract(&request)
+        .await
+        .expect("context should be extracted");
+    let second = AuditContext::extract(&request)
+        .await
+        .expect("context should be extracted");
+    first.set_action("read");
+    second.set_actor("alice");
+
+    // Assert
+    let mut event = AuditEvent::Intermediate(ChainedAuditEvent::begin());
+    first.apply(&mut event).expect("changes should be applied");
+    assert_matches!(event, AuditEvent::Intermediate(ChainedAuditEvent {
+        action: Some(action),
+        actor: Some(actor),
+        ..
+    }) => {
+        assert_eq!(action, "read");
+        assert_eq!(actor, "alice");
+    });
+}
+
+#[actix_web::test]
+async fn test_extract_audit_context_without_audit_event() {
+    // Arrange
+    let request = TestRequest::get().to_http_request();
+
+    // Act
+    let result = AuditContext::of_request(&request);
+
+    // Assert
+    assert_matches!(result, Err(AuditPipelineError::MissingAuditEvent));
+}
+
+#[actix_web::test]
+async fn test_extract_audit_context_with_final_event() {
+    // Arrange
+    let request = TestRequest::get().to_http_request();
+    request
+        .extensions_mut()
+        .insert(AuditEvent::Final(ChainedAuditEvent::begin()));
+
+    // Act
+    let result = AuditContext::of_request(&request);
+
+    // Assert
+    assert_matches!(result, Err(AuditPipelineError::UnexpectedFinalEvent));
+}
+
+#[test]
+fn test_apply_decision_and_attributes() {
+    // Arrange
+    let context = AuditContext::default();
+    context.set_decision(Decision::Allow, None);
+    context.set_attribute("tenant", "acme");
+    let mut event = AuditEvent::Intermediate(ChainedAuditEvent::begin());
+
+    // Act
+    context.apply(&mut event).expect("changes should be applied");
+
+    // Assert
+    assert_matches!(event, AuditEvent::Intermediate(ChainedAuditEvent {
+        decision: Some(Decision::Allow),
+        attributes,
+        ..
+    }) => {
+        assert_eq!(attributes.get("tenant"), Some(&"acme".to_string()));
+    });
+}
+
+#[test]
+fn test_apply_to_final_event() {
+    // Arrange
+    let unchanged = AuditContext::default();
+    let changed = AuditContext::default();
+    changed.set_resource("documents/1");
+    let mut event = AuditEvent::Final(ChainedAuditEvent::begin());
+
+    // Act
+    let unchanged_result = unchanged.apply(&mut event);
+    let changed_result = changed.apply(&mut event);
+
+    // Assert
+    assert_eq!(unchanged_result, Ok(()));
+    assert_eq!(changed_result, Err(AuditTransitionError::AlreadyFinal));
+}
@@ -10,7 +10,7 @@ impl FromRequest for BoxerClaims {
     fn from_request(req: &HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
         let claims = match req.extensions().get::<DynamicClaimsCollection>() {
             None => Err(anyhow!("Missing claims, probably the jwt filter is not in place")),
-            Some(c) => c.to_boxer_claims(),
+            Some(c) => c.to_boxer_claims().map_err(anyhow::Error::from),
         };
         let res = claims.map_err(|e| actix_web::error::ErrorUnauthorized(e.to_string()));
         ready(res)
@@ -0,0 +1,19 @@
+#[cfg(test)]
+mod tests;
+
+use crate::services::key_management::key_manager::KeyManager;
+use actix_web::{HttpResponse, Responder, web};
+use serde_json::{Map, Value};
+use std::sync::Arc;
+
+/// The well-known path the JWKS document is served from.
+pub const JWKS_PATH: &str = "/.well-known/jwks.json";
+
+/// Serves the public keys of the current key set as a JWKS document.
+///
+/// Only active and next keys are published, so services that verify tokens can pick up the
+/// next key before it is promoted.
+pub async fn jwks_handler(key_manager: web::Data<Arc<KeyManager>>) -> impl Responder {
+    let jwk_set: Map<String, Value> = key_manager.current().to_jwk_set().into();
+    HttpResponse::Ok().json(jwk_set)
+}
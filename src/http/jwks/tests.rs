@@ -0,0 +1,35 @@
+use crate::http::jwks::{JWKS_PATH, jwks_handler};
+use crate::services::key_management::signing_key::KeyStatus;
+use crate::testing::signing_keys::{make_key_manager, make_signing_key};
+use actix_web::{App, test, web};
+use serde_json::Value;
+
+#[actix_web::test]
+async fn test_jwks_publishes_active_and_next_keys() {
+    // Arrange
+    let manager = make_key_manager(vec![
+        make_signing_key("active", KeyStatus::Active),
+        make_signing_key("next", KeyStatus::Next),
+        make_signing_key("retired", KeyStatus::Retired),
+    ])
+    .await;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(manager))
+            .route(JWKS_PATH, web::get().to(jwks_handler)),
+    )
+    .await;
+
+    // Act
+    let request = test::TestRequest::get().uri(JWKS_PATH).to_request();
+    let response: Value = test::call_and_read_body_json(&app, request).await;
+
+    // Assert
+    let kids: Vec<&str> = response["keys"]
+        .as_array()
+        .expect("keys should be an array")
+        .iter()
+        .filter_map(|k| k["kid"].as_str())
+        .collect();
+    assert_eq!(kids, vec!["active", "next"]);
+}
@@ -1,9 +1,12 @@
 use crate::http::middleware::extract_external_token::external_token_error::ExternalTokenError;
 use crate::http::middleware::extract_external_token::token_with_id::TokenWithId;
+use crate::http::middleware::token_source::TokenSourceFor;
+use crate::http::middleware::token_source::token_source_error::TokenSourceError;
+use crate::services::audit::token_id_strategy::TokenIdStrategy;
 use actix_web::body::MessageBody;
 use actix_web::dev::{ServiceRequest, ServiceResponse};
-use actix_web::http::header::HeaderValue;
 use actix_web::middleware::Next;
+use actix_web::web;
 use request_with_token_id::RequestWithTokenId;
 
 pub mod audit;
@@ -11,26 +14,38 @@ pub mod extract_external_token;
 pub mod extract_internal_token;
 pub mod logging;
 pub mod request_with_token_id;
+pub mod resume_audit_chain;
+pub mod token_source;
 pub mod tracer;
+pub mod validate_external_token;
 
-async fn extract_token_from_header<TokenType, Request, Error>(
+async fn extract_token<TokenType, Request, Error>(
     request: ServiceRequest,
     next: Next<impl MessageBody>,
 ) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error>
 where
-    TokenType: TokenWithId + TryFrom<HeaderValue, Error = anyhow::Error> + 'static,
+    TokenType: TokenWithId + 'static,
     Error: ExternalTokenError + 'static,
     Request: RequestWithTokenId<Token = TokenType>,
 {
-    let header_value = request.headers().get("Authorization").cloned();
+    let source = TokenSourceFor::<TokenType>::resolve(&request);
 
-    match header_value {
-        None => Err(Error::external_token_not_present(&request).into()),
+    let strategy = request
+        .app_data::<web::Data<TokenIdStrategy>>()
+        .map(|strategy| strategy.get_ref().clone())
+        .unwrap_or_default();
 
-        Some(header_value) => {
-            let token =
-                Request::Token::try_from(header_value).map_err(|e| Error::token_extraction_failed(&request, e))?;
-            next.call(Request::from(request).add_token(token)).await
+    match source.extract(&request) {
+        Err(TokenSourceError::NotPresent) => Err(Error::external_token_not_present(&request, &source).into()),
+        Err(TokenSourceError::UnsupportedScheme) => {
+            Err(Error::unsupported_authorization_scheme(&request, &source).into())
+        }
+        Err(TokenSourceError::Malformed) => Err(Error::malformed_authorization_header(&request, &source).into()),
+        Err(error) => Err(Error::token_extraction_failed(&request, &source, error.into()).into()),
+        Ok(token) => {
+            TokenSourceFor::<TokenType>::record(&request, source);
+            let request = Request::from(request).add_token(TokenType::from(token), &strategy)?;
+            next.call(request).await
         }
     }
 }
@@ -1,9 +1,11 @@
+pub mod audit_pipeline_error;
 pub mod audit_recorder;
 pub mod audited_error;
 pub mod audited_response;
 pub mod begin_audit_chain;
 pub mod external_request;
 pub mod internal_request;
+pub mod propagated_request;
 
 pub mod audit_scope;
 #[cfg(test)]
@@ -0,0 +1,112 @@
+use crate::services::audit::chained::audit_transition_error::AuditTransitionError;
+use actix_web::ResponseError;
+use actix_web::http::StatusCode;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// [`AuditPipelineError`] reports a violation of the audit middleware invariants, e.g. a request
+/// that reaches the token extraction without an audit event, a request that starts a second
+/// audit chain or an error that was not wrapped into an [`AuditedError`].
+///
+/// These errors indicate a misconfigured middleware chain rather than a client error, so the
+/// response status is `500 Internal Server Error`. The audit recorder records a diagnostic audit
+/// event for every error and counts it with the pipeline failure metric of the
+/// [`AuditRecorderOptions`], if any.
+///
+/// [`AuditedError`]: crate::http::middleware::audit::audited_error::AuditedError
+/// [`AuditRecorderOptions`]: crate::http::middleware::audit::audit_recorder::audit_recorder_options::AuditRecorderOptions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditPipelineError {
+    /// The request does not contain an audit event.
+    MissingAuditEvent,
+
+    /// The request already contains an audit event when the audit chain is started.
+    AuditChainAlreadyStarted,
+
+    /// The request contains a final audit event where an intermediate event is expected.
+    UnexpectedFinalEvent,
+
+    /// The intermediate audit event of the request is not in the state expected at this stage.
+    UnexpectedAuditEvent(String),
+
+    /// The audit event of the request already contains the token entry of the given kind.
+    DuplicateToken(&'static str),
+
+    /// The request does not contain the extension inserted by an earlier middleware.
+    MissingRequestExtension(&'static str),
+
+    /// The application data required by the middleware is not registered.
+    MissingAppData(&'static str),
+
+    /// An error without an audit event reached the audit recorder.
+    UnauditedError(String),
+}
+
+impl AuditPipelineError {
+    /// Returns the reason code recorded in the diagnostic audit event.
+    pub fn reason_code(&self) -> &'static str {
+        match self {
+            AuditPipelineError::MissingAuditEvent => "missing-audit-event",
+            AuditPipelineError::AuditChainAlreadyStarted => "audit-chain-already-started",
+            AuditPipelineError::UnexpectedFinalEvent => "unexpected-final-event",
+            AuditPipelineError::UnexpectedAuditEvent(_) => "unexpected-audit-event",
+            AuditPipelineError::DuplicateToken(_) => "duplicate-token",
+            AuditPipelineError::MissingRequestExtension(_) => "missing-request-extension",
+            AuditPipelineError::MissingAppData(_) => "missing-app-data",
+            AuditPipelineError::UnauditedError(_) => "unaudited-error",
+        }
+    }
+}
+
+impl Display for AuditPipelineError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuditPipelineError::MissingAuditEvent => write!(f, "Audit event not found in request extensions"),
+            AuditPipelineError::AuditChainAlreadyStarted => write!(
+                f,
+                "Failed to create audited request: audit chain already exists in request extensions"
+            ),
+            AuditPipelineError::UnexpectedFinalEvent => {
+                write!(f, "Final audit event in a request cannot be modified")
+            }
+            AuditPipelineError::UnexpectedAuditEvent(e) => write!(f, "Unexpected audit event: {}", e),
+            AuditPipelineError::DuplicateToken(kind) => {
+                write!(f, "The {} token audit event already exists in request extensions", kind)
+            }
+            AuditPipelineError::MissingRequestExtension(name) => {
+                write!(
+                    f,
+                    "Request extension {} not found, the middleware chain is misconfigured",
+                    name
+                )
+            }
+            AuditPipelineError::MissingAppData(name) => {
+                write!(
+                    f,
+                    "Application data {} not found, the middleware chain is misconfigured",
+                    name
+                )
+            }
+            AuditPipelineError::UnauditedError(e) => write!(f, "Error without an audit event: {}", e),
+        }
+    }
+}
+
+impl Error for AuditPipelineError {}
+
+/// An invalid audit event transition in the middleware indicates an audit event in an unexpected state.
+impl From<AuditTransitionError> for AuditPipelineError {
+    fn from(error: AuditTransitionError) -> Self {
+        match error {
+            AuditTransitionError::AlreadyFinal => AuditPipelineError::UnexpectedFinalEvent,
+            AuditTransitionError::DuplicateToken(kind) => AuditPipelineError::DuplicateToken(kind),
+            other => AuditPipelineError::UnexpectedAuditEvent(other.to_string()),
+        }
+    }
+}
+
+impl ResponseError for AuditPipelineError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
@@ -1,30 +1,64 @@
 pub mod audit_event_source;
 pub mod audit_recorder_factory;
+pub mod audit_recorder_options;
+pub mod audit_sampling_settings;
+pub mod audit_write_error;
 pub mod audit_writer;
+pub mod request_metadata_settings;
 #[cfg(test)]
 mod tests;
 
+use super::audit_pipeline_error::AuditPipelineError;
 use super::audited_error::AuditedError;
+use crate::http::extractors::audit_context::AuditContext;
 use crate::http::middleware::audit::audit_recorder::audit_event_source::AuditEventSource;
-use crate::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
+use crate::http::middleware::audit::audit_recorder::audit_recorder_options::AuditRecorderOptions;
+use crate::services::audit::chained::audit_event::AuditEvent;
 use actix_web::dev::{Service, ServiceRequest, ServiceResponse, forward_ready};
+use actix_web::http::StatusCode;
+use actix_web::{HttpMessage, ResponseError};
 use futures_util::future::LocalBoxFuture;
 use std::sync::Arc;
+use std::time::Instant;
 
 /// [`AuditRecorder`] is an Actix Web middleware that intercepts incoming requests and outgoing
-/// responses to record audit information using the provided [`AuditService`].
+/// responses to record audit information with the [`AuditWriter`] of the [`AuditRecorderOptions`].
+///
+/// If the audit middleware invariants are violated, e.g. the response has no audit event or an
+/// error without an audit event reaches the recorder, a diagnostic audit event is recorded and
+/// the pipeline failure metric of the [`AuditRecorderOptions`], if any, is incremented.
+///
+/// Every written event is assigned a new event id and the sequence number of intermediate events is
+/// incremented. The sequenced event of a successful response is stored back in the request
+/// extensions, so the next write of the chain continues from it. The metadata of the
+/// request is added to the recorded events for the fields enabled in the
+/// [`RequestMetadataSettings`] of the [`AuditRecorderOptions`]. The changes made by the request
+/// handlers through the [`AuditContext`] extractor are applied to the event of the response.
+///
+/// The events of the successful responses may be skipped according to the
+/// [`AuditSamplingSettings`] of the [`AuditRecorderOptions`]. Every skipped event is counted by
+/// the [`AuditEventsSkippedMetric`] provided with the settings.
+///
+/// If the [`AuditWriter`] rejects the event of a successful response, e.g. because its buffer is
+/// full, the request fails with the [`AuditWriteError`].
+///
+/// [`AuditWriter`]: audit_writer::AuditWriter
+/// [`AuditWriteError`]: audit_write_error::AuditWriteError
+/// [`AuditSamplingSettings`]: audit_sampling_settings::AuditSamplingSettings
+/// [`RequestMetadataSettings`]: request_metadata_settings::RequestMetadataSettings
+/// [`AuditEventsSkippedMetric`]: crate::services::observability::open_telemetry::metrics::metric_recorders::audit_events_skipped::AuditEventsSkippedMetric
 pub struct AuditRecorder<NextService, Req> {
-    audit_service: Arc<dyn AuditWriter>,
+    options: AuditRecorderOptions,
     next: Arc<NextService>,
     phantom: std::marker::PhantomData<Req>,
 }
 
 /// The constructor for the middleware
 impl<NextService, Req: AuditEventSource> AuditRecorder<NextService, Req> {
-    pub fn new(next: Arc<NextService>, audit_service: Arc<dyn AuditWriter>) -> Self {
+    pub fn new(next: Arc<NextService>, options: AuditRecorderOptions) -> Self {
         AuditRecorder {
             next,
-            audit_service,
+            options,
             phantom: std::marker::PhantomData,
         }
     }
@@ -48,25 +82,76 @@ where
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
         let next = Arc::clone(&self.next);
-        let audit_writer = Arc::clone(&self.audit_service);
+        let audit_writer = Arc::clone(&self.options.writer);
+        let metric = self.options.pipeline_failure_metric.clone();
+        let settings = self.options.request_metadata.clone();
+        let metadata = settings.read_request(&req);
+        let sampling = self.options.sampling.clone();
+        let path = req.path().to_string();
+        let started = Instant::now();
 
         let future = async move {
             let result = next.call(req.into()).await;
+            let write = |event: AuditEvent, status: StatusCode| match metadata.clone() {
+                Some(metadata) => {
+                    let metadata = settings.complete(metadata, status, started.elapsed());
+                    audit_writer.try_write(event.with_request_metadata(metadata))
+                }
+                None => audit_writer.try_write(event),
+            };
+            // The request already fails, so an event rejected by the writer is reported by the writer only
+            let record_failure = |error: &AuditPipelineError, status: StatusCode| {
+                let _ = write(AuditEvent::pipeline_failure(error).sequenced(), status);
+                if let Some(metric) = metric.as_ref() {
+                    metric.increment(error.reason_code());
+                }
+            };
 
             match result {
                 Ok(response) => {
-                    let audited: AES = AES::try_from(response)?;
-                    audit_writer.write(audited.audit_event());
-                    Ok(audited.into())
+                    let status = response.status();
+                    let request = response.request().clone();
+                    let context = response.request().extensions().get::<AuditContext>().cloned();
+                    let audited = AES::try_from(response).inspect_err(|error| {
+                        record_failure(&as_pipeline_error(error), error.as_response_error().status_code());
+                    })?;
+                    match audited
+                        .audit_event()
+                        .and_then(|event| with_context(event, context.as_ref()))
+                    {
+                        Ok(event) => {
+                            match sampling.as_ref().and_then(|(settings, metric)| {
+                                settings
+                                    .skip_reason(&path, &event, status)
+                                    .map(|reason| (reason, metric))
+                            }) {
+                                Some((reason, metric)) => metric.increment(reason),
+                                None => {
+                                    let event = event.sequenced();
+                                    request.extensions_mut().insert(event.clone());
+                                    write(event, status)?
+                                }
+                            }
+                            Ok(audited.into())
+                        }
+                        Err(error) => {
+                            record_failure(&error, error.status_code());
+                            Err(error.into())
+                        }
+                    }
                 }
 
                 Err(error) => {
                     match error.as_error::<AuditedError>() {
-                        Some(audited_error) => audit_writer.write(audited_error.event.clone()),
-                        None => panic!(
-                            "Error without audit should not reach audit recorder middleware: {:?}",
-                            error
-                        ),
+                        Some(audited_error) => {
+                            let _ = write(audited_error.event.clone().sequenced(), audited_error.status_code());
+                            if let (Some(pipeline_error), Some(metric)) =
+                                (audited_error.pipeline_error(), metric.as_ref())
+                            {
+                                metric.increment(pipeline_error.reason_code());
+                            }
+                        }
+                        None => record_failure(&as_pipeline_error(&error), error.as_response_error().status_code()),
                     };
                     Err(error)
                 }
@@ -75,3 +160,19 @@ where
         Box::pin(future)
     }
 }
+
+/// Applies the changes made by the request handlers through the [`AuditContext`] to the event.
+fn with_context(mut event: AuditEvent, context: Option<&AuditContext>) -> Result<AuditEvent, AuditPipelineError> {
+    if let Some(context) = context {
+        context.apply(&mut event)?;
+    }
+    Ok(event)
+}
+
+/// Returns the audit pipeline error carried by the error, or reports the error as unaudited.
+fn as_pipeline_error(error: &actix_web::Error) -> AuditPipelineError {
+    error
+        .as_error::<AuditPipelineError>()
+        .cloned()
+        .unwrap_or_else(|| AuditPipelineError::UnauditedError(error.to_string()))
+}
@@ -1,20 +1,20 @@
 use crate::http::middleware::audit::audit_recorder::AuditRecorder;
 use crate::http::middleware::audit::audit_recorder::audit_event_source::AuditEventSource;
-use crate::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
+use crate::http::middleware::audit::audit_recorder::audit_recorder_options::AuditRecorderOptions;
 use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
 use futures_util::future::LocalBoxFuture;
 use std::sync::Arc;
 
 /// Middleware for audit logging factory
 pub struct AuditRecorderFactory<AES> {
-    pub audit_service: Arc<dyn AuditWriter>,
+    options: AuditRecorderOptions,
     phantom: std::marker::PhantomData<AES>,
 }
 
 impl<AES> AuditRecorderFactory<AES> {
-    pub fn new(audit_service: Arc<dyn AuditWriter>) -> Self {
+    pub fn new(options: AuditRecorderOptions) -> Self {
         AuditRecorderFactory {
-            audit_service,
+            options,
             phantom: std::marker::PhantomData,
         }
     }
@@ -39,7 +39,7 @@ where
     type Future = LocalBoxFuture<'static, Result<AuditRecorder<NextService, AES>, Self::InitError>>;
 
     fn new_transform(&self, service: NextService) -> Self::Future {
-        let audit_service = self.audit_service.clone();
-        Box::pin(async move { Ok(AuditRecorder::new(Arc::new(service), audit_service)) })
+        let options = self.options.clone();
+        Box::pin(async move { Ok(AuditRecorder::new(Arc::new(service), options)) })
     }
 }
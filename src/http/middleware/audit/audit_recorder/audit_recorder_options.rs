@@ -0,0 +1,55 @@
+use crate::http::middleware::audit::audit_recorder::audit_sampling_settings::AuditSamplingSettings;
+use crate::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
+use crate::http::middleware::audit::audit_recorder::request_metadata_settings::RequestMetadataSettings;
+use crate::services::observability::open_telemetry::metrics::metric_recorders::audit_events_skipped::AuditEventsSkippedMetric;
+use crate::services::observability::open_telemetry::metrics::metric_recorders::audit_pipeline_failure::AuditPipelineFailureMetric;
+use std::sync::Arc;
+
+/// [`AuditRecorderOptions`] holds the dependencies of the [`AuditRecorder`]: the [`AuditWriter`]
+/// the events are written to, the fields of the request metadata recorded in the events, the
+/// optional sampling rules and the metrics updated by the recorder.
+///
+/// [`AuditRecorder`]: crate::http::middleware::audit::audit_recorder::AuditRecorder
+#[derive(Clone)]
+pub struct AuditRecorderOptions {
+    pub(crate) writer: Arc<dyn AuditWriter>,
+    pub(crate) pipeline_failure_metric: Option<Arc<dyn AuditPipelineFailureMetric>>,
+    pub(crate) sampling: Option<(AuditSamplingSettings, Arc<dyn AuditEventsSkippedMetric>)>,
+    pub(crate) request_metadata: RequestMetadataSettings,
+}
+
+impl AuditRecorderOptions {
+    /// Creates the options writing all events to the provided writer without request metadata and
+    /// metrics.
+    pub fn new(writer: Arc<dyn AuditWriter>) -> Self {
+        AuditRecorderOptions {
+            writer,
+            pipeline_failure_metric: None,
+            sampling: None,
+            request_metadata: RequestMetadataSettings::default(),
+        }
+    }
+
+    /// Records the fields of the request metadata enabled in the provided settings.
+    pub fn with_request_metadata(mut self, settings: RequestMetadataSettings) -> Self {
+        self.request_metadata = settings;
+        self
+    }
+
+    /// Counts the requests that violated the audit middleware invariants with the provided metric.
+    pub fn with_pipeline_failure_metric(mut self, metric: Arc<dyn AuditPipelineFailureMetric>) -> Self {
+        self.pipeline_failure_metric = Some(metric);
+        self
+    }
+
+    /// Skips the events according to the provided sampling settings. Every skipped event is
+    /// counted by the provided metric.
+    pub fn with_sampling(
+        mut self,
+        settings: AuditSamplingSettings,
+        skipped_metric: Arc<dyn AuditEventsSkippedMetric>,
+    ) -> Self {
+        self.sampling = Some((settings, skipped_metric));
+        self
+    }
+}
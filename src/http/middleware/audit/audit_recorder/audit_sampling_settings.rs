@@ -0,0 +1,72 @@
+use crate::services::audit::chained::audit_event::AuditEvent;
+use actix_web::http::StatusCode;
+use cedar_policy::Decision;
+use serde::Deserialize;
+use uuid::Uuid;
+
+/// The reason of the events skipped for an excluded path.
+pub(crate) const EXCLUDED_PATH: &str = "excluded-path";
+
+/// The reason of the events skipped by the sampling of the allowed requests.
+pub(crate) const SAMPLED_OUT: &str = "sampled-out";
+
+/// Selects the audit events recorded by the audit recorder, so that high-volume traffic, e.g. the
+/// health checks, does not produce a record for every request. By default all events are recorded.
+///
+/// The events of the denied requests and of the failed responses are always recorded. The rules
+/// apply only to the events of the successful responses that were not denied.
+///
+/// The settings are passed to the recorder with
+/// [`AuditRecorderOptions::with_sampling`](super::audit_recorder_options::AuditRecorderOptions::with_sampling)
+/// together with the metric counting the skipped events.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AuditSamplingSettings {
+    /// The paths of the requests that are not recorded. A path ending with `*` matches all paths
+    /// starting with the preceding prefix.
+    pub exclude_paths: Vec<String>,
+
+    /// The share of the allowed requests that are recorded, from `0.0` to `1.0`. The requests are
+    /// sampled by the chain id, so all services record or skip the same call tree.
+    pub allowed_sample_rate: f64,
+}
+
+impl Default for AuditSamplingSettings {
+    fn default() -> Self {
+        AuditSamplingSettings {
+            exclude_paths: Vec::new(),
+            allowed_sample_rate: 1.0,
+        }
+    }
+}
+
+impl AuditSamplingSettings {
+    /// Returns the reason to skip the event of the response, or `None` if it should be recorded.
+    pub(crate) fn skip_reason(&self, path: &str, event: &AuditEvent, status: StatusCode) -> Option<&'static str> {
+        let (AuditEvent::Final(chained) | AuditEvent::Intermediate(chained)) = event;
+        if chained.decision == Some(Decision::Deny) || status.is_client_error() || status.is_server_error() {
+            return None;
+        }
+        if self.exclude_paths.iter().any(|pattern| matches_path(pattern, path)) {
+            return Some(EXCLUDED_PATH);
+        }
+        if self.allowed_sample_rate >= 1.0 {
+            return None;
+        }
+        let chain_id = chained
+            .chain_id
+            .as_deref()
+            .and_then(|id| Uuid::parse_str(id).ok())
+            .unwrap_or_else(Uuid::new_v4);
+        let (sample, _) = chain_id.as_u64_pair();
+        let sampled = (sample as f64 / u64::MAX as f64) < self.allowed_sample_rate;
+        (!sampled).then_some(SAMPLED_OUT)
+    }
+}
+
+fn matches_path(pattern: &str, path: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => path.starts_with(prefix),
+        None => path == pattern,
+    }
+}
@@ -0,0 +1,49 @@
+use crate::services::audit::composed_audit_service::composed_audit_error::ComposedAuditError;
+use actix_web::ResponseError;
+use actix_web::http::StatusCode;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// The error returned by an [`AuditWriter`] that cannot accept an audit event. The request is
+/// failed with `503 Service Unavailable`, since it cannot be processed without its audit record.
+///
+/// [`AuditWriter`]: crate::http::middleware::audit::audit_recorder::audit_writer::AuditWriter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditWriteError {
+    /// The buffer of the writer is full.
+    QueueFull,
+
+    /// The writer is shut down.
+    Closed,
+}
+
+impl AuditWriteError {
+    /// Finds the rejection among the causes of the error, including the failures of the sinks of
+    /// the `ComposedAuditService`.
+    pub(crate) fn find(error: &anyhow::Error) -> Option<AuditWriteError> {
+        if let Some(composed) = error.downcast_ref::<ComposedAuditError>() {
+            return composed.errors().iter().find_map(AuditWriteError::find);
+        }
+        error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<AuditWriteError>())
+            .cloned()
+    }
+}
+
+impl Display for AuditWriteError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuditWriteError::QueueFull => write!(f, "Audit event queue is full"),
+            AuditWriteError::Closed => write!(f, "Audit writer is shut down"),
+        }
+    }
+}
+
+impl Error for AuditWriteError {}
+
+impl ResponseError for AuditWriteError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::SERVICE_UNAVAILABLE
+    }
+}
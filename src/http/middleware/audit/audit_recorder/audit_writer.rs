@@ -1,8 +1,43 @@
+use crate::http::middleware::audit::audit_recorder::audit_write_error::AuditWriteError;
+use crate::services::audit::AuditService;
+use crate::services::audit::audit_record::AuditRecord;
 use crate::services::audit::chained::audit_event::AuditEvent;
+use log::warn;
 
 /// The `AuditWriter` trait defines the contract for writing audit events. It abstracts the logic of
 /// persisting or transmitting audit events to the desired destination.
 pub trait AuditWriter: Send + Sync + 'static {
     /// Writes the given `AuditEvent` to the configured audit destination.
     fn write(&self, event: AuditEvent);
+
+    /// Writes the given `AuditEvent` or returns an error if the writer cannot accept it, e.g. when
+    /// the buffer of an asynchronous writer is full. The audit recorder fails the request in this
+    /// case. The default implementation delegates to `write` and always accepts the event.
+    fn try_write(&self, event: AuditEvent) -> Result<(), AuditWriteError> {
+        self.write(event);
+        Ok(())
+    }
+}
+
+/// Every [`AuditService`] writes the events as audit records. The records rejected with an
+/// [`AuditWriteError`] are returned to the audit recorder, and other failures are logged.
+impl<Service: AuditService> AuditWriter for Service {
+    fn write(&self, event: AuditEvent) {
+        if let Err(error) = self.try_write(event) {
+            warn!("Failed to write the audit event: {}", error);
+        }
+    }
+
+    fn try_write(&self, event: AuditEvent) -> Result<(), AuditWriteError> {
+        let Err(error) = self.record(AuditRecord::from(event)) else {
+            return Ok(());
+        };
+        match AuditWriteError::find(&error) {
+            Some(rejection) => Err(rejection),
+            None => {
+                warn!("Failed to write the audit event: {:?}", error);
+                Ok(())
+            }
+        }
+    }
 }
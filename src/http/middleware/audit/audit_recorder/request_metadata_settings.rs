@@ -0,0 +1,100 @@
+use crate::services::audit::chained::request_metadata::RequestMetadata;
+use actix_web::dev::ServiceRequest;
+use actix_web::http::StatusCode;
+use actix_web::http::header::{HeaderName, USER_AGENT};
+use serde::Deserialize;
+use std::net::IpAddr;
+use std::time::Duration;
+
+const X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+
+/// Selects the fields of the [`RequestMetadata`] recorded by the audit recorder. All fields are
+/// disabled by default, so the metadata section is recorded only for the fields a service opts in.
+///
+/// The settings are provided to the audit recorder through the `AuditRecorderOptions`, e.g.
+/// `AuditRecorderOptions::new(writer).with_request_metadata(RequestMetadataSettings::all())`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct RequestMetadataSettings {
+    pub method: bool,
+    pub path: bool,
+    pub status_code: bool,
+    pub latency: bool,
+
+    /// Records the peer address of the connection. The `X-Forwarded-For` header is honored only
+    /// when the peer is one of the [`trusted_proxies`](Self::trusted_proxies).
+    pub client_ip: bool,
+    pub user_agent: bool,
+
+    /// Addresses of the reverse proxies allowed to report the client address. The client address
+    /// is the rightmost `X-Forwarded-For` entry that is not a trusted proxy.
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+impl RequestMetadataSettings {
+    /// Enables all fields.
+    pub fn all() -> Self {
+        RequestMetadataSettings {
+            method: true,
+            path: true,
+            status_code: true,
+            latency: true,
+            client_ip: true,
+            user_agent: true,
+            trusted_proxies: Vec::new(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        !(self.method || self.path || self.status_code || self.latency || self.client_ip || self.user_agent)
+    }
+
+    /// Reads the enabled fields known before the request is processed. Returns `None` if no
+    /// field is enabled.
+    pub(crate) fn read_request(&self, request: &ServiceRequest) -> Option<RequestMetadata> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(RequestMetadata {
+            method: self.method.then(|| request.method().to_string()),
+            path: self.path.then(|| request.path().to_string()),
+            client_ip: self.client_ip.then(|| self.client_ip(request)).flatten(),
+            user_agent: self
+                .user_agent
+                .then(|| request.headers().get(USER_AGENT).and_then(|v| v.to_str().ok()))
+                .flatten()
+                .map(|v| v.to_string()),
+            ..RequestMetadata::default()
+        })
+    }
+
+    fn client_ip(&self, request: &ServiceRequest) -> Option<String> {
+        let mut client = request.peer_addr()?.ip();
+        if !self.trusted_proxies.contains(&client) {
+            return Some(client.to_string());
+        }
+        let forwarded = request
+            .headers()
+            .get_all(X_FORWARDED_FOR)
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .collect::<Vec<_>>();
+        for hop in forwarded.into_iter().rev() {
+            match hop.trim().parse::<IpAddr>() {
+                Ok(ip) if self.trusted_proxies.contains(&ip) => client = ip,
+                Ok(ip) => return Some(ip.to_string()),
+                Err(_) => break,
+            }
+        }
+        Some(client.to_string())
+    }
+
+    /// Completes the request metadata with the enabled response fields.
+    pub(crate) fn complete(&self, metadata: RequestMetadata, status: StatusCode, latency: Duration) -> RequestMetadata {
+        RequestMetadata {
+            status_code: self.status_code.then(|| status.as_u16()),
+            latency_ms: self.latency.then_some(latency.as_millis() as u64),
+            ..metadata
+        }
+    }
+}
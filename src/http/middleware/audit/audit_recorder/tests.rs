@@ -1,16 +1,31 @@
+use crate::http::extractors::audit_context::AuditContext;
+use crate::http::middleware::audit::audit_pipeline_error::AuditPipelineError;
 use crate::http::middleware::audit::audit_recorder::audit_event_source::AuditEventSource;
 use crate::http::middleware::audit::audit_recorder::audit_recorder_factory::AuditRecorderFactory;
+use crate::http::middleware::audit::audit_recorder::audit_recorder_options::AuditRecorderOptions;
+use crate::http::middleware::audit::audit_recorder::audit_sampling_settings::AuditSamplingSettings;
+use crate::http::middleware::audit::audit_recorder::audit_write_error::AuditWriteError;
 use crate::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
+use crate::http::middleware::audit::audit_recorder::request_metadata_settings::RequestMetadataSettings;
 use crate::http::middleware::audit::audited_error::AuditedError;
+use crate::http::middleware::audit::audited_response::AuditedResponse;
+use crate::http::middleware::extract_external_token::external_token_error::ExternalTokenError;
 use crate::services::audit::chained::audit_event::AuditEvent;
 use crate::services::audit::chained::chained_audit_event::ChainedAuditEvent;
+use crate::services::audit::chained::request_metadata::RequestMetadata;
+use crate::services::identity::external_token_validation_error::ExternalTokenValidationError;
+use crate::services::observability::open_telemetry::metrics::metric_recorders::audit_events_skipped::AuditEventsSkippedMetric;
+use crate::services::observability::open_telemetry::metrics::metric_recorders::audit_pipeline_failure::AuditPipelineFailureMetric;
 use actix_web::body::BoxBody;
-use actix_web::dev::ServiceResponse;
+use actix_web::dev::{Service, ServiceResponse};
 use actix_web::error::ErrorInternalServerError;
+use actix_web::http::StatusCode;
 use actix_web::{App, Error, HttpMessage, HttpResponse, test, web};
 use anyhow::Result;
+use cedar_policy::Decision;
 use mockall::mock;
-use pretty_assertions::assert_matches;
+use pretty_assertions::{assert_eq, assert_matches};
+use rstest::rstest;
 use std::sync::Arc;
 
 #[actix_web::test]
@@ -20,7 +35,9 @@ async fn test_audit_success() {
     audit.expect_write().returning(|_| ());
 
     let chain = App::new()
-        .wrap(AuditRecorderFactory::<MockAuditEventSource>::new(Arc::new(audit)))
+        .wrap(AuditRecorderFactory::<MockAuditEventSource>::new(
+            AuditRecorderOptions::new(Arc::new(audit)),
+        ))
         .default_service(web::to(|| async move { HttpResponse::Ok().finish() }));
 
     let service = test::init_service(chain).await;
@@ -29,7 +46,7 @@ async fn test_audit_success() {
     // Act
     let _ = test::call_service(&service, request).await;
 
-    // Expectations are verified automatically when `mock_audit` is dropped at the end of the scope.
+    // Expectations are verified automatically when the mocks are dropped at the end of the scope.
 }
 
 #[actix_web::test]
@@ -39,7 +56,9 @@ async fn test_audit_error() {
     audit.expect_write().returning(|_| ());
 
     let chain = App::new()
-        .wrap(AuditRecorderFactory::<MockAuditEventSource>::new(Arc::new(audit)))
+        .wrap(AuditRecorderFactory::<MockAuditEventSource>::new(
+            AuditRecorderOptions::new(Arc::new(audit)),
+        ))
         .default_service(web::to(|| async move {
             Result::<HttpResponse, Error>::Err(ErrorInternalServerError("Some error"))
         }));
@@ -51,15 +70,24 @@ async fn test_audit_error() {
 
     let _ = test::call_service(&service, request).await;
 
-    // Expectations are verified automatically when `mock_audit` is dropped at the end of the scope.
+    // Expectations are verified automatically when the mocks are dropped at the end of the scope.
 }
 
 #[actix_web::test]
-#[should_panic(expected = "Error without audit should not reach audit recorder middleware: \"Some error\"")]
 async fn test_custom_error_without_error_event() {
     // Arrange
     let mut audit = MockAuditWriter::new();
-    audit.expect_write().returning(|_| ());
+    audit
+        .expect_write()
+        .withf(|event| is_pipeline_failure(event, "unaudited-error"))
+        .times(1)
+        .returning(|_| ());
+    let mut metric = MockAuditPipelineFailureMetric::new();
+    metric
+        .expect_increment()
+        .withf(|reason_code| reason_code == "unaudited-error")
+        .times(1)
+        .returning(|_| ());
 
     let chain = App::new()
         .wrap_fn(|_req, _srv| {
@@ -67,41 +95,89 @@ async fn test_custom_error_without_error_event() {
                 "Some error",
             )))
         })
-        .wrap(AuditRecorderFactory::<MockAuditEventSource>::new(Arc::new(audit)))
+        .wrap(AuditRecorderFactory::<MockAuditEventSource>::new(
+            AuditRecorderOptions::new(Arc::new(audit)).with_pipeline_failure_metric(Arc::new(metric)),
+        ))
         .default_service(web::to(|| async move { HttpResponse::Ok().finish() }));
 
     let service = test::init_service(chain).await;
     let request = test::TestRequest::get().uri("/any-route").to_request();
 
     // Act
+    let result = test::try_call_service(&service, request).await;
 
-    let _: ServiceResponse<BoxBody> = test::call_service(&service, request).await;
-
-    // The code above should panic.
+    // Assert
+    let err = result.expect_err("Expected service call to fail with an error");
+    assert_eq!(err.to_string(), "Some error");
 }
 
 #[actix_web::test]
-#[should_panic(expected = "Attempt to wrap an error without an audit event")]
-async fn test_custom_error_panic_request_without_event() {
+async fn test_custom_error_request_without_event() {
     // Arrange
     let mut audit = MockAuditWriter::new();
-    audit.expect_write().returning(|_| ());
+    audit
+        .expect_write()
+        .withf(|event| is_pipeline_failure(event, "missing-audit-event"))
+        .times(1)
+        .returning(|_| ());
+    let mut metric = MockAuditPipelineFailureMetric::new();
+    metric
+        .expect_increment()
+        .withf(|reason_code| reason_code == "missing-audit-event")
+        .times(1)
+        .returning(|_| ());
 
     let chain = App::new()
         .wrap_fn(|req, _src| {
-            let error = AuditedError::from_request(&req, ErrorInternalServerError("Some error"));
-            std::future::ready(Err::<ServiceResponse<BoxBody>, _>(Error::from(error)))
+            let error = AuditedError::from_request(&req, ErrorInternalServerError("Some error"))
+                .map_or_else(Error::from, Error::from);
+            std::future::ready(Err::<ServiceResponse<BoxBody>, _>(error))
         })
-        .wrap(AuditRecorderFactory::<MockAuditEventSource>::new(Arc::new(audit)))
+        .wrap(AuditRecorderFactory::<MockAuditEventSource>::new(
+            AuditRecorderOptions::new(Arc::new(audit)).with_pipeline_failure_metric(Arc::new(metric)),
+        ))
         .default_service(web::to(|| async move { HttpResponse::Ok().finish() }));
 
     let service = test::init_service(chain).await;
     let request = test::TestRequest::get().uri("/any-route").to_request();
 
     // Act
-    let _: ServiceResponse<BoxBody> = test::call_service(&service, request).await;
+    let result = test::try_call_service(&service, request).await;
 
-    // The code above should panic.
+    // Assert
+    let err = result.expect_err("Expected service call to fail with an error");
+    assert_eq!(err.as_response_error().status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_matches!(
+        err.as_error::<AuditPipelineError>(),
+        Some(AuditPipelineError::MissingAuditEvent)
+    );
+}
+
+#[actix_web::test]
+async fn test_response_without_audit_event() {
+    // Arrange
+    let mut audit = MockAuditWriter::new();
+    audit
+        .expect_write()
+        .withf(|event| is_pipeline_failure(event, "missing-audit-event"))
+        .times(1)
+        .returning(|_| ());
+
+    let chain = App::new()
+        .wrap(AuditRecorderFactory::<AuditedResponse>::new(AuditRecorderOptions::new(
+            Arc::new(audit),
+        )))
+        .default_service(web::to(|| async move { HttpResponse::Ok().finish() }));
+
+    let service = test::init_service(chain).await;
+    let request = test::TestRequest::get().uri("/any-route").to_request();
+
+    // Act
+    let result = test::try_call_service(&service, request).await;
+
+    // Assert
+    let err = result.expect_err("Expected service call to fail with an error");
+    assert_eq!(err.as_response_error().status_code(), StatusCode::INTERNAL_SERVER_ERROR);
 }
 
 #[actix_web::test]
@@ -114,10 +190,13 @@ async fn test_custom_error_recording() {
         .wrap_fn(|req, _src| {
             req.extensions_mut()
                 .insert(AuditEvent::Intermediate(ChainedAuditEvent::empty()));
-            let error = AuditedError::from_request(&req, ErrorInternalServerError("Some error"));
+            let error = AuditedError::from_request(&req, ErrorInternalServerError("Some error"))
+                .expect("Expected the request to contain an intermediate audit event");
             std::future::ready(Err::<ServiceResponse<BoxBody>, _>(Error::from(error)))
         })
-        .wrap(AuditRecorderFactory::<MockAuditEventSource>::new(Arc::new(audit)))
+        .wrap(AuditRecorderFactory::<MockAuditEventSource>::new(
+            AuditRecorderOptions::new(Arc::new(audit)),
+        ))
         .default_service(web::to(|| async move { HttpResponse::Ok().finish() }));
 
     let service = test::init_service(chain).await;
@@ -140,11 +219,473 @@ async fn test_custom_error_recording() {
     );
 }
 
+#[actix_web::test]
+async fn test_request_metadata_recording() {
+    // Arrange
+    let mut audit = MockAuditWriter::new();
+    audit
+        .expect_write()
+        .withf(|event| {
+            matches!(event, AuditEvent::Intermediate(ChainedAuditEvent { request: Some(metadata), .. })
+                if metadata.method.as_deref() == Some("GET")
+                    && metadata.path.as_deref() == Some("/any-route")
+                    && metadata.status_code == Some(202)
+                    && metadata.latency_ms.is_some()
+                    && metadata.client_ip.as_deref() == Some("10.0.0.1")
+                    && metadata.user_agent.as_deref() == Some("test-agent"))
+        })
+        .times(1)
+        .returning(|_| ());
+
+    let settings = RequestMetadataSettings {
+        trusted_proxies: vec!["10.0.0.100".parse().unwrap()],
+        ..RequestMetadataSettings::all()
+    };
+
+    let chain = App::new()
+        .wrap(AuditRecorderFactory::<MockAuditEventSource>::new(
+            AuditRecorderOptions::new(Arc::new(audit)).with_request_metadata(settings),
+        ))
+        .default_service(web::to(|| async move { HttpResponse::Accepted().finish() }));
+
+    let service = test::init_service(chain).await;
+    let request = test::TestRequest::get()
+        .uri("/any-route")
+        .peer_addr("10.0.0.100:443".parse().unwrap())
+        .insert_header(("User-Agent", "test-agent"))
+        .insert_header(("X-Forwarded-For", "192.168.0.1, 10.0.0.1"))
+        .to_request();
+
+    // Act
+    let _ = test::call_service(&service, request).await;
+
+    // Expectations are verified automatically when `audit` is dropped at the end of the scope.
+}
+
+#[actix_web::test]
+async fn test_request_metadata_ignores_forwarded_header_from_untrusted_peer() {
+    // Arrange
+    let mut audit = MockAuditWriter::new();
+    audit
+        .expect_write()
+        .withf(|event| {
+            matches!(event, AuditEvent::Intermediate(ChainedAuditEvent { request: Some(metadata), .. })
+                if metadata.client_ip.as_deref() == Some("172.16.0.5"))
+        })
+        .times(1)
+        .returning(|_| ());
+
+    let chain = App::new()
+        .wrap(AuditRecorderFactory::<MockAuditEventSource>::new(
+            AuditRecorderOptions::new(Arc::new(audit)).with_request_metadata(RequestMetadataSettings::all()),
+        ))
+        .default_service(web::to(|| async move { HttpResponse::Accepted().finish() }));
+
+    let service = test::init_service(chain).await;
+    let request = test::TestRequest::get()
+        .uri("/any-route")
+        .peer_addr("172.16.0.5:443".parse().unwrap())
+        .insert_header(("X-Forwarded-For", "10.0.0.1"))
+        .to_request();
+
+    // Act
+    let _ = test::call_service(&service, request).await;
+
+    // Expectations are verified automatically when `audit` is dropped at the end of the scope.
+}
+
+#[actix_web::test]
+async fn test_request_metadata_opt_in_fields() {
+    // Arrange
+    let mut audit = MockAuditWriter::new();
+    audit
+        .expect_write()
+        .withf(|event| {
+            matches!(event, AuditEvent::Final(ChainedAuditEvent { request: Some(metadata), .. })
+            if *metadata == RequestMetadata {
+                method: Some("POST".to_string()),
+                status_code: Some(500),
+                ..RequestMetadata::default()
+            })
+        })
+        .times(1)
+        .returning(|_| ());
+    let settings = RequestMetadataSettings {
+        method: true,
+        status_code: true,
+        ..RequestMetadataSettings::default()
+    };
+
+    let chain = App::new()
+        .wrap_fn(|_req, _srv| {
+            std::future::ready(Err::<ServiceResponse<BoxBody>, _>(ErrorInternalServerError(
+                "Some error",
+            )))
+        })
+        .wrap(AuditRecorderFactory::<MockAuditEventSource>::new(
+            AuditRecorderOptions::new(Arc::new(audit)).with_request_metadata(settings),
+        ))
+        .default_service(web::to(|| async move { HttpResponse::Ok().finish() }));
+
+    let service = test::init_service(chain).await;
+    let request = test::TestRequest::post()
+        .uri("/any-route")
+        .insert_header(("User-Agent", "test-agent"))
+        .to_request();
+
+    // Act
+    let _ = test::try_call_service(&service, request).await;
+
+    // Expectations are verified automatically when the mocks are dropped at the end of the scope.
+}
+
+#[actix_web::test]
+async fn test_request_metadata_disabled_by_default() {
+    // Arrange
+    let mut audit = MockAuditWriter::new();
+    audit
+        .expect_write()
+        .withf(|event| matches!(event, AuditEvent::Intermediate(ChainedAuditEvent { request: None, .. })))
+        .times(1)
+        .returning(|_| ());
+
+    let chain = App::new()
+        .wrap(AuditRecorderFactory::<MockAuditEventSource>::new(
+            AuditRecorderOptions::new(Arc::new(audit)),
+        ))
+        .default_service(web::to(|| async move { HttpResponse::Ok().finish() }));
+
+    let service = test::init_service(chain).await;
+    let request = test::TestRequest::get().uri("/any-route").to_request();
+
+    // Act
+    let _ = test::call_service(&service, request).await;
+
+    // Expectations are verified automatically when the mocks are dropped at the end of the scope.
+}
+
+#[actix_web::test]
+async fn test_intermediate_write_increments_sequence() {
+    // Arrange
+    let mut audit = MockAuditWriter::new();
+    audit
+        .expect_write()
+        .withf(|event| matches!(event, AuditEvent::Intermediate(ChainedAuditEvent { sequence: 1, .. })))
+        .times(1)
+        .returning(|_| ());
+
+    let chain = App::new()
+        .wrap(AuditRecorderFactory::<MockAuditEventSource>::new(
+            AuditRecorderOptions::new(Arc::new(audit)),
+        ))
+        .default_service(web::to(|| async move { HttpResponse::Ok().finish() }));
+
+    let service = test::init_service(chain).await;
+    let request = test::TestRequest::get().uri("/any-route").to_request();
+
+    // Act
+    let _ = test::call_service(&service, request).await;
+
+    // Expectations are verified automatically when the mocks are dropped at the end of the scope.
+}
+
+#[actix_web::test]
+async fn test_sequenced_event_is_stored_in_request() {
+    // Arrange
+    let written = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut audit = MockAuditWriter::new();
+    let sink = Arc::clone(&written);
+    audit
+        .expect_write()
+        .times(1)
+        .returning(move |event| sink.lock().unwrap().push(event));
+
+    let chain = App::new()
+        .wrap(AuditRecorderFactory::<AuditedResponse>::new(AuditRecorderOptions::new(
+            Arc::new(audit),
+        )))
+        .wrap_fn(|req, srv| {
+            req.extensions_mut()
+                .insert(AuditEvent::Intermediate(ChainedAuditEvent::begin()));
+            srv.call(req)
+        })
+        .default_service(web::to(|| async move { HttpResponse::Ok().finish() }));
+
+    let service = test::init_service(chain).await;
+    let request = test::TestRequest::get().uri("/any-route").to_request();
+
+    // Act
+    let response = test::call_service(&service, request).await;
+
+    // Assert
+    let stored = response.request().extensions().get::<AuditEvent>().cloned();
+    let written = written.lock().unwrap();
+    assert_matches!(
+        (stored, written.as_slice()),
+        (
+            Some(AuditEvent::Intermediate(ChainedAuditEvent { sequence: 1, event_id: Some(stored_id), .. })),
+            [AuditEvent::Intermediate(ChainedAuditEvent { sequence: 1, event_id: Some(written_id), .. })]
+        ) if stored_id == *written_id
+    );
+}
+
+#[actix_web::test]
+async fn test_final_write_keeps_sequence() {
+    // Arrange
+    let mut audit = MockAuditWriter::new();
+    audit
+        .expect_write()
+        .withf(|event| {
+            matches!(
+                event,
+                AuditEvent::Final(ChainedAuditEvent {
+                    sequence: 0,
+                    finalized_at: Some(_),
+                    event_id: Some(_),
+                    ..
+                })
+            )
+        })
+        .times(1)
+        .returning(|_| ());
+
+    let chain = App::new()
+        .wrap_fn(|req, _src| {
+            req.extensions_mut()
+                .insert(AuditEvent::Intermediate(ChainedAuditEvent::begin()));
+            let error = AuditedError::token_expired(&req, ExternalTokenValidationError::Expired);
+            std::future::ready(Err::<ServiceResponse<BoxBody>, _>(Error::from(error)))
+        })
+        .wrap(AuditRecorderFactory::<MockAuditEventSource>::new(
+            AuditRecorderOptions::new(Arc::new(audit)),
+        ))
+        .default_service(web::to(|| async move { HttpResponse::Ok().finish() }));
+
+    let service = test::init_service(chain).await;
+    let request = test::TestRequest::get().uri("/any-route").to_request();
+
+    // Act
+    let _ = test::try_call_service(&service, request).await;
+
+    // Expectations are verified automatically when the mocks are dropped at the end of the scope.
+}
+
+#[actix_web::test]
+async fn test_audit_context_changes_are_recorded() {
+    // Arrange
+    let mut audit = MockAuditWriter::new();
+    audit
+        .expect_write()
+        .withf(|event| {
+            matches!(
+                event,
+                AuditEvent::Intermediate(ChainedAuditEvent {
+                    actor: Some(actor),
+                    resource: Some(resource),
+                    attributes,
+                    ..
+                }) if actor == "alice" && resource == "documents/1" && attributes.get("origin").is_some_and(|v| v == "task")
+            )
+        })
+        .times(1)
+        .returning(|_| ());
+
+    let chain = App::new()
+        .wrap(AuditRecorderFactory::<MockAuditEventSource>::new(
+            AuditRecorderOptions::new(Arc::new(audit)),
+        ))
+        .wrap_fn(|request, service| {
+            request
+                .extensions_mut()
+                .insert(AuditEvent::Intermediate(ChainedAuditEvent::begin()));
+            service.call(request)
+        })
+        .default_service(web::to(|context: AuditContext| async move {
+            context.set_actor("alice");
+            context.set_resource("documents/1");
+            let task_context = context.clone();
+            tokio::spawn(async move { task_context.set_attribute("origin", "task") })
+                .await
+                .expect("task should complete");
+            HttpResponse::Ok().finish()
+        }));
+
+    let service = test::init_service(chain).await;
+    let request = test::TestRequest::get().uri("/any-route").to_request();
+
+    // Act
+    let _ = test::call_service(&service, request).await;
+
+    // Expectations are verified automatically when the mocks are dropped at the end of the scope.
+}
+
+#[actix_web::test]
+async fn test_rejected_write_fails_request() {
+    // Arrange
+    let chain = App::new()
+        .wrap(AuditRecorderFactory::<MockAuditEventSource>::new(
+            AuditRecorderOptions::new(Arc::new(FullAuditWriter)),
+        ))
+        .default_service(web::to(|| async move { HttpResponse::Ok().finish() }));
+
+    let service = test::init_service(chain).await;
+    let request = test::TestRequest::get().uri("/any-route").to_request();
+
+    // Act
+    let result = test::try_call_service(&service, request).await;
+
+    // Assert
+    let error = result.expect_err("request should fail");
+    assert_eq!(error.as_error::<AuditWriteError>(), Some(&AuditWriteError::QueueFull));
+    assert_eq!(error.as_response_error().status_code(), StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[actix_web::test]
+async fn test_skip_excluded_paths() {
+    // Arrange
+    let mut audit = MockAuditWriter::new();
+    audit.expect_write().times(1).returning(|_| ());
+    let mut metric = MockAuditEventsSkippedMetric::new();
+    metric
+        .expect_increment()
+        .withf(|reason| reason == "excluded-path")
+        .times(2)
+        .returning(|_| ());
+    let sampling = AuditSamplingSettings {
+        exclude_paths: vec!["/health".to_string(), "/ready/*".to_string()],
+        ..AuditSamplingSettings::default()
+    };
+
+    let chain = App::new()
+        .wrap(AuditRecorderFactory::<MockAuditEventSource>::new(
+            AuditRecorderOptions::new(Arc::new(audit)).with_sampling(sampling, Arc::new(metric)),
+        ))
+        .default_service(web::to(|| async move { HttpResponse::Ok().finish() }));
+    let service = test::init_service(chain).await;
+
+    // Act
+    for uri in ["/health", "/ready/database", "/healthy"] {
+        let _ = test::call_service(&service, test::TestRequest::get().uri(uri).to_request()).await;
+    }
+
+    // Expectations are verified automatically when the mocks are dropped at the end of the scope.
+}
+
+#[rstest]
+#[case(Decision::Deny, StatusCode::OK)]
+#[case(Decision::Allow, StatusCode::INTERNAL_SERVER_ERROR)]
+#[actix_web::test]
+async fn test_keep_denies_and_errors(#[case] decision: Decision, #[case] status: StatusCode) {
+    // Arrange
+    let mut audit = MockAuditWriter::new();
+    audit.expect_write().times(1).returning(|_| ());
+    let mut metric = MockAuditEventsSkippedMetric::new();
+    metric.expect_increment().never();
+    let sampling = AuditSamplingSettings {
+        exclude_paths: vec!["/*".to_string()],
+        allowed_sample_rate: 0.0,
+    };
+
+    let chain = App::new()
+        .wrap(AuditRecorderFactory::<AuditedResponse>::new(
+            AuditRecorderOptions::new(Arc::new(audit)).with_sampling(sampling, Arc::new(metric)),
+        ))
+        .wrap_fn(move |request, service| {
+            request
+                .extensions_mut()
+                .insert(AuditEvent::Intermediate(ChainedAuditEvent {
+                    decision: Some(decision),
+                    ..ChainedAuditEvent::begin()
+                }));
+            service.call(request)
+        })
+        .default_service(web::to(move || async move { HttpResponse::build(status).finish() }));
+    let service = test::init_service(chain).await;
+    let request = test::TestRequest::get().uri("/any-route").to_request();
+
+    // Act
+    let _ = test::call_service(&service, request).await;
+
+    // Expectations are verified automatically when the mocks are dropped at the end of the scope.
+}
+
+#[actix_web::test]
+async fn test_sample_allowed_requests() {
+    // Arrange
+    let mut audit = MockAuditWriter::new();
+    audit.expect_write().never();
+    let mut metric = MockAuditEventsSkippedMetric::new();
+    metric
+        .expect_increment()
+        .withf(|reason| reason == "sampled-out")
+        .times(1)
+        .returning(|_| ());
+    let sampling = AuditSamplingSettings {
+        allowed_sample_rate: 0.0,
+        ..AuditSamplingSettings::default()
+    };
+
+    let chain = App::new()
+        .wrap(AuditRecorderFactory::<MockAuditEventSource>::new(
+            AuditRecorderOptions::new(Arc::new(audit)).with_sampling(sampling, Arc::new(metric)),
+        ))
+        .default_service(web::to(|| async move { HttpResponse::Ok().finish() }));
+    let service = test::init_service(chain).await;
+    let request = test::TestRequest::get().uri("/any-route").to_request();
+
+    // Act
+    let response = test::call_service(&service, request).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn test_sample_by_chain_id() {
+    // Arrange
+    let sampling = AuditSamplingSettings {
+        allowed_sample_rate: 0.5,
+        ..AuditSamplingSettings::default()
+    };
+    let event = |chain_id: &str| {
+        AuditEvent::Final(ChainedAuditEvent {
+            chain_id: Some(chain_id.to_string()),
+            decision: Some(Decision::Allow),
+            ..ChainedAuditEvent::begin()
+        })
+    };
+
+    // Act
+    let reasons: Vec<Option<&str>> = [
+        "00000000-0000-4000-8000-000000000000",
+        "7fffffff-ffff-4fff-bfff-ffffffffffff",
+        "80000000-0000-4000-8000-000000000000",
+        "ffffffff-ffff-4fff-bfff-ffffffffffff",
+    ]
+    .into_iter()
+    .map(|chain_id| sampling.skip_reason("/any-route", &event(chain_id), StatusCode::OK))
+    .collect();
+
+    // Assert
+    assert_eq!(reasons, vec![None, None, Some("sampled-out"), Some("sampled-out")]);
+}
+
+/// Rejects every event as an asynchronous writer with a full buffer.
+struct FullAuditWriter;
+
+impl AuditWriter for FullAuditWriter {
+    fn write(&self, _event: AuditEvent) {}
+
+    fn try_write(&self, _event: AuditEvent) -> Result<(), AuditWriteError> {
+        Err(AuditWriteError::QueueFull)
+    }
+}
+
 mock! {
     pub AuditEventSource {}
 
     impl AuditEventSource for AuditEventSource {
-        fn audit_event(&self) -> AuditEvent;
+        fn audit_event(&self) -> Result<AuditEvent, AuditPipelineError>;
     }
 }
 
@@ -157,13 +698,34 @@ mock! {
     }
 }
 
+mock! {
+    pub AuditEventsSkippedMetric {}
+
+    impl AuditEventsSkippedMetric for AuditEventsSkippedMetric {
+        fn increment(&self, reason: &str);
+    }
+}
+
+mock! {
+    pub AuditPipelineFailureMetric {}
+
+    impl AuditPipelineFailureMetric for AuditPipelineFailureMetric {
+        fn increment(&self, reason_code: &str);
+    }
+}
+
+fn is_pipeline_failure(event: &AuditEvent, reason_code: &str) -> bool {
+    matches!(event, AuditEvent::Final(ChainedAuditEvent { reason: Some(reason), .. })
+        if reason.errors.iter().any(|e| e.starts_with(&format!("audit-pipeline-error: {}", reason_code))))
+}
+
 impl<B> TryFrom<ServiceResponse<B>> for MockAuditEventSource {
     type Error = actix_web::Error;
 
     fn try_from(_value: ServiceResponse<B>) -> Result<Self, Self::Error> {
         let mut mock = MockAuditEventSource::new();
         mock.expect_audit_event()
-            .returning(|| AuditEvent::Intermediate(ChainedAuditEvent::empty()));
+            .returning(|| Ok(AuditEvent::Intermediate(ChainedAuditEvent::empty())));
         Ok(mock)
     }
 }
@@ -1,33 +1,113 @@
 use crate::http::middleware::audit::audit_recorder::audit_recorder_factory::AuditRecorderFactory;
-use crate::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
+use crate::http::middleware::audit::audit_recorder::audit_recorder_options::AuditRecorderOptions;
 use crate::http::middleware::audit::audited_error::AuditedError;
 use crate::http::middleware::audit::audited_response::AuditedResponse;
 use crate::http::middleware::audit::begin_audit_chain::begin_audit_chain;
 use crate::http::middleware::audit::external_request::ExternalRequest;
+use crate::http::middleware::audit::internal_request::InternalRequest;
+use crate::http::middleware::audit::propagated_request::PropagatedRequest;
 use crate::http::middleware::extract_external_token::extract_external_token;
-use actix_web::Scope;
+use crate::http::middleware::extract_internal_token::extract_encrypted_token;
+use crate::http::middleware::resume_audit_chain::resume_audit_chain;
+use crate::http::middleware::validate_external_token::validate_external_token;
+use crate::services::audit::chained::audit_chain_header::AuditChainHeader;
+use crate::services::identity::external_token_validator::ExternalTokenValidator;
+use crate::services::identity::internal_token_decoder::InternalTokenDecoder;
 use actix_web::dev::HttpServiceFactory;
 use actix_web::middleware::from_fn;
+use actix_web::{Scope, web};
 use std::sync::Arc;
 
 /// Extension trait for attaching the complete audit middleware chain to an Actix [`Scope`].
 ///
 /// The resulting scope initializes an audit event, extracts the external token,
-/// and records a final audit event through the provided [`AuditWriter`].
+/// and records a final audit event with the provided [`AuditRecorderOptions`].
+///
+/// The audit recorder wraps the whole pipeline, so a request rejected by any middleware of the
+/// pipeline, including the start of the audit chain, is recorded.
 pub trait AuditScope {
     /// Wraps this scope with the audit middleware pipeline.
     ///
     /// Middleware order is significant:
+    /// - records the terminal audit event (`AuditRecorderFactory`),
+    /// - starts the audit chain (`begin_audit_chain`),
+    /// - extracts the external token (`extract_external_token`).
+    fn with_initial_audit_scope(self, recorder: AuditRecorderOptions) -> impl HttpServiceFactory;
+
+    /// Same as [`AuditScope::with_initial_audit_scope`], but additionally validates the
+    /// extracted external token with the provided [`ExternalTokenValidator`]
+    /// (`validate_external_token`) before the request reaches the handlers.
+    fn with_validated_audit_scope(
+        self,
+        recorder: AuditRecorderOptions,
+        validator: Arc<dyn ExternalTokenValidator>,
+    ) -> impl HttpServiceFactory;
+
+    /// Wraps this scope with the audit middleware pipeline for services receiving internal tokens.
+    ///
+    /// Middleware order is significant:
+    /// - records the terminal audit event (`AuditRecorderFactory`),
     /// - starts the audit chain (`begin_audit_chain`),
-    /// - extracts and validates the external token (`extract_external_token`),
-    /// - records the terminal audit event (`AuditRecorderFactory`).
-    fn with_initial_audit_scope(self, writer: Arc<dyn AuditWriter>) -> impl HttpServiceFactory;
+    /// - extracts the internal token (`extract_encrypted_token`),
+    /// - decodes the token with the provided [`InternalTokenDecoder`] and resumes the audit event
+    ///   embedded in v2 tokens (`resume_audit_chain`).
+    fn with_internal_audit_scope(
+        self,
+        recorder: AuditRecorderOptions,
+        decoder: Arc<dyn InternalTokenDecoder>,
+    ) -> impl HttpServiceFactory;
+
+    /// Wraps this scope with the audit middleware pipeline for services called by other services.
+    ///
+    /// Middleware order is significant:
+    /// - records the terminal audit event (`AuditRecorderFactory`),
+    /// - resumes the audit chain from the signed header of the caller, verified with the provided
+    ///   [`AuditChainHeader`] (`begin_audit_chain`).
+    fn with_propagated_audit_scope(
+        self,
+        recorder: AuditRecorderOptions,
+        header: Arc<AuditChainHeader>,
+    ) -> impl HttpServiceFactory;
 }
 
 impl AuditScope for Scope {
-    fn with_initial_audit_scope(self, writer: Arc<dyn AuditWriter>) -> impl HttpServiceFactory {
+    fn with_initial_audit_scope(self, recorder: AuditRecorderOptions) -> impl HttpServiceFactory {
         self.wrap(from_fn(extract_external_token::<ExternalRequest, AuditedError>))
-            .wrap(AuditRecorderFactory::<AuditedResponse<_>>::new(writer))
             .wrap(from_fn(begin_audit_chain::<ExternalRequest>))
+            .wrap(AuditRecorderFactory::<AuditedResponse<_>>::new(recorder))
+    }
+
+    fn with_validated_audit_scope(
+        self,
+        recorder: AuditRecorderOptions,
+        validator: Arc<dyn ExternalTokenValidator>,
+    ) -> impl HttpServiceFactory {
+        self.app_data(web::Data::from(validator))
+            .wrap(from_fn(validate_external_token::<AuditedError>))
+            .wrap(from_fn(extract_external_token::<ExternalRequest, AuditedError>))
+            .wrap(from_fn(begin_audit_chain::<ExternalRequest>))
+            .wrap(AuditRecorderFactory::<AuditedResponse<_>>::new(recorder))
+    }
+
+    fn with_internal_audit_scope(
+        self,
+        recorder: AuditRecorderOptions,
+        decoder: Arc<dyn InternalTokenDecoder>,
+    ) -> impl HttpServiceFactory {
+        self.app_data(web::Data::from(decoder))
+            .wrap(from_fn(resume_audit_chain::<AuditedError>))
+            .wrap(from_fn(extract_encrypted_token::<InternalRequest, AuditedError>))
+            .wrap(from_fn(begin_audit_chain::<InternalRequest>))
+            .wrap(AuditRecorderFactory::<AuditedResponse<_>>::new(recorder))
+    }
+
+    fn with_propagated_audit_scope(
+        self,
+        recorder: AuditRecorderOptions,
+        header: Arc<AuditChainHeader>,
+    ) -> impl HttpServiceFactory {
+        self.app_data(web::Data::from(header))
+            .wrap(from_fn(begin_audit_chain::<PropagatedRequest>))
+            .wrap(AuditRecorderFactory::<AuditedResponse<_>>::new(recorder))
     }
 }
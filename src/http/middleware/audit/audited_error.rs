@@ -1,12 +1,22 @@
 #[cfg(test)]
 mod tests;
 
+use crate::http::middleware::audit::audit_pipeline_error::AuditPipelineError;
+use crate::http::middleware::extract_external_token::authentication_challenge::AuthenticationChallenge;
 use crate::http::middleware::extract_external_token::external_token_error::ExternalTokenError;
+use crate::http::middleware::extract_internal_token::internal_token_error::InternalTokenError;
+use crate::http::middleware::token_source::token_source_error::TokenSourceError;
+use crate::http::middleware::token_source::{TokenSource, TokenSourceFor};
+use crate::models::external_token::ExternalToken;
+use crate::services::audit::chained::audit_chain_header::AuditChainHeaderError;
 use crate::services::audit::chained::audit_event::AuditEvent;
+use crate::services::audit::chained::chained_audit_event::ChainedAuditEvent;
+use crate::services::identity::external_token_validation_error::ExternalTokenValidationError;
 use actix_web::dev::ServiceRequest;
 use actix_web::error::InternalError;
 use actix_web::http::StatusCode;
-use actix_web::{HttpMessage, ResponseError};
+use actix_web::http::header::WWW_AUTHENTICATE;
+use actix_web::{HttpMessage, HttpResponse, ResponseError};
 use anyhow::anyhow;
 use std::error::Error;
 use std::fmt::{Debug, Display, Formatter};
@@ -22,111 +32,255 @@ pub struct AuditedError {
 impl AuditedError {
     /// Wraps a given `ResponseError` into an `AuditedError`, extracting the associated
     /// `AuditEvent` from the error's response extensions.
-    pub fn wrap(cause: impl ResponseError + 'static) -> AuditedError {
+    ///
+    /// Returns [`AuditPipelineError::MissingAuditEvent`] if the response of the error does not
+    /// contain an `AuditEvent` extension.
+    pub fn wrap(cause: impl ResponseError + 'static) -> Result<AuditedError, AuditPipelineError> {
         let event = cause
             .error_response()
             .extensions()
             .get::<AuditEvent>()
-            .expect("Attempt to wrap an error without an audit event")
-            .clone();
-        AuditedError {
+            .cloned()
+            .ok_or(AuditPipelineError::MissingAuditEvent)?;
+        Ok(AuditedError {
             event,
             cause: Box::new(cause),
-        }
+        })
     }
 
     /// Similar to `wrap` but extracts the `AuditEvent` from the request's extensions instead of
     /// the error's response.
     ///
-    /// # Panics
+    /// Returns [`AuditPipelineError::MissingAuditEvent`] if the request does not contain an
+    /// `AuditEvent` extension and [`AuditPipelineError::UnexpectedFinalEvent`] if the contained
+    /// event is `AuditEvent::Final`, since final audit events are not intended to be wrapped as errors.
+    pub fn from_request(
+        request: &ServiceRequest,
+        cause: impl Error + 'static,
+    ) -> Result<AuditedError, AuditPipelineError> {
+        let data = intermediate_event(request)?;
+        Ok(AuditedError {
+            event: AuditEvent::Intermediate(data),
+            cause: Box::new(InternalError::new(cause, StatusCode::INTERNAL_SERVER_ERROR)),
+        })
+    }
+
+    /// Returns the audit pipeline error if this error was raised because the audit middleware
+    /// invariants were violated.
+    pub fn pipeline_error(&self) -> Option<&AuditPipelineError> {
+        self.cause.downcast_ref::<AuditPipelineError>()
+    }
+
+    /// Wraps the audit pipeline error with the diagnostic audit event. The response status
+    /// is `500 Internal Server Error`.
+    fn pipeline_failure(error: AuditPipelineError) -> AuditedError {
+        AuditedError {
+            event: AuditEvent::pipeline_failure(&error),
+            cause: Box::new(error),
+        }
+    }
+
+    /// Wraps the error of an audit chain header that cannot be verified with the final audit event
+    /// of the rejected request. The response status is `401 Unauthorized` with an empty body.
+    pub(crate) fn audit_chain_rejected(error: AuditChainHeaderError) -> AuditedError {
+        AuditedError {
+            event: AuditEvent::audit_chain_rejected(&error),
+            cause: Box::new(InternalError::from_response(
+                error,
+                HttpResponse::Unauthorized().finish(),
+            )),
+        }
+    }
+
+    /// Finalizes the empty audit event of the request for a token that could not be read from the
+    /// source and responds with `401 Unauthorized` and the `WWW-Authenticate` challenge.
     ///
-    /// Panics if the request does not contain an AuditEvent extension.
-    /// Panics if the contained AuditEvent is AuditEvent::Final, since final
-    /// audit events are not intended to be wrapped as errors.
-    pub fn from_request(request: &ServiceRequest, cause: impl Error + 'static) -> AuditedError {
-        let event = request
-            .extensions()
-            .get::<AuditEvent>()
-            .expect("Attempt to wrap an error without an audit event")
-            .clone();
+    /// If the audit event of the request cannot be rejected, e.g. because it is missing, final or
+    /// not empty, the error is replaced with the [`AuditPipelineError`] and the diagnostic audit event.
+    fn token_rejected<Cause>(
+        request: &ServiceRequest,
+        source: &TokenSource,
+        reason_code: &str,
+        detail: Option<String>,
+        cause: Cause,
+        challenge: AuthenticationChallenge,
+    ) -> AuditedError
+    where
+        Cause: Debug + Display + 'static,
+    {
+        let event =
+            request_event(request).and_then(|event| Ok(event.reject_token(reason_code, source.to_string(), detail)?));
         match event {
-            AuditEvent::Final(_) => {
-                panic!("Final audit event in a request should not be wrapped for an error")
-            }
-            AuditEvent::Intermediate(data) => AuditedError {
-                event: AuditEvent::Intermediate(data),
-                cause: Box::new(InternalError::new(cause, StatusCode::INTERNAL_SERVER_ERROR)),
+            Ok(event) => AuditedError {
+                event,
+                cause: unauthorized(cause, challenge),
             },
+            Err(error) => AuditedError::pipeline_failure(error),
         }
     }
-}
-impl ExternalTokenError for AuditedError {
-    /// Creates an `AuditedError` in case when the external token is not present.
-    ///
-    /// # Panics
+
+    /// Finalizes the audit event of the request for a token rejected by the token validator and
+    /// responds with `401 Unauthorized` and the `WWW-Authenticate` challenge for the scheme of the
+    /// source the token was extracted from.
     ///
-    /// Panics if the request does not contain an AuditEvent extension.
-    /// Panics if the contained AuditEvent is AuditEvent::Final, since final
-    /// audit events are not intended to be wrapped as errors.
-    /// Panics if token is not present, but audit event is not empty
-    fn external_token_not_present(request: &ServiceRequest) -> AuditedError {
-        let event = request
-            .extensions()
-            .get::<AuditEvent>()
-            .expect("Attempt to wrap a request for an error without audit event")
-            .clone();
-        match event {
-            AuditEvent::Final(_) => {
-                panic!("Final audit event in a request should not be wrapped for token not present error")
-            }
-            AuditEvent::Intermediate(data) if data.is_empty() => AuditedError {
-                event: AuditEvent::token_not_present(),
-                cause: Box::new(InternalError::new(
-                    anyhow!("Token not present"),
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                )),
+    /// If the request does not contain an intermediate audit event, the error is replaced with
+    /// the [`AuditPipelineError`] and the diagnostic audit event.
+    fn token_validation_rejected(
+        request: &ServiceRequest,
+        reason_code: &str,
+        cause: ExternalTokenValidationError,
+        description: &'static str,
+    ) -> AuditedError {
+        let scheme = TokenSourceFor::<ExternalToken>::extracted(request)
+            .challenge_scheme()
+            .to_string();
+        match request_event(request).and_then(|event| Ok(event.reject_external_token(reason_code)?)) {
+            Ok(event) => AuditedError {
+                event,
+                cause: unauthorized(cause, AuthenticationChallenge::invalid_token(scheme, description)),
             },
-            AuditEvent::Intermediate(data) => {
-                panic!("Non-empty audit event when token is not present: {:?}", data)
-            }
+            Err(error) => AuditedError::pipeline_failure(error),
         }
     }
+}
+
+impl ExternalTokenError for AuditedError {
+    /// Creates an `AuditedError` in case when the external token is not present in the source.
+    /// The response challenges the client to authenticate without an error code.
+    fn external_token_not_present(request: &ServiceRequest, source: &TokenSource) -> AuditedError {
+        AuditedError::token_rejected(
+            request,
+            source,
+            "token-not-present",
+            None,
+            anyhow!("Token not present"),
+            AuthenticationChallenge::new(source.challenge_scheme()),
+        )
+    }
 
     /// Creates an `AuditedError` for requests where an external token is present
-    /// but cannot be parsed.
+    /// but cannot be extracted, e.g. because its proof of possession is invalid.
     ///
-    /// The method reads the current request `AuditEvent` from extensions and
-    /// converts an empty intermediate event into a final token-extraction-failed event,
-    /// preserving the original parsing error as the underlying HTTP error cause.
-    ///
-    /// # Panics
-    ///
-    /// Panics if the request does not contain an `AuditEvent` extension.
-    /// Panics if the contained event is `AuditEvent::Final`, because final events
-    /// are not expected at this stage.
-    /// Panics if the contained intermediate event is not empty because it is not expected in this
-    /// context.
-    fn token_extraction_failed(request: &ServiceRequest, cause: anyhow::Error) -> Self {
-        let event = request
-            .extensions()
-            .get::<AuditEvent>()
-            .expect("Attempt to wrap a request for an error without audit event")
-            .clone();
-        match event {
-            AuditEvent::Final(_) => {
-                panic!("Final audit event in a request should not be wrapped for token extracted error")
+    /// The audit event records the `token-extraction-failed` reason code and the original error
+    /// as the reason detail.
+    fn token_extraction_failed(request: &ServiceRequest, source: &TokenSource, cause: anyhow::Error) -> Self {
+        let challenge = match cause.downcast_ref::<TokenSourceError>() {
+            Some(TokenSourceError::InvalidProof(_)) => {
+                AuthenticationChallenge::invalid_dpop_proof("The DPoP proof is invalid")
             }
-            AuditEvent::Intermediate(data) if data.is_empty() => AuditedError {
-                event: AuditEvent::token_extraction_failed(cause.to_string()),
-                cause: Box::new(InternalError::new(cause, StatusCode::INTERNAL_SERVER_ERROR)),
+            _ => AuthenticationChallenge::invalid_request(source.challenge_scheme(), "The access token is malformed"),
+        };
+        let detail = Some(cause.to_string());
+        AuditedError::token_rejected(request, source, "token-extraction-failed", detail, cause, challenge)
+    }
+
+    /// Creates an `AuditedError` for requests where the external token was extracted but
+    /// rejected by the token validator. The audit event is finalized with the reason code of
+    /// the validation error.
+    fn token_validation_failed(request: &ServiceRequest, cause: ExternalTokenValidationError) -> Self {
+        let reason_code = format!("token-validation-failed: {}", cause.reason_code());
+        AuditedError::token_validation_rejected(request, &reason_code, cause, "The access token is invalid")
+    }
+
+    /// Creates an `AuditedError` for credentials with an authorization scheme the source does
+    /// not accept.
+    fn unsupported_authorization_scheme(request: &ServiceRequest, source: &TokenSource) -> Self {
+        AuditedError::token_rejected(
+            request,
+            source,
+            "token-unsupported-scheme",
+            None,
+            TokenSourceError::UnsupportedScheme,
+            AuthenticationChallenge::new(source.challenge_scheme()),
+        )
+    }
+
+    /// Creates an `AuditedError` for a token source value that cannot be parsed.
+    fn malformed_authorization_header(request: &ServiceRequest, source: &TokenSource) -> Self {
+        AuditedError::token_rejected(
+            request,
+            source,
+            "token-malformed-header",
+            None,
+            TokenSourceError::Malformed,
+            AuthenticationChallenge::invalid_request(source.challenge_scheme(), "The credentials are malformed"),
+        )
+    }
+
+    fn token_expired(request: &ServiceRequest, cause: ExternalTokenValidationError) -> Self {
+        AuditedError::token_validation_rejected(request, "token-expired", cause, "The access token expired")
+    }
+
+    fn invalid_token_signature(request: &ServiceRequest, cause: ExternalTokenValidationError) -> Self {
+        AuditedError::token_validation_rejected(
+            request,
+            "token-invalid-signature",
+            cause,
+            "The access token signature is invalid",
+        )
+    }
+
+    fn invalid_token_audience(request: &ServiceRequest, cause: ExternalTokenValidationError) -> Self {
+        AuditedError::token_validation_rejected(
+            request,
+            "token-invalid-audience",
+            cause,
+            "The access token is not intended for this service",
+        )
+    }
+}
+
+impl InternalTokenError for AuditedError {
+    /// Creates an `AuditedError` for requests where the internal token was extracted but cannot
+    /// be decoded. The audit event is finalized with the decoding error as the reason detail and
+    /// the response status is `401 Unauthorized` with an empty body.
+    ///
+    /// If the request does not contain an intermediate audit event, the error is replaced with
+    /// the [`AuditPipelineError`] and the diagnostic audit event.
+    fn internal_token_rejected(request: &ServiceRequest, cause: anyhow::Error) -> Self {
+        let detail = cause.to_string();
+        match request_event(request).and_then(|event| Ok(event.reject_internal_token(detail)?)) {
+            Ok(event) => AuditedError {
+                event,
+                cause: Box::new(InternalError::from_response(
+                    cause,
+                    HttpResponse::Unauthorized().finish(),
+                )),
             },
-            AuditEvent::Intermediate(data) => {
-                panic!("Non-empty audit event when token is not present: {:?}", data)
-            }
+            Err(error) => AuditedError::pipeline_failure(error),
         }
     }
 }
 
+/// Returns the audit event of the request.
+fn request_event(request: &ServiceRequest) -> Result<AuditEvent, AuditPipelineError> {
+    request
+        .extensions()
+        .get::<AuditEvent>()
+        .cloned()
+        .ok_or(AuditPipelineError::MissingAuditEvent)
+}
+
+/// Returns the intermediate audit event of the request.
+fn intermediate_event(request: &ServiceRequest) -> Result<ChainedAuditEvent, AuditPipelineError> {
+    match request_event(request)? {
+        AuditEvent::Intermediate(data) => Ok(data),
+        AuditEvent::Final(_) => Err(AuditPipelineError::UnexpectedFinalEvent),
+    }
+}
+
+/// Builds a `401 Unauthorized` response with the `WWW-Authenticate` challenge. The response body
+/// is empty, so the error message is only recorded in the audit event and never sent to the client.
+fn unauthorized<Cause>(cause: Cause, challenge: AuthenticationChallenge) -> Box<dyn ResponseError>
+where
+    Cause: Debug + Display + 'static,
+{
+    let response = HttpResponse::Unauthorized()
+        .insert_header((WWW_AUTHENTICATE, challenge.to_string()))
+        .finish();
+    Box::new(InternalError::from_response(cause, response))
+}
+
 /// The `Display` implementation for `AuditedError` simply formats the contained `AuditEvent`
 /// for debugging purposes.
 impl Display for AuditedError {
@@ -137,6 +291,10 @@ impl Display for AuditedError {
 
 /// The `ResponseError` implementation for `AuditedError` delegates to the underlying cause's
 impl ResponseError for AuditedError {
+    fn status_code(&self) -> StatusCode {
+        self.cause.status_code()
+    }
+
     fn error_response(&self) -> actix_web::HttpResponse {
         self.cause.error_response()
     }
@@ -1,26 +1,36 @@
+use crate::http::middleware::audit::audit_pipeline_error::AuditPipelineError;
 use crate::http::middleware::audit::audited_error::AuditedError;
 use crate::http::middleware::extract_external_token::external_token_error::ExternalTokenError;
+use crate::http::middleware::token_source::dpop_proof_error::DpopProofError;
+use crate::http::middleware::token_source::dpop_proof_validator::DpopProofValidator;
+use crate::http::middleware::token_source::token_source_error::TokenSourceError;
+use crate::http::middleware::token_source::{TokenSource, TokenSourceFor};
+use crate::models::external_token::ExternalToken;
 use crate::services::audit::chained::audit_event::AuditEvent;
 use crate::services::audit::chained::chained_audit_event::ChainedAuditEvent;
 use crate::services::audit::chained::token_audit_event::TokenAuditEvent;
+use crate::services::identity::external_token_validation_error::ExternalTokenValidationError;
+use actix_web::body::to_bytes;
 use actix_web::error::{ErrorInternalServerError, InternalError};
 use actix_web::http::StatusCode;
+use actix_web::http::header::WWW_AUTHENTICATE;
 use actix_web::test::TestRequest;
-use actix_web::{HttpMessage, HttpResponse};
+use actix_web::{HttpMessage, HttpResponse, ResponseError, web};
 use anyhow::anyhow;
 use assert_matches::assert_matches;
 use pretty_assertions::assert_eq;
+use std::sync::Arc;
+use std::time::Duration;
 
 #[test]
-#[should_panic(expected = "Attempt to wrap an error without an audit event")]
-fn test_audited_error_wrap_panic() {
+fn test_audited_error_wrap_without_audit_event() {
     // Arrange
 
     // Act
-    AuditedError::wrap(InternalError::new(anyhow!("Error"), StatusCode::INTERNAL_SERVER_ERROR));
+    let result = AuditedError::wrap(InternalError::new(anyhow!("Error"), StatusCode::INTERNAL_SERVER_ERROR));
 
     // Assert
-    // Test should panic
+    assert_matches!(result, Err(AuditPipelineError::MissingAuditEvent));
 }
 
 #[test]
@@ -35,27 +45,25 @@ fn test_audited_error_wrap_success() {
     let result = AuditedError::wrap(InternalError::from_response(anyhow!("Error"), response));
 
     // Assert
-    assert_matches!(result, audited_error => {
+    assert_matches!(result, Ok(audited_error) => {
         assert_matches!(audited_error.event, AuditEvent::Intermediate(_));
         assert_eq!(audited_error.cause.to_string(), "Error");
     });
 }
 
 #[test]
-#[should_panic(expected = "Attempt to wrap an error without an audit event")]
 fn test_audited_error_from_request_no_audit_event() {
     // Arrange
     let request = TestRequest::get().uri("/any-route").to_srv_request();
 
     // Act
-    AuditedError::from_request(&request, ErrorInternalServerError("Some error"));
+    let result = AuditedError::from_request(&request, ErrorInternalServerError("Some error"));
 
     // Assert
-    // Test should panic
+    assert_matches!(result, Err(AuditPipelineError::MissingAuditEvent));
 }
 
 #[test]
-#[should_panic(expected = "Final audit event in a request should not be wrapped for an error")]
 fn test_audited_error_from_request_final_audit_event() {
     // Arrange
     let request = TestRequest::get().uri("/any-route").to_srv_request();
@@ -65,10 +73,10 @@ fn test_audited_error_from_request_final_audit_event() {
         .insert(AuditEvent::Final(ChainedAuditEvent::empty()));
 
     // Act
-    AuditedError::from_request(&request, ErrorInternalServerError("Some error"));
+    let result = AuditedError::from_request(&request, ErrorInternalServerError("Some error"));
 
     // Assert
-    // Test should panic
+    assert_matches!(result, Err(AuditPipelineError::UnexpectedFinalEvent));
 }
 
 #[test]
@@ -84,7 +92,7 @@ fn test_audited_error_from_request_success() {
     let error = AuditedError::from_request(&request, ErrorInternalServerError("Error"));
 
     // Assert
-    assert_matches!(error, audited_error => {
+    assert_matches!(error, Ok(audited_error) => {
         assert_matches!(audited_error.event, AuditEvent::Intermediate(_));
         assert_eq!(audited_error.cause.to_string(), "Error");
     });
@@ -100,7 +108,7 @@ fn test_audited_error_external_token_not_present() {
         .insert(AuditEvent::Intermediate(ChainedAuditEvent::empty()));
 
     // Act
-    let error = AuditedError::external_token_not_present(&request);
+    let error = AuditedError::external_token_not_present(&request, &TokenSource::bearer());
 
     // Assert
     assert_matches!(error, audited_error => {
@@ -115,3 +123,128 @@ fn test_audited_error_external_token_not_present() {
         assert_eq!(audited_error.cause.to_string(), "Token not present");
     });
 }
+
+#[test]
+fn test_audited_error_external_token_not_present_challenge() {
+    // Arrange
+    let request = TestRequest::get().uri("/any-route").to_srv_request();
+    request
+        .request()
+        .extensions_mut()
+        .insert(AuditEvent::Intermediate(ChainedAuditEvent::empty()));
+
+    // Act
+    let error = AuditedError::external_token_not_present(&request, &TokenSource::bearer());
+
+    // Assert
+    let response = error.error_response();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(response.headers().get(WWW_AUTHENTICATE).unwrap(), "Bearer");
+}
+
+#[test]
+fn test_audited_error_invalid_dpop_proof_challenge() {
+    // Arrange
+    let request = TestRequest::get().uri("/any-route").to_srv_request();
+    request
+        .request()
+        .extensions_mut()
+        .insert(AuditEvent::Intermediate(ChainedAuditEvent::empty()));
+    let cause = TokenSourceError::InvalidProof(DpopProofError::MissingProof);
+
+    // Act
+    let error = AuditedError::token_extraction_failed(&request, &TokenSource::bearer(), cause.into());
+
+    // Assert
+    let response = error.error_response();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        response.headers().get(WWW_AUTHENTICATE).unwrap(),
+        "DPoP error=\"invalid_dpop_proof\", error_description=\"The DPoP proof is invalid\""
+    );
+    assert_matches!(error.event, AuditEvent::Final(ChainedAuditEvent {
+        external_token: Some(TokenAuditEvent { reason_errors, reason_detail: Some(reason_detail), .. }),
+        ..
+    }) if reason_errors.contains("token-extraction-failed")
+        && reason_detail == "Invalid DPoP proof: DPoP proof not present");
+}
+
+#[test]
+fn test_audited_error_external_token_not_present_without_audit_event() {
+    // Arrange
+    let request = TestRequest::get().uri("/any-route").to_srv_request();
+
+    // Act
+    let error = AuditedError::external_token_not_present(&request, &TokenSource::bearer());
+
+    // Assert
+    assert_eq!(error.error_response().status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(error.pipeline_error(), Some(&AuditPipelineError::MissingAuditEvent));
+    assert_matches!(error.event, AuditEvent::Final(ChainedAuditEvent {
+        reason: Some(reason),
+        ..
+    }) if reason.errors.iter().any(|e| e.starts_with("audit-pipeline-error: missing-audit-event")));
+}
+
+#[test]
+fn test_audited_error_token_expired_with_final_audit_event() {
+    // Arrange
+    let request = TestRequest::get().uri("/any-route").to_srv_request();
+    request
+        .request()
+        .extensions_mut()
+        .insert(AuditEvent::Final(ChainedAuditEvent::empty()));
+
+    // Act
+    let error = AuditedError::token_expired(&request, ExternalTokenValidationError::Expired);
+
+    // Assert
+    assert_eq!(error.error_response().status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(error.pipeline_error(), Some(&AuditPipelineError::UnexpectedFinalEvent));
+}
+
+#[actix_web::test]
+async fn test_audited_error_token_validation_challenge_uses_extracted_source() {
+    // Arrange
+    let validator = DpopProofValidator::new(Duration::from_secs(60), Duration::from_secs(5));
+    let request = TestRequest::get()
+        .uri("/any-route")
+        .app_data(web::Data::new(TokenSourceFor::<ExternalToken>::new(
+            TokenSource::bearer(),
+        )))
+        .to_srv_request();
+    request
+        .request()
+        .extensions_mut()
+        .insert(AuditEvent::Intermediate(ChainedAuditEvent::empty()));
+    TokenSourceFor::<ExternalToken>::record(&request, TokenSource::dpop(Arc::new(validator)));
+
+    // Act
+    let error = AuditedError::token_expired(&request, ExternalTokenValidationError::Expired);
+
+    // Assert
+    let response = error.error_response();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        response.headers().get(WWW_AUTHENTICATE).unwrap(),
+        "DPoP error=\"invalid_token\", error_description=\"The access token expired\""
+    );
+}
+
+#[actix_web::test]
+async fn test_audited_error_response_body_is_empty() {
+    // Arrange
+    let request = TestRequest::get().uri("/any-route").to_srv_request();
+    request
+        .request()
+        .extensions_mut()
+        .insert(AuditEvent::Intermediate(ChainedAuditEvent::empty()));
+    let cause = TokenSourceError::InvalidProof(DpopProofError::MissingProof);
+
+    // Act
+    let error = AuditedError::token_extraction_failed(&request, &TokenSource::bearer(), cause.into());
+
+    // Assert
+    let body = to_bytes(error.error_response().into_body()).await.unwrap();
+    assert!(body.is_empty(), "{:?}", body);
+}
@@ -1,3 +1,4 @@
+use crate::http::middleware::audit::audit_pipeline_error::AuditPipelineError;
 use crate::http::middleware::audit::audit_recorder::audit_event_source::AuditEventSource;
 use crate::services::audit::chained::audit_event::AuditEvent;
 use actix_web::HttpMessage;
@@ -9,13 +10,13 @@ use actix_web::dev::ServiceResponse;
 pub struct AuditedResponse<BodyType = BoxBody>(ServiceResponse<BodyType>);
 
 impl<BodyType> AuditEventSource for AuditedResponse<BodyType> {
-    fn audit_event(&self) -> AuditEvent {
+    fn audit_event(&self) -> Result<AuditEvent, AuditPipelineError> {
         self.0
             .request()
             .extensions()
             .get::<AuditEvent>()
             .cloned()
-            .expect("Audited event not exists in request extensions")
+            .ok_or(AuditPipelineError::MissingAuditEvent)
     }
 }
 
@@ -29,9 +30,7 @@ impl<BodyType> TryFrom<ServiceResponse<BodyType>> for AuditedResponse<BodyType>
         let contains = { value.request().extensions().contains::<AuditEvent>() };
 
         match contains {
-            false => Err(actix_web::error::ErrorInternalServerError(
-                "Audit event not found in request extensions",
-            )),
+            false => Err(AuditPipelineError::MissingAuditEvent.into()),
             true => Ok(Self(value)),
         }
     }
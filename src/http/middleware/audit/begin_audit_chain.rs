@@ -17,6 +17,6 @@ pub async fn begin_audit_chain<AuditContext: TryCreateAuditContext + 'static>(
     req: ServiceRequest,
     next: Next<impl MessageBody>,
 ) -> Result<ServiceResponse<impl MessageBody>, Error> {
-    let audited_request = AuditContext::try_create_audit_context(req)?;
+    let audited_request = AuditContext::try_create_audit_context(req).await?;
     next.call(audited_request.into()).await
 }
@@ -32,14 +32,14 @@ async fn test_begin_audit_chain() {
     // Act
     let _ = test::call_service(&service, request).await;
 
-    // Expectations are verified automatically when `mock_audit` is dropped at the end of the scope.
+    // Expectations are verified automatically when the mock context is dropped at the end of the scope.
 }
 
 mock! {
     pub AuditContext {}
 
     impl TryCreateAuditContext for AuditContext {
-        fn try_create_audit_context(request: ServiceRequest) -> Result<Self, actix_web::Error>;
+        async fn try_create_audit_context(request: ServiceRequest) -> Result<Self, actix_web::Error>;
     }
 
     impl Into<ServiceRequest> for AuditContext {
@@ -1,17 +1,17 @@
 #[cfg(test)]
 mod tests;
 
+use super::audit_pipeline_error::AuditPipelineError;
 use super::begin_audit_chain::try_create_audit_context::TryCreateAuditContext;
 use crate::contracts::internal_token::encrypted_token::EncryptedToken;
 use crate::http::middleware::audit::audit_recorder::audit_event_source::AuditEventSource;
-use crate::http::middleware::extract_external_token::token_with_id::TokenWithId;
 use crate::http::middleware::request_with_token_id::RequestWithTokenId;
 use crate::services::audit::chained::audit_event::AuditEvent;
 use crate::services::audit::chained::chained_audit_event::ChainedAuditEvent;
 use crate::services::audit::chained::token_audit_event::TokenAuditEvent;
+use crate::services::audit::token_id_strategy::TokenIdStrategy;
 use actix_web::HttpMessage;
 use actix_web::dev::ServiceRequest;
-use actix_web::error::ErrorInternalServerError;
 
 /// [`InternalRequest`] is a wrapper around `ServiceRequest` that indicates the request has been
 /// processed by the `begin_audit_chain` middleware and has an audit context initialized.
@@ -31,17 +31,17 @@ impl Into<ServiceRequest> for InternalRequest {
 /// The `TryCreateAuditContext` trait is implemented for `AuditedRequest` to define the logic for
 /// creating an audit context from a `ServiceRequest`. The implementation checks if the request
 /// already contains an `AuditEvent` in its extensions, which would indicate that an audit context
-/// has already been initialized.
+/// has already been initialized, and rejects such requests with
+/// [`AuditPipelineError::AuditChainAlreadyStarted`], recorded by the audit recorder wrapping the
+/// middleware.
 impl TryCreateAuditContext for InternalRequest {
-    fn try_create_audit_context(request: ServiceRequest) -> Result<Self, actix_web::Error> {
+    async fn try_create_audit_context(request: ServiceRequest) -> Result<Self, actix_web::Error> {
         if request.extensions().get::<AuditEvent>().is_some() {
-            return Err(ErrorInternalServerError(
-                "Failed to create audited request: audit chain already exists in request extensions",
-            ));
+            return Err(AuditPipelineError::AuditChainAlreadyStarted.into());
         }
         request
             .extensions_mut()
-            .insert(AuditEvent::Intermediate(ChainedAuditEvent::empty()));
+            .insert(AuditEvent::Intermediate(ChainedAuditEvent::begin()));
         Ok(InternalRequest(request))
     }
 }
@@ -49,38 +49,26 @@ impl TryCreateAuditContext for InternalRequest {
 impl AuditEventSource for InternalRequest {
     /// Returns the current [`AuditEvent`] stored in the request extensions.
     ///
-    /// # Panics
-    ///
-    /// Panics if the request does not contain an `AuditEvent` extension.
-    /// This should never happen for a properly constructed [`InternalRequest`],
+    /// Returns [`AuditPipelineError::MissingAuditEvent`] if the request does not contain an
+    /// `AuditEvent` extension. This should never happen for a properly constructed [`InternalRequest`],
     /// since `try_create_audit_context` always inserts an event on creation.
-    fn audit_event(&self) -> AuditEvent {
+    fn audit_event(&self) -> Result<AuditEvent, AuditPipelineError> {
         self.0
             .extensions()
             .get::<AuditEvent>()
             .cloned()
-            .expect("Audited event not exists in request extensions")
+            .ok_or(AuditPipelineError::MissingAuditEvent)
     }
 }
 
 impl From<ServiceRequest> for InternalRequest {
-    /// Wraps a [`ServiceRequest`] into an [`InternalRequest`], asserting that an audit context
-    /// is already present in request extensions.
+    /// Wraps a [`ServiceRequest`] into an [`InternalRequest`] that is expected to carry an audit context
+    /// in request extensions.
     ///
-    /// This is the counterpart to [`Into<ServiceRequest>`] and is used by the external token
+    /// This is the counterpart to [`Into<ServiceRequest>`] and is used by the internal token
     /// middleware to re-wrap the request after extracting the token, preserving the existing
-    /// audit context.
-    ///
-    /// # Panics
-    ///
-    /// Panics if the request does not contain an [`AuditEvent`] extension.
-
+    /// audit context. The audit context is verified when the token is added.
     fn from(value: ServiceRequest) -> Self {
-        value
-            .extensions()
-            .get::<AuditEvent>()
-            .cloned()
-            .expect("Audited event not exists in request extensions");
         InternalRequest(value)
     }
 }
@@ -88,47 +76,29 @@ impl From<ServiceRequest> for InternalRequest {
 impl RequestWithTokenId for InternalRequest {
     type Token = EncryptedToken;
 
-    /// Stores the external token identifier in the request's audit context and returns
+    /// Stores the internal token identifier in the request's audit context and returns
     /// the underlying [`ServiceRequest`].
     ///
-    /// The token id is derived from the provided [`ExternalToken`] and written into the
-    /// intermediate [`ChainedAuditEvent`] held in request extensions.
-    ///
-    /// # Panics
+    /// The token id is derived from the provided [`EncryptedToken`] with the [`TokenIdStrategy`]
+    /// and written into the intermediate [`ChainedAuditEvent`] held in request extensions.
     ///
-    /// Panics if the request extensions already contain an external token audit event,
-    /// indicating a duplicate token id assignment.
+    /// Returns [`AuditPipelineError::DuplicateToken`] if the request extensions already contain
+    /// an internal token audit event, indicating a duplicate token id assignment.
     ///
-    /// Panics if the audit event in extensions is not an `AuditEvent::Intermediate`,
-    /// which would mean the audit chain is in an unexpected state.
-    fn add_token(self, token: Self::Token) -> ServiceRequest {
-        let token_id = token.id();
-
+    /// Returns [`AuditPipelineError::UnexpectedFinalEvent`] or
+    /// [`AuditPipelineError::MissingAuditEvent`] if the audit event in extensions is not an
+    /// `AuditEvent::Intermediate`, which would mean the audit chain is in an unexpected state.
+    fn add_token(self, token: Self::Token, strategy: &TokenIdStrategy) -> Result<ServiceRequest, AuditPipelineError> {
         {
             let mut binding = self.0.extensions_mut();
-            let audit_event = binding.get_mut::<AuditEvent>();
-
-            // Mutate the audit event if the audit event complains the expected structure
-            if let Some(AuditEvent::Intermediate(chained_audit_event)) = audit_event {
-                if chained_audit_event.internal_token.is_some() {
-                    panic!(
-                        "External token audit event already exists in request extensions: {:?}",
-                        chained_audit_event.internal_token
-                    );
-                }
-                chained_audit_event.internal_token = Some(TokenAuditEvent::external().with_token_id(&token_id))
-            } else {
-                // Otherwise, stop processing immediately
-                panic!(
-                    "Expected Intermediate Audit event to exist in request extension, but got {:?}",
-                    audit_event
-                );
-            }
-
+            binding
+                .get_mut::<AuditEvent>()
+                .ok_or(AuditPipelineError::MissingAuditEvent)?
+                .record_internal_token(TokenAuditEvent::internal().with_token_id(token.as_ref(), strategy))?;
             binding.insert(token.clone());
         }
 
         // Return the updated value
-        self.0
+        Ok(self.0)
     }
 }
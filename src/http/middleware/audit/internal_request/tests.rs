@@ -1,18 +1,22 @@
 use super::InternalRequest;
+use crate::contracts::internal_token::encrypted_token::EncryptedToken;
+use crate::http::middleware::audit::audit_pipeline_error::AuditPipelineError;
 use crate::http::middleware::audit::begin_audit_chain::try_create_audit_context::TryCreateAuditContext;
+use crate::http::middleware::request_with_token_id::RequestWithTokenId;
 use crate::services::audit::chained::audit_event::AuditEvent;
+use crate::services::audit::token_id_strategy::TokenIdStrategy;
 use actix_web::HttpMessage;
 use actix_web::dev::ServiceRequest;
 use actix_web::test::TestRequest;
 use pretty_assertions::assert_matches;
 
-#[test]
-fn test_audit_event_initialization() {
+#[actix_web::test]
+async fn test_audit_event_initialization() {
     // Arrange
     let request = TestRequest::get().uri("/any-route").to_srv_request();
 
     // Act
-    let result = InternalRequest::try_create_audit_context(request);
+    let result = InternalRequest::try_create_audit_context(request).await;
 
     // Assert
     assert_matches!(result, Ok(_));
@@ -21,14 +25,14 @@ fn test_audit_event_initialization() {
     assert_eq!(audit_event.is_some(), true);
 }
 
-#[test]
-fn test_audit_event_double_initialization() {
+#[actix_web::test]
+async fn test_audit_event_double_initialization() {
     // Arrange
     let request = TestRequest::get().uri("/any-route").to_srv_request();
 
     // Act
-    let request: ServiceRequest = InternalRequest::try_create_audit_context(request).unwrap().into();
-    let result = InternalRequest::try_create_audit_context(request);
+    let request: ServiceRequest = InternalRequest::try_create_audit_context(request).await.unwrap().into();
+    let result = InternalRequest::try_create_audit_context(request).await;
 
     // Assert
     assert_matches!(result, Err(_));
@@ -40,3 +44,33 @@ fn test_audit_event_double_initialization() {
         true
     );
 }
+
+#[test]
+fn test_add_token_without_audit_event() {
+    // Arrange
+    let request = TestRequest::get().uri("/any-route").to_srv_request();
+
+    // Act
+    let result = InternalRequest::from(request)
+        .add_token(EncryptedToken::from("token".to_string()), &TokenIdStrategy::default());
+
+    // Assert
+    assert_matches!(result, Err(AuditPipelineError::MissingAuditEvent));
+}
+
+#[actix_web::test]
+async fn test_add_token_twice() {
+    // Arrange
+    let request = TestRequest::get().uri("/any-route").to_srv_request();
+    let request: ServiceRequest = InternalRequest::try_create_audit_context(request).await.unwrap().into();
+    let request = InternalRequest::from(request)
+        .add_token(EncryptedToken::from("token".to_string()), &TokenIdStrategy::default())
+        .unwrap();
+
+    // Act
+    let result = InternalRequest::from(request)
+        .add_token(EncryptedToken::from("token".to_string()), &TokenIdStrategy::default());
+
+    // Assert
+    assert_matches!(result, Err(AuditPipelineError::DuplicateToken("internal")));
+}
@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod tests;
+
+use super::audit_pipeline_error::AuditPipelineError;
+use super::audited_error::AuditedError;
+use super::begin_audit_chain::try_create_audit_context::TryCreateAuditContext;
+use crate::http::middleware::audit::audit_recorder::audit_event_source::AuditEventSource;
+use crate::services::audit::chained::audit_chain_header::{
+    AUDIT_CHAIN_HEADER, AuditChainHeader, AuditChainHeaderError,
+};
+use crate::services::audit::chained::audit_event::AuditEvent;
+use crate::services::audit::chained::chained_audit_event::ChainedAuditEvent;
+use actix_web::dev::ServiceRequest;
+use actix_web::{HttpMessage, web};
+
+/// [`PropagatedRequest`] is a wrapper around `ServiceRequest` for requests made by another
+/// service in the call tree. The audit context is resumed from the signed audit chain header
+/// of the caller, verified with the [`AuditChainHeader`] registered in the application data.
+/// Requests without the header start a new audit chain.
+#[derive(Debug)]
+pub struct PropagatedRequest(ServiceRequest);
+
+/// Converting to `ServiceRequest` allows us to easily pass a `PropagatedRequest` back to the next
+/// middleware or handler in the chain.
+impl From<PropagatedRequest> for ServiceRequest {
+    fn from(value: PropagatedRequest) -> Self {
+        value.0
+    }
+}
+
+/// The `TryCreateAuditContext` implementation verifies the audit chain header and inserts the
+/// resumed event to request extensions. Requests with a header that cannot be verified are
+/// rejected with `401 Unauthorized` and a final audit event recording the reason. Requests that already contain an audit event are rejected
+/// with [`AuditPipelineError::AuditChainAlreadyStarted`], recorded by the audit recorder wrapping
+/// the middleware.
+impl TryCreateAuditContext for PropagatedRequest {
+    async fn try_create_audit_context(request: ServiceRequest) -> Result<Self, actix_web::Error> {
+        if request.extensions().get::<AuditEvent>().is_some() {
+            return Err(AuditPipelineError::AuditChainAlreadyStarted.into());
+        }
+
+        let event = match request.headers().get(AUDIT_CHAIN_HEADER) {
+            None => ChainedAuditEvent::begin(),
+            Some(value) => {
+                let header = request
+                    .app_data::<web::Data<AuditChainHeader>>()
+                    .ok_or(AuditPipelineError::MissingAppData("AuditChainHeader"))?;
+                let verified = match value.to_str() {
+                    Ok(value) => header.verify(value).await,
+                    Err(e) => Err(AuditChainHeaderError::InvalidSignature(e.to_string())),
+                };
+                verified.map_err(AuditedError::audit_chain_rejected)?
+            }
+        };
+
+        request.extensions_mut().insert(AuditEvent::Intermediate(event));
+        Ok(PropagatedRequest(request))
+    }
+}
+
+impl AuditEventSource for PropagatedRequest {
+    /// Returns the current [`AuditEvent`] stored in the request extensions.
+    ///
+    /// Returns [`AuditPipelineError::MissingAuditEvent`] if the request does not contain an
+    /// `AuditEvent` extension.
+    fn audit_event(&self) -> Result<AuditEvent, AuditPipelineError> {
+        self.0
+            .extensions()
+            .get::<AuditEvent>()
+            .cloned()
+            .ok_or(AuditPipelineError::MissingAuditEvent)
+    }
+}
@@ -0,0 +1,93 @@
+use super::PropagatedRequest;
+use crate::http::middleware::audit::audit_pipeline_error::AuditPipelineError;
+use crate::http::middleware::audit::audited_error::AuditedError;
+use crate::http::middleware::audit::begin_audit_chain::try_create_audit_context::TryCreateAuditContext;
+use crate::services::audit::chained::audit_chain_header::{AUDIT_CHAIN_HEADER, AuditChainHeader};
+use crate::services::audit::chained::audit_event::AuditEvent;
+use crate::services::audit::chained::chained_audit_event::ChainedAuditEvent;
+use crate::services::key_management::signing_key::KeyStatus;
+use crate::testing::signing_keys::{make_key_manager, make_signing_key};
+use actix_web::dev::ServiceRequest;
+use actix_web::http::StatusCode;
+use actix_web::test::TestRequest;
+use actix_web::{HttpMessage, web};
+use pretty_assertions::{assert_eq, assert_matches};
+use std::time::Duration;
+
+#[actix_web::test]
+async fn test_audit_event_initialization_without_header() {
+    // Arrange
+    let request = TestRequest::get().uri("/any-route").to_srv_request();
+
+    // Act
+    let result = PropagatedRequest::try_create_audit_context(request).await;
+
+    // Assert
+    assert_matches!(result, Ok(_));
+    let service_request: ServiceRequest = result.unwrap().into();
+    let audit_event = service_request.extensions().get::<AuditEvent>().cloned();
+    assert_matches!(audit_event, Some(AuditEvent::Intermediate(event)) if event.is_empty());
+}
+
+#[actix_web::test]
+async fn test_audit_event_double_initialization() {
+    // Arrange
+    let request = TestRequest::get().uri("/any-route").to_srv_request();
+
+    // Act
+    let request: ServiceRequest = PropagatedRequest::try_create_audit_context(request)
+        .await
+        .unwrap()
+        .into();
+    let result = PropagatedRequest::try_create_audit_context(request).await;
+
+    // Assert
+    let error = result.unwrap_err();
+    assert_matches!(
+        error.as_error::<AuditPipelineError>(),
+        Some(AuditPipelineError::AuditChainAlreadyStarted)
+    );
+}
+
+#[actix_web::test]
+async fn test_audit_event_initialization_without_header_codec() {
+    // Arrange
+    let request = TestRequest::get()
+        .uri("/any-route")
+        .insert_header((AUDIT_CHAIN_HEADER, "signed-header"))
+        .to_srv_request();
+
+    // Act
+    let result = PropagatedRequest::try_create_audit_context(request).await;
+
+    // Assert
+    let error = result.unwrap_err();
+    assert_matches!(
+        error.as_error::<AuditPipelineError>(),
+        Some(AuditPipelineError::MissingAppData("AuditChainHeader"))
+    );
+}
+
+#[actix_web::test]
+async fn test_audit_event_initialization_with_invalid_header() {
+    // Arrange
+    let keys = make_key_manager(vec![make_signing_key("active", KeyStatus::Active)]).await;
+    let header = AuditChainHeader::new(keys, "gateway", Duration::from_secs(60));
+    let request = TestRequest::get()
+        .uri("/any-route")
+        .app_data(web::Data::new(header))
+        .insert_header((AUDIT_CHAIN_HEADER, "signed-header"))
+        .to_srv_request();
+
+    // Act
+    let result = PropagatedRequest::try_create_audit_context(request).await;
+
+    // Assert
+    let error = result.unwrap_err();
+    assert_eq!(error.as_response_error().status_code(), StatusCode::UNAUTHORIZED);
+    assert_matches!(
+        error.as_error::<AuditedError>(),
+        Some(AuditedError { event: AuditEvent::Final(ChainedAuditEvent { reason: Some(reason), .. }), .. })
+            if reason.errors.contains("audit-chain-rejected: invalid-signature")
+    );
+}
@@ -1,16 +1,42 @@
+use crate::contracts::dynamic_claims_collection::DynamicClaimsCollection;
+use crate::contracts::internal_token::encrypted_token::EncryptedToken;
+use crate::http::middleware::audit::audit_recorder::audit_recorder_options::AuditRecorderOptions;
 use crate::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
 use crate::http::middleware::audit::audit_scope::AuditScope;
 use crate::http::middleware::audit::audited_error::AuditedError;
+use crate::http::middleware::token_source::dpop_proof_validator::DpopProofValidator;
+use crate::http::middleware::token_source::{TokenSource, TokenSourceFor};
+use crate::models::external_token::ExternalToken;
+use crate::services::audit::chained::audit_chain_header::{AUDIT_CHAIN_HEADER, AuditChainHeader};
 use crate::services::audit::chained::audit_event::AuditEvent;
 use crate::services::audit::chained::chained_audit_event::ChainedAuditEvent;
 use crate::services::audit::chained::token_audit_event::TokenAuditEvent;
 use crate::services::audit::events::token_validation_event::TokenValidationResult;
+use crate::services::audit::token_id_strategy::TokenIdStrategy;
+use crate::services::identity::external_token_validation_error::ExternalTokenValidationError;
+use crate::services::identity::external_token_validator::ExternalTokenValidator;
+use crate::services::identity::internal_token_decoder::InternalTokenDecoder;
+use crate::services::identity::jwks_cache::JwksCache;
+use crate::services::identity::validated_external_token::ValidatedExternalToken;
+use crate::services::key_management::signing_key::KeyStatus;
+use crate::services::observability::open_telemetry::metrics::metric_recorders::audit_pipeline_failure::AuditPipelineFailureMetric;
+use crate::testing::signing_keys::{make_key_manager, make_signing_key};
+use crate::testing::stub_jwks_server::StubJwksServer;
+use actix_web::cookie::Cookie;
+use actix_web::dev::Service;
+use actix_web::http::StatusCode;
+use actix_web::http::header::WWW_AUTHENTICATE;
 use actix_web::web::scope;
 use actix_web::{App, HttpMessage, HttpRequest, test, web};
 use assert_matches::assert_matches;
+use async_trait::async_trait;
 use cedar_policy::Decision;
+use josekit::jwt::JwtPayload;
+use maplit::hashset;
 use mockall::mock;
+use rstest::rstest;
 use std::sync::Arc;
+use std::time::Duration;
 
 #[actix_web::test]
 async fn test_token_not_present() {
@@ -22,7 +48,7 @@ async fn test_token_not_present() {
     let mut writer = MockAuditWriter::new();
     writer.expect_final_failed_event();
 
-    let pipeline = scope.with_initial_audit_scope(Arc::new(writer));
+    let pipeline = scope.with_initial_audit_scope(AuditRecorderOptions::new(Arc::new(writer)));
 
     let chain = App::new().service(pipeline);
     let service = test::init_service(chain).await;
@@ -42,19 +68,30 @@ async fn test_token_not_present() {
                         token_id: _,
                         result: Some(TokenValidationResult::Deny),
                         reason_errors,
-                        token_type: _
+                        token_type: _,
+                        token_source: Some(token_source),
+                        reason_detail: None,
                     }),
                     internal_token: None,
                     action: None,
                     actor: None,
                     resource: None,
                     decision: Some(Decision::Deny),
-                    reason: None
+                    reason: None,
+                    attributes: _,
+                    parent: None,
+                    request: None,
+                    chain_id: Some(_),
+                    created_at: Some(_),
+                    finalized_at: Some(_),
+                    sequence: 0,
+                    event_id: _,
                 }
             ),
             ..
         }) => {
-            assert!(reason_errors.contains("token-not-present"), "{:?}", reason_errors)
+            assert!(reason_errors.contains("token-not-present"), "{:?}", reason_errors);
+            assert_eq!(token_source, "header:authorization");
         })
     });
 }
@@ -69,7 +106,7 @@ async fn test_broken_token() {
         "/token",
         web::to(|| async move { actix_web::HttpResponse::Ok().finish() }),
     );
-    let pipeline = scope.with_initial_audit_scope(Arc::new(writer));
+    let pipeline = scope.with_initial_audit_scope(AuditRecorderOptions::new(Arc::new(writer)));
 
     let chain = App::new().service(pipeline);
     let service = test::init_service(chain).await;
@@ -93,18 +130,69 @@ async fn test_broken_token() {
                         result: Some(TokenValidationResult::Deny),
                         reason_errors,
                         token_type: _,
+                        token_source: Some(token_source),
+                        reason_detail: None,
                     }),
                     internal_token: None,
                     action: None,
                     actor: None,
                     resource: None,
                     decision: Some(Decision::Deny),
-                    reason: None
+                    reason: None,
+                    attributes: _,
+                    parent: None,
+                    request: None,
+                    chain_id: Some(_),
+                    created_at: Some(_),
+                    finalized_at: Some(_),
+                    sequence: 0,
+                    event_id: _,
                 }
             ),
             ..
         }) => {
-            assert!(reason_errors.contains("token-extraction-failed: Invalid token format"), "{:?}", reason_errors)
+            assert!(reason_errors.contains("token-unsupported-scheme"), "{:?}", reason_errors);
+            assert_eq!(token_source, "header:authorization");
+        })
+    });
+}
+
+#[actix_web::test]
+async fn test_malformed_header() {
+    // Arrange
+    let mut writer = MockAuditWriter::new();
+    writer.expect_final_failed_event();
+
+    let scope = scope("").route(
+        "/token",
+        web::to(|| async move { actix_web::HttpResponse::Ok().finish() }),
+    );
+    let pipeline = scope.with_initial_audit_scope(AuditRecorderOptions::new(Arc::new(writer)));
+    let service = test::init_service(App::new().service(pipeline)).await;
+    let request = test::TestRequest::get()
+        .uri("/token")
+        .append_header(("Authorization", "Bearer two tokens"))
+        .to_request();
+
+    // Act
+    let response = test::try_call_service(&service, request).await;
+
+    // Assert that the response challenges the client and the audit records the reason
+    assert_matches!(response, Err(error) => {
+        let response = error.error_response();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response.headers().get(WWW_AUTHENTICATE).unwrap(),
+            "Bearer error=\"invalid_request\", error_description=\"The credentials are malformed\""
+        );
+        assert_matches!(error.as_error::<AuditedError>(), Some(AuditedError{
+            event: AuditEvent::Final(ChainedAuditEvent{
+                external_token: Some(TokenAuditEvent{ reason_errors, .. }),
+                ..
+            }),
+            ..
+        }) => {
+            assert!(reason_errors.contains("token-malformed-header"), "{:?}", reason_errors);
         })
     });
 }
@@ -124,14 +212,24 @@ async fn test_successful_token() {
                         token_id: Some(_),
                         result: None,
                         reason_errors: _,
-                        token_type: Some(token_type)
+                        token_type: Some(token_type),
+                        token_source: None,
+                        reason_detail: None,
                     }),
                     internal_token: None,
                     action: None,
                     actor: None,
                     resource: None,
                     decision: None,
-                    reason: None
+                    reason: None,
+                    attributes: _,
+                    parent: None,
+                    request: None,
+                    chain_id: Some(_),
+                    created_at: Some(_),
+                    finalized_at: None,
+                    sequence: 0,
+                    event_id: _,
                 }) => {
                     assert_eq!(token_type, "external".to_string());
                 }
@@ -145,7 +243,7 @@ async fn test_successful_token() {
     let mut writer = MockAuditWriter::new();
     writer.expect_write().times(1).returning(|_| ());
 
-    let pipeline = scope.with_initial_audit_scope(Arc::new(writer));
+    let pipeline = scope.with_initial_audit_scope(AuditRecorderOptions::new(Arc::new(writer)));
     let chain = App::new() /*.app_data(Data::new(Arc::new(writer)))*/
         .service(pipeline);
     let service = test::init_service(chain).await;
@@ -161,6 +259,777 @@ async fn test_successful_token() {
     // Assert is in the handler above
 }
 
+#[actix_web::test]
+async fn test_validated_token() {
+    let scope = scope("").route(
+        "/token",
+        web::to(|request: HttpRequest| async move {
+            // Assert that the validated token is available and accepted in the audit event
+            assert!(request.extensions().get::<ValidatedExternalToken>().is_some());
+
+            let event = request.extensions().get::<AuditEvent>().unwrap().clone();
+            assert_matches!(
+                event,
+                AuditEvent::Intermediate(ChainedAuditEvent {
+                    external_token: Some(TokenAuditEvent {
+                        token_id: Some(_),
+                        result: Some(TokenValidationResult::Allow),
+                        ..
+                    }),
+                    ..
+                })
+            );
+
+            actix_web::HttpResponse::Ok().finish()
+        }),
+    );
+
+    // Arrange
+    let mut writer = MockAuditWriter::new();
+    writer.expect_write().times(1).returning(|_| ());
+    let mut validator = MockExternalTokenValidator::new();
+    validator.expect_validate().times(1).returning(|_| {
+        Ok(ValidatedExternalToken {
+            issuer: "issuer".to_string(),
+            claims: JwtPayload::new(),
+        })
+    });
+
+    let pipeline = scope.with_validated_audit_scope(AuditRecorderOptions::new(Arc::new(writer)), Arc::new(validator));
+    let service = test::init_service(App::new().service(pipeline)).await;
+    let request = test::TestRequest::get()
+        .uri("/token")
+        .append_header(("Authorization", "Bearer token"))
+        .to_request();
+
+    // Act
+    let response = test::call_service(&service, request).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn test_token_id_from_validated_jti() {
+    let scope = scope("")
+        .app_data(web::Data::new(TokenIdStrategy::jti(TokenIdStrategy::Md5)))
+        .route(
+            "/token",
+            web::to(|request: HttpRequest| async move {
+                let event = request.extensions().get::<AuditEvent>().unwrap().clone();
+                assert_matches!(event, AuditEvent::Intermediate(ChainedAuditEvent{
+                    external_token: Some(TokenAuditEvent{ token_id: Some(token_id), .. }),
+                    ..
+                }) => {
+                    assert_eq!(token_id, "jti:issuer#token-1");
+                });
+                actix_web::HttpResponse::Ok().finish()
+            }),
+        );
+
+    // Arrange
+    let mut writer = MockAuditWriter::new();
+    writer.expect_write().times(1).returning(|_| ());
+    let mut validator = MockExternalTokenValidator::new();
+    validator.expect_validate().times(1).returning(|_| {
+        let mut claims = JwtPayload::new();
+        claims.set_jwt_id("token-1");
+        Ok(ValidatedExternalToken {
+            issuer: "issuer".to_string(),
+            claims,
+        })
+    });
+
+    let pipeline = scope.with_validated_audit_scope(AuditRecorderOptions::new(Arc::new(writer)), Arc::new(validator));
+    let service = test::init_service(App::new().service(pipeline)).await;
+    let request = test::TestRequest::get()
+        .uri("/token")
+        .append_header(("Authorization", "Bearer token"))
+        .to_request();
+
+    // Act
+    let response = test::call_service(&service, request).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn test_reject_dpop_bound_bearer_token() {
+    // Arrange
+    let scope = scope("").route(
+        "/token",
+        web::to(|| async move { actix_web::HttpResponse::Ok().finish() }),
+    );
+    let mut writer = MockAuditWriter::new();
+    writer.expect_write().times(1).returning(|_| ());
+    let mut validator = MockExternalTokenValidator::new();
+    validator.expect_validate().times(1).returning(|_| {
+        let mut claims = JwtPayload::new();
+        claims
+            .set_claim("cnf", Some(serde_json::json!({ "jkt": "thumbprint" })))
+            .unwrap();
+        Ok(ValidatedExternalToken {
+            issuer: "issuer".to_string(),
+            claims,
+        })
+    });
+
+    let pipeline = scope.with_validated_audit_scope(AuditRecorderOptions::new(Arc::new(writer)), Arc::new(validator));
+    let service = test::init_service(App::new().service(pipeline)).await;
+    let request = test::TestRequest::get()
+        .uri("/token")
+        .append_header(("Authorization", "Bearer token"))
+        .to_request();
+
+    // Act
+    let response = test::try_call_service(&service, request).await;
+
+    // Assert
+    assert_matches!(response, Err(error) => {
+        assert_eq!(error.as_response_error().status_code(), StatusCode::UNAUTHORIZED);
+        assert_matches!(error.as_error::<AuditedError>(), Some(AuditedError{
+            event: AuditEvent::Final(ChainedAuditEvent{
+                external_token: Some(TokenAuditEvent{ reason_errors, .. }),
+                ..
+            }),
+            ..
+        }) => {
+            assert!(reason_errors.iter().any(|e| e.contains("dpop-proof-required")), "{:?}", reason_errors);
+        })
+    });
+}
+
+#[actix_web::test]
+async fn test_rejected_token() {
+    // Arrange
+    let scope = scope("").route(
+        "/token",
+        web::to(|| async move { actix_web::HttpResponse::Ok().finish() }),
+    );
+    let mut writer = MockAuditWriter::new();
+    writer.expect_write().times(1).returning(|_| ());
+    let mut validator = MockExternalTokenValidator::new();
+    validator
+        .expect_validate()
+        .times(1)
+        .returning(|_| Err(ExternalTokenValidationError::Expired));
+
+    let pipeline = scope.with_validated_audit_scope(AuditRecorderOptions::new(Arc::new(writer)), Arc::new(validator));
+    let service = test::init_service(App::new().service(pipeline)).await;
+    let request = test::TestRequest::get()
+        .uri("/token")
+        .append_header(("Authorization", "Bearer token"))
+        .to_request();
+
+    // Act
+    let response = test::try_call_service(&service, request).await;
+
+    // Assert that the error keeps the token id and records the validation reason
+    assert_matches!(response, Err(error) => {
+        assert_eq!(error.as_response_error().status_code(), StatusCode::UNAUTHORIZED);
+        let cause = error.as_error::<AuditedError>();
+
+        assert_matches!(cause, Some(AuditedError{
+            event: AuditEvent::Final(
+                ChainedAuditEvent{
+                    external_token: Some(TokenAuditEvent{
+                        token_id: Some(_),
+                        result: Some(TokenValidationResult::Deny),
+                        reason_errors,
+                        token_type: Some(_),
+                        token_source: None,
+                        reason_detail: None,
+                    }),
+                    decision: Some(Decision::Deny),
+                    ..
+                }
+            ),
+            ..
+        }) => {
+            assert!(reason_errors.contains("token-expired"), "{:?}", reason_errors)
+        })
+    });
+}
+
+#[rstest]
+#[case(ExternalTokenValidationError::Expired, "token-expired", "The access token expired")]
+#[case(
+    ExternalTokenValidationError::InvalidSignature("signature".to_string()),
+    "token-invalid-signature",
+    "The access token signature is invalid"
+)]
+#[case(
+    ExternalTokenValidationError::InvalidAudience,
+    "token-invalid-audience",
+    "The access token is not intended for this service"
+)]
+#[case(
+    ExternalTokenValidationError::UnknownIssuer("issuer".to_string()),
+    "token-validation-failed: unknown-issuer",
+    "The access token is invalid"
+)]
+#[actix_web::test]
+async fn test_rejected_token_reason(
+    #[case] validation_error: ExternalTokenValidationError,
+    #[case] reason_code: &str,
+    #[case] description: &str,
+) {
+    // Arrange
+    let scope = scope("").route(
+        "/token",
+        web::to(|| async move { actix_web::HttpResponse::Ok().finish() }),
+    );
+    let mut writer = MockAuditWriter::new();
+    writer.expect_write().times(1).returning(|_| ());
+    let mut validator = MockExternalTokenValidator::new();
+    validator
+        .expect_validate()
+        .times(1)
+        .returning(move |_| Err(validation_error.clone()));
+
+    let pipeline = scope.with_validated_audit_scope(AuditRecorderOptions::new(Arc::new(writer)), Arc::new(validator));
+    let service = test::init_service(App::new().service(pipeline)).await;
+    let request = test::TestRequest::get()
+        .uri("/token")
+        .append_header(("Authorization", "Bearer token"))
+        .to_request();
+
+    // Act
+    let response = test::try_call_service(&service, request).await;
+
+    // Assert that each rejection has its own reason code and challenge
+    assert_matches!(response, Err(error) => {
+        let response = error.error_response();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response.headers().get(WWW_AUTHENTICATE).unwrap().to_str().unwrap(),
+            format!("Bearer error=\"invalid_token\", error_description=\"{}\"", description)
+        );
+        assert_matches!(error.as_error::<AuditedError>(), Some(AuditedError{
+            event: AuditEvent::Final(ChainedAuditEvent{
+                external_token: Some(TokenAuditEvent{ reason_errors, .. }),
+                ..
+            }),
+            ..
+        }) => {
+            assert!(reason_errors.contains(reason_code), "{:?}", reason_errors);
+        })
+    });
+}
+
+#[actix_web::test]
+async fn test_token_from_scope_source() {
+    // Arrange
+    let scope = scope("")
+        .app_data(web::Data::new(TokenSourceFor::<ExternalToken>::new(
+            TokenSource::cookie("session"),
+        )))
+        .route(
+            "/token",
+            web::to(|request: HttpRequest| async move {
+                let token: String = request.extensions().get::<ExternalToken>().cloned().unwrap().into();
+                assert_eq!(token, "token");
+                actix_web::HttpResponse::Ok().finish()
+            }),
+        );
+    let mut writer = MockAuditWriter::new();
+    writer.expect_write().times(1).returning(|_| ());
+
+    let pipeline = scope.with_initial_audit_scope(AuditRecorderOptions::new(Arc::new(writer)));
+    let service = test::init_service(App::new().service(pipeline)).await;
+    let request = test::TestRequest::get()
+        .uri("/token")
+        .cookie(Cookie::new("session", "token"))
+        .to_request();
+
+    // Act
+    let response = test::call_service(&service, request).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn test_token_not_present_in_scope_source() {
+    // Arrange
+    let scope = scope("")
+        .app_data(web::Data::new(TokenSourceFor::<ExternalToken>::new(
+            TokenSource::query("access_token"),
+        )))
+        .route(
+            "/token",
+            web::to(|| async move { actix_web::HttpResponse::Ok().finish() }),
+        );
+    let mut writer = MockAuditWriter::new();
+    writer.expect_final_failed_event();
+
+    let pipeline = scope.with_initial_audit_scope(AuditRecorderOptions::new(Arc::new(writer)));
+    let service = test::init_service(App::new().service(pipeline)).await;
+    let request = test::TestRequest::get()
+        .uri("/token")
+        .append_header(("Authorization", "Bearer token"))
+        .to_request();
+
+    // Act
+    let response = test::try_call_service(&service, request).await;
+
+    // Assert that the failure records the source that was tried
+    assert_matches!(response, Err(error) => {
+        assert_matches!(error.as_error::<AuditedError>(), Some(AuditedError{
+            event: AuditEvent::Final(ChainedAuditEvent{
+                external_token: Some(TokenAuditEvent{ reason_errors, token_source: Some(token_source), .. }),
+                ..
+            }),
+            ..
+        }) => {
+            assert!(reason_errors.contains("token-not-present"), "{:?}", reason_errors);
+            assert_eq!(token_source, "query:access_token");
+        })
+    });
+}
+
+#[actix_web::test]
+async fn test_token_id_from_scope_strategy() {
+    // Arrange
+    let scope = scope("")
+        .app_data(web::Data::new(TokenIdStrategy::hmac_sha256("secret")))
+        .route(
+            "/token",
+            web::to(|request: HttpRequest| async move {
+                let event = request.extensions().get::<AuditEvent>().unwrap().clone();
+                assert_matches!(event, AuditEvent::Intermediate(ChainedAuditEvent{
+                    external_token: Some(TokenAuditEvent{ token_id: Some(token_id), .. }),
+                    ..
+                }) => {
+                    assert_eq!(token_id, TokenIdStrategy::hmac_sha256("secret").token_id("token"));
+                });
+                actix_web::HttpResponse::Ok().finish()
+            }),
+        );
+    let mut writer = MockAuditWriter::new();
+    writer.expect_write().times(1).returning(|_| ());
+
+    let pipeline = scope.with_initial_audit_scope(AuditRecorderOptions::new(Arc::new(writer)));
+    let service = test::init_service(App::new().service(pipeline)).await;
+    let request = test::TestRequest::get()
+        .uri("/token")
+        .append_header(("Authorization", "Bearer token"))
+        .to_request();
+
+    // Act
+    let response = test::call_service(&service, request).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn test_invalid_dpop_proof() {
+    // Arrange
+    let validator = DpopProofValidator::new(Duration::from_secs(60), Duration::from_secs(5));
+    let scope = scope("")
+        .app_data(web::Data::new(TokenSourceFor::<ExternalToken>::new(TokenSource::dpop(
+            Arc::new(validator),
+        ))))
+        .route(
+            "/token",
+            web::to(|| async move { actix_web::HttpResponse::Ok().finish() }),
+        );
+    let mut writer = MockAuditWriter::new();
+    writer.expect_final_failed_event();
+
+    let pipeline = scope.with_initial_audit_scope(AuditRecorderOptions::new(Arc::new(writer)));
+    let service = test::init_service(App::new().service(pipeline)).await;
+    let request = test::TestRequest::get()
+        .uri("/token")
+        .append_header(("Authorization", "DPoP token"))
+        .append_header(("DPoP", "not-a-proof"))
+        .to_request();
+
+    // Act
+    let response = test::try_call_service(&service, request).await;
+
+    // Assert that the proof failure is recorded as an extraction failure
+    assert_matches!(response, Err(error) => {
+        assert_matches!(error.as_error::<AuditedError>(), Some(AuditedError{
+            event: AuditEvent::Final(ChainedAuditEvent{
+                external_token: Some(TokenAuditEvent{
+                    reason_errors,
+                    token_source: Some(token_source),
+                    reason_detail: Some(reason_detail),
+                    ..
+                }),
+                ..
+            }),
+            ..
+        }) => {
+            assert_eq!(reason_errors, &hashset! {"token-extraction-failed".to_string()});
+            assert!(reason_detail.starts_with("Invalid DPoP proof: Malformed DPoP proof"), "{}", reason_detail);
+            assert_eq!(token_source, "dpop:authorization");
+        })
+    });
+}
+
+#[actix_web::test]
+async fn test_internal_scope_resumes_audit_event() {
+    // Arrange
+    let scope = scope("").route(
+        "/token",
+        web::to(|request: HttpRequest| async move {
+            let event = request.extensions().get::<AuditEvent>().unwrap().clone();
+            assert_matches!(event, AuditEvent::Intermediate(ChainedAuditEvent{
+                external_token: Some(TokenAuditEvent{ token_id: Some(external_token_id), .. }),
+                internal_token: Some(TokenAuditEvent{
+                    token_id: Some(_),
+                    result: Some(TokenValidationResult::Allow),
+                    token_type: Some(token_type),
+                    ..
+                }),
+                action: Some(action),
+                ..
+            }) => {
+                assert_eq!(external_token_id, "md5:issuer");
+                assert_eq!(token_type, "internal");
+                assert_eq!(action, "issue-token");
+            });
+            assert!(request.extensions().get::<DynamicClaimsCollection>().is_some());
+            actix_web::HttpResponse::Ok().finish()
+        }),
+    );
+    let mut writer = MockAuditWriter::new();
+    writer.expect_write().times(1).returning(|_| ());
+    let mut decoder = MockInternalTokenDecoder::new();
+    decoder.expect_decode().times(1).returning(|_| Ok(make_v2_claims()));
+
+    let pipeline = scope.with_internal_audit_scope(AuditRecorderOptions::new(Arc::new(writer)), Arc::new(decoder));
+    let service = test::init_service(App::new().service(pipeline)).await;
+    let request = test::TestRequest::get()
+        .uri("/token")
+        .append_header(("Authorization", "encrypted-token"))
+        .to_request();
+
+    // Act
+    let response = test::call_service(&service, request).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn test_internal_scope_ignores_external_token_source() {
+    // Arrange
+    let scope = scope("")
+        .app_data(web::Data::new(TokenSourceFor::<ExternalToken>::new(
+            TokenSource::cookie("session"),
+        )))
+        .route(
+            "/token",
+            web::to(|| async move { actix_web::HttpResponse::Ok().finish() }),
+        );
+    let mut writer = MockAuditWriter::new();
+    writer.expect_write().times(1).returning(|_| ());
+    let mut decoder = MockInternalTokenDecoder::new();
+    decoder
+        .expect_decode()
+        .withf(|token| token.as_ref() == "encrypted-token")
+        .times(1)
+        .returning(|_| Ok(make_v2_claims()));
+
+    let pipeline = scope.with_internal_audit_scope(AuditRecorderOptions::new(Arc::new(writer)), Arc::new(decoder));
+    let service = test::init_service(App::new().service(pipeline)).await;
+    let request = test::TestRequest::get()
+        .uri("/token")
+        .append_header(("Authorization", "encrypted-token"))
+        .cookie(Cookie::new("session", "external-token"))
+        .to_request();
+
+    // Act
+    let response = test::call_service(&service, request).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn test_internal_scope_keeps_audit_event_of_v1_token() {
+    // Arrange
+    let scope = scope("").route(
+        "/token",
+        web::to(|request: HttpRequest| async move {
+            let event = request.extensions().get::<AuditEvent>().unwrap().clone();
+            assert_matches!(
+                event,
+                AuditEvent::Intermediate(ChainedAuditEvent {
+                    external_token: None,
+                    internal_token: Some(TokenAuditEvent {
+                        result: Some(TokenValidationResult::Allow),
+                        ..
+                    }),
+                    action: None,
+                    ..
+                })
+            );
+            actix_web::HttpResponse::Ok().finish()
+        }),
+    );
+    let mut writer = MockAuditWriter::new();
+    writer.expect_write().times(1).returning(|_| ());
+    let mut decoder = MockInternalTokenDecoder::new();
+    decoder.expect_decode().times(1).returning(|_| {
+        let mut claims = JwtPayload::new();
+        claims.set_claim("boxer.sneaksanddata.com/api-version", Some("v1".into()))?;
+        Ok(claims)
+    });
+
+    let pipeline = scope.with_internal_audit_scope(AuditRecorderOptions::new(Arc::new(writer)), Arc::new(decoder));
+    let service = test::init_service(App::new().service(pipeline)).await;
+    let request = test::TestRequest::get()
+        .uri("/token")
+        .append_header(("Authorization", "encrypted-token"))
+        .to_request();
+
+    // Act
+    let response = test::call_service(&service, request).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn test_internal_scope_rejected_token() {
+    // Arrange
+    let scope = scope("").route(
+        "/token",
+        web::to(|| async move { actix_web::HttpResponse::Ok().finish() }),
+    );
+    let mut writer = MockAuditWriter::new();
+    writer.expect_write().times(1).returning(|_| ());
+    let mut decoder = MockInternalTokenDecoder::new();
+    decoder
+        .expect_decode()
+        .times(1)
+        .returning(|_| Err(anyhow::anyhow!("Invalid token signature")));
+
+    let pipeline = scope.with_internal_audit_scope(AuditRecorderOptions::new(Arc::new(writer)), Arc::new(decoder));
+    let service = test::init_service(App::new().service(pipeline)).await;
+    let request = test::TestRequest::get()
+        .uri("/token")
+        .append_header(("Authorization", "encrypted-token"))
+        .to_request();
+
+    // Act
+    let response = test::try_call_service(&service, request).await;
+
+    // Assert that the internal token is denied in the final audit event
+    assert_matches!(response, Err(error) => {
+        assert_eq!(error.as_response_error().status_code(), StatusCode::UNAUTHORIZED);
+        assert_matches!(error.as_error::<AuditedError>(), Some(AuditedError{
+            event: AuditEvent::Final(ChainedAuditEvent{
+                internal_token: Some(TokenAuditEvent{
+                    token_id: Some(_),
+                    result: Some(TokenValidationResult::Deny),
+                    reason_errors,
+                    reason_detail: Some(reason_detail),
+                    ..
+                }),
+                decision: Some(Decision::Deny),
+                ..
+            }),
+            ..
+        }) => {
+            assert_eq!(reason_errors, &hashset! {"internal-token-decoding-failed".to_string()});
+            assert_eq!(reason_detail, "Invalid token signature");
+        })
+    });
+}
+
+#[actix_web::test]
+async fn test_propagated_scope_resumes_audit_chain() {
+    // Arrange
+    let (header, server) = make_audit_chain_header().await;
+    let value = header
+        .sign(&ChainedAuditEvent {
+            actor: Some("alice".to_string()),
+            ..ChainedAuditEvent::empty()
+        })
+        .unwrap();
+    let scope = scope("").route(
+        "/token",
+        web::to(|| async move { actix_web::HttpResponse::Ok().finish() }),
+    );
+    let mut writer = MockAuditWriter::new();
+    writer
+        .expect_write()
+        .times(1)
+        .withf(|event| {
+            matches!(event, AuditEvent::Intermediate(ChainedAuditEvent { parent: Some(link), .. })
+                if link.issuer == "gateway"
+                    && link.event.as_ref().and_then(|e| e.actor.as_deref()) == Some("alice"))
+        })
+        .returning(|_| ());
+
+    let pipeline = scope.with_propagated_audit_scope(AuditRecorderOptions::new(Arc::new(writer)), header);
+    let service = test::init_service(App::new().service(pipeline)).await;
+    let request = test::TestRequest::get()
+        .uri("/token")
+        .append_header((AUDIT_CHAIN_HEADER, value))
+        .to_request();
+
+    // Act
+    let response = test::call_service(&service, request).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    server.stop().await;
+}
+
+#[actix_web::test]
+async fn test_propagated_scope_rejects_invalid_header() {
+    // Arrange
+    let (header, server) = make_audit_chain_header().await;
+    let scope = scope("").route(
+        "/token",
+        web::to(|| async move { actix_web::HttpResponse::Ok().finish() }),
+    );
+    let mut writer = MockAuditWriter::new();
+    writer
+        .expect_write()
+        .times(1)
+        .withf(|event| {
+            matches!(event, AuditEvent::Final(ChainedAuditEvent {
+                decision: Some(Decision::Deny),
+                reason: Some(reason),
+                parent: None,
+                ..
+            }) if reason.errors.contains("audit-chain-rejected: invalid-signature"))
+        })
+        .returning(|_| ());
+
+    let pipeline = scope.with_propagated_audit_scope(AuditRecorderOptions::new(Arc::new(writer)), header);
+    let service = test::init_service(App::new().service(pipeline)).await;
+    let request = test::TestRequest::get()
+        .uri("/token")
+        .append_header((AUDIT_CHAIN_HEADER, "not-a-jws"))
+        .to_request();
+
+    // Act
+    let response = test::try_call_service(&service, request).await;
+
+    // Assert
+    let error = response.expect_err("Expected the request to be rejected");
+    assert_eq!(error.as_response_error().status_code(), StatusCode::UNAUTHORIZED);
+    server.stop().await;
+}
+
+#[actix_web::test]
+async fn test_audit_chain_already_started() {
+    // Arrange
+    let scope = scope("").route(
+        "/token",
+        web::to(|| async move { actix_web::HttpResponse::Ok().finish() }),
+    );
+    let mut writer = MockAuditWriter::new();
+    writer
+        .expect_write()
+        .withf(|event| {
+            matches!(event, AuditEvent::Final(ChainedAuditEvent { reason: Some(reason), .. })
+                if reason.errors.iter().any(|e| e.starts_with("audit-pipeline-error: audit-chain-already-started")))
+        })
+        .times(1)
+        .returning(|_| ());
+    let mut metric = MockAuditPipelineFailureMetric::new();
+    metric
+        .expect_increment()
+        .withf(|reason_code| reason_code == "audit-chain-already-started")
+        .times(1)
+        .returning(|_| ());
+
+    let recorder = AuditRecorderOptions::new(Arc::new(writer)).with_pipeline_failure_metric(Arc::new(metric));
+    let app = App::new()
+        .service(scope.with_initial_audit_scope(recorder))
+        .wrap_fn(|request, service| {
+            request
+                .extensions_mut()
+                .insert(AuditEvent::Intermediate(ChainedAuditEvent::begin()));
+            service.call(request)
+        });
+    let service = test::init_service(app).await;
+    let request = test::TestRequest::get()
+        .uri("/token")
+        .append_header(("Authorization", "Bearer token"))
+        .to_request();
+
+    // Act
+    let response = test::try_call_service(&service, request).await;
+
+    // Assert
+    let error = response.expect_err("Expected the request to be rejected");
+    assert_eq!(
+        error.as_response_error().status_code(),
+        StatusCode::INTERNAL_SERVER_ERROR
+    );
+}
+
+/// Creates the header of the `gateway` service trusting its own headers, verified with the keys
+/// published by the returned server.
+async fn make_audit_chain_header() -> (Arc<AuditChainHeader>, StubJwksServer) {
+    let key = make_signing_key("active", KeyStatus::Active);
+    let server = StubJwksServer::start(vec![key.public_jwk().clone()]).unwrap();
+    let keys = make_key_manager(vec![key]).await;
+    let header = AuditChainHeader::new(keys, "gateway", Duration::from_secs(60)).with_trusted_issuer(
+        "gateway",
+        JwksCache::new(server.jwks_uri(), Duration::from_secs(60), Duration::ZERO),
+    );
+    (Arc::new(header), server)
+}
+
+fn make_v2_claims() -> JwtPayload {
+    let mut issued_event = ChainedAuditEvent::empty();
+    issued_event.external_token = Some(TokenAuditEvent {
+        token_id: Some("md5:issuer".to_string()),
+        ..TokenAuditEvent::external()
+    });
+    issued_event.action = Some("issue-token".to_string());
+
+    let mut claims = JwtPayload::new();
+    claims
+        .set_claim("boxer.sneaksanddata.com/api-version", Some("v2".into()))
+        .unwrap();
+    claims
+        .set_claim(
+            "boxer.sneaksanddata.com/audit-event",
+            Some(serde_json::to_value(issued_event).unwrap()),
+        )
+        .unwrap();
+    claims
+}
+
+mock! {
+    pub InternalTokenDecoder {}
+
+    impl InternalTokenDecoder for InternalTokenDecoder {
+        fn decode(&self, token: &EncryptedToken) -> anyhow::Result<DynamicClaimsCollection>;
+    }
+}
+
+mock! {
+    pub AuditPipelineFailureMetric {}
+
+    impl AuditPipelineFailureMetric for AuditPipelineFailureMetric {
+        fn increment(&self, reason_code: &str);
+    }
+}
+
+mock! {
+    pub ExternalTokenValidator {}
+
+    #[async_trait]
+    impl ExternalTokenValidator for ExternalTokenValidator {
+        async fn validate(&self, token: &ExternalToken) -> Result<ValidatedExternalToken, ExternalTokenValidationError>;
+    }
+}
+
 mock! {
 
     pub AuditWriter {}
@@ -183,6 +1052,8 @@ impl MockAuditWriter {
                             result: Some(TokenValidationResult::Deny),
                             reason_errors: _,
                             token_type: None,
+                            token_source: Some(_),
+                            reason_detail: _,
                         }),
                         internal_token: None,
                         action: None,
@@ -190,6 +1061,14 @@ impl MockAuditWriter {
                         resource: None,
                         decision: Some(Decision::Deny),
                         reason: None,
+                        attributes: _,
+                        parent: None,
+                        request: None,
+                        chain_id: Some(_),
+                        created_at: Some(_),
+                        finalized_at: Some(_),
+                        sequence: 0,
+                        event_id: _,
                     })
                 )
             })
@@ -1,3 +1,4 @@
+pub mod authentication_challenge;
 pub mod external_token_error;
 pub mod token_with_id;
 
@@ -9,9 +10,11 @@ use actix_web::dev::{ServiceRequest, ServiceResponse};
 use actix_web::middleware::Next;
 
 /// Extracts the external token from the incoming request and inserts the extracted token to
-/// request extensions for further processing. If the token is not present or extraction fails,
-/// returns an error response. Inserts a token ID to the AuditEvent for the incoming request if
-/// the token is successfully extracted.
+/// request extensions for further processing. The token is read from the `TokenSource`
+/// registered for the scope, or from the `Authorization` header with the `Bearer` scheme by
+/// default. If the token is not present or extraction fails, returns an error response.
+/// Inserts a token ID to the AuditEvent for the incoming request if the token is successfully
+/// extracted.
 pub async fn extract_external_token<Request, Error>(
     request: ServiceRequest,
     next: Next<impl MessageBody>,
@@ -20,5 +23,5 @@ where
     Error: ExternalTokenError + 'static,
     Request: RequestWithTokenId<Token = ExternalToken>,
 {
-    super::extract_token_from_header::<ExternalToken, Request, Error>(request, next).await
+    super::extract_token::<ExternalToken, Request, Error>(request, next).await
 }
@@ -0,0 +1,63 @@
+use std::fmt::{Display, Formatter};
+
+/// [`AuthenticationChallenge`] is the value of the `WWW-Authenticate` header returned with
+/// `401 Unauthorized` responses, following section 3 of RFC 6750.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthenticationChallenge {
+    scheme: String,
+    error: Option<&'static str>,
+    description: Option<&'static str>,
+}
+
+impl AuthenticationChallenge {
+    /// A challenge without an error code, used when the request carries no credentials.
+    pub fn new(scheme: impl Into<String>) -> Self {
+        AuthenticationChallenge {
+            scheme: scheme.into(),
+            error: None,
+            description: None,
+        }
+    }
+
+    /// A challenge for a request that is missing a parameter or carries malformed credentials.
+    pub fn invalid_request(scheme: impl Into<String>, description: &'static str) -> Self {
+        AuthenticationChallenge {
+            error: Some("invalid_request"),
+            description: Some(description),
+            ..AuthenticationChallenge::new(scheme)
+        }
+    }
+
+    /// A challenge for a token that is expired, revoked, malformed or invalid for other reasons.
+    pub fn invalid_token(scheme: impl Into<String>, description: &'static str) -> Self {
+        AuthenticationChallenge {
+            error: Some("invalid_token"),
+            description: Some(description),
+            ..AuthenticationChallenge::new(scheme)
+        }
+    }
+
+    /// A challenge for a request with an invalid `DPoP` proof (RFC 9449, section 7.1).
+    pub fn invalid_dpop_proof(description: &'static str) -> Self {
+        AuthenticationChallenge {
+            error: Some("invalid_dpop_proof"),
+            description: Some(description),
+            ..AuthenticationChallenge::new("DPoP")
+        }
+    }
+}
+
+/// Formats the challenge as a header value, e.g. `Bearer error="invalid_token"`.
+impl Display for AuthenticationChallenge {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.scheme)?;
+        if let Some(error) = self.error {
+            write!(f, " error=\"{}\"", error)?;
+        }
+        if let Some(description) = self.description {
+            let separator = if self.error.is_some() { "," } else { "" };
+            write!(f, "{} error_description=\"{}\"", separator, description)?;
+        }
+        Ok(())
+    }
+}
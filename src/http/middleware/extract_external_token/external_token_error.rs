@@ -1,15 +1,67 @@
+use crate::http::middleware::token_source::TokenSource;
+use crate::http::middleware::token_source::token_source_error::TokenSourceError;
+use crate::services::identity::external_token_validation_error::ExternalTokenValidationError;
 use actix_web::ResponseError;
 use actix_web::dev::ServiceRequest;
 
 /// Error contract for failures related to extracting the external token from an incoming request.
 ///
-/// Implementors provide constructors for the two middleware failure cases:
-/// missing `Authorization` header and token parsing/validation failure.
+/// Implementors provide constructors for the middleware failure cases:
+/// token missing from the source, token parsing failure and token validation failure.
+///
+/// The typed cases (unsupported scheme, malformed header, expired token, invalid signature and
+/// wrong audience) fall back to the generic extraction and validation failures by default.
+/// Implementors should override them to report each case with its own status and reason.
 pub trait ExternalTokenError: ResponseError {
-    /// Builds an error for requests that do not include an `Authorization` header.
-    fn external_token_not_present(request: &ServiceRequest) -> Self;
+    /// Builds an error for requests that do not carry a token in the configured source.
+    fn external_token_not_present(request: &ServiceRequest, source: &TokenSource) -> Self;
 
-    /// Builds an error for requests where the `Authorization` header exists
+    /// Builds an error for requests where the token source exists
     /// but the token cannot be extracted or parsed.
-    fn token_extraction_failed(request: &ServiceRequest, cause: anyhow::Error) -> Self;
+    fn token_extraction_failed(request: &ServiceRequest, source: &TokenSource, cause: anyhow::Error) -> Self;
+
+    /// Builds an error for requests where the token was extracted but rejected by the
+    /// external token validator.
+    fn token_validation_failed(request: &ServiceRequest, cause: ExternalTokenValidationError) -> Self;
+
+    /// Builds an error for requests carrying credentials with an authorization scheme that the
+    /// source does not accept.
+    fn unsupported_authorization_scheme(request: &ServiceRequest, source: &TokenSource) -> Self
+    where
+        Self: Sized,
+    {
+        Self::token_extraction_failed(request, source, TokenSourceError::UnsupportedScheme.into())
+    }
+
+    /// Builds an error for requests where the token source value cannot be parsed.
+    fn malformed_authorization_header(request: &ServiceRequest, source: &TokenSource) -> Self
+    where
+        Self: Sized,
+    {
+        Self::token_extraction_failed(request, source, TokenSourceError::Malformed.into())
+    }
+
+    /// Builds an error for tokens rejected because they are expired.
+    fn token_expired(request: &ServiceRequest, cause: ExternalTokenValidationError) -> Self
+    where
+        Self: Sized,
+    {
+        Self::token_validation_failed(request, cause)
+    }
+
+    /// Builds an error for tokens rejected because their signature is invalid.
+    fn invalid_token_signature(request: &ServiceRequest, cause: ExternalTokenValidationError) -> Self
+    where
+        Self: Sized,
+    {
+        Self::token_validation_failed(request, cause)
+    }
+
+    /// Builds an error for tokens rejected because they were not issued for this service.
+    fn invalid_token_audience(request: &ServiceRequest, cause: ExternalTokenValidationError) -> Self
+    where
+        Self: Sized,
+    {
+        Self::token_validation_failed(request, cause)
+    }
 }
@@ -1,10 +1,14 @@
-use actix_web::http::header::HeaderValue;
+use crate::http::middleware::token_source::TokenSource;
 
-/// Contract for tokens that can be parsed from an HTTP header and expose a stable token id.
+/// Contract for tokens that can be extracted from a [`TokenSource`].
 ///
-/// Implementors are expected to validate header format in `TryFrom<HeaderValue>` and provide
-/// an identifier suitable for audit and trace records.
-pub trait TokenWithId: TryFrom<HeaderValue, Error = anyhow::Error> {
-    /// Returns the token identifier used in audit events.
-    fn id(&self) -> String;
+/// Implementors are built from the raw token value returned by the source. The token id written
+/// to audit records is derived from the raw value with the [`TokenIdStrategy`] of the scope.
+///
+/// [`TokenIdStrategy`]: crate::services::audit::token_id_strategy::TokenIdStrategy
+pub trait TokenWithId: From<String> {
+    /// Returns the source used when no [`TokenSource`] is registered for the request scope.
+    fn default_source() -> TokenSource {
+        TokenSource::bearer()
+    }
 }
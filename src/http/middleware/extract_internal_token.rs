@@ -1,3 +1,5 @@
+pub mod internal_token_error;
+
 use super::request_with_token_id::RequestWithTokenId;
 use crate::contracts::internal_token::encrypted_token::EncryptedToken;
 use crate::http::middleware::extract_external_token::external_token_error::ExternalTokenError;
@@ -6,7 +8,8 @@ use actix_web::dev::{ServiceRequest, ServiceResponse};
 use actix_web::middleware::Next;
 
 /// Extracts the internal token from the incoming request and inserts the extracted token to
-/// request extensions for further processing. This method does neither decrypt nor validates the
+/// request extensions for further processing. The token is read from the `TokenSource`
+/// registered for the scope, or from the raw `Authorization` header value by default. This method does neither decrypt nor validates the
 /// extracted token, it inserts a token ID to the AuditEvent for the incoming request if
 /// the token is successfully extracted. Additionally, this method inserts the raw token value for
 /// further processing. The main purpose os this method is unification the audit logging.
@@ -18,5 +21,5 @@ where
     Error: ExternalTokenError + 'static,
     Request: RequestWithTokenId<Token = EncryptedToken>,
 {
-    super::extract_token_from_header::<EncryptedToken, Request, Error>(request, next).await
+    super::extract_token::<EncryptedToken, Request, Error>(request, next).await
 }
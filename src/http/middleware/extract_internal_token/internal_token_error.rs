@@ -0,0 +1,9 @@
+use actix_web::ResponseError;
+use actix_web::dev::ServiceRequest;
+
+/// Error contract for failures related to decoding the internal token of an incoming request.
+pub trait InternalTokenError: ResponseError {
+    /// Builds an error for requests where the internal token was extracted but cannot be
+    /// decrypted, verified or does not carry a valid audit event.
+    fn internal_token_rejected(request: &ServiceRequest, cause: anyhow::Error) -> Self;
+}
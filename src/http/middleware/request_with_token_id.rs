@@ -1,4 +1,6 @@
+use crate::http::middleware::audit::audit_pipeline_error::AuditPipelineError;
 use crate::http::middleware::extract_external_token::token_with_id::TokenWithId;
+use crate::services::audit::token_id_strategy::TokenIdStrategy;
 use actix_web::dev::ServiceRequest;
 
 /// Adds an external token identifier to request-scoped audit context.
@@ -13,6 +15,8 @@ pub trait RequestWithTokenId: From<ServiceRequest> {
     /// Stores the provided token in the request context and returns the updated request.
     /// The method additionally enriches the audit event coming to the request with the token id.
     /// This method should be called to add the token id and convert the request to the appropriate
-    /// type for downstream handlers and middleware.
-    fn add_token(self, token: Self::Token) -> ServiceRequest;
+    /// type for downstream handlers and middleware. The token id is derived with the provided strategy.
+    /// Returns an [`AuditPipelineError`] if the audit context of the request is missing or in an
+    /// unexpected state.
+    fn add_token(self, token: Self::Token, strategy: &TokenIdStrategy) -> Result<ServiceRequest, AuditPipelineError>;
 }
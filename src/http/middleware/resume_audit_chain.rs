@@ -0,0 +1,58 @@
+use crate::contracts::dynamic_claims_collection::DynamicClaims;
+use crate::contracts::internal_token::encrypted_token::EncryptedToken;
+use crate::contracts::internal_token::v2::boxer_claims::read_audit_event;
+use crate::http::middleware::audit::audit_pipeline_error::AuditPipelineError;
+use crate::http::middleware::extract_internal_token::internal_token_error::InternalTokenError;
+use crate::services::audit::chained::audit_event::AuditEvent;
+use crate::services::identity::internal_token_decoder::InternalTokenDecoder;
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::{HttpMessage, web};
+
+/// Decodes the internal token extracted by the `extract_encrypted_token` middleware with the
+/// [`InternalTokenDecoder`] registered in the application data and inserts the claims to request
+/// extensions.
+///
+/// For v2 tokens, the audit event of the request is replaced with the audit event embedded in the
+/// token, so the chain started by the issuer is resumed. The internal token entry recorded during
+/// extraction is kept and marked as accepted. Other token versions keep the audit event of the
+/// request. Returns an [`AuditPipelineError`] if the audit event of the request is missing or
+/// cannot be resumed.
+pub async fn resume_audit_chain<Error>(
+    decoder: web::Data<dyn InternalTokenDecoder>,
+    request: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error>
+where
+    Error: InternalTokenError + 'static,
+{
+    let token = request.extensions().get::<EncryptedToken>().cloned();
+    let Some(token) = token else {
+        return Err(AuditPipelineError::MissingRequestExtension("EncryptedToken").into());
+    };
+
+    let claims = decoder
+        .decode(&token)
+        .map_err(|e| Error::internal_token_rejected(&request, e))?;
+    let resumed = match claims.get_version() {
+        Ok(version) if version == "v2" => {
+            Some(read_audit_event(&claims).map_err(|e| Error::internal_token_rejected(&request, e.into()))?)
+        }
+        _ => None,
+    };
+
+    {
+        let mut extensions = request.extensions_mut();
+        let event = extensions
+            .get_mut::<AuditEvent>()
+            .ok_or(AuditPipelineError::MissingAuditEvent)?;
+        if let Some(resumed) = resumed {
+            event.resume(resumed).map_err(AuditPipelineError::from)?;
+        }
+        event.accept_internal_token().map_err(AuditPipelineError::from)?;
+        extensions.insert(claims);
+    }
+
+    next.call(request).await
+}
@@ -0,0 +1,230 @@
+#[cfg(test)]
+mod tests;
+
+pub mod authorization_scheme;
+pub mod dpop_proof;
+pub mod dpop_proof_error;
+pub mod dpop_proof_validator;
+pub mod token_source_error;
+
+use crate::http::middleware::extract_external_token::token_with_id::TokenWithId;
+use crate::http::middleware::token_source::authorization_scheme::AuthorizationScheme;
+use crate::http::middleware::token_source::dpop_proof_validator::DpopProofValidator;
+use crate::http::middleware::token_source::token_source_error::TokenSourceError;
+use actix_web::dev::ServiceRequest;
+use actix_web::http::header::{AUTHORIZATION, HeaderName};
+use actix_web::{HttpMessage, web};
+use std::collections::HashMap;
+use std::fmt::{Debug, Display, Formatter};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// [`TokenSource`] describes where a token is carried in the incoming request.
+///
+/// The source is registered per scope and token type as `web::Data<TokenSourceFor<TokenType>>`.
+/// Token extraction middleware falls back to the default source of the extracted token type
+/// otherwise.
+#[derive(Debug, Clone)]
+pub enum TokenSource {
+    /// Reads the token from a header. When schemes are provided, the header value must follow
+    /// the `<scheme> <token>` format of RFC 6750 with one of the schemes. Otherwise, the whole
+    /// header value is the token.
+    Header {
+        name: HeaderName,
+        schemes: Vec<AuthorizationScheme>,
+    },
+
+    /// Reads the token from a cookie.
+    Cookie { name: String },
+
+    /// Reads the token from a query parameter.
+    Query { name: String },
+
+    /// Reads a sender-constrained token from the `Authorization` header with the `DPoP` scheme
+    /// and validates the proof of possession sent in the `DPoP` header. The proof key is inserted
+    /// in the request extensions as [`DpopProof`].
+    ///
+    /// [`DpopProof`]: crate::http::middleware::token_source::dpop_proof::DpopProof
+    Dpop { validator: Arc<DpopProofValidator> },
+}
+
+impl TokenSource {
+    /// The `Authorization` header with the `Bearer` scheme.
+    pub fn bearer() -> Self {
+        TokenSource::Header {
+            name: AUTHORIZATION,
+            schemes: vec![AuthorizationScheme::Bearer],
+        }
+    }
+
+    /// A header carrying the raw token value without an authorization scheme.
+    pub fn header(name: HeaderName) -> Self {
+        TokenSource::Header { name, schemes: vec![] }
+    }
+
+    /// A header carrying the token with one of the provided authorization schemes.
+    pub fn header_with_schemes(name: HeaderName, schemes: Vec<AuthorizationScheme>) -> Self {
+        TokenSource::Header { name, schemes }
+    }
+
+    pub fn cookie(name: impl Into<String>) -> Self {
+        TokenSource::Cookie { name: name.into() }
+    }
+
+    pub fn query(name: impl Into<String>) -> Self {
+        TokenSource::Query { name: name.into() }
+    }
+
+    pub fn dpop(validator: Arc<DpopProofValidator>) -> Self {
+        TokenSource::Dpop { validator }
+    }
+
+    /// Returns the authorization scheme used in `WWW-Authenticate` challenges for this source.
+    /// Sources that do not use an authorization scheme challenge with `Bearer`.
+    pub fn challenge_scheme(&self) -> &str {
+        match self {
+            TokenSource::Header { schemes, .. } => schemes.first().map(AuthorizationScheme::name).unwrap_or("Bearer"),
+            TokenSource::Dpop { .. } => "DPoP",
+            TokenSource::Cookie { .. } | TokenSource::Query { .. } => "Bearer",
+        }
+    }
+
+    /// Extracts the raw token from the request.
+    pub fn extract(&self, request: &ServiceRequest) -> Result<String, TokenSourceError> {
+        match self {
+            TokenSource::Header { name, schemes } => {
+                let value = request.headers().get(name).ok_or(TokenSourceError::NotPresent)?;
+                let value = value.to_str().map_err(|_| TokenSourceError::Malformed)?;
+                if schemes.is_empty() {
+                    non_empty(value.trim())
+                } else {
+                    parse_credentials(value, schemes).map(|(_, token)| token.to_string())
+                }
+            }
+            TokenSource::Cookie { name } => {
+                let cookie = request.cookie(name).ok_or(TokenSourceError::NotPresent)?;
+                non_empty(cookie.value().trim())
+            }
+            TokenSource::Query { name } => {
+                let query = web::Query::<HashMap<String, String>>::from_query(request.query_string())
+                    .map_err(|_| TokenSourceError::Malformed)?;
+                let value = query.get(name).ok_or(TokenSourceError::NotPresent)?;
+                non_empty(value.trim())
+            }
+            TokenSource::Dpop { validator } => {
+                let value = request
+                    .headers()
+                    .get(AUTHORIZATION)
+                    .ok_or(TokenSourceError::NotPresent)?;
+                let value = value.to_str().map_err(|_| TokenSourceError::Malformed)?;
+                let (_, token) = parse_credentials(value, &[AuthorizationScheme::DPoP])?;
+                let proof = validator
+                    .validate(request, token)
+                    .map_err(TokenSourceError::InvalidProof)?;
+                request.extensions_mut().insert(proof);
+                Ok(token.to_string())
+            }
+        }
+    }
+}
+
+/// [`TokenSourceFor`] registers the [`TokenSource`] of a single token type in the app data, so
+/// the external and the internal token of a scope are never read from each other's source.
+pub struct TokenSourceFor<TokenType> {
+    source: TokenSource,
+    _marker: PhantomData<fn() -> TokenType>,
+}
+
+impl<TokenType: TokenWithId + 'static> TokenSourceFor<TokenType> {
+    pub fn new(source: TokenSource) -> Self {
+        TokenSourceFor {
+            source,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the source of the token type registered for the request scope, or the default
+    /// source of the token type.
+    pub fn resolve(request: &ServiceRequest) -> TokenSource {
+        request
+            .app_data::<web::Data<TokenSourceFor<TokenType>>>()
+            .map(|registered| registered.source.clone())
+            .unwrap_or_else(TokenType::default_source)
+    }
+
+    /// Records the source the token was extracted from in the request extensions.
+    pub(crate) fn record(request: &ServiceRequest, source: TokenSource) {
+        request
+            .extensions_mut()
+            .insert(TokenSourceFor::<TokenType>::new(source));
+    }
+
+    /// Returns the source the token was extracted from, or the source resolved for the request
+    /// scope if the token has not been extracted.
+    pub fn extracted(request: &ServiceRequest) -> TokenSource {
+        request
+            .extensions()
+            .get::<TokenSourceFor<TokenType>>()
+            .map(|recorded| recorded.source.clone())
+            .unwrap_or_else(|| Self::resolve(request))
+    }
+}
+
+impl<TokenType> Debug for TokenSourceFor<TokenType> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("TokenSourceFor").field(&self.source).finish()
+    }
+}
+
+/// Formats the source for audit records, e.g. `header:authorization`, `dpop:authorization`,
+/// `cookie:session`.
+impl Display for TokenSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenSource::Header { name, .. } => write!(f, "header:{}", name),
+            TokenSource::Cookie { name } => write!(f, "cookie:{}", name),
+            TokenSource::Query { name } => write!(f, "query:{}", name),
+            TokenSource::Dpop { .. } => write!(f, "dpop:{}", AUTHORIZATION),
+        }
+    }
+}
+
+/// Parses credentials in the `<scheme> <token>` format (RFC 6750, section 2.1). The scheme is
+/// matched case-insensitively, whitespace around the token is ignored and the token must be
+/// a valid `b64token`. Credentials with a scheme not listed in `schemes` are reported as
+/// [`TokenSourceError::UnsupportedScheme`].
+pub fn parse_credentials<'a, 'b>(
+    value: &'a str,
+    schemes: &'b [AuthorizationScheme],
+) -> Result<(&'b AuthorizationScheme, &'a str), TokenSourceError> {
+    let (scheme, token) = value
+        .trim()
+        .split_once([' ', '\t'])
+        .ok_or(TokenSourceError::Malformed)?;
+    let scheme = schemes
+        .iter()
+        .find(|s| s.name().eq_ignore_ascii_case(scheme))
+        .ok_or(TokenSourceError::UnsupportedScheme)?;
+    let token = token.trim();
+    if is_b64token(token) {
+        Ok((scheme, token))
+    } else {
+        Err(TokenSourceError::Malformed)
+    }
+}
+
+fn is_b64token(token: &str) -> bool {
+    let value = token.trim_end_matches('=');
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '~' | '+' | '/'))
+}
+
+fn non_empty(value: &str) -> Result<String, TokenSourceError> {
+    if value.is_empty() {
+        Err(TokenSourceError::Malformed)
+    } else {
+        Ok(value.to_string())
+    }
+}
@@ -0,0 +1,25 @@
+/// An authorization scheme accepted by a header [`TokenSource`].
+///
+/// [`TokenSource`]: crate::http::middleware::token_source::TokenSource
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthorizationScheme {
+    /// The `Bearer` scheme of RFC 6750.
+    Bearer,
+
+    /// The `DPoP` scheme of RFC 9449 for sender-constrained tokens.
+    DPoP,
+
+    /// A custom scheme with the given name.
+    Custom(String),
+}
+
+impl AuthorizationScheme {
+    /// Returns the scheme name as it appears in the header.
+    pub fn name(&self) -> &str {
+        match self {
+            AuthorizationScheme::Bearer => "Bearer",
+            AuthorizationScheme::DPoP => "DPoP",
+            AuthorizationScheme::Custom(name) => name,
+        }
+    }
+}
@@ -0,0 +1,42 @@
+#[cfg(test)]
+mod tests;
+
+use crate::services::identity::external_token_validation_error::ExternalTokenValidationError;
+use josekit::jwt::JwtPayload;
+use serde_json::Value;
+
+/// The key of the valid `DPoP` proof sent with the access token. The token source inserts it in
+/// the request extensions, so the key binding can be confirmed once the access token signature
+/// is validated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DpopProof {
+    thumbprint: String,
+}
+
+impl DpopProof {
+    pub fn new(thumbprint: String) -> Self {
+        DpopProof { thumbprint }
+    }
+
+    /// Returns the JWK SHA-256 thumbprint of the proof key.
+    pub fn thumbprint(&self) -> &str {
+        &self.thumbprint
+    }
+}
+
+/// Confirms the key binding of the validated access token claims (RFC 9449, section 6).
+///
+/// A token bound to a key with the `cnf.jkt` claim is only accepted with a proof signed by that
+/// key, and a proof is only accepted with a token bound to its key.
+pub fn confirm_key_binding(claims: &JwtPayload, proof: Option<&DpopProof>) -> Result<(), ExternalTokenValidationError> {
+    let confirmation = claims
+        .claim("cnf")
+        .and_then(|cnf| cnf.get("jkt"))
+        .and_then(Value::as_str);
+    match (confirmation, proof) {
+        (None, None) => Ok(()),
+        (Some(_), None) => Err(ExternalTokenValidationError::ProofRequired),
+        (Some(jkt), Some(proof)) if jkt == proof.thumbprint() => Ok(()),
+        _ => Err(ExternalTokenValidationError::KeyBindingMismatch),
+    }
+}
@@ -0,0 +1,32 @@
+use crate::http::middleware::token_source::dpop_proof::{DpopProof, confirm_key_binding};
+use crate::services::identity::external_token_validation_error::ExternalTokenValidationError;
+use josekit::jwt::JwtPayload;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use serde_json::json;
+
+#[rstest]
+#[case(None, None, Ok(()))]
+#[case(Some("key"), Some("key"), Ok(()))]
+#[case(Some("key"), None, Err(ExternalTokenValidationError::ProofRequired))]
+#[case(Some("key"), Some("other"), Err(ExternalTokenValidationError::KeyBindingMismatch))]
+#[case(None, Some("key"), Err(ExternalTokenValidationError::KeyBindingMismatch))]
+fn test_confirm_key_binding(
+    #[case] jkt: Option<&str>,
+    #[case] thumbprint: Option<&str>,
+    #[case] expected: Result<(), ExternalTokenValidationError>,
+) {
+    // Arrange
+    let mut claims = JwtPayload::new();
+    claims.set_subject("alice");
+    if let Some(jkt) = jkt {
+        claims.set_claim("cnf", Some(json!({ "jkt": jkt }))).unwrap();
+    }
+    let proof = thumbprint.map(|thumbprint| DpopProof::new(thumbprint.to_string()));
+
+    // Act
+    let result = confirm_key_binding(&claims, proof.as_ref());
+
+    // Assert
+    assert_eq!(result, expected);
+}
@@ -0,0 +1,59 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// The reason a `DPoP` proof (RFC 9449) was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DpopProofError {
+    /// The request does not carry the `DPoP` header.
+    MissingProof,
+
+    /// The request carries more than one `DPoP` header.
+    MultipleProofs,
+
+    /// The proof is not a valid `dpop+jwt` JWS or its header does not carry a public key.
+    Malformed(String),
+
+    /// The proof signature does not match the embedded public key.
+    InvalidSignature(String),
+
+    /// The `htm` claim does not match the request method.
+    MethodMismatch,
+
+    /// The `htu` claim does not match the request URI.
+    UriMismatch,
+
+    /// The `iat` claim is missing, in the future or too old.
+    InvalidIssuedAt,
+
+    /// The `jti` claim is missing.
+    MissingJti,
+
+    /// The proof with the same `jti` was already used.
+    ReplayedJti,
+
+    /// Too many proofs that have not expired yet are remembered for the replay detection.
+    JtiCacheFull,
+
+    /// The `ath` claim does not match the hash of the access token.
+    AccessTokenHashMismatch,
+}
+
+impl Display for DpopProofError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DpopProofError::MissingProof => write!(f, "DPoP proof not present"),
+            DpopProofError::MultipleProofs => write!(f, "Multiple DPoP proofs provided"),
+            DpopProofError::Malformed(details) => write!(f, "Malformed DPoP proof: {}", details),
+            DpopProofError::InvalidSignature(details) => write!(f, "Invalid DPoP proof signature: {}", details),
+            DpopProofError::MethodMismatch => write!(f, "DPoP proof htm does not match the request method"),
+            DpopProofError::UriMismatch => write!(f, "DPoP proof htu does not match the request URI"),
+            DpopProofError::InvalidIssuedAt => write!(f, "DPoP proof iat is missing or out of range"),
+            DpopProofError::MissingJti => write!(f, "DPoP proof jti is missing"),
+            DpopProofError::ReplayedJti => write!(f, "DPoP proof jti was already used"),
+            DpopProofError::JtiCacheFull => write!(f, "Too many DPoP proofs in use"),
+            DpopProofError::AccessTokenHashMismatch => write!(f, "DPoP proof ath does not match the access token"),
+        }
+    }
+}
+
+impl Error for DpopProofError {}
@@ -0,0 +1,204 @@
+#[cfg(test)]
+mod tests;
+
+use crate::http::middleware::token_source::dpop_proof::DpopProof;
+use crate::http::middleware::token_source::dpop_proof_error::DpopProofError;
+use crate::services::key_management::jti_cache::{JtiCache, JtiCacheError};
+use crate::services::key_management::jws_algorithms::verifier_from_jwk;
+use actix_web::dev::ServiceRequest;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use josekit::JoseHeader;
+use josekit::jwk::Jwk;
+use josekit::jwt;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use url::Url;
+
+/// The name of the header carrying the proof.
+pub const DPOP_HEADER: &str = "DPoP";
+
+const DPOP_TOKEN_TYPE: &str = "dpop+jwt";
+
+const DEFAULT_JTI_CACHE_CAPACITY: usize = 100_000;
+
+/// [`DpopProofValidator`] validates `DPoP` proofs (RFC 9449) sent along with sender-constrained
+/// access tokens.
+///
+/// A proof is accepted when it is signed with the public key embedded in its header, targets the
+/// method and the URI of the request, is fresh, has not been used before and is bound to the
+/// access token (`ath`). The binding of the access token to the proof key (`cnf.jkt`) is confirmed
+/// with [`confirm_key_binding`] once the access token signature is validated.
+///
+/// [`confirm_key_binding`]: crate::http::middleware::token_source::dpop_proof::confirm_key_binding
+#[derive(Debug)]
+pub struct DpopProofValidator {
+    max_age: Duration,
+    clock_skew: Duration,
+    external_url: Option<Url>,
+    seen_jtis: Mutex<JtiCache>,
+}
+
+impl DpopProofValidator {
+    /// Creates a validator accepting proofs issued at most `max_age` ago. The `clock_skew` is
+    /// tolerated in both directions.
+    pub fn new(max_age: Duration, clock_skew: Duration) -> Self {
+        DpopProofValidator {
+            max_age,
+            clock_skew,
+            external_url: None,
+            seen_jtis: Mutex::new(JtiCache::new(DEFAULT_JTI_CACHE_CAPACITY)),
+        }
+    }
+
+    /// Sets the URL the clients use to reach the service, e.g. the URL of the reverse proxy in
+    /// front of it. The `htu` claim is compared with the scheme and the authority of this URL.
+    /// Otherwise, it is compared with the local address the request was received on. The
+    /// forwarding headers are never trusted.
+    pub fn with_external_url(mut self, external_url: Url) -> Self {
+        self.external_url = Some(external_url);
+        self
+    }
+
+    /// Sets the maximum number of proofs remembered for the replay detection. Proofs are rejected
+    /// while the cache is full of proofs that have not expired yet.
+    pub fn with_jti_cache_capacity(mut self, capacity: usize) -> Self {
+        self.seen_jtis = Mutex::new(JtiCache::new(capacity));
+        self
+    }
+
+    /// Validates the proof of the request for the provided access token and returns the proof key.
+    pub fn validate(&self, request: &ServiceRequest, access_token: &str) -> Result<DpopProof, DpopProofError> {
+        let mut proofs = request.headers().get_all(DPOP_HEADER);
+        let proof = proofs.next().ok_or(DpopProofError::MissingProof)?;
+        if proofs.next().is_some() {
+            return Err(DpopProofError::MultipleProofs);
+        }
+        let proof = proof.to_str().map_err(|e| DpopProofError::Malformed(e.to_string()))?;
+
+        let header = jwt::decode_header(proof).map_err(|e| DpopProofError::Malformed(e.to_string()))?;
+        let jwk = Self::proof_key(header.as_ref())?;
+        let verifier = verifier_from_jwk(&jwk, header.claim("alg").and_then(Value::as_str))
+            .map_err(|e| DpopProofError::Malformed(e.to_string()))?;
+        let (claims, _) = jwt::decode_with_verifier(proof, verifier.as_ref())
+            .map_err(|e| DpopProofError::InvalidSignature(e.to_string()))?;
+
+        if claims.claim("htm").and_then(Value::as_str) != Some(request.method().as_str()) {
+            return Err(DpopProofError::MethodMismatch);
+        }
+        self.validate_uri(request, claims.claim("htu").and_then(Value::as_str))?;
+        let issued_at = self.validate_issued_at(claims.issued_at())?;
+
+        if claims.claim("ath").and_then(Value::as_str) != Some(access_token_hash(access_token).as_str()) {
+            return Err(DpopProofError::AccessTokenHashMismatch);
+        }
+        let thumbprint = jwk_thumbprint(&jwk)?;
+
+        let jti = claims.jwt_id().ok_or(DpopProofError::MissingJti)?;
+        self.register_jti(jti, issued_at)?;
+        Ok(DpopProof::new(thumbprint))
+    }
+
+    /// Reads the public key from the proof header. Proofs carrying a private key or a
+    /// symmetric key are rejected.
+    fn proof_key(header: &dyn JoseHeader) -> Result<Jwk, DpopProofError> {
+        if header.claim("typ").and_then(Value::as_str) != Some(DPOP_TOKEN_TYPE) {
+            return Err(DpopProofError::Malformed(format!(
+                "Proof type must be {}",
+                DPOP_TOKEN_TYPE
+            )));
+        }
+        let jwk = header
+            .claim("jwk")
+            .and_then(Value::as_object)
+            .ok_or(DpopProofError::Malformed(
+                "Proof header does not contain a key".to_string(),
+            ))?;
+        let jwk = Jwk::from_map(jwk.clone()).map_err(|e| DpopProofError::Malformed(e.to_string()))?;
+        if jwk.key_type() == "oct" || jwk.parameter("d").is_some() {
+            return Err(DpopProofError::Malformed(
+                "Proof header must contain a public asymmetric key".to_string(),
+            ));
+        }
+        Ok(jwk)
+    }
+
+    /// Compares the `htu` claim with the request URI without the query and the fragment.
+    fn validate_uri(&self, request: &ServiceRequest, htu: Option<&str>) -> Result<(), DpopProofError> {
+        let mut htu = htu
+            .and_then(|htu| Url::parse(htu).ok())
+            .ok_or(DpopProofError::UriMismatch)?;
+        htu.set_query(None);
+        htu.set_fragment(None);
+
+        let mut target = match &self.external_url {
+            Some(external_url) => external_url.clone(),
+            None => {
+                let config = request.app_config();
+                let scheme = if config.secure() { "https" } else { "http" };
+                Url::parse(&format!("{}://{}", scheme, config.local_addr())).map_err(|_| DpopProofError::UriMismatch)?
+            }
+        };
+        target.set_path(request.path());
+        target.set_query(None);
+        target.set_fragment(None);
+
+        if htu == target {
+            Ok(())
+        } else {
+            Err(DpopProofError::UriMismatch)
+        }
+    }
+
+    fn validate_issued_at(&self, issued_at: Option<SystemTime>) -> Result<SystemTime, DpopProofError> {
+        let issued_at = issued_at.ok_or(DpopProofError::InvalidIssuedAt)?;
+        let now = SystemTime::now();
+        if issued_at > now + self.clock_skew {
+            return Err(DpopProofError::InvalidIssuedAt);
+        }
+        if issued_at + self.max_age + self.clock_skew < now {
+            return Err(DpopProofError::InvalidIssuedAt);
+        }
+        Ok(issued_at)
+    }
+
+    /// Remembers the `jti` until the proof expires.
+    fn register_jti(&self, jti: &str, issued_at: SystemTime) -> Result<(), DpopProofError> {
+        let expires_at = issued_at + self.max_age + self.clock_skew;
+        let mut seen = self.seen_jtis.lock().unwrap_or_else(|e| e.into_inner());
+        seen.register(jti, expires_at, SystemTime::now())
+            .map_err(|error| match error {
+                JtiCacheError::Replayed => DpopProofError::ReplayedJti,
+                JtiCacheError::Full => DpopProofError::JtiCacheFull,
+            })
+    }
+}
+
+/// Computes the `ath` value for the access token: the base64url-encoded SHA-256 hash.
+pub fn access_token_hash(access_token: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(access_token.as_bytes()))
+}
+
+/// Computes the JWK SHA-256 thumbprint of the public key as defined in RFC 7638.
+pub fn jwk_thumbprint(jwk: &Jwk) -> Result<String, DpopProofError> {
+    let members: &[&str] = match jwk.key_type() {
+        "EC" => &["crv", "kty", "x", "y"],
+        "RSA" => &["e", "kty", "n"],
+        "OKP" => &["crv", "kty", "x"],
+        other => {
+            return Err(DpopProofError::Malformed(format!("Unsupported key type: {}", other)));
+        }
+    };
+    let mut canonical = Vec::with_capacity(members.len());
+    for member in members {
+        let value = jwk
+            .parameter(member)
+            .and_then(Value::as_str)
+            .ok_or(DpopProofError::Malformed(format!("Key does not contain {}", member)))?;
+        canonical.push(format!("\"{}\":{}", member, Value::from(value)));
+    }
+    let canonical = format!("{{{}}}", canonical.join(","));
+    Ok(URL_SAFE_NO_PAD.encode(Sha256::digest(canonical.as_bytes())))
+}
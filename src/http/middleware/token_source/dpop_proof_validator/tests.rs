@@ -0,0 +1,328 @@
+use crate::http::middleware::token_source::dpop_proof::DpopProof;
+use crate::http::middleware::token_source::dpop_proof_error::DpopProofError;
+use crate::http::middleware::token_source::dpop_proof_validator::{
+    DPOP_HEADER, DpopProofValidator, access_token_hash, jwk_thumbprint,
+};
+use crate::services::key_management::jti_cache::{JtiCache, JtiCacheError};
+use crate::testing::signing_keys::make_jwk;
+use actix_web::dev::ServiceRequest;
+use actix_web::test::TestRequest;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use josekit::jwk::Jwk;
+use josekit::jws::{ES256, JwsHeader};
+use josekit::jwt;
+use josekit::jwt::JwtPayload;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use serde_json::{Value, json};
+use std::time::{Duration, SystemTime};
+use url::Url;
+
+const TARGET_URI: &str = "http://127.0.0.1:8080/resource";
+
+#[test]
+fn test_valid_proof() {
+    // Arrange
+    let key = make_jwk();
+    let access_token = make_access_token(&key);
+    let proof = make_proof(&key, &make_claims("GET", TARGET_URI, &access_token));
+
+    // Act
+    let result = make_validator().validate(&make_request(Some(&proof)), &access_token);
+
+    // Assert
+    let thumbprint = jwk_thumbprint(&key.to_public_key().unwrap()).unwrap();
+    assert_eq!(result, Ok(DpopProof::new(thumbprint)));
+}
+
+#[rstest]
+#[case("http://127.0.0.1:8080/resource?page=1")]
+#[case("HTTP://127.0.0.1:8080/resource")]
+#[case("http://127.0.0.1:8080/resource#fragment")]
+fn test_uri_normalization(#[case] htu: &str) {
+    // Arrange
+    let key = make_jwk();
+    let access_token = make_access_token(&key);
+    let proof = make_proof(&key, &make_claims("GET", htu, &access_token));
+
+    // Act
+    let result = make_validator().validate(&make_request(Some(&proof)), &access_token);
+
+    // Assert
+    assert!(result.is_ok(), "{:?}", result);
+}
+
+#[rstest]
+#[case(None, "http://127.0.0.1:8080/resource", true)]
+#[case(None, "https://api.example.com/resource", false)]
+#[case(Some("https://api.example.com"), "https://api.example.com/resource", true)]
+#[case(Some("https://api.example.com"), "http://127.0.0.1:8080/resource", false)]
+fn test_uri_ignores_forwarding_headers(#[case] external_url: Option<&str>, #[case] htu: &str, #[case] accepted: bool) {
+    // Arrange
+    let key = make_jwk();
+    let access_token = make_access_token(&key);
+    let proof = make_proof(&key, &make_claims("GET", htu, &access_token));
+    let validator = match external_url {
+        Some(url) => make_validator().with_external_url(Url::parse(url).unwrap()),
+        None => make_validator(),
+    };
+    let request = TestRequest::get()
+        .uri("/resource")
+        .insert_header(("X-Forwarded-Proto", "https"))
+        .insert_header(("X-Forwarded-Host", "api.example.com"))
+        .insert_header((DPOP_HEADER, proof))
+        .to_srv_request();
+
+    // Act
+    let result = validator.validate(&request, &access_token);
+
+    // Assert
+    assert_eq!(result.is_ok(), accepted, "{:?}", result);
+}
+
+#[test]
+fn test_missing_proof() {
+    // Arrange
+    let key = make_jwk();
+    let access_token = make_access_token(&key);
+
+    // Act
+    let result = make_validator().validate(&make_request(None), &access_token);
+
+    // Assert
+    assert_eq!(result, Err(DpopProofError::MissingProof));
+}
+
+#[test]
+fn test_multiple_proofs() {
+    // Arrange
+    let key = make_jwk();
+    let access_token = make_access_token(&key);
+    let proof = make_proof(&key, &make_claims("GET", TARGET_URI, &access_token));
+    let request = TestRequest::get()
+        .uri("/resource")
+        .append_header((DPOP_HEADER, proof.clone()))
+        .append_header((DPOP_HEADER, proof))
+        .to_srv_request();
+
+    // Act
+    let result = make_validator().validate(&request, &access_token);
+
+    // Assert
+    assert_eq!(result, Err(DpopProofError::MultipleProofs));
+}
+
+#[test]
+fn test_replayed_jti() {
+    // Arrange
+    let key = make_jwk();
+    let access_token = make_access_token(&key);
+    let proof = make_proof(&key, &make_claims("GET", TARGET_URI, &access_token));
+    let validator = make_validator();
+    validator
+        .validate(&make_request(Some(&proof)), &access_token)
+        .expect("first use should be accepted");
+
+    // Act
+    let result = validator.validate(&make_request(Some(&proof)), &access_token);
+
+    // Assert
+    assert_eq!(result, Err(DpopProofError::ReplayedJti));
+}
+
+#[rstest]
+#[case("htm", json!("POST"), DpopProofError::MethodMismatch)]
+#[case("htu", json!("http://127.0.0.1:8080/other"), DpopProofError::UriMismatch)]
+#[case("htu", json!("https://127.0.0.1:8080/resource"), DpopProofError::UriMismatch)]
+#[case("iat", json!(unix_time(SystemTime::now() - Duration::from_secs(600))), DpopProofError::InvalidIssuedAt)]
+#[case("iat", json!(unix_time(SystemTime::now() + Duration::from_secs(600))), DpopProofError::InvalidIssuedAt)]
+#[case("iat", Value::Null, DpopProofError::InvalidIssuedAt)]
+#[case("ath", json!("bm90LXRoZS1oYXNo"), DpopProofError::AccessTokenHashMismatch)]
+#[case("jti", Value::Null, DpopProofError::MissingJti)]
+fn test_invalid_claims(#[case] claim: &str, #[case] value: Value, #[case] expected: DpopProofError) {
+    // Arrange
+    let key = make_jwk();
+    let access_token = make_access_token(&key);
+    let mut claims = make_claims("GET", TARGET_URI, &access_token);
+    if value.is_null() {
+        claims.remove(claim);
+    } else {
+        claims.insert(claim.to_string(), value);
+    }
+    let proof = make_proof(&key, &claims);
+
+    // Act
+    let result = make_validator().validate(&make_request(Some(&proof)), &access_token);
+
+    // Assert
+    assert_eq!(result, Err(expected));
+}
+
+#[test]
+fn test_jti_cache_full() {
+    // Arrange
+    let key = make_jwk();
+    let access_token = make_access_token(&key);
+    let validator = make_validator().with_jti_cache_capacity(1);
+    let first = make_proof(&key, &make_claims("GET", TARGET_URI, &access_token));
+    let second = make_proof(&key, &make_claims("GET", TARGET_URI, &access_token));
+    validator
+        .validate(&make_request(Some(&first)), &access_token)
+        .expect("first proof should be accepted");
+
+    // Act
+    let result = validator.validate(&make_request(Some(&second)), &access_token);
+
+    // Assert
+    assert_eq!(result, Err(DpopProofError::JtiCacheFull));
+}
+
+#[test]
+fn test_jti_cache_evicts_expired_entries() {
+    // Arrange
+    let now = SystemTime::now();
+    let mut cache = JtiCache::new(2);
+    cache.register("expired", now - Duration::from_secs(1), now).unwrap();
+    cache.register("live", now + Duration::from_secs(60), now).unwrap();
+
+    // Act
+    let result = cache.register("new", now + Duration::from_secs(60), now);
+
+    // Assert
+    assert_eq!(result, Ok(()));
+    assert_eq!(cache.len(), 2);
+    assert_eq!(
+        cache.register("live", now + Duration::from_secs(60), now),
+        Err(JtiCacheError::Replayed)
+    );
+    assert_eq!(
+        cache.register("other", now + Duration::from_secs(60), now),
+        Err(JtiCacheError::Full)
+    );
+}
+
+#[test]
+fn test_invalid_signature() {
+    // Arrange
+    let key = make_jwk();
+    let access_token = make_access_token(&key);
+    let claims = make_claims("GET", TARGET_URI, &access_token);
+    let mut header = JwsHeader::new();
+    header.set_token_type("dpop+jwt");
+    header.set_jwk(key.to_public_key().unwrap());
+    let signer = ES256.signer_from_jwk(&make_jwk()).unwrap();
+    let proof = jwt::encode_with_signer(&JwtPayload::from_map(claims).unwrap(), &header, &signer).unwrap();
+
+    // Act
+    let result = make_validator().validate(&make_request(Some(&proof)), &access_token);
+
+    // Assert
+    assert!(
+        matches!(result, Err(DpopProofError::InvalidSignature(_))),
+        "{:?}",
+        result
+    );
+}
+
+#[rstest]
+#[case("JWT", false)]
+#[case("dpop+jwt", true)]
+fn test_malformed_header(#[case] token_type: &str, #[case] private_key: bool) {
+    // Arrange
+    let key = make_jwk();
+    let access_token = make_access_token(&key);
+    let claims = make_claims("GET", TARGET_URI, &access_token);
+    let mut header = JwsHeader::new();
+    header.set_token_type(token_type);
+    header.set_jwk(if private_key {
+        key.clone()
+    } else {
+        key.to_public_key().unwrap()
+    });
+    let signer = ES256.signer_from_jwk(&key).unwrap();
+    let proof = jwt::encode_with_signer(&JwtPayload::from_map(claims).unwrap(), &header, &signer).unwrap();
+
+    // Act
+    let result = make_validator().validate(&make_request(Some(&proof)), &access_token);
+
+    // Assert
+    assert!(matches!(result, Err(DpopProofError::Malformed(_))), "{:?}", result);
+}
+
+#[test]
+fn test_jwk_thumbprint() {
+    // Arrange: the example key of RFC 7638, section 3.1
+    let jwk = Jwk::from_map(
+        json!({
+            "kty": "RSA",
+            "n": "0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw",
+            "e": "AQAB",
+            "alg": "RS256",
+            "kid": "2011-04-29"
+        })
+        .as_object()
+        .unwrap()
+        .clone(),
+    )
+    .unwrap();
+
+    // Act
+    let thumbprint = jwk_thumbprint(&jwk);
+
+    // Assert
+    assert_eq!(
+        thumbprint,
+        Ok("NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs".to_string())
+    );
+}
+
+fn make_validator() -> DpopProofValidator {
+    DpopProofValidator::new(Duration::from_secs(60), Duration::from_secs(5))
+}
+
+fn make_request(proof: Option<&str>) -> ServiceRequest {
+    let request = TestRequest::get().uri("/resource?page=1");
+    match proof {
+        Some(proof) => request.insert_header((DPOP_HEADER, proof)).to_srv_request(),
+        None => request.to_srv_request(),
+    }
+}
+
+fn make_access_token(key: &Jwk) -> String {
+    let header = json!({"alg": "ES256", "typ": "JWT"});
+    let payload = json!({
+        "sub": "alice",
+        "cnf": {"jkt": jwk_thumbprint(&key.to_public_key().unwrap()).unwrap()}
+    });
+    format!(
+        "{}.{}.c2lnbmF0dXJl",
+        URL_SAFE_NO_PAD.encode(header.to_string()),
+        URL_SAFE_NO_PAD.encode(payload.to_string())
+    )
+}
+
+fn make_claims(method: &str, uri: &str, access_token: &str) -> serde_json::Map<String, Value> {
+    json!({
+        "htm": method,
+        "htu": uri,
+        "iat": unix_time(SystemTime::now()),
+        "jti": uuid::Uuid::new_v4().to_string(),
+        "ath": access_token_hash(access_token),
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
+fn make_proof(key: &Jwk, claims: &serde_json::Map<String, Value>) -> String {
+    let mut header = JwsHeader::new();
+    header.set_token_type("dpop+jwt");
+    header.set_jwk(key.to_public_key().unwrap());
+    let signer = ES256.signer_from_jwk(key).unwrap();
+    jwt::encode_with_signer(&JwtPayload::from_map(claims.clone()).unwrap(), &header, &signer).unwrap()
+}
+
+fn unix_time(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs()
+}
@@ -0,0 +1,34 @@
+use crate::http::middleware::token_source::dpop_proof_error::DpopProofError;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// The error returned when a token cannot be read from a [`TokenSource`].
+///
+/// [`TokenSource`]: crate::http::middleware::token_source::TokenSource
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenSourceError {
+    /// The request does not carry the token in the source.
+    NotPresent,
+
+    /// The source is present, but its value is not a valid token.
+    Malformed,
+
+    /// The header carries credentials with an authorization scheme the source does not accept.
+    UnsupportedScheme,
+
+    /// The token is present, but its proof of possession is not valid.
+    InvalidProof(DpopProofError),
+}
+
+impl Display for TokenSourceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenSourceError::NotPresent => write!(f, "Token not present"),
+            TokenSourceError::Malformed => write!(f, "Invalid token format"),
+            TokenSourceError::UnsupportedScheme => write!(f, "Unsupported authorization scheme"),
+            TokenSourceError::InvalidProof(e) => write!(f, "Invalid DPoP proof: {}", e),
+        }
+    }
+}
+
+impl Error for TokenSourceError {}
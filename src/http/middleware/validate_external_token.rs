@@ -0,0 +1,67 @@
+use crate::http::middleware::audit::audit_pipeline_error::AuditPipelineError;
+use crate::http::middleware::extract_external_token::external_token_error::ExternalTokenError;
+use crate::http::middleware::token_source::dpop_proof::{DpopProof, confirm_key_binding};
+use crate::models::external_token::ExternalToken;
+use crate::services::audit::chained::audit_event::AuditEvent;
+use crate::services::audit::token_id_strategy::TokenIdStrategy;
+use crate::services::identity::external_token_validation_error::ExternalTokenValidationError;
+use crate::services::identity::external_token_validator::ExternalTokenValidator;
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::{HttpMessage, web};
+
+/// Validates the external token extracted by the `extract_external_token` middleware with the
+/// [`ExternalTokenValidator`] registered in the application data and confirms its binding to the
+/// key of the `DPoP` proof of the request, if any. On success, inserts the
+/// validated token to request extensions and marks the external token in the audit event as
+/// accepted, identifying it from its validated claims if the [`TokenIdStrategy`] requires so.
+/// Otherwise, returns an error response built from the validation error. Returns an
+/// [`AuditPipelineError`] if the audit event of the request does not contain the external token.
+pub async fn validate_external_token<Error>(
+    validator: web::Data<dyn ExternalTokenValidator>,
+    request: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error>
+where
+    Error: ExternalTokenError + 'static,
+{
+    let token = request.extensions().get::<ExternalToken>().cloned();
+    let Some(token) = token else {
+        return Err(AuditPipelineError::MissingRequestExtension("ExternalToken").into());
+    };
+
+    let proof = request.extensions().get::<DpopProof>().cloned();
+    let validated = validator
+        .validate(&token)
+        .await
+        .and_then(|validated| {
+            confirm_key_binding(&validated.claims, proof.as_ref())?;
+            Ok(validated)
+        })
+        .map_err(|e| match e {
+            ExternalTokenValidationError::Expired => Error::token_expired(&request, e),
+            ExternalTokenValidationError::InvalidSignature(_) => Error::invalid_token_signature(&request, e),
+            ExternalTokenValidationError::InvalidAudience => Error::invalid_token_audience(&request, e),
+            _ => Error::token_validation_failed(&request, e),
+        })?;
+
+    let token_id = request
+        .app_data::<web::Data<TokenIdStrategy>>()
+        .and_then(|strategy| strategy.validated_token_id(&validated.issuer, &validated.claims));
+    {
+        let mut extensions = request.extensions_mut();
+        let event = extensions
+            .get_mut::<AuditEvent>()
+            .ok_or(AuditPipelineError::MissingAuditEvent)?;
+        if let Some(token_id) = token_id {
+            event
+                .identify_external_token(token_id)
+                .map_err(AuditPipelineError::from)?;
+        }
+        event.accept_external_token().map_err(AuditPipelineError::from)?;
+        extensions.insert(validated);
+    }
+
+    next.call(request).await
+}
@@ -2,8 +2,10 @@
 mod tests;
 
 use crate::http::middleware::extract_external_token::token_with_id::TokenWithId;
+use crate::http::middleware::token_source::authorization_scheme::AuthorizationScheme;
+use crate::http::middleware::token_source::parse_credentials;
+use crate::http::middleware::token_source::token_source_error::TokenSourceError;
 use actix_web::http::header::HeaderValue;
-use anyhow::bail;
 
 /// Represents an external JWT Token used to authorize the `ExternalIdentity` and issue an `InternalToken`
 #[derive(Clone)]
@@ -16,6 +18,13 @@ impl Into<String> for ExternalToken {
     }
 }
 
+/// Exposes the raw token value for validation
+impl AsRef<str> for ExternalToken {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
 /// Allows a String to be converted to an `ExternalToken`
 impl From<String> for ExternalToken {
     fn from(token: String) -> Self {
@@ -23,35 +32,16 @@ impl From<String> for ExternalToken {
     }
 }
 
-/// Allows a `HeaderValue` to be converted to an `ExternalToken` if it follows the expected
-/// "Bearer <token>" format
+/// Allows a `HeaderValue` to be converted to an `ExternalToken` if it follows the
+/// "Bearer <token>" format of RFC 6750
 impl TryFrom<HeaderValue> for ExternalToken {
     type Error = anyhow::Error;
 
-    #[allow(unreachable_code)] // False detect
     fn try_from(value: HeaderValue) -> Result<Self, Self::Error> {
-        match value.to_str() {
-            Ok(string_value) => {
-                let tokens = string_value.split(" ").collect::<Vec<&str>>();
-
-                if tokens.len() != 2 {
-                    return Err(bail!("Invalid token format"));
-                }
-
-                if tokens[0] != "Bearer" {
-                    return Err(bail!("Invalid token format"));
-                }
-
-                Ok(ExternalToken::from(tokens[1].to_owned()))
-            }
-            Err(_) => bail!("Invalid token format"),
-        }
+        let value = value.to_str().map_err(|_| TokenSourceError::Malformed)?;
+        let (_, token) = parse_credentials(value, &[AuthorizationScheme::Bearer])?;
+        Ok(ExternalToken::from(token.to_owned()))
     }
 }
 
-impl TokenWithId for ExternalToken {
-    fn id(&self) -> String {
-        let token_hash = md5::compute(&self.0);
-        format!("md5:{:x}", token_hash)
-    }
-}
+impl TokenWithId for ExternalToken {}
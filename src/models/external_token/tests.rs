@@ -3,21 +3,24 @@ use actix_web::http::header::HeaderValue;
 use rstest::rstest;
 
 #[rstest]
-fn test_parsing_valid_token() {
-    let header = HeaderValue::from_static("Bearer token");
+#[case("Bearer token")]
+#[case("bearer token")]
+#[case("Bearer  token ")]
+fn test_parsing_valid_token(#[case] value: &'static str) {
+    let header = HeaderValue::from_static(value);
     let token = ExternalToken::try_from(header).unwrap();
     let string_token: String = token.into();
     assert_eq!(string_token, "token".to_string());
 }
 
 #[rstest]
-#[case("token")]
-#[case("My token")]
-#[case("My suer cool token")]
-#[case("")]
-#[case("Bearer")]
-fn test_parsing_invalid_token(#[case] token: &str) {
+#[case("token", "Invalid token format")]
+#[case("My token", "Unsupported authorization scheme")]
+#[case("My suer cool token", "Unsupported authorization scheme")]
+#[case("", "Invalid token format")]
+#[case("Bearer", "Invalid token format")]
+fn test_parsing_invalid_token(#[case] token: &str, #[case] expected: &str) {
     let header = HeaderValue::from_str(token).unwrap();
     let token = ExternalToken::try_from(header);
-    assert_eq!(token.is_err_and(|e| e.to_string() == "Invalid token format"), true);
+    assert_eq!(token.is_err_and(|e| e.to_string() == expected), true);
 }
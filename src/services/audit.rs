@@ -1,17 +1,25 @@
+pub mod audit_dispatcher;
 pub mod audit_facade;
+pub mod audit_format;
+pub mod audit_record;
+pub mod audit_redaction;
+pub mod audit_sink;
 pub mod chained;
+pub mod composed_audit_service;
 pub mod events;
+pub mod file_audit_service;
+pub mod hash_chained_audit_service;
+pub mod kubernetes_event_audit_service;
 pub mod log_audit_service;
+pub mod token_id_strategy;
+pub mod webhook_audit_service;
 
-use crate::services::audit::events::authorization_audit_event::AuthorizationAuditEvent;
-use crate::services::audit::events::resource_delete_audit_event::ResourceDeleteAuditEvent;
-use crate::services::audit::events::resource_modification_audit_event::ResourceModificationAuditEvent;
-use crate::services::audit::events::token_validation_event::TokenValidationEvent;
+use crate::services::audit::audit_record::AuditRecord;
 use anyhow::Result;
 
+/// The `AuditService` trait defines the destination of the audit records. Every kind of audit
+/// event is recorded as an [`AuditRecord`].
 pub trait AuditService: Send + Sync + 'static {
-    fn record_authorization(&self, event: AuthorizationAuditEvent) -> Result<()>;
-    fn record_resource_deletion(&self, event: ResourceDeleteAuditEvent) -> Result<()>;
-    fn record_resource_modification(&self, event: ResourceModificationAuditEvent) -> Result<()>;
-    fn record_token_validation(&self, event: TokenValidationEvent) -> Result<()>;
+    /// Records the audit record.
+    fn record(&self, record: AuditRecord) -> Result<()>;
 }
@@ -0,0 +1,152 @@
+pub mod audit_dispatcher_settings;
+mod audit_queue;
+#[cfg(test)]
+mod tests;
+
+use crate::http::middleware::audit::audit_recorder::audit_write_error::AuditWriteError;
+use crate::services::audit::AuditService;
+use crate::services::audit::audit_dispatcher::audit_dispatcher_settings::{AuditDispatcherSettings, OverflowPolicy};
+use crate::services::audit::audit_dispatcher::audit_queue::AuditQueue;
+use crate::services::audit::audit_record::AuditRecord;
+use crate::services::audit::audit_sink::AuditSink;
+use crate::services::audit::audit_sink::partial_batch_error::PartialBatchError;
+use crate::services::observability::open_telemetry::metrics::metric_recorders::audit_events_dropped::AuditEventsDroppedMetric;
+use crate::services::observability::open_telemetry::metrics::metric_recorders::audit_queue_depth::AuditQueueDepthMetric;
+use actix_web::dev::Server;
+use anyhow::Result;
+use log::warn;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+/// [`AuditDispatcher`] is an [`AuditService`] that moves the delivery of audit records off the
+/// request processing. Recorded events are placed in a bounded queue and delivered
+/// in batches to the [`AuditSink`] by a background task running on a dedicated thread, so a slow
+/// sink does not add latency to the requests until the queue is full. A full queue is handled
+/// according to the [`OverflowPolicy`].
+///
+/// The queue depth is reported with the [`AuditQueueDepthMetric`] and the records that are not
+/// delivered are counted with the [`AuditEventsDroppedMetric`] by the reason: `overflow`,
+/// `closed` or `sink-error`.
+///
+/// The queued records are delivered on `shutdown`, which is also called when the dispatcher is
+/// dropped. Use `serve` to shut the dispatcher down together with the HTTP server, so the records
+/// of the last requests are delivered before the process exits.
+pub struct AuditDispatcher {
+    queue: Arc<AuditQueue>,
+    overflow_policy: OverflowPolicy,
+    queue_depth: Arc<dyn AuditQueueDepthMetric>,
+    events_dropped: Arc<dyn AuditEventsDroppedMetric>,
+    worker: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl AuditDispatcher {
+    /// Starts the delivery task and returns the dispatcher.
+    pub fn start(
+        sink: Arc<dyn AuditSink>,
+        settings: AuditDispatcherSettings,
+        queue_depth: Arc<dyn AuditQueueDepthMetric>,
+        events_dropped: Arc<dyn AuditEventsDroppedMetric>,
+    ) -> Result<Self> {
+        let queue = Arc::new(AuditQueue::new(settings.capacity));
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+
+        let worker = {
+            let queue = Arc::clone(&queue);
+            let queue_depth = Arc::clone(&queue_depth);
+            let events_dropped = Arc::clone(&events_dropped);
+            std::thread::Builder::new()
+                .name("audit-dispatcher".to_string())
+                .spawn(move || {
+                    runtime.block_on(deliver(queue, sink, settings.batch_size, queue_depth, events_dropped))
+                })?
+        };
+
+        Ok(AuditDispatcher {
+            queue,
+            overflow_policy: settings.overflow_policy,
+            queue_depth,
+            events_dropped,
+            worker: Mutex::new(Some(worker)),
+        })
+    }
+
+    /// Stops accepting records and waits until the queued records are delivered to the sink.
+    pub fn shutdown(&self) {
+        self.queue.close();
+        let worker = self.worker.lock().expect("Audit dispatcher lock is poisoned").take();
+        if let Some(worker) = worker
+            && worker.join().is_err()
+        {
+            warn!("Audit dispatcher delivery task panicked, the queued audit records are lost");
+        }
+    }
+
+    /// Runs the server and shuts the dispatcher down once the server stops, e.g. on a termination
+    /// signal. The dispatcher stops accepting records only after the server has completed the
+    /// in-flight requests.
+    pub async fn serve(self: Arc<Self>, server: Server) -> std::io::Result<()> {
+        let result = server.await;
+        tokio::task::spawn_blocking(move || self.shutdown())
+            .await
+            .map_err(std::io::Error::other)?;
+        result
+    }
+
+    /// Queues the record. Returns [`AuditWriteError::QueueFull`] if the queue is full and the
+    /// overflow policy is `FailRequest`, and [`AuditWriteError::Closed`] after `shutdown`. With the
+    /// `Block` policy, the calling thread waits for space in the queue.
+    pub fn dispatch(&self, record: AuditRecord) -> Result<(), AuditWriteError> {
+        match self.queue.push(record, self.overflow_policy) {
+            Ok(pushed) => {
+                if pushed.dropped_oldest {
+                    self.events_dropped.increment("overflow", 1);
+                }
+                self.queue_depth.record(pushed.depth as u64);
+                Ok(())
+            }
+            Err(error) => {
+                let reason = match error {
+                    AuditWriteError::QueueFull => "overflow",
+                    AuditWriteError::Closed => "closed",
+                };
+                self.events_dropped.increment(reason, 1);
+                Err(error)
+            }
+        }
+    }
+}
+
+/// The records are queued, so the result reports whether the record was accepted by the queue.
+impl AuditService for AuditDispatcher {
+    fn record(&self, record: AuditRecord) -> Result<()> {
+        Ok(self.dispatch(record)?)
+    }
+}
+
+impl Drop for AuditDispatcher {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Delivers the queued records to the sink until the queue is closed and drained.
+async fn deliver(
+    queue: Arc<AuditQueue>,
+    sink: Arc<dyn AuditSink>,
+    batch_size: usize,
+    queue_depth: Arc<dyn AuditQueueDepthMetric>,
+    events_dropped: Arc<dyn AuditEventsDroppedMetric>,
+) {
+    while let Some(batch) = queue.next_batch(batch_size).await {
+        queue_depth.record(queue.len() as u64);
+        let count = batch.len();
+        if let Err(error) = sink.write_batch(batch).await {
+            let written = error
+                .downcast_ref::<PartialBatchError>()
+                .map_or(0, PartialBatchError::written);
+            let dropped = count.saturating_sub(written) as u64;
+            warn!("Failed to deliver {} of {} audit records: {:?}", dropped, count, error);
+            events_dropped.increment("sink-error", dropped);
+        }
+    }
+}
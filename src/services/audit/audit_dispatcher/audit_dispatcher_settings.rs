@@ -0,0 +1,48 @@
+use serde::Deserialize;
+
+/// The settings of the [`AuditDispatcher`].
+///
+/// [`AuditDispatcher`]: crate::services::audit::audit_dispatcher::AuditDispatcher
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AuditDispatcherSettings {
+    /// The maximum number of events waiting in the queue.
+    pub capacity: usize,
+
+    /// The maximum number of events delivered to the sink in one batch.
+    pub batch_size: usize,
+
+    pub overflow_policy: OverflowPolicy,
+}
+
+impl Default for AuditDispatcherSettings {
+    fn default() -> Self {
+        AuditDispatcherSettings {
+            capacity: 10_000,
+            batch_size: 100,
+            overflow_policy: OverflowPolicy::default(),
+        }
+    }
+}
+
+/// Selects how the [`AuditDispatcher`] handles an event written while the queue is full.
+///
+/// The default policy never blocks the writer, so the actix workers recording the events keep
+/// serving requests while the sink is slow.
+///
+/// [`AuditDispatcher`]: crate::services::audit::audit_dispatcher::AuditDispatcher
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowPolicy {
+    /// Blocks the writing thread until the queue has space, so no event is lost. The audit
+    /// recorder writes on the actix worker thread, so a full queue stalls all requests of the
+    /// worker. Use it only for writers running on dedicated threads.
+    Block,
+
+    /// Drops the oldest event in the queue to make space for the new one.
+    DropOldest,
+
+    /// Rejects the new event, so the audit recorder fails the request.
+    #[default]
+    FailRequest,
+}
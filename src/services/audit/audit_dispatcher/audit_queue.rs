@@ -0,0 +1,109 @@
+use crate::http::middleware::audit::audit_recorder::audit_write_error::AuditWriteError;
+use crate::services::audit::audit_dispatcher::audit_dispatcher_settings::OverflowPolicy;
+use crate::services::audit::audit_record::AuditRecord;
+use std::collections::VecDeque;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use tokio::sync::Notify;
+
+/// The bounded queue between the writers of the [`AuditDispatcher`] and its delivery task.
+///
+/// [`AuditDispatcher`]: crate::services::audit::audit_dispatcher::AuditDispatcher
+pub(super) struct AuditQueue {
+    state: Mutex<QueueState>,
+    capacity: usize,
+    not_full: Condvar,
+    not_empty: Notify,
+}
+
+struct QueueState {
+    records: VecDeque<AuditRecord>,
+    closed: bool,
+}
+
+/// The result of an accepted record.
+pub(super) struct Pushed {
+    /// The number of records in the queue after the push.
+    pub depth: usize,
+
+    /// Whether the oldest record was dropped to make space for the new one.
+    pub dropped_oldest: bool,
+}
+
+impl AuditQueue {
+    pub(super) fn new(capacity: usize) -> Self {
+        AuditQueue {
+            state: Mutex::new(QueueState {
+                records: VecDeque::with_capacity(capacity),
+                closed: false,
+            }),
+            capacity: capacity.max(1),
+            not_full: Condvar::new(),
+            not_empty: Notify::new(),
+        }
+    }
+
+    /// Adds the record to the queue, handling a full queue according to the policy.
+    pub(super) fn push(&self, record: AuditRecord, policy: OverflowPolicy) -> Result<Pushed, AuditWriteError> {
+        let mut state = self.state();
+        let mut dropped_oldest = false;
+        if policy == OverflowPolicy::Block {
+            while state.records.len() >= self.capacity && !state.closed {
+                state = self.not_full.wait(state).expect("Audit queue lock is poisoned");
+            }
+        }
+        if state.closed {
+            return Err(AuditWriteError::Closed);
+        }
+        if state.records.len() >= self.capacity {
+            match policy {
+                OverflowPolicy::DropOldest => {
+                    state.records.pop_front();
+                    dropped_oldest = true;
+                }
+                _ => return Err(AuditWriteError::QueueFull),
+            }
+        }
+        state.records.push_back(record);
+        let depth = state.records.len();
+        drop(state);
+
+        self.not_empty.notify_one();
+        Ok(Pushed { depth, dropped_oldest })
+    }
+
+    /// Waits for the next batch of at most `batch_size` records. Returns `None` once the queue is
+    /// closed and all queued records are taken.
+    pub(super) async fn next_batch(&self, batch_size: usize) -> Option<Vec<AuditRecord>> {
+        loop {
+            {
+                let mut state = self.state();
+                if !state.records.is_empty() {
+                    let count = batch_size.clamp(1, state.records.len());
+                    let batch = state.records.drain(..count).collect();
+                    self.not_full.notify_all();
+                    return Some(batch);
+                }
+                if state.closed {
+                    return None;
+                }
+            }
+            self.not_empty.notified().await;
+        }
+    }
+
+    /// Returns the number of records in the queue.
+    pub(super) fn len(&self) -> usize {
+        self.state().records.len()
+    }
+
+    /// Stops accepting records. The queued records are still returned by `next_batch`.
+    pub(super) fn close(&self) {
+        self.state().closed = true;
+        self.not_full.notify_all();
+        self.not_empty.notify_one();
+    }
+
+    fn state(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().expect("Audit queue lock is poisoned")
+    }
+}
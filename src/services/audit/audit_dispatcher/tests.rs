@@ -0,0 +1,421 @@
+use crate::http::middleware::audit::audit_recorder::audit_write_error::AuditWriteError;
+use crate::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
+use crate::services::audit::AuditService;
+use crate::services::audit::audit_dispatcher::AuditDispatcher;
+use crate::services::audit::audit_dispatcher::audit_dispatcher_settings::{AuditDispatcherSettings, OverflowPolicy};
+use crate::services::audit::audit_record::{AuditRecord, AuditRecordEvent};
+use crate::services::audit::audit_sink::AuditSink;
+use crate::services::audit::audit_sink::partial_batch_error::PartialBatchError;
+use crate::services::audit::chained::audit_event::AuditEvent;
+use crate::services::audit::chained::chained_audit_event::ChainedAuditEvent;
+use crate::services::audit::events::authorization_audit_event::{AuthorizationAuditEvent, Reason};
+use crate::testing::audit_metrics::{MockAuditEventsDroppedMetric, queue_depth_metric};
+use actix_web::{App, HttpServer};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use cedar_policy::Decision;
+use pretty_assertions::assert_eq;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender, channel};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[test]
+fn test_delivers_events_in_batches_on_shutdown() {
+    // Arrange
+    let sink = Arc::new(RecordingSink::default());
+    let dispatcher = AuditDispatcher::start(
+        sink.clone(),
+        AuditDispatcherSettings {
+            batch_size: 2,
+            ..AuditDispatcherSettings::default()
+        },
+        Arc::new(queue_depth_metric()),
+        Arc::new(MockAuditEventsDroppedMetric::new()),
+    )
+    .expect("dispatcher should start");
+
+    // Act
+    for actor in ["a", "b", "c", "d", "e"] {
+        dispatcher.try_write(make_event(actor)).expect("event should be queued");
+    }
+    dispatcher.shutdown();
+
+    // Assert
+    let batches = sink.batches.lock().unwrap();
+    assert!(batches.iter().all(|batch| batch.len() <= 2));
+    let actors: Vec<String> = batches.iter().flatten().map(actor_of).collect();
+    assert_eq!(actors, vec!["a", "b", "c", "d", "e"]);
+}
+
+#[test]
+fn test_delivers_audit_service_events() {
+    // Arrange
+    let sink = Arc::new(RecordingSink::default());
+    let dispatcher = AuditDispatcher::start(
+        sink.clone(),
+        AuditDispatcherSettings::default(),
+        Arc::new(queue_depth_metric()),
+        Arc::new(MockAuditEventsDroppedMetric::new()),
+    )
+    .expect("dispatcher should start");
+    let event = AuthorizationAuditEvent {
+        action: "read".to_string(),
+        actor: "alice".to_string(),
+        resource: "documents/1".to_string(),
+        decision: Decision::Allow,
+        reason: Reason {
+            policies: Default::default(),
+            errors: Default::default(),
+        },
+    };
+
+    // Act
+    dispatcher.write(make_event("a"));
+    dispatcher
+        .record(AuditRecord::from(event))
+        .expect("event should be queued");
+    dispatcher.shutdown();
+
+    // Assert
+    let batches = sink.batches.lock().unwrap();
+    let actors: Vec<String> = batches.iter().flatten().map(actor_of).collect();
+    assert_eq!(actors, vec!["a", "alice"]);
+}
+
+#[test]
+fn test_fail_request_when_queue_is_full() {
+    // Arrange
+    let (sink, started, gate) = GatedSink::new();
+    let sink = Arc::new(sink);
+    let mut events_dropped = MockAuditEventsDroppedMetric::new();
+    events_dropped
+        .expect_increment()
+        .withf(|reason, count| reason == "overflow" && *count == 1)
+        .times(1)
+        .returning(|_, _| ());
+    let dispatcher = AuditDispatcher::start(
+        sink.clone(),
+        AuditDispatcherSettings {
+            capacity: 1,
+            batch_size: 1,
+            overflow_policy: OverflowPolicy::FailRequest,
+        },
+        Arc::new(queue_depth_metric()),
+        Arc::new(events_dropped),
+    )
+    .expect("dispatcher should start");
+    dispatcher.try_write(make_event("a")).expect("event should be queued");
+    started.recv().expect("delivery should start");
+    dispatcher.try_write(make_event("b")).expect("event should be queued");
+
+    // Act
+    let result = dispatcher.try_write(make_event("c"));
+
+    // Assert
+    assert_eq!(result, Err(AuditWriteError::QueueFull));
+    drop(gate);
+    dispatcher.shutdown();
+    let actors: Vec<String> = sink.delivered.lock().unwrap().iter().map(actor_of).collect();
+    assert_eq!(actors, vec!["a", "b"]);
+}
+
+#[test]
+fn test_drop_oldest_when_queue_is_full() {
+    // Arrange
+    let (sink, started, gate) = GatedSink::new();
+    let sink = Arc::new(sink);
+    let mut events_dropped = MockAuditEventsDroppedMetric::new();
+    events_dropped
+        .expect_increment()
+        .withf(|reason, count| reason == "overflow" && *count == 1)
+        .times(1)
+        .returning(|_, _| ());
+    let dispatcher = AuditDispatcher::start(
+        sink.clone(),
+        AuditDispatcherSettings {
+            capacity: 1,
+            batch_size: 1,
+            overflow_policy: OverflowPolicy::DropOldest,
+        },
+        Arc::new(queue_depth_metric()),
+        Arc::new(events_dropped),
+    )
+    .expect("dispatcher should start");
+    dispatcher.try_write(make_event("a")).expect("event should be queued");
+    started.recv().expect("delivery should start");
+    dispatcher.try_write(make_event("b")).expect("event should be queued");
+
+    // Act
+    let result = dispatcher.try_write(make_event("c"));
+
+    // Assert
+    assert_eq!(result, Ok(()));
+    drop(gate);
+    dispatcher.shutdown();
+    let actors: Vec<String> = sink.delivered.lock().unwrap().iter().map(actor_of).collect();
+    assert_eq!(actors, vec!["a", "c"]);
+}
+
+#[test]
+fn test_block_when_queue_is_full() {
+    // Arrange
+    let (sink, started, gate) = GatedSink::new();
+    let sink = Arc::new(sink);
+    let dispatcher = Arc::new(
+        AuditDispatcher::start(
+            sink.clone(),
+            AuditDispatcherSettings {
+                capacity: 1,
+                batch_size: 1,
+                overflow_policy: OverflowPolicy::Block,
+            },
+            Arc::new(queue_depth_metric()),
+            Arc::new(MockAuditEventsDroppedMetric::new()),
+        )
+        .expect("dispatcher should start"),
+    );
+    dispatcher.try_write(make_event("a")).expect("event should be queued");
+    started.recv().expect("delivery should start");
+    dispatcher.try_write(make_event("b")).expect("event should be queued");
+
+    // Act
+    let (written_tx, written_rx) = channel();
+    let writer = {
+        let dispatcher = Arc::clone(&dispatcher);
+        std::thread::spawn(move || {
+            let _ = written_tx.send(dispatcher.try_write(make_event("c")));
+        })
+    };
+
+    // Assert
+    assert_eq!(
+        written_rx.recv_timeout(Duration::from_millis(100)),
+        Err(RecvTimeoutError::Timeout)
+    );
+    drop(gate);
+    assert_eq!(written_rx.recv(), Ok(Ok(())));
+    writer.join().expect("writer should complete");
+    dispatcher.shutdown();
+    let actors: Vec<String> = sink.delivered.lock().unwrap().iter().map(actor_of).collect();
+    assert_eq!(actors, vec!["a", "b", "c"]);
+}
+
+#[actix_web::test]
+async fn test_serve_shuts_down_with_server() {
+    // Arrange
+    let sink = Arc::new(RecordingSink::default());
+    let mut events_dropped = MockAuditEventsDroppedMetric::new();
+    events_dropped
+        .expect_increment()
+        .withf(|reason, count| reason == "closed" && *count == 1)
+        .times(1)
+        .returning(|_, _| ());
+    let dispatcher = Arc::new(
+        AuditDispatcher::start(
+            sink.clone(),
+            AuditDispatcherSettings::default(),
+            Arc::new(queue_depth_metric()),
+            Arc::new(events_dropped),
+        )
+        .expect("dispatcher should start"),
+    );
+    let server = HttpServer::new(App::new)
+        .workers(1)
+        .disable_signals()
+        .bind("127.0.0.1:0")
+        .expect("server should bind")
+        .run();
+    let handle = server.handle();
+    let serving = actix_web::rt::spawn(Arc::clone(&dispatcher).serve(server));
+    dispatcher.try_write(make_event("a")).expect("event should be queued");
+
+    // Act
+    handle.stop(true).await;
+    serving
+        .await
+        .expect("serve should complete")
+        .expect("server should stop");
+
+    // Assert
+    assert_eq!(dispatcher.try_write(make_event("b")), Err(AuditWriteError::Closed));
+    let batches = sink.batches.lock().unwrap();
+    let actors: Vec<String> = batches.iter().flatten().map(actor_of).collect();
+    assert_eq!(actors, vec!["a"]);
+}
+
+#[test]
+fn test_write_after_shutdown() {
+    // Arrange
+    let mut events_dropped = MockAuditEventsDroppedMetric::new();
+    events_dropped
+        .expect_increment()
+        .withf(|reason, count| reason == "closed" && *count == 1)
+        .times(1)
+        .returning(|_, _| ());
+    let dispatcher = AuditDispatcher::start(
+        Arc::new(RecordingSink::default()),
+        AuditDispatcherSettings::default(),
+        Arc::new(queue_depth_metric()),
+        Arc::new(events_dropped),
+    )
+    .expect("dispatcher should start");
+    dispatcher.shutdown();
+
+    // Act
+    let result = dispatcher.try_write(make_event("a"));
+
+    // Assert
+    assert_eq!(result, Err(AuditWriteError::Closed));
+}
+
+#[test]
+fn test_sink_errors_are_counted_as_dropped() {
+    // Arrange
+    let dropped = Arc::new(AtomicU64::new(0));
+    let mut events_dropped = MockAuditEventsDroppedMetric::new();
+    let counter = Arc::clone(&dropped);
+    events_dropped
+        .expect_increment()
+        .withf(|reason, _| reason == "sink-error")
+        .returning(move |_, count| {
+            counter.fetch_add(count, Ordering::SeqCst);
+        });
+    let dispatcher = AuditDispatcher::start(
+        Arc::new(FailingSink),
+        AuditDispatcherSettings::default(),
+        Arc::new(queue_depth_metric()),
+        Arc::new(events_dropped),
+    )
+    .expect("dispatcher should start");
+
+    // Act
+    dispatcher.write(make_event("a"));
+    dispatcher.write(make_event("b"));
+    dispatcher.shutdown();
+
+    // Assert
+    assert_eq!(dropped.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_written_records_are_not_counted_as_dropped() {
+    // Arrange
+    let dropped = Arc::new(AtomicU64::new(0));
+    let mut events_dropped = MockAuditEventsDroppedMetric::new();
+    let counter = Arc::clone(&dropped);
+    events_dropped
+        .expect_increment()
+        .withf(|reason, _| reason == "sink-error")
+        .returning(move |_, count| {
+            counter.fetch_add(count, Ordering::SeqCst);
+        });
+    let sink = Arc::new(PartialSink::default());
+    let dispatcher = AuditDispatcher::start(
+        sink.clone(),
+        AuditDispatcherSettings::default(),
+        Arc::new(queue_depth_metric()),
+        Arc::new(events_dropped),
+    )
+    .expect("dispatcher should start");
+
+    // Act
+    for actor in ["a", "b", "c"] {
+        dispatcher.try_write(make_event(actor)).expect("event should be queued");
+    }
+    dispatcher.shutdown();
+
+    // Assert
+    let written: Vec<String> = sink.written.lock().unwrap().iter().map(actor_of).collect();
+    assert_eq!(written.first().map(String::as_str), Some("a"));
+    assert_eq!(dropped.load(Ordering::SeqCst) + written.len() as u64, 3);
+}
+
+fn make_event(actor: &str) -> AuditEvent {
+    AuditEvent::Intermediate(ChainedAuditEvent {
+        actor: Some(actor.to_string()),
+        ..ChainedAuditEvent::begin()
+    })
+}
+
+fn actor_of(record: &AuditRecord) -> String {
+    match record.event() {
+        AuditRecordEvent::Chained(event) => match event.as_ref() {
+            AuditEvent::Intermediate(event) | AuditEvent::Final(event) => event.actor.clone().unwrap_or_default(),
+        },
+        AuditRecordEvent::Authorization(event) => event.actor.clone(),
+        _ => String::new(),
+    }
+}
+
+#[derive(Default)]
+struct RecordingSink {
+    batches: Mutex<Vec<Vec<AuditRecord>>>,
+}
+
+#[async_trait]
+impl AuditSink for RecordingSink {
+    async fn write_batch(&self, records: Vec<AuditRecord>) -> anyhow::Result<()> {
+        self.batches.lock().unwrap().push(records);
+        Ok(())
+    }
+}
+
+/// Holds every batch until the gate is dropped, so the queue can be filled deterministically.
+struct GatedSink {
+    delivered: Mutex<Vec<AuditRecord>>,
+    started: Mutex<Sender<()>>,
+    gate: Mutex<Receiver<()>>,
+}
+
+impl GatedSink {
+    fn new() -> (GatedSink, Receiver<()>, Sender<()>) {
+        let (started_tx, started_rx) = channel();
+        let (gate_tx, gate_rx) = channel();
+        let sink = GatedSink {
+            delivered: Mutex::new(Vec::new()),
+            started: Mutex::new(started_tx),
+            gate: Mutex::new(gate_rx),
+        };
+        (sink, started_rx, gate_tx)
+    }
+}
+
+#[async_trait]
+impl AuditSink for GatedSink {
+    async fn write_batch(&self, records: Vec<AuditRecord>) -> anyhow::Result<()> {
+        let _ = self.started.lock().unwrap().send(());
+        let _ = self.gate.lock().unwrap().recv();
+        self.delivered.lock().unwrap().extend(records);
+        Ok(())
+    }
+}
+
+/// Writes the records of the batch until it meets the record of the actor `b`.
+#[derive(Default)]
+struct PartialSink {
+    written: Mutex<Vec<AuditRecord>>,
+}
+
+#[async_trait]
+impl AuditSink for PartialSink {
+    async fn write_batch(&self, records: Vec<AuditRecord>) -> anyhow::Result<()> {
+        let mut written = self.written.lock().unwrap();
+        for (index, record) in records.into_iter().enumerate() {
+            if actor_of(&record) == "b" {
+                return Err(PartialBatchError::new(index, anyhow!("sink is unavailable")).into());
+            }
+            written.push(record);
+        }
+        Ok(())
+    }
+}
+
+struct FailingSink;
+
+#[async_trait]
+impl AuditSink for FailingSink {
+    async fn write_batch(&self, _records: Vec<AuditRecord>) -> anyhow::Result<()> {
+        Err(anyhow!("sink is unavailable"))
+    }
+}
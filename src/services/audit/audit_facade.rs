@@ -4,6 +4,7 @@ pub mod to_audit_record;
 
 use crate::services::audit::AuditService;
 use crate::services::audit::audit_facade::to_audit_record::ToAuditRecord;
+use crate::services::audit::audit_record::AuditRecord;
 use crate::services::audit::events::resource_delete_audit_event::ResourceDeleteAuditEvent;
 use crate::services::audit::events::resource_modification_audit_event::{
     ModificationResult, ResourceModificationAuditEvent,
@@ -96,7 +97,7 @@ where
 
         let event =
             ResourceModificationAuditEvent::new(id, self.resource_type.clone(), ModificationResult::from(&result));
-        self.audit_service.record_resource_modification(event)?;
+        self.audit_service.record(AuditRecord::from(event))?;
         result
     }
 
@@ -132,7 +133,7 @@ where
         let id = key.to_audit_record();
         let result = self.underlying.delete(key).await;
         let event = ResourceDeleteAuditEvent::new(id, self.resource_type.clone(), result.is_ok());
-        self.audit_service.record_resource_deletion(event)?;
+        self.audit_service.record(AuditRecord::from(event))?;
         result
     }
 }
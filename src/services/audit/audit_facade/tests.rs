@@ -7,10 +7,7 @@ use tokio::sync::RwLock;
 use crate::services::audit::AuditService;
 use crate::services::audit::audit_facade::WithAuditFacade;
 use crate::services::audit::audit_facade::to_audit_record::ToAuditRecord;
-use crate::services::audit::events::authorization_audit_event::AuthorizationAuditEvent;
-use crate::services::audit::events::resource_delete_audit_event::ResourceDeleteAuditEvent;
-use crate::services::audit::events::resource_modification_audit_event::ResourceModificationAuditEvent;
-use crate::services::audit::events::token_validation_event::TokenValidationEvent;
+use crate::services::audit::audit_record::{AuditRecord, AuditRecordEvent};
 use crate::services::backends::kubernetes::kubernetes_resource_manager::status::Status;
 use crate::services::base::upsert_repository::{
     CanDelete, ReadOnlyRepository, UpsertRepository, UpsertRepositoryWithDelete,
@@ -173,25 +170,13 @@ impl MockAuditService {
 }
 
 impl AuditService for MockAuditService {
-    // COVERAGE: ignore since it's stubbed out
-    #[cfg_attr(coverage, coverage(off))]
-    fn record_authorization(&self, _event: AuthorizationAuditEvent) -> anyhow::Result<()> {
-        unreachable!()
-    }
-
-    fn record_resource_deletion(&self, _event: ResourceDeleteAuditEvent) -> Result<(), anyhow::Error> {
-        self.deletion_events.fetch_add(1, Ordering::Relaxed);
+    fn record(&self, record: AuditRecord) -> anyhow::Result<()> {
+        let counter = match record.event() {
+            AuditRecordEvent::ResourceDeletion(_) => &self.deletion_events,
+            AuditRecordEvent::ResourceModification(_) => &self.modification_events,
+            _ => unreachable!(),
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
         Ok(())
     }
-
-    fn record_resource_modification(&self, _event: ResourceModificationAuditEvent) -> Result<(), anyhow::Error> {
-        self.modification_events.fetch_add(1, Ordering::Relaxed);
-        Ok(())
-    }
-
-    // COVERAGE: ignore since it's stubbed out
-    #[cfg_attr(coverage, coverage(off))]
-    fn record_token_validation(&self, _event: TokenValidationEvent) -> anyhow::Result<()> {
-        unreachable!()
-    }
 }
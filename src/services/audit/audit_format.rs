@@ -0,0 +1,46 @@
+pub mod audit_formatter;
+mod audit_summary;
+pub mod cef_formatter;
+pub mod cloud_events_formatter;
+pub mod native_formatter;
+pub mod ocsf_formatter;
+#[cfg(test)]
+mod tests;
+
+use crate::services::audit::audit_format::audit_formatter::AuditFormatter;
+use crate::services::audit::audit_format::cef_formatter::CefFormatter;
+use crate::services::audit::audit_format::cloud_events_formatter::CloudEventsFormatter;
+use crate::services::audit::audit_format::native_formatter::NativeFormatter;
+use crate::services::audit::audit_format::ocsf_formatter::OcsfFormatter;
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// Selects the format of the records written by a sink.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditFormat {
+    /// The native JSON layout of the audit records.
+    #[default]
+    Native,
+
+    /// CloudEvents 1.0 in the structured JSON mode.
+    CloudEvents,
+
+    /// OCSF `Authorize Session` and `API Activity` events.
+    Ocsf,
+
+    /// ArcSight Common Event Format lines.
+    Cef,
+}
+
+impl AuditFormat {
+    /// Returns the formatter of the format.
+    pub fn formatter(&self) -> Arc<dyn AuditFormatter> {
+        match self {
+            AuditFormat::Native => Arc::new(NativeFormatter),
+            AuditFormat::CloudEvents => Arc::new(CloudEventsFormatter),
+            AuditFormat::Ocsf => Arc::new(OcsfFormatter),
+            AuditFormat::Cef => Arc::new(CefFormatter),
+        }
+    }
+}
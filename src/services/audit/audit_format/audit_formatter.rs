@@ -0,0 +1,18 @@
+use crate::services::audit::audit_record::AuditRecord;
+use anyhow::Result;
+
+/// [`AuditFormatter`] converts the audit records to the format expected by the consumers of a sink.
+pub trait AuditFormatter: Send + Sync + 'static {
+    /// Formats the record as a single line.
+    fn format(&self, record: &AuditRecord) -> Result<String>;
+
+    /// Returns the media type of a batch of formatted records.
+    fn content_type(&self) -> &'static str {
+        "application/json"
+    }
+
+    /// Joins the formatted records into a batch, a JSON array by default.
+    fn batch(&self, lines: Vec<String>) -> String {
+        format!("[{}]", lines.join(","))
+    }
+}
@@ -0,0 +1,167 @@
+use crate::services::audit::audit_record::{AuditRecord, AuditRecordEvent, AuditRecordKind};
+use crate::services::audit::chained::audit_event::AuditEvent;
+use crate::services::audit::chained::chained_audit_event::ChainedAuditEvent;
+use crate::services::audit::chained::request_metadata::RequestMetadata;
+use crate::services::audit::events::authorization_audit_event::Reason;
+use crate::services::audit::events::resource_modification_audit_event::ModificationResult;
+use crate::services::audit::events::token_validation_event::TokenValidationResult;
+use anyhow::Result;
+use cedar_policy::Decision;
+use chrono::{DateTime, SecondsFormat};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+
+/// The vendor of the product written to the standard formats.
+pub(crate) const PRODUCT_VENDOR: &str = "SneaksAndData";
+
+/// The name of the product written to the standard formats.
+pub(crate) const PRODUCT_NAME: &str = "Boxer";
+
+/// The version of the product written to the standard formats.
+pub(crate) const PRODUCT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The outcome of the audited operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AuditOutcome {
+    Success,
+    Failure,
+    Unknown,
+}
+
+/// [`AuditSummary`] holds the fields of an audit record shared by the standard formats.
+pub(crate) struct AuditSummary {
+    pub kind: AuditRecordKind,
+
+    /// The identifier of the record, the same every time the record is formatted. The events of
+    /// the audit chain are identified by their id or their position in the chain, and the other
+    /// records by the digest of their envelope.
+    pub id: String,
+
+    /// The time of the event in milliseconds since the Unix epoch. The events of the audit chain
+    /// are timestamped by the chain, and the other events when their record was created.
+    pub time: u64,
+
+    pub actor: Option<String>,
+    pub action: Option<String>,
+    pub resource: Option<String>,
+    pub outcome: AuditOutcome,
+
+    /// The policies and errors explaining the outcome.
+    pub reason: Option<String>,
+
+    pub chain_id: Option<String>,
+    pub request: Option<RequestMetadata>,
+
+    /// The event as serialized by the native format.
+    pub event: Value,
+}
+
+impl AuditSummary {
+    pub(crate) fn of(record: &AuditRecord) -> Result<AuditSummary> {
+        let envelope = record.to_json()?;
+        let mut summary = AuditSummary {
+            kind: record.kind(),
+            id: format!("{:x}", Sha256::digest(envelope.to_string())),
+            time: record.timestamp(),
+            actor: None,
+            action: None,
+            resource: None,
+            outcome: AuditOutcome::Unknown,
+            reason: None,
+            chain_id: None,
+            request: None,
+            event: envelope["event"].clone(),
+        };
+        match record.event() {
+            AuditRecordEvent::Chained(event) => {
+                let (AuditEvent::Final(event) | AuditEvent::Intermediate(event)) = event.as_ref();
+                if let Some(id) = event.event_id.clone().or_else(|| position(event)) {
+                    summary.id = id;
+                }
+                summary.time = event.finalized_at.or(event.created_at).unwrap_or(summary.time);
+                summary.actor = event.actor.clone();
+                summary.action = event.action.clone();
+                summary.resource = event.resource.clone();
+                summary.outcome = event.decision.map_or(AuditOutcome::Unknown, outcome_of);
+                summary.reason = event.reason.as_ref().and_then(describe);
+                summary.chain_id = event.chain_id.clone();
+                summary.request = event.request.clone();
+            }
+            AuditRecordEvent::Authorization(event) => {
+                summary.actor = Some(event.actor.clone());
+                summary.action = Some(event.action.clone());
+                summary.resource = Some(event.resource.clone());
+                summary.outcome = outcome_of(event.decision);
+                summary.reason = describe(&event.reason);
+            }
+            AuditRecordEvent::TokenValidation(event) => {
+                summary.actor = event
+                    .token_metadata
+                    .as_ref()
+                    .and_then(|metadata| metadata.subject())
+                    .map(String::from);
+                summary.action = Some(format!("validate_{}_token", event.token_type));
+                summary.resource = Some(event.token_id.clone());
+                summary.outcome = match event.result {
+                    TokenValidationResult::Allow => AuditOutcome::Success,
+                    TokenValidationResult::Deny => AuditOutcome::Failure,
+                };
+                summary.reason = join(&event.reason_errors);
+            }
+            AuditRecordEvent::ResourceModification(event) => {
+                summary.action = Some("modify".to_string());
+                summary.resource = Some(format!("{}/{}", event.resource_type, event.id));
+                summary.outcome = match event.modification_result {
+                    ModificationResult::Success(_) => AuditOutcome::Success,
+                    ModificationResult::Failure => AuditOutcome::Failure,
+                };
+            }
+            AuditRecordEvent::ResourceDeletion(event) => {
+                summary.action = Some("delete".to_string());
+                summary.resource = Some(format!("{}/{}", event.resource_type, event.id));
+                summary.outcome = match event.successful {
+                    true => AuditOutcome::Success,
+                    false => AuditOutcome::Failure,
+                };
+            }
+        }
+        Ok(summary)
+    }
+
+    /// Returns the time of the event in the RFC 3339 format.
+    pub(crate) fn rfc3339_time(&self) -> String {
+        DateTime::from_timestamp_millis(self.time as i64)
+            .unwrap_or_default()
+            .to_rfc3339_opts(SecondsFormat::Millis, true)
+    }
+}
+
+fn outcome_of(decision: Decision) -> AuditOutcome {
+    match decision {
+        Decision::Allow => AuditOutcome::Success,
+        Decision::Deny => AuditOutcome::Failure,
+    }
+}
+
+fn describe(reason: &Reason) -> Option<String> {
+    let parts: Vec<String> = [("policies", &reason.policies), ("errors", &reason.errors)]
+        .into_iter()
+        .filter_map(|(name, values)| join(values).map(|values| format!("{}: {}", name, values)))
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("; "))
+}
+
+fn join(values: &HashSet<String>) -> Option<String> {
+    let mut values: Vec<&str> = values.iter().map(String::as_str).collect();
+    values.sort();
+    (!values.is_empty()).then(|| values.join(", "))
+}
+
+/// Returns the position of the event in the audit chain.
+fn position(event: &ChainedAuditEvent) -> Option<String> {
+    event
+        .chain_id
+        .as_ref()
+        .map(|chain_id| format!("{}/{}", chain_id, event.sequence))
+}
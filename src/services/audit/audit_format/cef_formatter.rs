@@ -0,0 +1,88 @@
+use crate::services::audit::audit_format::audit_formatter::AuditFormatter;
+use crate::services::audit::audit_format::audit_summary::{
+    AuditOutcome, AuditSummary, PRODUCT_NAME, PRODUCT_VENDOR, PRODUCT_VERSION,
+};
+use crate::services::audit::audit_record::{AuditRecord, AuditRecordKind};
+use anyhow::Result;
+
+/// [`CefFormatter`] writes the records as ArcSight Common Event Format lines:
+/// `CEF:0|Vendor|Product|Version|Signature ID|Name|Severity|Extension`. The signature id is the
+/// kind of the record, and the audited resource is written to the `cs1` custom string.
+pub struct CefFormatter;
+
+impl AuditFormatter for CefFormatter {
+    fn format(&self, record: &AuditRecord) -> Result<String> {
+        let summary = AuditSummary::of(record)?;
+        let (severity, outcome) = match summary.outcome {
+            AuditOutcome::Success => (3, Some("success")),
+            AuditOutcome::Failure => (7, Some("failure")),
+            AuditOutcome::Unknown => (5, None),
+        };
+
+        let mut extension = vec![("rt", Some(summary.time.to_string()))];
+        extension.push(("suser", summary.actor.clone()));
+        extension.push(("act", summary.action.clone()));
+        extension.push(("outcome", outcome.map(String::from)));
+        extension.push(("reason", summary.reason.clone()));
+        if summary.resource.is_some() {
+            extension.push(("cs1Label", Some("resource".to_string())));
+            extension.push(("cs1", summary.resource.clone()));
+        }
+        if summary.chain_id.is_some() {
+            extension.push(("externalId", summary.chain_id.clone()));
+        }
+        if let Some(request) = &summary.request {
+            extension.push(("requestMethod", request.method.clone()));
+            extension.push(("request", request.path.clone()));
+            extension.push(("src", request.client_ip.clone()));
+            extension.push(("requestClientApplication", request.user_agent.clone()));
+        }
+        let extension: Vec<String> = extension
+            .into_iter()
+            .filter_map(|(key, value)| value.map(|value| format!("{}={}", key, escape_extension(&value))))
+            .collect();
+
+        Ok(format!(
+            "CEF:0|{}|{}|{}|{}|{}|{}|{}",
+            escape_header(PRODUCT_VENDOR),
+            escape_header(PRODUCT_NAME),
+            escape_header(PRODUCT_VERSION),
+            summary.kind.name(),
+            name_of(summary.kind),
+            severity,
+            extension.join(" ")
+        ))
+    }
+
+    fn content_type(&self) -> &'static str {
+        "text/plain"
+    }
+
+    /// Joins the formatted records with new lines.
+    fn batch(&self, lines: Vec<String>) -> String {
+        lines.join("\n")
+    }
+}
+
+fn name_of(kind: AuditRecordKind) -> &'static str {
+    match kind {
+        AuditRecordKind::Chained => "Request audited",
+        AuditRecordKind::Authorization => "Authorization",
+        AuditRecordKind::ResourceDeletion => "Resource deleted",
+        AuditRecordKind::ResourceModification => "Resource modified",
+        AuditRecordKind::TokenValidation => "Token validated",
+    }
+}
+
+fn escape_header(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('|', "\\|")
+}
+
+fn escape_extension(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('=', "\\=")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
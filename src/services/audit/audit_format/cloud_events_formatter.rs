@@ -0,0 +1,39 @@
+use crate::services::audit::audit_format::audit_formatter::AuditFormatter;
+use crate::services::audit::audit_format::audit_summary::AuditSummary;
+use crate::services::audit::audit_record::AuditRecord;
+use anyhow::Result;
+use serde_json::{Map, Value, json};
+
+/// The source of the CloudEvents.
+pub const CLOUD_EVENTS_SOURCE: &str = "urn:sneaksanddata:boxer";
+
+/// [`CloudEventsFormatter`] writes the records as CloudEvents 1.0 in the structured JSON mode.
+/// The type of the event is `com.sneaksanddata.boxer.audit.<kind>`, the subject is the audited
+/// resource and the data is the event in the native format. The id is derived from the record, so
+/// a record delivered several times can be deduplicated by the consumers.
+pub struct CloudEventsFormatter;
+
+impl AuditFormatter for CloudEventsFormatter {
+    fn format(&self, record: &AuditRecord) -> Result<String> {
+        let summary = AuditSummary::of(record)?;
+        let mut event = Map::new();
+        event.insert("specversion".to_string(), json!("1.0"));
+        event.insert("id".to_string(), json!(summary.id));
+        event.insert("source".to_string(), json!(CLOUD_EVENTS_SOURCE));
+        event.insert(
+            "type".to_string(),
+            json!(format!("com.sneaksanddata.boxer.audit.{}", summary.kind.name())),
+        );
+        event.insert("time".to_string(), json!(summary.rfc3339_time()));
+        if let Some(resource) = &summary.resource {
+            event.insert("subject".to_string(), json!(resource));
+        }
+        event.insert("datacontenttype".to_string(), json!("application/json"));
+        event.insert("data".to_string(), summary.event);
+        Ok(serde_json::to_string(&Value::Object(event))?)
+    }
+
+    fn content_type(&self) -> &'static str {
+        "application/cloudevents-batch+json"
+    }
+}
@@ -0,0 +1,13 @@
+use crate::services::audit::audit_format::audit_formatter::AuditFormatter;
+use crate::services::audit::audit_record::AuditRecord;
+use anyhow::Result;
+
+/// [`NativeFormatter`] writes the records as the versioned JSON envelopes of the [`AuditRecord`]:
+/// `{"schema_version": 2, "kind": ..., "timestamp": ..., "is_final": ..., "event": {...}}`.
+pub struct NativeFormatter;
+
+impl AuditFormatter for NativeFormatter {
+    fn format(&self, record: &AuditRecord) -> Result<String> {
+        Ok(serde_json::to_string(record)?)
+    }
+}
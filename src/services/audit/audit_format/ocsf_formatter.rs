@@ -0,0 +1,153 @@
+use crate::services::audit::audit_format::audit_formatter::AuditFormatter;
+use crate::services::audit::audit_format::audit_summary::{
+    AuditOutcome, AuditSummary, PRODUCT_NAME, PRODUCT_VENDOR, PRODUCT_VERSION,
+};
+use crate::services::audit::audit_record::{AuditRecord, AuditRecordKind};
+use anyhow::Result;
+use serde_json::{Value, json};
+
+/// The version of the OCSF schema of the written events.
+pub const OCSF_VERSION: &str = "1.1.0";
+
+/// The OCSF class and activity of an event.
+struct OcsfActivity {
+    category_uid: u32,
+    category_name: &'static str,
+    class_uid: u32,
+    class_name: &'static str,
+    activity_id: u32,
+    activity_name: &'static str,
+}
+
+const AUTHORIZE_SESSION: OcsfActivity = OcsfActivity {
+    category_uid: 3,
+    category_name: "Identity & Access Management",
+    class_uid: 3003,
+    class_name: "Authorize Session",
+    activity_id: 99,
+    activity_name: "Other",
+};
+
+const API_ACTIVITY: OcsfActivity = OcsfActivity {
+    category_uid: 6,
+    category_name: "Application Activity",
+    class_uid: 6003,
+    class_name: "API Activity",
+    activity_id: 99,
+    activity_name: "Other",
+};
+
+/// [`OcsfFormatter`] writes the records as OCSF events. The authorization and token validation
+/// events are written as the `Authorize Session` class, and the events of the audit chain and the
+/// resource events as the `API Activity` class. The event in the native format is kept in the
+/// `unmapped` attribute.
+pub struct OcsfFormatter;
+
+impl AuditFormatter for OcsfFormatter {
+    fn format(&self, record: &AuditRecord) -> Result<String> {
+        let summary = AuditSummary::of(record)?;
+        let activity = activity_of(&summary);
+        let (status_id, status, severity_id, severity) = match summary.outcome {
+            AuditOutcome::Success => (1, "Success", 1, "Informational"),
+            AuditOutcome::Failure => (2, "Failure", 3, "Medium"),
+            AuditOutcome::Unknown => (0, "Unknown", 1, "Informational"),
+        };
+
+        let mut event = json!({
+            "category_uid": activity.category_uid,
+            "category_name": activity.category_name,
+            "class_uid": activity.class_uid,
+            "class_name": activity.class_name,
+            "activity_id": activity.activity_id,
+            "activity_name": activity.activity_name,
+            "type_uid": activity.class_uid * 100 + activity.activity_id,
+            "time": summary.time,
+            "status_id": status_id,
+            "status": status,
+            "severity_id": severity_id,
+            "severity": severity,
+            "metadata": {
+                "version": OCSF_VERSION,
+                "product": {
+                    "name": PRODUCT_NAME,
+                    "vendor_name": PRODUCT_VENDOR,
+                    "version": PRODUCT_VERSION,
+                },
+            },
+            "unmapped": summary.event,
+        });
+        if let Some(actor) = &summary.actor {
+            event["actor"] = json!({ "user": { "name": actor } });
+        }
+        if let Some(action) = &summary.action {
+            event["api"] = json!({ "operation": action });
+        }
+        if let Some(resource) = &summary.resource {
+            event["resources"] = json!([{ "name": resource }]);
+        }
+        if let Some(reason) = &summary.reason {
+            event["status_detail"] = json!(reason);
+        }
+        if let Some(chain_id) = &summary.chain_id {
+            event["metadata"]["correlation_uid"] = json!(chain_id);
+        }
+        if let Some(request) = &summary.request {
+            event["http_request"] = json!({
+                "http_method": request.method,
+                "url": { "path": request.path },
+                "user_agent": request.user_agent,
+            });
+            if let Some(client_ip) = &request.client_ip {
+                event["src_endpoint"] = json!({ "ip": client_ip });
+            }
+            if let Some(status_code) = request.status_code {
+                event["http_response"] = json!({ "code": status_code });
+            }
+        }
+        strip_nulls(&mut event);
+        Ok(serde_json::to_string(&event)?)
+    }
+}
+
+fn activity_of(summary: &AuditSummary) -> OcsfActivity {
+    match summary.kind {
+        AuditRecordKind::Authorization => AUTHORIZE_SESSION,
+        AuditRecordKind::TokenValidation => AUTHORIZE_SESSION,
+        AuditRecordKind::ResourceModification => OcsfActivity {
+            activity_id: 3,
+            activity_name: "Update",
+            ..API_ACTIVITY
+        },
+        AuditRecordKind::ResourceDeletion => OcsfActivity {
+            activity_id: 4,
+            activity_name: "Delete",
+            ..API_ACTIVITY
+        },
+        AuditRecordKind::Chained => {
+            let method = summary.request.as_ref().and_then(|r| r.method.as_deref());
+            let (activity_id, activity_name) = match method {
+                Some("POST") => (1, "Create"),
+                Some("GET") | Some("HEAD") => (2, "Read"),
+                Some("PUT") | Some("PATCH") => (3, "Update"),
+                Some("DELETE") => (4, "Delete"),
+                _ => (99, "Other"),
+            };
+            OcsfActivity {
+                activity_id,
+                activity_name,
+                ..API_ACTIVITY
+            }
+        }
+    }
+}
+
+/// Removes the attributes without a value from the objects built by the formatter. The original
+/// event in the `unmapped` attribute is kept as is.
+fn strip_nulls(event: &mut Value) {
+    if let Value::Object(map) = event {
+        map.retain(|key, value| key == "unmapped" || !value.is_null());
+        map.iter_mut()
+            .filter(|(key, _)| key.as_str() != "unmapped")
+            .for_each(|(_, value)| strip_nulls(value));
+    }
+}
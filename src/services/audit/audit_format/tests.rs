@@ -0,0 +1,217 @@
+use crate::services::audit::audit_format::AuditFormat;
+use crate::services::audit::audit_format::audit_formatter::AuditFormatter;
+use crate::services::audit::audit_format::audit_summary::AuditSummary;
+use crate::services::audit::audit_format::cef_formatter::CefFormatter;
+use crate::services::audit::audit_format::cloud_events_formatter::{CLOUD_EVENTS_SOURCE, CloudEventsFormatter};
+use crate::services::audit::audit_format::native_formatter::NativeFormatter;
+use crate::services::audit::audit_format::ocsf_formatter::OcsfFormatter;
+use crate::services::audit::audit_record::AuditRecord;
+use crate::services::audit::chained::audit_event::AuditEvent;
+use crate::services::audit::chained::chained_audit_event::ChainedAuditEvent;
+use crate::services::audit::chained::request_metadata::RequestMetadata;
+use crate::services::audit::events::authorization_audit_event::{AuthorizationAuditEvent, Reason};
+use crate::services::audit::events::resource_delete_audit_event::ResourceDeleteAuditEvent;
+use crate::services::audit::events::token_validation_event::TokenValidationEvent;
+use crate::testing::audit_events::AuthorizationAuditEventBuilder;
+use cedar_policy::Decision;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use serde_json::{Value, json};
+use std::collections::HashSet;
+
+#[test]
+fn test_native_format() {
+    // Arrange
+    let record = AuthorizationAuditEventBuilder::default()
+        .with_decision(Decision::Allow)
+        .record();
+
+    // Act
+    let line = NativeFormatter.format(&record).expect("record should be formatted");
+
+    // Assert
+    let value: Value = serde_json::from_str(&line).expect("line should be JSON");
+    assert_eq!(value, record.to_json().unwrap());
+}
+
+#[test]
+fn test_cloud_events_format() {
+    // Arrange
+    let record = make_chained();
+
+    // Act
+    let line = CloudEventsFormatter
+        .format(&record)
+        .expect("record should be formatted");
+
+    // Assert
+    let value: Value = serde_json::from_str(&line).expect("line should be JSON");
+    assert_eq!(value["specversion"], json!("1.0"));
+    assert_eq!(value["source"], json!(CLOUD_EVENTS_SOURCE));
+    assert_eq!(value["type"], json!("com.sneaksanddata.boxer.audit.chained"));
+    assert_eq!(value["time"], json!("2023-11-14T22:13:20.000Z"));
+    assert_eq!(value["subject"], json!("documents/1"));
+    assert_eq!(value["datacontenttype"], json!("application/json"));
+    assert_eq!(value["data"]["actor"], json!("alice"));
+    assert_eq!(value["id"], json!("chain-1/0"));
+}
+
+#[test]
+fn test_cloud_events_identify_record() {
+    // Arrange
+    let record = make_deletion();
+
+    // Act
+    let first: Value = serde_json::from_str(&CloudEventsFormatter.format(&record).unwrap()).unwrap();
+    let retried: Value = serde_json::from_str(&CloudEventsFormatter.format(&record).unwrap()).unwrap();
+
+    // Assert
+    assert_eq!(first["id"], retried["id"]);
+    assert_eq!(first["id"], json!(AuditSummary::of(&record).unwrap().id));
+    assert_eq!(first["time"], retried["time"]);
+    assert_eq!(first["time"], json!(AuditSummary::of(&record).unwrap().rfc3339_time()));
+    assert_eq!(AuditSummary::of(&record).unwrap().time, record.timestamp());
+}
+
+#[rstest]
+#[case(make_chained(), 6003, 600304, "delete")]
+#[case(AuthorizationAuditEventBuilder::default().with_decision(Decision::Deny).record(), 3003, 300399, "read")]
+#[case(make_token_validation(), 3003, 300399, "validate_external_token")]
+#[case(make_deletion(), 6003, 600304, "delete")]
+fn test_ocsf_format(
+    #[case] record: AuditRecord,
+    #[case] class_uid: u64,
+    #[case] type_uid: u64,
+    #[case] operation: &str,
+) {
+    // Act
+    let line = OcsfFormatter.format(&record).expect("record should be formatted");
+
+    // Assert
+    let value: Value = serde_json::from_str(&line).expect("line should be JSON");
+    assert_eq!(value["class_uid"], json!(class_uid));
+    assert_eq!(value["type_uid"], json!(type_uid));
+    assert_eq!(value["api"]["operation"], json!(operation));
+    assert_eq!(value["metadata"]["product"]["name"], json!("Boxer"));
+    assert_eq!(value["unmapped"], record.to_json().unwrap()["event"]);
+}
+
+#[test]
+fn test_ocsf_format_chained_event() {
+    // Arrange
+    let record = make_chained();
+
+    // Act
+    let line = OcsfFormatter.format(&record).expect("record should be formatted");
+
+    // Assert
+    let value: Value = serde_json::from_str(&line).expect("line should be JSON");
+    assert_eq!(value["activity_name"], json!("Delete"));
+    assert_eq!(value["time"], json!(1_700_000_000_000u64));
+    assert_eq!(value["status_id"], json!(2));
+    assert_eq!(value["status_detail"], json!("policies: deny-all"));
+    assert_eq!(value["actor"]["user"]["name"], json!("alice"));
+    assert_eq!(value["resources"], json!([{ "name": "documents/1" }]));
+    assert_eq!(value["metadata"]["correlation_uid"], json!("chain-1"));
+    assert_eq!(
+        value["http_request"],
+        json!({ "http_method": "DELETE", "url": { "path": "/documents/1" } })
+    );
+    assert_eq!(value["http_response"], json!({ "code": 403 }));
+}
+
+#[test]
+fn test_cef_format() {
+    // Arrange
+    let record = make_chained();
+
+    // Act
+    let line = CefFormatter.format(&record).expect("record should be formatted");
+
+    // Assert
+    assert_eq!(
+        line,
+        format!(
+            "CEF:0|SneaksAndData|Boxer|{}|chained|Request audited|7|rt=1700000000000 suser=alice act=delete \
+             outcome=failure reason=policies: deny-all cs1Label=resource cs1=documents/1 externalId=chain-1 \
+             requestMethod=DELETE request=/documents/1",
+            env!("CARGO_PKG_VERSION")
+        )
+    );
+}
+
+#[test]
+fn test_cef_format_escapes_extensions() {
+    // Arrange
+    let record = AuditRecord::from(AuthorizationAuditEvent {
+        action: "read".to_string(),
+        actor: "alice=admin\\ops\nroot".to_string(),
+        resource: "documents|1".to_string(),
+        decision: Decision::Allow,
+        reason: Reason {
+            policies: HashSet::new(),
+            errors: HashSet::new(),
+        },
+    });
+
+    // Act
+    let line = CefFormatter.format(&record).expect("record should be formatted");
+
+    // Assert
+    assert!(line.contains("suser=alice\\=admin\\\\ops\\nroot "), "{}", line);
+    assert!(line.contains("cs1=documents|1"), "{}", line);
+    assert!(!line.contains('\n'), "{}", line);
+}
+
+#[rstest]
+#[case(AuditFormat::Native, "[{\"a\":1},{\"b\":2}]")]
+#[case(AuditFormat::CloudEvents, "[{\"a\":1},{\"b\":2}]")]
+#[case(AuditFormat::Ocsf, "[{\"a\":1},{\"b\":2}]")]
+#[case(AuditFormat::Cef, "{\"a\":1}\n{\"b\":2}")]
+fn test_batch(#[case] format: AuditFormat, #[case] expected: &str) {
+    // Act
+    let batch = format
+        .formatter()
+        .batch(vec!["{\"a\":1}".to_string(), "{\"b\":2}".to_string()]);
+
+    // Assert
+    assert_eq!(batch, expected);
+}
+
+fn make_chained() -> AuditRecord {
+    AuditRecord::from(AuditEvent::Final(ChainedAuditEvent {
+        action: Some("delete".to_string()),
+        actor: Some("alice".to_string()),
+        resource: Some("documents/1".to_string()),
+        decision: Some(Decision::Deny),
+        reason: Some(Reason {
+            policies: HashSet::from(["deny-all".to_string()]),
+            errors: HashSet::new(),
+        }),
+        request: Some(RequestMetadata {
+            method: Some("DELETE".to_string()),
+            path: Some("/documents/1".to_string()),
+            status_code: Some(403),
+            ..RequestMetadata::default()
+        }),
+        chain_id: Some("chain-1".to_string()),
+        created_at: Some(1_699_999_999_000),
+        finalized_at: Some(1_700_000_000_000),
+        ..ChainedAuditEvent::empty()
+    }))
+}
+
+fn make_token_validation() -> AuditRecord {
+    AuditRecord::from(TokenValidationEvent::external_empty(
+        false,
+        HashSet::from(["expired".to_string()]),
+    ))
+}
+
+fn make_deletion() -> AuditRecord {
+    AuditRecord::from(ResourceDeleteAuditEvent::new(
+        "schema-a".to_string(),
+        "schema".to_string(),
+        true,
+    ))
+}
@@ -0,0 +1,246 @@
+#[cfg(test)]
+mod tests;
+
+use crate::services::audit::chained::audit_event::AuditEvent;
+use crate::services::audit::chained::chained_audit_event::now_millis;
+use crate::services::audit::events::authorization_audit_event::AuthorizationAuditEvent;
+use crate::services::audit::events::resource_delete_audit_event::ResourceDeleteAuditEvent;
+use crate::services::audit::events::resource_modification_audit_event::ResourceModificationAuditEvent;
+use crate::services::audit::events::token_validation_event::{TokenValidationEvent, TokenValidationResult};
+use cedar_policy::Decision;
+use serde::de::Error as _;
+use serde::ser::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+/// The version of the schema of the serialized audit records. It is incremented when the
+/// serialized form of a record changes in a way the readers must handle.
+pub const AUDIT_RECORD_SCHEMA_VERSION: u32 = 2;
+
+/// [`AuditRecord`] is any audit event recorded through the `AuditService`, timestamped when the
+/// record is created.
+///
+/// The record is serialized as a versioned envelope shared by all audit sinks:
+/// `{"schema_version": 2, "kind": ..., "timestamp": ..., "is_final": ..., "event": {...}}`. The
+/// `timestamp` is the time of the record in milliseconds since the Unix epoch. The `is_final` flag
+/// tells apart the final and intermediate events of the audit chain, and is always `true` for the
+/// other kinds.
+#[derive(Debug, Clone)]
+pub struct AuditRecord {
+    timestamp: u64,
+    event: AuditRecordEvent,
+}
+
+/// The event of the [`AuditRecord`].
+#[derive(Debug, Clone)]
+pub enum AuditRecordEvent {
+    /// The event of the audit chain written by the audit recorder.
+    Chained(Box<AuditEvent>),
+    Authorization(AuthorizationAuditEvent),
+    ResourceDeletion(ResourceDeleteAuditEvent),
+    ResourceModification(ResourceModificationAuditEvent),
+    TokenValidation(TokenValidationEvent),
+}
+
+/// The kind of the [`AuditRecord`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditRecordKind {
+    Chained,
+    Authorization,
+    ResourceDeletion,
+    ResourceModification,
+    TokenValidation,
+}
+
+impl AuditRecordKind {
+    /// Returns the name of the kind used in the serialized records.
+    pub fn name(&self) -> &'static str {
+        match self {
+            AuditRecordKind::Chained => "chained",
+            AuditRecordKind::Authorization => "authorization",
+            AuditRecordKind::ResourceDeletion => "resource_deletion",
+            AuditRecordKind::ResourceModification => "resource_modification",
+            AuditRecordKind::TokenValidation => "token_validation",
+        }
+    }
+}
+
+impl AuditRecord {
+    /// Creates the record of the event, timestamped with the current time.
+    pub fn new(event: AuditRecordEvent) -> Self {
+        AuditRecord {
+            timestamp: now_millis(),
+            event,
+        }
+    }
+
+    /// Returns the time of the record in milliseconds since the Unix epoch.
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    pub fn event(&self) -> &AuditRecordEvent {
+        &self.event
+    }
+
+    pub fn into_event(self) -> AuditRecordEvent {
+        self.event
+    }
+
+    /// Replaces the event of the record, keeping its timestamp.
+    pub(crate) fn map_event(self, map: impl FnOnce(AuditRecordEvent) -> AuditRecordEvent) -> Self {
+        AuditRecord {
+            timestamp: self.timestamp,
+            event: map(self.event),
+        }
+    }
+
+    /// Returns the kind of the record.
+    pub fn kind(&self) -> AuditRecordKind {
+        match &self.event {
+            AuditRecordEvent::Chained(_) => AuditRecordKind::Chained,
+            AuditRecordEvent::Authorization(_) => AuditRecordKind::Authorization,
+            AuditRecordEvent::ResourceDeletion(_) => AuditRecordKind::ResourceDeletion,
+            AuditRecordEvent::ResourceModification(_) => AuditRecordKind::ResourceModification,
+            AuditRecordEvent::TokenValidation(_) => AuditRecordKind::TokenValidation,
+        }
+    }
+
+    /// Returns the decision of the record. The result of a token validation is returned as the
+    /// decision, and resource records have no decision.
+    pub fn decision(&self) -> Option<Decision> {
+        match &self.event {
+            AuditRecordEvent::Chained(event) => event.event().decision(),
+            AuditRecordEvent::Authorization(event) => Some(event.decision),
+            AuditRecordEvent::TokenValidation(event) => match event.result {
+                TokenValidationResult::Allow => Some(Decision::Allow),
+                TokenValidationResult::Deny => Some(Decision::Deny),
+            },
+            AuditRecordEvent::ResourceDeletion(_) | AuditRecordEvent::ResourceModification(_) => None,
+        }
+    }
+
+    /// Checks if the record is final. Only the events of the audit chain can be intermediate.
+    pub fn is_final(&self) -> bool {
+        match &self.event {
+            AuditRecordEvent::Chained(event) => event.is_final(),
+            _ => true,
+        }
+    }
+
+    /// Converts the record to the JSON object written by the audit sinks.
+    pub fn to_json(&self) -> serde_json::Result<Value> {
+        serde_json::to_value(self)
+    }
+}
+
+impl From<AuditEvent> for AuditRecord {
+    fn from(event: AuditEvent) -> Self {
+        AuditRecord::new(AuditRecordEvent::Chained(Box::new(event)))
+    }
+}
+
+impl From<AuthorizationAuditEvent> for AuditRecord {
+    fn from(event: AuthorizationAuditEvent) -> Self {
+        AuditRecord::new(AuditRecordEvent::Authorization(event))
+    }
+}
+
+impl From<ResourceDeleteAuditEvent> for AuditRecord {
+    fn from(event: ResourceDeleteAuditEvent) -> Self {
+        AuditRecord::new(AuditRecordEvent::ResourceDeletion(event))
+    }
+}
+
+impl From<ResourceModificationAuditEvent> for AuditRecord {
+    fn from(event: ResourceModificationAuditEvent) -> Self {
+        AuditRecord::new(AuditRecordEvent::ResourceModification(event))
+    }
+}
+
+impl From<TokenValidationEvent> for AuditRecord {
+    fn from(event: TokenValidationEvent) -> Self {
+        AuditRecord::new(AuditRecordEvent::TokenValidation(event))
+    }
+}
+
+/// The serialized form of the [`AuditRecord`].
+#[derive(Serialize, Deserialize)]
+struct AuditRecordEnvelope {
+    /// The records written before the schema was versioned have no version, and are read as the
+    /// first version.
+    #[serde(default = "first_schema_version")]
+    schema_version: u32,
+    kind: AuditRecordKind,
+
+    /// The records of the first version have no timestamp, and are read with zero.
+    #[serde(default)]
+    timestamp: u64,
+
+    is_final: bool,
+    event: Value,
+}
+
+impl Serialize for AuditRecord {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let event = match &self.event {
+            AuditRecordEvent::Chained(event) => serde_json::to_value(event.event()),
+            AuditRecordEvent::Authorization(event) => serde_json::to_value(event),
+            AuditRecordEvent::ResourceDeletion(event) => serde_json::to_value(event),
+            AuditRecordEvent::ResourceModification(event) => serde_json::to_value(event),
+            AuditRecordEvent::TokenValidation(event) => serde_json::to_value(event),
+        }
+        .map_err(S::Error::custom)?;
+        AuditRecordEnvelope {
+            schema_version: AUDIT_RECORD_SCHEMA_VERSION,
+            kind: self.kind(),
+            timestamp: self.timestamp,
+            is_final: self.is_final(),
+            event,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for AuditRecord {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let envelope = AuditRecordEnvelope::deserialize(deserializer)?;
+        if envelope.schema_version > AUDIT_RECORD_SCHEMA_VERSION {
+            return Err(D::Error::custom(format!(
+                "Unsupported audit record schema version: {}",
+                envelope.schema_version
+            )));
+        }
+        let event = envelope.event;
+        let event = match envelope.kind {
+            AuditRecordKind::Chained => {
+                let event = serde_json::from_value(event).map_err(D::Error::custom)?;
+                AuditRecordEvent::Chained(Box::new(match envelope.is_final {
+                    true => AuditEvent::Final(event),
+                    false => AuditEvent::Intermediate(event),
+                }))
+            }
+            AuditRecordKind::Authorization => {
+                AuditRecordEvent::Authorization(serde_json::from_value(event).map_err(D::Error::custom)?)
+            }
+            AuditRecordKind::ResourceDeletion => {
+                AuditRecordEvent::ResourceDeletion(serde_json::from_value(event).map_err(D::Error::custom)?)
+            }
+            AuditRecordKind::ResourceModification => {
+                AuditRecordEvent::ResourceModification(serde_json::from_value(event).map_err(D::Error::custom)?)
+            }
+            AuditRecordKind::TokenValidation => {
+                AuditRecordEvent::TokenValidation(serde_json::from_value(event).map_err(D::Error::custom)?)
+            }
+        };
+        Ok(AuditRecord {
+            timestamp: envelope.timestamp,
+            event,
+        })
+    }
+}
+
+fn first_schema_version() -> u32 {
+    1
+}
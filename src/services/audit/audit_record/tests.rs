@@ -0,0 +1,162 @@
+use crate::services::audit::audit_record::{AUDIT_RECORD_SCHEMA_VERSION, AuditRecord, AuditRecordKind};
+use crate::services::audit::chained::audit_event::AuditEvent;
+use crate::services::audit::chained::chained_audit_event::{ChainedAuditEvent, now_millis};
+use crate::services::audit::chained::request_metadata::RequestMetadata;
+use crate::services::audit::chained::token_audit_event::TokenAuditEvent;
+use crate::services::audit::events::authorization_audit_event::Reason;
+use crate::services::audit::events::resource_delete_audit_event::ResourceDeleteAuditEvent;
+use crate::services::audit::events::resource_modification_audit_event::{
+    ModificationResult, ResourceModificationAuditEvent,
+};
+use crate::services::audit::events::token_validation_event::TokenValidationEvent;
+use crate::testing::audit_events::AuthorizationAuditEventBuilder;
+use cedar_policy::Decision;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use serde_json::json;
+use std::collections::HashSet;
+
+#[rstest]
+#[case(make_chained(true), AuditRecordKind::Chained, true)]
+#[case(make_chained(false), AuditRecordKind::Chained, false)]
+#[case(AuthorizationAuditEventBuilder::default().with_policy("allow-read").record(), AuditRecordKind::Authorization, true)]
+#[case(make_deletion(), AuditRecordKind::ResourceDeletion, true)]
+#[case(make_modification(ModificationResult::Success("{}".to_string())), AuditRecordKind::ResourceModification, true)]
+#[case(
+    make_modification(ModificationResult::Failure),
+    AuditRecordKind::ResourceModification,
+    true
+)]
+#[case(make_token_validation(), AuditRecordKind::TokenValidation, true)]
+fn test_round_trip(#[case] record: AuditRecord, #[case] kind: AuditRecordKind, #[case] is_final: bool) {
+    // Act
+    let serialized = serde_json::to_string(&record).expect("record should be serialized");
+    let deserialized: AuditRecord = serde_json::from_str(&serialized).expect("record should be deserialized");
+
+    // Assert
+    assert_eq!(deserialized.kind(), kind);
+    assert_eq!(deserialized.is_final(), is_final);
+    assert_eq!(deserialized.timestamp(), record.timestamp());
+    assert_eq!(deserialized.to_json().unwrap(), record.to_json().unwrap());
+}
+
+#[test]
+fn test_serialize_envelope() {
+    // Arrange
+    let record = make_deletion();
+
+    // Act
+    let value = serde_json::to_value(&record).expect("record should be serialized");
+
+    // Assert
+    assert_eq!(
+        value,
+        json!({
+            "schema_version": AUDIT_RECORD_SCHEMA_VERSION,
+            "kind": "resource_deletion",
+            "timestamp": record.timestamp(),
+            "is_final": true,
+            "event": {
+                "id": "schema-a",
+                "resource_type": "schema",
+                "successful": true,
+            },
+        })
+    );
+}
+
+#[test]
+fn test_deserialize_unversioned_record() {
+    // Arrange
+    let value = json!({
+        "kind": "authorization",
+        "is_final": true,
+        "event": {
+            "action": "read",
+            "actor": "alice",
+            "resource": "documents/1",
+            "decision": "allow",
+            "reason": { "policies": [], "errors": [] },
+        },
+    });
+
+    // Act
+    let record: AuditRecord = serde_json::from_value(value).expect("record should be deserialized");
+
+    // Assert
+    assert_eq!(record.kind(), AuditRecordKind::Authorization);
+    assert_eq!(record.decision(), Some(Decision::Allow));
+    assert_eq!(record.timestamp(), 0);
+}
+
+#[test]
+fn test_timestamp_on_creation() {
+    // Arrange
+    let before = now_millis();
+
+    // Act
+    let record = make_deletion();
+
+    // Assert
+    assert!(record.timestamp() >= before && record.timestamp() <= now_millis());
+}
+
+#[rstest]
+#[case(json!({ "schema_version": AUDIT_RECORD_SCHEMA_VERSION + 1, "kind": "resource_deletion", "is_final": true, "event": {} }), "Unsupported audit record schema version")]
+#[case(json!({ "schema_version": 1, "kind": "unknown", "is_final": true, "event": {} }), "unknown variant")]
+#[case(json!({ "schema_version": 1, "kind": "resource_deletion", "is_final": true, "event": {} }), "missing field")]
+fn test_reject_invalid_record(#[case] value: serde_json::Value, #[case] expected: &str) {
+    // Act
+    let result = serde_json::from_value::<AuditRecord>(value);
+
+    // Assert
+    let error = result.expect_err("record should be rejected");
+    assert!(error.to_string().contains(expected), "{}", error);
+}
+
+fn make_chained(is_final: bool) -> AuditRecord {
+    let event = ChainedAuditEvent {
+        external_token: Some(TokenAuditEvent::external()),
+        action: Some("read".to_string()),
+        actor: Some("alice".to_string()),
+        decision: Some(Decision::Deny),
+        reason: Some(Reason {
+            policies: HashSet::from(["deny-all".to_string()]),
+            errors: HashSet::new(),
+        }),
+        request: Some(RequestMetadata {
+            method: Some("GET".to_string()),
+            status_code: Some(403),
+            ..RequestMetadata::default()
+        }),
+        ..ChainedAuditEvent::begin()
+    };
+    AuditRecord::from(match is_final {
+        true => AuditEvent::Final(event),
+        false => AuditEvent::Intermediate(event),
+    })
+}
+
+fn make_deletion() -> AuditRecord {
+    AuditRecord::from(ResourceDeleteAuditEvent::new(
+        "schema-a".to_string(),
+        "schema".to_string(),
+        true,
+    ))
+}
+
+fn make_modification(result: ModificationResult) -> AuditRecord {
+    AuditRecord::from(ResourceModificationAuditEvent::new(
+        "schema-a".to_string(),
+        "schema".to_string(),
+        result,
+    ))
+}
+
+fn make_token_validation() -> AuditRecord {
+    let metadata = serde_json::from_value(json!({ "sub": "alice", "iss": "issuer", "exp": 1700000000 }))
+        .expect("metadata should be deserialized");
+    let mut event = TokenValidationEvent::external_empty(true, HashSet::new());
+    event.token_metadata = Some(metadata);
+    AuditRecord::from(event)
+}
@@ -0,0 +1,40 @@
+pub mod audit_redaction_settings;
+pub mod audit_redactor;
+#[cfg(test)]
+mod tests;
+
+use crate::services::audit::AuditService;
+use crate::services::audit::audit_record::AuditRecord;
+use crate::services::audit::audit_redaction::audit_redactor::AuditRedactor;
+use crate::services::audit::audit_sink::AuditSink;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// [`RedactingAuditService`] redacts the personal data of the audit events with the
+/// [`AuditRedactor`] before they are passed to the inner service. Placed in front of the
+/// `ComposedAuditService`, it makes sure that none of the sinks writes the redacted fields.
+pub struct RedactingAuditService<Inner> {
+    redactor: AuditRedactor,
+    inner: Arc<Inner>,
+}
+
+impl<Inner> RedactingAuditService<Inner> {
+    pub fn new(redactor: AuditRedactor, inner: Arc<Inner>) -> Self {
+        RedactingAuditService { redactor, inner }
+    }
+}
+
+impl<Inner: AuditService> AuditService for RedactingAuditService<Inner> {
+    fn record(&self, record: AuditRecord) -> Result<()> {
+        self.inner.record(self.redactor.redact(record))
+    }
+}
+
+#[async_trait]
+impl<Inner: AuditSink> AuditSink for RedactingAuditService<Inner> {
+    async fn write_batch(&self, records: Vec<AuditRecord>) -> Result<()> {
+        let records = records.into_iter().map(|record| self.redactor.redact(record)).collect();
+        self.inner.write_batch(records).await
+    }
+}
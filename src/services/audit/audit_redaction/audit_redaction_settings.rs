@@ -0,0 +1,72 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+
+/// The settings of the [`AuditRedactor`].
+///
+/// [`AuditRedactor`]: crate::services::audit::audit_redaction::audit_redactor::AuditRedactor
+#[derive(Clone, Default, Deserialize)]
+pub struct AuditRedactionSettings {
+    /// The salt of the hashed fields. Required if any field is hashed.
+    #[serde(default)]
+    pub salt: Option<String>,
+
+    /// The rules of the redacted fields. The fields without a rule are kept as is.
+    #[serde(default)]
+    pub fields: HashMap<RedactedField, RedactionRule>,
+
+    /// The rules of the custom attributes of the events of the audit chain, by the attribute name.
+    /// The attributes are set by the request handlers, so the attributes carrying personal data
+    /// must be listed here. The attributes without a rule are kept as is.
+    #[serde(default)]
+    pub attributes: HashMap<String, RedactionRule>,
+}
+
+impl Debug for AuditRedactionSettings {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuditRedactionSettings")
+            .field("salt", &self.salt.as_ref().map(|_| "<redacted>"))
+            .field("fields", &self.fields)
+            .field("attributes", &self.attributes)
+            .finish()
+    }
+}
+
+/// The fields of the audit records carrying personal data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactedField {
+    /// The actor of the authorization events and of the events of the audit chain, including the
+    /// events of the callers.
+    Actor,
+
+    /// The `sub` claim of the validated tokens.
+    TokenSubject,
+
+    /// The `iss` claim of the validated tokens.
+    TokenIssuer,
+
+    /// The `aud` claim of the validated tokens.
+    TokenAudience,
+
+    /// The address of the client in the request metadata.
+    ClientIp,
+
+    /// The user agent of the client in the request metadata.
+    UserAgent,
+}
+
+/// Selects how a field is redacted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "strategy")]
+pub enum RedactionRule {
+    /// The field is removed. The required fields are replaced with an empty string.
+    Drop,
+
+    /// The field is replaced with the hex-encoded HMAC-SHA256 of the value keyed by the salt, so
+    /// the records of the same person can still be correlated.
+    Hash,
+
+    /// The field is shortened to the number of characters.
+    Truncate { length: usize },
+}
@@ -0,0 +1,118 @@
+use crate::services::audit::audit_record::{AuditRecord, AuditRecordEvent};
+use crate::services::audit::audit_redaction::audit_redaction_settings::{
+    AuditRedactionSettings, RedactedField, RedactionRule,
+};
+use crate::services::audit::chained::audit_event::AuditEvent;
+use crate::services::audit::chained::chained_audit_event::ChainedAuditEvent;
+use crate::services::audit::events::authorization_audit_event::AuthorizationAuditEvent;
+use crate::services::audit::events::token_validation_event::TokenValidationEvent;
+use anyhow::{Result, bail};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::HashMap;
+
+/// [`AuditRedactor`] pseudonymizes the personal data of the audit records according to the
+/// rules configured per field.
+pub struct AuditRedactor {
+    rules: HashMap<RedactedField, RedactionRule>,
+    attribute_rules: HashMap<String, RedactionRule>,
+    salt: Vec<u8>,
+}
+
+impl AuditRedactor {
+    /// Creates the redactor. Fails if a field is hashed and the salt is not set.
+    pub fn new(settings: AuditRedactionSettings) -> Result<Self> {
+        let hashes = settings
+            .fields
+            .values()
+            .chain(settings.attributes.values())
+            .any(|rule| *rule == RedactionRule::Hash);
+        let salt = match settings.salt {
+            Some(salt) if !salt.is_empty() => salt.into_bytes(),
+            _ if hashes => bail!("The salt must be set to hash the audit record fields"),
+            _ => Vec::new(),
+        };
+        Ok(AuditRedactor {
+            rules: settings.fields,
+            attribute_rules: settings.attributes,
+            salt,
+        })
+    }
+
+    /// Redacts the fields of the record.
+    pub fn redact(&self, record: AuditRecord) -> AuditRecord {
+        record.map_event(|event| match event {
+            AuditRecordEvent::Chained(event) => AuditRecordEvent::Chained(Box::new(self.redact_event(*event))),
+            AuditRecordEvent::Authorization(event) => AuditRecordEvent::Authorization(self.redact_authorization(event)),
+            AuditRecordEvent::TokenValidation(event) => {
+                AuditRecordEvent::TokenValidation(self.redact_token_validation(event))
+            }
+            resource_event => resource_event,
+        })
+    }
+
+    /// Redacts the fields of the event of the audit chain. The state of the event is preserved.
+    pub fn redact_event(&self, event: AuditEvent) -> AuditEvent {
+        match event {
+            AuditEvent::Final(event) => AuditEvent::Final(self.redact_chained(event)),
+            AuditEvent::Intermediate(event) => AuditEvent::Intermediate(self.redact_chained(event)),
+        }
+    }
+
+    pub fn redact_authorization(&self, mut event: AuthorizationAuditEvent) -> AuthorizationAuditEvent {
+        event.actor = self.apply(RedactedField::Actor, Some(event.actor)).unwrap_or_default();
+        event
+    }
+
+    pub fn redact_token_validation(&self, mut event: TokenValidationEvent) -> TokenValidationEvent {
+        if let Some(metadata) = event.token_metadata.as_mut() {
+            metadata.sub = self.apply(RedactedField::TokenSubject, metadata.sub.take());
+            metadata.iss = self.apply(RedactedField::TokenIssuer, metadata.iss.take());
+            metadata.aud = self.apply(RedactedField::TokenAudience, metadata.aud.take());
+        }
+        event
+    }
+
+    /// Redacts the fields and the attributes of the event, and of the event of the caller linked
+    /// to it.
+    fn redact_chained(&self, mut event: ChainedAuditEvent) -> ChainedAuditEvent {
+        event.actor = self.apply(RedactedField::Actor, event.actor.take());
+        event.attributes = std::mem::take(&mut event.attributes)
+            .into_iter()
+            .filter_map(|(key, value)| {
+                let value = self.apply_rule(self.attribute_rules.get(&key), Some(value))?;
+                Some((key, value))
+            })
+            .collect();
+        if let Some(request) = event.request.as_mut() {
+            request.client_ip = self.apply(RedactedField::ClientIp, request.client_ip.take());
+            request.user_agent = self.apply(RedactedField::UserAgent, request.user_agent.take());
+        }
+        if let Some(parent) = event.parent.as_mut()
+            && let Some(caller) = parent.event.take()
+        {
+            parent.event = Some(Box::new(self.redact_chained(*caller)));
+        }
+        event
+    }
+
+    fn apply(&self, field: RedactedField, value: Option<String>) -> Option<String> {
+        self.apply_rule(self.rules.get(&field), value)
+    }
+
+    fn apply_rule(&self, rule: Option<&RedactionRule>, value: Option<String>) -> Option<String> {
+        let value = value?;
+        match rule {
+            None => Some(value),
+            Some(RedactionRule::Drop) => None,
+            Some(RedactionRule::Hash) => Some(self.hash(&value)),
+            Some(RedactionRule::Truncate { length }) => Some(value.chars().take(*length).collect()),
+        }
+    }
+
+    fn hash(&self, value: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.salt).expect("HMAC accepts keys of any length");
+        mac.update(value.as_bytes());
+        format!("{:x}", mac.finalize().into_bytes())
+    }
+}
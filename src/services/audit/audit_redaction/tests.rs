@@ -0,0 +1,276 @@
+use crate::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
+use crate::services::audit::AuditService;
+use crate::services::audit::audit_record::{AuditRecord, AuditRecordEvent};
+use crate::services::audit::audit_redaction::RedactingAuditService;
+use crate::services::audit::audit_redaction::audit_redaction_settings::{
+    AuditRedactionSettings, RedactedField, RedactionRule,
+};
+use crate::services::audit::audit_redaction::audit_redactor::AuditRedactor;
+use crate::services::audit::chained::audit_chain_link::AuditChainLink;
+use crate::services::audit::chained::audit_event::AuditEvent;
+use crate::services::audit::chained::chained_audit_event::ChainedAuditEvent;
+use crate::services::audit::chained::request_metadata::RequestMetadata;
+use crate::services::audit::events::token_validation_event::TokenValidationEvent;
+use crate::testing::audit_events::AuthorizationAuditEventBuilder;
+use anyhow::Result;
+use hmac::{Hmac, Mac};
+use mockall::mock;
+use pretty_assertions::assert_eq;
+use serde_json::{Value, json};
+use sha2::Sha256;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+#[test]
+fn test_redact_chained_event() {
+    // Arrange
+    let redactor = make_redactor([
+        (RedactedField::Actor, RedactionRule::Hash),
+        (RedactedField::ClientIp, RedactionRule::Drop),
+        (RedactedField::UserAgent, RedactionRule::Truncate { length: 7 }),
+    ]);
+    let event = AuditEvent::Final(ChainedAuditEvent {
+        actor: Some("alice".to_string()),
+        request: Some(RequestMetadata {
+            client_ip: Some("10.0.0.1".to_string()),
+            user_agent: Some("Mozilla/5.0".to_string()),
+            ..RequestMetadata::default()
+        }),
+        parent: Some(AuditChainLink {
+            id: "link".to_string(),
+            issuer: "caller".to_string(),
+            event: Some(Box::new(ChainedAuditEvent {
+                actor: Some("alice".to_string()),
+                ..ChainedAuditEvent::empty()
+            })),
+        }),
+        ..ChainedAuditEvent::begin()
+    });
+
+    // Act
+    let redacted = redactor.redact_event(event);
+
+    // Assert
+    let AuditEvent::Final(redacted) = redacted else {
+        panic!("event should remain final");
+    };
+    assert_eq!(redacted.actor, Some(hash_of("alice")));
+    let request = redacted.request.expect("request should be kept");
+    assert_eq!(request.client_ip, None);
+    assert_eq!(request.user_agent, Some("Mozilla".to_string()));
+    let caller = redacted
+        .parent
+        .and_then(|parent| parent.event)
+        .expect("caller should be kept");
+    assert_eq!(caller.actor, Some(hash_of("alice")));
+}
+
+#[test]
+fn test_redact_attributes() {
+    // Arrange
+    let redactor = AuditRedactor::new(AuditRedactionSettings {
+        salt: Some("salt".to_string()),
+        attributes: HashMap::from([
+            ("email".to_string(), RedactionRule::Drop),
+            ("customer".to_string(), RedactionRule::Hash),
+        ]),
+        ..AuditRedactionSettings::default()
+    })
+    .expect("redactor should be created");
+    let event = AuditEvent::Intermediate(ChainedAuditEvent {
+        attributes: BTreeMap::from([
+            ("email".to_string(), "alice@example.com".to_string()),
+            ("customer".to_string(), "alice".to_string()),
+            ("tenant".to_string(), "acme".to_string()),
+        ]),
+        ..ChainedAuditEvent::begin()
+    });
+
+    // Act
+    let redacted = redactor.redact_event(event);
+
+    // Assert
+    assert_eq!(
+        redacted.event().attributes(),
+        &BTreeMap::from([
+            ("customer".to_string(), hash_of("alice")),
+            ("tenant".to_string(), "acme".to_string()),
+        ])
+    );
+}
+
+#[test]
+fn test_redact_authorization_event() {
+    // Arrange
+    let redactor = make_redactor([(RedactedField::Actor, RedactionRule::Drop)]);
+
+    // Act
+    let redacted = redactor.redact_authorization(AuthorizationAuditEventBuilder::default().build());
+
+    // Assert
+    assert_eq!(redacted.actor, "");
+    assert_eq!(redacted.resource, "documents/1");
+}
+
+#[test]
+fn test_redact_token_metadata() {
+    // Arrange
+    let redactor = make_redactor([
+        (RedactedField::TokenSubject, RedactionRule::Hash),
+        (RedactedField::TokenIssuer, RedactionRule::Truncate { length: 5 }),
+        (RedactedField::TokenAudience, RedactionRule::Drop),
+    ]);
+
+    // Act
+    let redacted = redactor.redact(AuditRecord::from(make_token_validation()));
+
+    // Assert
+    let metadata = &redacted.to_json().unwrap()["event"]["token_metadata"];
+    assert_eq!(metadata["sub"], json!(hash_of("alice")));
+    assert_eq!(metadata["iss"], json!("https"));
+    assert_eq!(metadata["aud"], Value::Null);
+    assert_eq!(metadata["exp"], json!(1700000000));
+}
+
+#[test]
+fn test_keep_fields_without_rules() {
+    // Arrange
+    let redactor = make_redactor([]);
+    let record = AuditRecord::from(make_token_validation());
+
+    // Act
+    let redacted = redactor.redact(record.clone());
+
+    // Assert
+    assert_eq!(redacted.to_json().unwrap(), record.to_json().unwrap());
+}
+
+#[test]
+fn test_hash_requires_salt() {
+    // Arrange
+    let settings = AuditRedactionSettings {
+        salt: None,
+        fields: HashMap::from([(RedactedField::Actor, RedactionRule::Hash)]),
+        ..AuditRedactionSettings::default()
+    };
+
+    // Act
+    let result = AuditRedactor::new(settings);
+
+    // Assert
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_deserialize_settings() {
+    // Arrange
+    let value = json!({
+        "salt": "pepper",
+        "fields": {
+            "actor": { "strategy": "hash" },
+            "client_ip": { "strategy": "drop" },
+            "user_agent": { "strategy": "truncate", "length": 16 },
+        },
+        "attributes": {
+            "email": { "strategy": "drop" },
+        },
+    });
+
+    // Act
+    let settings: AuditRedactionSettings = serde_json::from_value(value).expect("settings should be deserialized");
+
+    // Assert
+    assert_eq!(
+        settings.fields,
+        HashMap::from([
+            (RedactedField::Actor, RedactionRule::Hash),
+            (RedactedField::ClientIp, RedactionRule::Drop),
+            (RedactedField::UserAgent, RedactionRule::Truncate { length: 16 }),
+        ])
+    );
+    assert_eq!(
+        settings.attributes,
+        HashMap::from([("email".to_string(), RedactionRule::Drop)])
+    );
+    assert!(!format!("{:?}", settings).contains("pepper"));
+}
+
+#[test]
+fn test_redact_before_inner_service() {
+    // Arrange
+    let mut inner = MockAuditSink::new();
+    inner
+        .expect_record()
+        .withf(|record| match record.event() {
+            AuditRecordEvent::Authorization(event) => event.actor == hash_of("alice"),
+            AuditRecordEvent::Chained(event) => event.event().actor() == Some(hash_of("alice").as_str()),
+            _ => false,
+        })
+        .times(2)
+        .returning(|_| Ok(()));
+    let service = RedactingAuditService::new(
+        make_redactor([(RedactedField::Actor, RedactionRule::Hash)]),
+        Arc::new(inner),
+    );
+
+    // Act
+    let result = service.record(AuthorizationAuditEventBuilder::default().record());
+    service.write(AuditEvent::Intermediate(ChainedAuditEvent {
+        actor: Some("alice".to_string()),
+        ..ChainedAuditEvent::begin()
+    }));
+
+    // Assert
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_redact_keeps_record_timestamp() {
+    // Arrange
+    let redactor = make_redactor([(RedactedField::Actor, RedactionRule::Drop)]);
+    let record = AuthorizationAuditEventBuilder::default().record();
+    let timestamp = record.timestamp();
+
+    // Act
+    let redacted = redactor.redact(record);
+
+    // Assert
+    assert_eq!(redacted.timestamp(), timestamp);
+}
+
+mock! {
+    pub AuditSink {}
+
+    impl AuditService for AuditSink {
+        fn record(&self, record: AuditRecord) -> Result<()>;
+    }
+}
+
+fn make_redactor<const N: usize>(rules: [(RedactedField, RedactionRule); N]) -> AuditRedactor {
+    AuditRedactor::new(AuditRedactionSettings {
+        salt: Some("salt".to_string()),
+        fields: HashMap::from(rules),
+        ..AuditRedactionSettings::default()
+    })
+    .expect("redactor should be created")
+}
+
+fn hash_of(value: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(b"salt").unwrap();
+    mac.update(value.as_bytes());
+    format!("{:x}", mac.finalize().into_bytes())
+}
+
+fn make_token_validation() -> TokenValidationEvent {
+    let mut event = TokenValidationEvent::external_empty(true, HashSet::new());
+    event.token_metadata = Some(
+        serde_json::from_value(json!({
+            "sub": "alice",
+            "iss": "https://issuer",
+            "aud": "boxer",
+            "exp": 1700000000,
+        }))
+        .expect("metadata should be deserialized"),
+    );
+    event
+}
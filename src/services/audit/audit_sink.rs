@@ -0,0 +1,17 @@
+pub mod partial_batch_error;
+
+use crate::services::audit::audit_record::AuditRecord;
+use async_trait::async_trait;
+
+/// The `AuditSink` trait defines the destination of the audit records delivered in batches by the
+/// [`AuditDispatcher`]. Unlike the `AuditService`, the sink runs outside of
+/// the request processing, so it can perform slow or remote I/O.
+///
+/// [`AuditDispatcher`]: crate::services::audit::audit_dispatcher::AuditDispatcher
+#[async_trait]
+pub trait AuditSink: Send + Sync + 'static {
+    /// Writes a batch of audit records. The records of a failed batch are reported as dropped,
+    /// except the records written before the failure, which the sink reports with a
+    /// [`PartialBatchError`](partial_batch_error::PartialBatchError).
+    async fn write_batch(&self, records: Vec<AuditRecord>) -> anyhow::Result<()>;
+}
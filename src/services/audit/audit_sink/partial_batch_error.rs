@@ -0,0 +1,36 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// The error returned by an [`AuditSink`] that wrote the first records of a batch before it failed.
+/// The written records are not retried or reported as dropped by the [`AuditDispatcher`].
+///
+/// [`AuditSink`]: crate::services::audit::audit_sink::AuditSink
+/// [`AuditDispatcher`]: crate::services::audit::audit_dispatcher::AuditDispatcher
+#[derive(Debug)]
+pub struct PartialBatchError {
+    written: usize,
+    error: anyhow::Error,
+}
+
+impl PartialBatchError {
+    pub fn new(written: usize, error: anyhow::Error) -> Self {
+        PartialBatchError { written, error }
+    }
+
+    /// Returns the number of records written before the failure.
+    pub fn written(&self) -> usize {
+        self.written
+    }
+}
+
+impl Display for PartialBatchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "audit batch failed after {} records: {:#}", self.written, self.error)
+    }
+}
+
+impl Error for PartialBatchError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.error.as_ref())
+    }
+}
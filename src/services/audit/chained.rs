@@ -1,3 +1,7 @@
+pub mod audit_chain_header;
+pub mod audit_chain_link;
 pub mod audit_event;
+pub mod audit_transition_error;
 pub mod chained_audit_event;
+pub mod request_metadata;
 pub mod token_audit_event;
@@ -0,0 +1,221 @@
+#[cfg(test)]
+mod tests;
+
+use crate::services::audit::chained::audit_chain_link::AuditChainLink;
+use crate::services::audit::chained::chained_audit_event::ChainedAuditEvent;
+use crate::services::identity::jwks_cache::JwksCache;
+use crate::services::identity::oidc_token_validator::decode_unverified;
+use crate::services::key_management::jti_cache::{JtiCache, JtiCacheError};
+use crate::services::key_management::jws_algorithms::verifier_from_jwk;
+use crate::services::key_management::key_manager::KeyManager;
+use josekit::jwt;
+use josekit::jwt::JwtPayload;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+/// The name of the header carrying the signed audit chain of the caller.
+pub const AUDIT_CHAIN_HEADER: &str = "X-Audit-Chain";
+
+const AUDIT_EVENT_CLAIM: &str = "audit_event";
+
+const DEFAULT_JTI_CACHE_CAPACITY: usize = 100_000;
+
+/// The error returned when the audit chain header of an incoming request cannot be verified.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditChainHeaderError {
+    /// The header is not a valid compact JWS or its signature cannot be verified.
+    InvalidSignature(String),
+
+    /// The header is expired.
+    Expired,
+
+    /// The header does not contain the required claim.
+    MissingClaim(&'static str),
+
+    /// The audit event claim cannot be deserialized.
+    InvalidAuditEvent(String),
+
+    /// The header is issued by a service that is not trusted to propagate the audit chain.
+    UntrustedIssuer(String),
+
+    /// The header was already used in another request.
+    Replayed,
+
+    /// Too many headers are in use to remember their `jti`s until they expire.
+    JtiCacheFull,
+}
+
+impl AuditChainHeaderError {
+    /// Returns the reason code recorded in the audit event of the rejected request.
+    pub fn reason_code(&self) -> &'static str {
+        match self {
+            AuditChainHeaderError::InvalidSignature(_) => "invalid-signature",
+            AuditChainHeaderError::Expired => "expired",
+            AuditChainHeaderError::MissingClaim(_) => "missing-claim",
+            AuditChainHeaderError::InvalidAuditEvent(_) => "invalid-audit-event",
+            AuditChainHeaderError::UntrustedIssuer(_) => "untrusted-issuer",
+            AuditChainHeaderError::Replayed => "replayed",
+            AuditChainHeaderError::JtiCacheFull => "jti-cache-full",
+        }
+    }
+}
+
+impl Display for AuditChainHeaderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuditChainHeaderError::InvalidSignature(e) => write!(f, "Invalid audit chain signature: {}", e),
+            AuditChainHeaderError::Expired => write!(f, "Audit chain header expired"),
+            AuditChainHeaderError::MissingClaim(claim) => write!(f, "Audit chain header is missing claim: {}", claim),
+            AuditChainHeaderError::InvalidAuditEvent(e) => write!(f, "Invalid audit event in audit chain: {}", e),
+            AuditChainHeaderError::UntrustedIssuer(issuer) => {
+                write!(f, "Audit chain header issuer is not trusted: {}", issuer)
+            }
+            AuditChainHeaderError::Replayed => write!(f, "Audit chain header was already used"),
+            AuditChainHeaderError::JtiCacheFull => write!(f, "Too many audit chain headers in use"),
+        }
+    }
+}
+
+impl Error for AuditChainHeaderError {}
+
+/// [`AuditChainHeader`] propagates the audit chain across services. The current
+/// [`ChainedAuditEvent`] is signed with the active key of the service into a compact JWS that is
+/// sent in the [`AUDIT_CHAIN_HEADER`] of outgoing requests. The receiving service verifies the
+/// header and starts its own audit event with a link to the caller event.
+///
+/// Headers are only accepted from the trusted issuers and are verified with the JWKS published
+/// by the issuer. Every header is accepted once: its `jti` is remembered until it expires.
+pub struct AuditChainHeader {
+    keys: Arc<KeyManager>,
+    issuer: String,
+    max_age: Duration,
+    trusted_issuers: HashMap<String, JwksCache>,
+    seen_jtis: Mutex<JtiCache>,
+}
+
+impl AuditChainHeader {
+    /// Creates the header codec. The issuer identifies the signing service in the parent links,
+    /// and headers older than `max_age` are rejected. No issuer is trusted until added with
+    /// [`AuditChainHeader::with_trusted_issuer`].
+    pub fn new(keys: Arc<KeyManager>, issuer: impl Into<String>, max_age: Duration) -> Self {
+        AuditChainHeader {
+            keys,
+            issuer: issuer.into(),
+            max_age,
+            trusted_issuers: HashMap::new(),
+            seen_jtis: Mutex::new(JtiCache::new(DEFAULT_JTI_CACHE_CAPACITY)),
+        }
+    }
+
+    /// Accepts the headers of the issuer, verified with the keys of its JWKS.
+    pub fn with_trusted_issuer(mut self, issuer: impl Into<String>, jwks: JwksCache) -> Self {
+        self.trusted_issuers.insert(issuer.into(), jwks);
+        self
+    }
+
+    /// Sets the maximum number of `jti`s remembered to reject replayed headers. Headers are
+    /// rejected while the cache is full of unexpired entries.
+    pub fn with_jti_cache_capacity(mut self, capacity: usize) -> Self {
+        self.seen_jtis = Mutex::new(JtiCache::new(capacity));
+        self
+    }
+
+    /// Signs the event into the value of the audit chain header for an outgoing request.
+    pub fn sign(&self, event: &ChainedAuditEvent) -> anyhow::Result<String> {
+        let mut event = event.clone();
+        event.parent = event.parent.as_ref().map(AuditChainLink::without_event);
+
+        let now = SystemTime::now();
+        let mut payload = JwtPayload::new();
+        payload.set_jwt_id(uuid::Uuid::new_v4().to_string());
+        payload.set_issuer(&self.issuer);
+        payload.set_issued_at(&now);
+        payload.set_expires_at(&(now + self.max_age));
+        payload.set_claim(AUDIT_EVENT_CLAIM, Some(serde_json::to_value(event)?))?;
+        self.keys.current().sign(&payload)
+    }
+
+    /// Verifies the value of the audit chain header and returns the event that resumes the chain
+    /// in the receiving service: an empty event linked to the caller event. The chain id, the
+    /// creation time and the sequence number of the caller are kept, and a new chain is started
+    /// if the caller event does not have a chain id.
+    ///
+    /// The header must be signed with a key published by a trusted issuer and is rejected if it
+    /// was already accepted.
+    pub async fn verify(&self, value: &str) -> Result<ChainedAuditEvent, AuditChainHeaderError> {
+        let invalid_signature = |e: &dyn Display| AuditChainHeaderError::InvalidSignature(e.to_string());
+        let (header, unverified_claims) = decode_unverified(value).map_err(|e| invalid_signature(&e))?;
+        let issuer = unverified_claims
+            .get("iss")
+            .and_then(Value::as_str)
+            .ok_or(AuditChainHeaderError::MissingClaim("iss"))?;
+        let jwks = self
+            .trusted_issuers
+            .get(issuer)
+            .ok_or_else(|| AuditChainHeaderError::UntrustedIssuer(issuer.to_string()))?;
+        let kid = header
+            .get("kid")
+            .and_then(Value::as_str)
+            .ok_or_else(|| invalid_signature(&"the header does not contain a key id"))?;
+        let jwk = jwks.find(kid).await.map_err(|e| invalid_signature(&e))?;
+        let verifier =
+            verifier_from_jwk(&jwk, header.get("alg").and_then(Value::as_str)).map_err(|e| invalid_signature(&e))?;
+        let (payload, _) = jwt::decode_with_verifier(value, verifier.as_ref()).map_err(|e| invalid_signature(&e))?;
+
+        let now = SystemTime::now();
+        let expires_at = payload.expires_at().ok_or(AuditChainHeaderError::MissingClaim("exp"))?;
+        if expires_at < now {
+            return Err(AuditChainHeaderError::Expired);
+        }
+
+        let id = payload.jwt_id().ok_or(AuditChainHeaderError::MissingClaim("jti"))?;
+        let event = payload
+            .claim(AUDIT_EVENT_CLAIM)
+            .cloned()
+            .ok_or(AuditChainHeaderError::MissingClaim(AUDIT_EVENT_CLAIM))?;
+        let event: ChainedAuditEvent =
+            serde_json::from_value(event).map_err(|e| AuditChainHeaderError::InvalidAuditEvent(e.to_string()))?;
+        self.remember(issuer, id, expires_at, now)?;
+
+        let chain = match event.chain_id {
+            Some(_) => ChainedAuditEvent {
+                chain_id: event.chain_id.clone(),
+                created_at: event.created_at,
+                sequence: event.sequence,
+                ..ChainedAuditEvent::empty()
+            },
+            None => ChainedAuditEvent::begin(),
+        };
+        Ok(ChainedAuditEvent {
+            parent: Some(AuditChainLink {
+                id: id.to_string(),
+                issuer: issuer.to_string(),
+                event: Some(Box::new(event)),
+            }),
+            ..chain
+        })
+    }
+
+    /// Remembers the `jti` of an accepted header until it expires. The `jti`s are scoped by the
+    /// issuer, since each issuer generates its own.
+    fn remember(
+        &self,
+        issuer: &str,
+        id: &str,
+        expires_at: SystemTime,
+        now: SystemTime,
+    ) -> Result<(), AuditChainHeaderError> {
+        self.seen_jtis
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .register(&format!("{}#{}", issuer, id), expires_at, now)
+            .map_err(|e| match e {
+                JtiCacheError::Replayed => AuditChainHeaderError::Replayed,
+                JtiCacheError::Full => AuditChainHeaderError::JtiCacheFull,
+            })
+    }
+}
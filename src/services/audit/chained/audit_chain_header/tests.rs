@@ -0,0 +1,293 @@
+use crate::services::audit::chained::audit_chain_header::{AuditChainHeader, AuditChainHeaderError};
+use crate::services::audit::chained::audit_chain_link::AuditChainLink;
+use crate::services::audit::chained::chained_audit_event::ChainedAuditEvent;
+use crate::services::identity::jwks_cache::JwksCache;
+use crate::services::key_management::key_set::KeySet;
+use crate::services::key_management::signing_key::{KeyStatus, SigningKey};
+use crate::testing::signing_keys::{make_key_manager, make_signing_key};
+use crate::testing::stub_jwks_server::StubJwksServer;
+use assert_matches::assert_matches;
+use josekit::jwt::JwtPayload;
+use pretty_assertions::assert_eq;
+use std::time::{Duration, SystemTime};
+
+#[actix_web::test]
+async fn test_sign_and_verify() {
+    // Arrange
+    let key = make_key("active");
+    let server = StubJwksServer::start(vec![key.public_jwk().clone()]).unwrap();
+    let header = make_header(key, &server).await;
+    let event = ChainedAuditEvent {
+        actor: Some("alice".to_string()),
+        action: Some("read".to_string()),
+        ..ChainedAuditEvent::empty()
+    };
+
+    // Act
+    let value = header.sign(&event).expect("event should be signed");
+    let resumed = header.verify(&value).await.expect("header should be valid");
+
+    // Assert
+    assert!(resumed.actor.is_none());
+    let parent = resumed.parent.expect("resumed event should be linked to the caller");
+    assert_eq!(parent.issuer, "gateway");
+    assert!(!parent.id.is_empty());
+    let caller = parent.event.expect("link should carry the caller event");
+    assert_eq!(caller.actor, Some("alice".to_string()));
+    assert_eq!(caller.action, Some("read".to_string()));
+    server.stop().await;
+}
+
+#[actix_web::test]
+async fn test_sign_strips_nested_caller_event() {
+    // Arrange
+    let key = make_key("active");
+    let server = StubJwksServer::start(vec![key.public_jwk().clone()]).unwrap();
+    let header = make_header(key, &server).await;
+    let event = ChainedAuditEvent {
+        parent: Some(AuditChainLink {
+            id: "grandparent".to_string(),
+            issuer: "edge".to_string(),
+            event: Some(Box::new(ChainedAuditEvent::empty())),
+        }),
+        ..ChainedAuditEvent::empty()
+    };
+
+    // Act
+    let value = header.sign(&event).expect("event should be signed");
+    let resumed = header.verify(&value).await.expect("header should be valid");
+
+    // Assert
+    let caller = resumed
+        .parent
+        .and_then(|p| p.event)
+        .expect("link should carry the caller event");
+    let grandparent = caller.parent.expect("caller event should keep its own link");
+    assert_eq!(grandparent.id, "grandparent");
+    assert_eq!(grandparent.issuer, "edge");
+    assert!(grandparent.event.is_none());
+    server.stop().await;
+}
+
+#[actix_web::test]
+async fn test_verify_rejects_unpublished_key() {
+    // Arrange
+    let server = StubJwksServer::start(vec![make_key("active").public_jwk().clone()]).unwrap();
+    let caller = make_header(make_key("other"), &server).await;
+    let value = caller
+        .sign(&ChainedAuditEvent::empty())
+        .expect("event should be signed");
+    let header = make_header(make_key("active"), &server).await;
+
+    // Act
+    let result = header.verify(&value).await;
+
+    // Assert
+    assert_matches!(result, Err(AuditChainHeaderError::InvalidSignature(_)));
+    server.stop().await;
+}
+
+#[actix_web::test]
+async fn test_verify_rejects_own_key_not_published_by_issuer() {
+    // Arrange: the receiver signs a header claiming to be the trusted issuer with its own key
+    let server = StubJwksServer::start(vec![make_key("gateway-key").public_jwk().clone()]).unwrap();
+    let header = make_header(make_key("gateway-key"), &server).await;
+    let value = header
+        .sign(&ChainedAuditEvent::empty())
+        .expect("event should be signed");
+
+    // Act
+    let result = header.verify(&value).await;
+
+    // Assert
+    assert_matches!(result, Err(AuditChainHeaderError::InvalidSignature(_)));
+    server.stop().await;
+}
+
+#[actix_web::test]
+async fn test_verify_rejects_untrusted_issuer() {
+    // Arrange
+    let key = make_key("active");
+    let server = StubJwksServer::start(vec![key.public_jwk().clone()]).unwrap();
+    let keys = make_key_manager(vec![key]).await;
+    let caller = AuditChainHeader::new(keys.clone(), "unknown-service", Duration::from_secs(60));
+    let value = caller
+        .sign(&ChainedAuditEvent::empty())
+        .expect("event should be signed");
+    let header = AuditChainHeader::new(keys, "gateway", Duration::from_secs(60)).with_trusted_issuer(
+        "gateway",
+        JwksCache::new(server.jwks_uri(), Duration::from_secs(60), Duration::ZERO),
+    );
+
+    // Act
+    let result = header.verify(&value).await;
+
+    // Assert
+    assert_eq!(
+        result.unwrap_err(),
+        AuditChainHeaderError::UntrustedIssuer("unknown-service".to_string())
+    );
+    server.stop().await;
+}
+
+#[actix_web::test]
+async fn test_verify_rejects_replayed_header() {
+    // Arrange
+    let key = make_key("active");
+    let server = StubJwksServer::start(vec![key.public_jwk().clone()]).unwrap();
+    let header = make_header(key, &server).await;
+    let value = header
+        .sign(&ChainedAuditEvent::empty())
+        .expect("event should be signed");
+    header.verify(&value).await.expect("header should be valid");
+
+    // Act
+    let result = header.verify(&value).await;
+
+    // Assert
+    assert_eq!(result.unwrap_err(), AuditChainHeaderError::Replayed);
+    server.stop().await;
+}
+
+#[actix_web::test]
+async fn test_verify_rejects_when_jti_cache_is_full() {
+    // Arrange
+    let key = make_key("active");
+    let server = StubJwksServer::start(vec![key.public_jwk().clone()]).unwrap();
+    let header = make_header(key, &server).await.with_jti_cache_capacity(1);
+    let first = header.sign(&ChainedAuditEvent::empty()).unwrap();
+    let second = header.sign(&ChainedAuditEvent::empty()).unwrap();
+    header.verify(&first).await.expect("header should be valid");
+
+    // Act
+    let result = header.verify(&second).await;
+
+    // Assert
+    assert_eq!(result.unwrap_err(), AuditChainHeaderError::JtiCacheFull);
+    server.stop().await;
+}
+
+#[actix_web::test]
+async fn test_verify_rejects_tampered_header() {
+    // Arrange
+    let key = make_key("active");
+    let server = StubJwksServer::start(vec![key.public_jwk().clone()]).unwrap();
+    let header = make_header(key, &server).await;
+    let value = header
+        .sign(&ChainedAuditEvent::empty())
+        .expect("event should be signed");
+    let other = header
+        .sign(&ChainedAuditEvent {
+            actor: Some("mallory".to_string()),
+            ..ChainedAuditEvent::empty()
+        })
+        .expect("event should be signed");
+    let parts: Vec<&str> = value.split('.').collect();
+    let other_parts: Vec<&str> = other.split('.').collect();
+    let tampered = format!("{}.{}.{}", parts[0], other_parts[1], parts[2]);
+
+    // Act
+    let result = header.verify(&tampered).await;
+
+    // Assert
+    assert_matches!(result, Err(AuditChainHeaderError::InvalidSignature(_)));
+    server.stop().await;
+}
+
+#[actix_web::test]
+async fn test_verify_rejects_expired_header() {
+    // Arrange
+    let key = make_key("active");
+    let server = StubJwksServer::start(vec![key.public_jwk().clone()]).unwrap();
+    let key_set = KeySet::new(vec![key.clone()]).unwrap();
+    let header = make_header(key, &server).await;
+    let mut payload = JwtPayload::new();
+    payload.set_jwt_id("id");
+    payload.set_issuer("gateway");
+    payload.set_expires_at(&(SystemTime::now() - Duration::from_secs(60)));
+    let value = key_set.sign(&payload).unwrap();
+
+    // Act
+    let result = header.verify(&value).await;
+
+    // Assert
+    assert_matches!(result, Err(AuditChainHeaderError::Expired));
+    server.stop().await;
+}
+
+#[actix_web::test]
+async fn test_verify_rejects_missing_audit_event() {
+    // Arrange
+    let key = make_key("active");
+    let server = StubJwksServer::start(vec![key.public_jwk().clone()]).unwrap();
+    let key_set = KeySet::new(vec![key.clone()]).unwrap();
+    let header = make_header(key, &server).await;
+    let mut payload = JwtPayload::new();
+    payload.set_jwt_id("id");
+    payload.set_issuer("gateway");
+    payload.set_expires_at(&(SystemTime::now() + Duration::from_secs(60)));
+    let value = key_set.sign(&payload).unwrap();
+
+    // Act
+    let result = header.verify(&value).await;
+
+    // Assert
+    assert_matches!(result, Err(AuditChainHeaderError::MissingClaim("audit_event")));
+    server.stop().await;
+}
+
+#[actix_web::test]
+async fn test_verify_keeps_chain_identity() {
+    // Arrange
+    let key = make_key("active");
+    let server = StubJwksServer::start(vec![key.public_jwk().clone()]).unwrap();
+    let header = make_header(key, &server).await;
+    let event = ChainedAuditEvent {
+        sequence: 2,
+        ..ChainedAuditEvent::begin()
+    };
+
+    // Act
+    let value = header.sign(&event).expect("event should be signed");
+    let resumed = header.verify(&value).await.expect("header should be valid");
+
+    // Assert
+    assert_eq!(resumed.chain_id, event.chain_id);
+    assert_eq!(resumed.created_at, event.created_at);
+    assert_eq!(resumed.sequence, 2);
+    server.stop().await;
+}
+
+#[actix_web::test]
+async fn test_verify_starts_chain_for_caller_without_chain_id() {
+    // Arrange
+    let key = make_key("active");
+    let server = StubJwksServer::start(vec![key.public_jwk().clone()]).unwrap();
+    let header = make_header(key, &server).await;
+
+    // Act
+    let value = header
+        .sign(&ChainedAuditEvent::empty())
+        .expect("event should be signed");
+    let resumed = header.verify(&value).await.expect("header should be valid");
+
+    // Assert
+    assert!(resumed.chain_id.is_some());
+    assert!(resumed.created_at.is_some());
+    assert_eq!(resumed.sequence, 0);
+    server.stop().await;
+}
+
+/// Creates the header of the `gateway` service signing with the key and trusting the `gateway`
+/// headers verified with the keys published by the server.
+async fn make_header(key: SigningKey, server: &StubJwksServer) -> AuditChainHeader {
+    let keys = make_key_manager(vec![key]).await;
+    AuditChainHeader::new(keys, "gateway", Duration::from_secs(60)).with_trusted_issuer(
+        "gateway",
+        JwksCache::new(server.jwks_uri(), Duration::from_secs(60), Duration::ZERO),
+    )
+}
+
+fn make_key(kid: &str) -> SigningKey {
+    make_signing_key(kid, KeyStatus::Active)
+}
@@ -0,0 +1,30 @@
+use crate::services::audit::chained::chained_audit_event::ChainedAuditEvent;
+use serde::{Deserialize, Serialize};
+
+/// [`AuditChainLink`] links the audit event of a request to the audit event of the service that
+/// made the request. The link is created when the signed audit chain header of an incoming
+/// request is verified.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditChainLink {
+    /// The unique identifier of the signed header, shared by the caller and the callee records.
+    pub id: String,
+
+    /// The service that signed the header.
+    pub issuer: String,
+
+    /// The audit event of the caller at the moment the request was made. The event of the
+    /// caller keeps only the identifier and the issuer of its own parent link, so the header
+    /// size does not grow with the depth of the call tree.
+    pub event: Option<Box<ChainedAuditEvent>>,
+}
+
+impl AuditChainLink {
+    /// Returns a copy of the link without the caller event.
+    pub fn without_event(&self) -> AuditChainLink {
+        AuditChainLink {
+            id: self.id.clone(),
+            issuer: self.issuer.clone(),
+            event: None,
+        }
+    }
+}
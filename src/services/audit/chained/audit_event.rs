@@ -1,55 +1,294 @@
+#[cfg(test)]
+mod tests;
+
+use crate::http::middleware::audit::audit_pipeline_error::AuditPipelineError;
+use crate::services::audit::chained::audit_chain_header::AuditChainHeaderError;
+use crate::services::audit::chained::audit_transition_error::AuditTransitionError;
 use crate::services::audit::chained::chained_audit_event::ChainedAuditEvent;
+use crate::services::audit::chained::request_metadata::RequestMetadata;
 use crate::services::audit::chained::token_audit_event::TokenAuditEvent;
+use crate::services::audit::events::authorization_audit_event::Reason;
 use crate::services::audit::events::token_validation_event::TokenValidationResult;
 use cedar_policy::Decision;
 use maplit::hashset;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
 /// [`AuditEvent`] represents the state of the audit information collected during the processing of a request.
+///
+/// The events of a request are created and modified through the transitions below. An event read
+/// back with `Deserialize`, e.g. from an audit record, is not checked against the transitions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum AuditEvent {
     /// [`Final`] indicates that the audit information is complete and should not be modified further.
+    #[non_exhaustive]
     Final(ChainedAuditEvent),
 
     /// [`Intermediate`] indicates that the audit information is still being collected and can be modified.
+    #[non_exhaustive]
     Intermediate(ChainedAuditEvent),
 }
 
+/// The transitions of the audit event. Intermediate events are modified in place, while the
+/// transitions to a final event consume the event. Every transition returns an
+/// [`AuditTransitionError`] when it is not valid in the current state of the event, e.g. when
+/// the event is already final.
 impl AuditEvent {
-    pub fn token_not_present() -> AuditEvent {
-        AuditEvent::Final(ChainedAuditEvent {
-            external_token: Some(TokenAuditEvent {
-                token_id: None,
-                result: Some(TokenValidationResult::Deny),
-                reason_errors: hashset! {
-                    "token-not-present".into()
+    /// Returns a final event for a request without a token in the source.
+    pub fn token_not_present(token_source: String) -> AuditEvent {
+        rejected_token(ChainedAuditEvent::begin(), "token-not-present", token_source, None)
+    }
+
+    /// Returns the audit information of the event.
+    pub fn event(&self) -> &ChainedAuditEvent {
+        match self {
+            AuditEvent::Final(event) | AuditEvent::Intermediate(event) => event,
+        }
+    }
+
+    /// Checks if the event is final.
+    pub fn is_final(&self) -> bool {
+        matches!(self, AuditEvent::Final(_))
+    }
+
+    /// Records the external token entry.
+    pub fn record_external_token(&mut self, token: TokenAuditEvent) -> Result<(), AuditTransitionError> {
+        let event = self.intermediate_mut()?;
+        if event.external_token.is_some() {
+            return Err(AuditTransitionError::DuplicateToken("external"));
+        }
+        event.external_token = Some(token);
+        Ok(())
+    }
+
+    /// Records the internal token entry.
+    pub fn record_internal_token(&mut self, token: TokenAuditEvent) -> Result<(), AuditTransitionError> {
+        let event = self.intermediate_mut()?;
+        if event.internal_token.is_some() {
+            return Err(AuditTransitionError::DuplicateToken("internal"));
+        }
+        event.internal_token = Some(token);
+        Ok(())
+    }
+
+    /// Marks the recorded external token as accepted.
+    pub fn accept_external_token(&mut self) -> Result<(), AuditTransitionError> {
+        let token = self
+            .intermediate_mut()?
+            .external_token
+            .as_mut()
+            .ok_or(AuditTransitionError::MissingToken("external"))?;
+        token.result = Some(TokenValidationResult::Allow);
+        Ok(())
+    }
+
+    /// Replaces the id of the recorded external token, e.g. with the id derived from its
+    /// validated claims.
+    pub fn identify_external_token(&mut self, token_id: String) -> Result<(), AuditTransitionError> {
+        let token = self
+            .intermediate_mut()?
+            .external_token
+            .as_mut()
+            .ok_or(AuditTransitionError::MissingToken("external"))?;
+        token.token_id = Some(token_id);
+        Ok(())
+    }
+
+    /// Marks the recorded internal token as accepted.
+    pub fn accept_internal_token(&mut self) -> Result<(), AuditTransitionError> {
+        let token = self
+            .intermediate_mut()?
+            .internal_token
+            .as_mut()
+            .ok_or(AuditTransitionError::MissingToken("internal"))?;
+        token.result = Some(TokenValidationResult::Allow);
+        Ok(())
+    }
+
+    /// Replaces the event with the event resumed from the issuer of the internal token. The
+    /// internal token entry of the current event is kept.
+    pub(crate) fn resume(&mut self, mut resumed: ChainedAuditEvent) -> Result<(), AuditTransitionError> {
+        let event = self.intermediate_mut()?;
+        resumed.internal_token = event.internal_token.take();
+        *event = resumed;
+        Ok(())
+    }
+
+    /// Records the decision of the authorization engine and its reason.
+    pub fn record_decision(&mut self, decision: Decision, reason: Option<Reason>) -> Result<(), AuditTransitionError> {
+        let event = self.intermediate_mut()?;
+        event.decision = Some(decision);
+        event.reason = reason;
+        Ok(())
+    }
+
+    /// Records the actor of the request.
+    pub fn record_actor(&mut self, actor: String) -> Result<(), AuditTransitionError> {
+        self.intermediate_mut()?.actor = Some(actor);
+        Ok(())
+    }
+
+    /// Records the action performed by the request.
+    pub fn record_action(&mut self, action: String) -> Result<(), AuditTransitionError> {
+        self.intermediate_mut()?.action = Some(action);
+        Ok(())
+    }
+
+    /// Records the resource accessed by the request.
+    pub fn record_resource(&mut self, resource: String) -> Result<(), AuditTransitionError> {
+        self.intermediate_mut()?.resource = Some(resource);
+        Ok(())
+    }
+
+    /// Records a custom attribute. An attribute with the same key is replaced.
+    pub fn record_attribute(&mut self, key: String, value: String) -> Result<(), AuditTransitionError> {
+        self.intermediate_mut()?.attributes.insert(key, value);
+        Ok(())
+    }
+
+    /// Finalizes the event. The finalization time is recorded.
+    pub fn finalize(self) -> Result<AuditEvent, AuditTransitionError> {
+        Ok(self.into_intermediate()?.finalize())
+    }
+
+    /// Finalizes the empty event of a request where the token could not be read from the source.
+    /// The reason code is recorded in the external token reason errors and the description of the
+    /// failure, if any, in the reason detail.
+    pub fn reject_token(
+        self,
+        reason_code: &str,
+        token_source: String,
+        detail: Option<String>,
+    ) -> Result<AuditEvent, AuditTransitionError> {
+        let event = self.into_intermediate()?;
+        if !event.is_empty() {
+            return Err(AuditTransitionError::NotEmpty);
+        }
+        Ok(rejected_token(event, reason_code, token_source, detail))
+    }
+
+    /// Finalizes the event for an external token rejected by the token validator with the typed
+    /// reason code, e.g. `token-expired`. The token id recorded during extraction is preserved.
+    pub fn reject_external_token(self, reason_code: &str) -> Result<AuditEvent, AuditTransitionError> {
+        let mut event = self.into_intermediate()?;
+        let mut external_token = event.external_token.take().unwrap_or_else(TokenAuditEvent::external);
+        external_token.result = Some(TokenValidationResult::Deny);
+        external_token.reason_errors.insert(reason_code.to_string());
+        event.external_token = Some(external_token);
+        event.decision = Some(Decision::Deny);
+        Ok(event.finalize())
+    }
+
+    /// Finalizes the event for an internal token that could not be decoded. The token id
+    /// recorded during extraction is preserved and the decoding error is recorded as the reason
+    /// detail.
+    pub fn reject_internal_token(self, detail: String) -> Result<AuditEvent, AuditTransitionError> {
+        let mut event = self.into_intermediate()?;
+        let mut internal_token = event.internal_token.take().unwrap_or_else(TokenAuditEvent::internal);
+        internal_token.result = Some(TokenValidationResult::Deny);
+        internal_token
+            .reason_errors
+            .insert("internal-token-decoding-failed".to_string());
+        internal_token.reason_detail = Some(detail);
+        event.internal_token = Some(internal_token);
+        event.decision = Some(Decision::Deny);
+        Ok(event.finalize())
+    }
+
+    fn intermediate_mut(&mut self) -> Result<&mut ChainedAuditEvent, AuditTransitionError> {
+        match self {
+            AuditEvent::Intermediate(event) => Ok(event),
+            AuditEvent::Final(_) => Err(AuditTransitionError::AlreadyFinal),
+        }
+    }
+
+    fn into_intermediate(self) -> Result<ChainedAuditEvent, AuditTransitionError> {
+        match self {
+            AuditEvent::Intermediate(event) => Ok(event),
+            AuditEvent::Final(_) => Err(AuditTransitionError::AlreadyFinal),
+        }
+    }
+
+    /// Builds the diagnostic audit event recorded when the audit pipeline fails. The event denies
+    /// the request and records the reason code and the message of the pipeline error.
+    pub(crate) fn pipeline_failure(error: &AuditPipelineError) -> AuditEvent {
+        ChainedAuditEvent {
+            decision: Some(Decision::Deny),
+            reason: Some(Reason {
+                policies: hashset! {},
+                errors: hashset! {
+                    format!("audit-pipeline-error: {}: {}", error.reason_code(), error)
                 },
-                token_type: None,
             }),
-            internal_token: None,
-            action: None,
-            actor: None,
-            resource: None,
-            decision: Some(Decision::Deny),
-            reason: None,
-        })
+            ..ChainedAuditEvent::begin()
+        }
+        .finalize()
     }
 
-    pub(crate) fn token_extraction_failed(reason: String) -> AuditEvent {
-        AuditEvent::Final(ChainedAuditEvent {
-            external_token: Some(TokenAuditEvent {
-                token_id: None,
-                result: Some(TokenValidationResult::Deny),
-                reason_errors: hashset! {
-                    format!("token-extraction-failed: {}", reason)
+    /// Builds the final event of a request whose audit chain header cannot be verified. The event
+    /// starts a new chain, denies the request and records the reason code of the error.
+    pub(crate) fn audit_chain_rejected(error: &AuditChainHeaderError) -> AuditEvent {
+        ChainedAuditEvent {
+            decision: Some(Decision::Deny),
+            reason: Some(Reason {
+                policies: hashset! {},
+                errors: hashset! {
+                    format!("audit-chain-rejected: {}", error.reason_code())
                 },
-                token_type: None,
             }),
-            internal_token: None,
-            action: None,
-            actor: None,
-            resource: None,
-            decision: Some(Decision::Deny),
-            reason: None,
-        })
+            ..ChainedAuditEvent::begin()
+        }
+        .finalize()
+    }
+
+    /// Returns the event with the request metadata section. The state of the event is preserved.
+    pub(crate) fn with_request_metadata(self, metadata: RequestMetadata) -> AuditEvent {
+        match self {
+            AuditEvent::Final(mut event) => {
+                event.request = Some(metadata);
+                AuditEvent::Final(event)
+            }
+            AuditEvent::Intermediate(mut event) => {
+                event.request = Some(metadata);
+                AuditEvent::Intermediate(event)
+            }
+        }
+    }
+
+    /// Prepares the event to be written: assigns a new event id and increments the sequence number
+    /// of an intermediate event. The sequence number of final events is kept.
+    pub(crate) fn sequenced(self) -> AuditEvent {
+        let event_id = Some(uuid::Uuid::new_v4().to_string());
+        match self {
+            AuditEvent::Intermediate(event) => AuditEvent::Intermediate(ChainedAuditEvent {
+                sequence: event.sequence + 1,
+                event_id,
+                ..event
+            }),
+            AuditEvent::Final(event) => AuditEvent::Final(ChainedAuditEvent { event_id, ..event }),
+        }
+    }
+}
+
+fn rejected_token(
+    event: ChainedAuditEvent,
+    reason_code: &str,
+    token_source: String,
+    detail: Option<String>,
+) -> AuditEvent {
+    ChainedAuditEvent {
+        external_token: Some(TokenAuditEvent {
+            token_id: None,
+            result: Some(TokenValidationResult::Deny),
+            reason_errors: hashset! {
+                reason_code.to_string()
+            },
+            token_type: None,
+            token_source: Some(token_source),
+            reason_detail: detail,
+        }),
+        decision: Some(Decision::Deny),
+        ..event
     }
+    .finalize()
 }
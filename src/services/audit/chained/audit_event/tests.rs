@@ -0,0 +1,157 @@
+use crate::services::audit::chained::audit_event::AuditEvent;
+use crate::services::audit::chained::audit_transition_error::AuditTransitionError;
+use crate::services::audit::chained::chained_audit_event::ChainedAuditEvent;
+use crate::services::audit::chained::token_audit_event::TokenAuditEvent;
+use crate::services::audit::events::token_validation_event::TokenValidationResult;
+use assert_matches::assert_matches;
+use cedar_policy::Decision;
+
+#[test]
+fn test_record_and_accept_external_token() {
+    // Arrange
+    let mut event = AuditEvent::Intermediate(ChainedAuditEvent::begin());
+
+    // Act
+    event
+        .record_external_token(TokenAuditEvent::external())
+        .expect("token should be recorded");
+    event.accept_external_token().expect("token should be accepted");
+
+    // Assert
+    assert_matches!(
+        event,
+        AuditEvent::Intermediate(ChainedAuditEvent {
+            external_token: Some(TokenAuditEvent {
+                result: Some(TokenValidationResult::Allow),
+                ..
+            }),
+            ..
+        })
+    );
+}
+
+#[test]
+fn test_record_duplicate_internal_token() {
+    // Arrange
+    let mut event = AuditEvent::Intermediate(ChainedAuditEvent::begin());
+    event
+        .record_internal_token(TokenAuditEvent::internal())
+        .expect("token should be recorded");
+
+    // Act
+    let result = event.record_internal_token(TokenAuditEvent::internal());
+
+    // Assert
+    assert_eq!(result, Err(AuditTransitionError::DuplicateToken("internal")));
+}
+
+#[test]
+fn test_accept_missing_token() {
+    // Arrange
+    let mut event = AuditEvent::Intermediate(ChainedAuditEvent::begin());
+
+    // Act
+    let result = event.accept_internal_token();
+
+    // Assert
+    assert_eq!(result, Err(AuditTransitionError::MissingToken("internal")));
+}
+
+#[test]
+fn test_transitions_of_final_event() {
+    // Arrange
+    let mut event = AuditEvent::Final(ChainedAuditEvent::begin());
+
+    // Act
+    let record = event.record_external_token(TokenAuditEvent::external());
+    let decision = event.record_decision(Decision::Allow, None);
+    let finalized = event.finalize();
+
+    // Assert
+    assert_eq!(record, Err(AuditTransitionError::AlreadyFinal));
+    assert_eq!(decision, Err(AuditTransitionError::AlreadyFinal));
+    assert_matches!(finalized, Err(AuditTransitionError::AlreadyFinal));
+}
+
+#[test]
+fn test_finalize_records_finalization_time() {
+    // Arrange
+    let mut event = AuditEvent::Intermediate(ChainedAuditEvent::begin());
+    event
+        .record_decision(Decision::Allow, None)
+        .expect("decision should be recorded");
+
+    // Act
+    let result = event.finalize();
+
+    // Assert
+    assert_matches!(
+        result,
+        Ok(AuditEvent::Final(ChainedAuditEvent {
+            decision: Some(Decision::Allow),
+            finalized_at: Some(_),
+            ..
+        }))
+    );
+}
+
+#[test]
+fn test_reject_token_of_non_empty_event() {
+    // Arrange
+    let mut event = AuditEvent::Intermediate(ChainedAuditEvent::begin());
+    event
+        .record_external_token(TokenAuditEvent::external())
+        .expect("token should be recorded");
+
+    // Act
+    let result = event.reject_token("token-not-present", "header".to_string(), None);
+
+    // Assert
+    assert_matches!(result, Err(AuditTransitionError::NotEmpty));
+}
+
+#[test]
+fn test_resume_keeps_internal_token() {
+    // Arrange
+    let mut event = AuditEvent::Intermediate(ChainedAuditEvent::begin());
+    event
+        .record_internal_token(TokenAuditEvent::internal())
+        .expect("token should be recorded");
+    let resumed = ChainedAuditEvent {
+        actor: Some("alice".to_string()),
+        ..ChainedAuditEvent::begin()
+    };
+
+    // Act
+    event.resume(resumed).expect("event should be resumed");
+
+    // Assert
+    assert_matches!(
+        event,
+        AuditEvent::Intermediate(ChainedAuditEvent {
+            actor: Some(_),
+            internal_token: Some(_),
+            ..
+        })
+    );
+}
+
+#[test]
+fn test_sequenced_events_have_unique_ids() {
+    // Arrange
+    let event = AuditEvent::Intermediate(ChainedAuditEvent::begin());
+
+    // Act
+    let first = event.sequenced();
+    let second = first.clone().sequenced();
+
+    // Assert
+    match (first, second) {
+        (AuditEvent::Intermediate(first), AuditEvent::Intermediate(second)) => {
+            assert_eq!((first.sequence, second.sequence), (1, 2));
+            assert!(first.event_id.is_some());
+            assert_ne!(first.event_id, second.event_id);
+        }
+        other => panic!("Expected intermediate events, got {:?}", other),
+    }
+}
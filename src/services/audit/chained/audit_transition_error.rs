@@ -0,0 +1,34 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// The error returned when an [`AuditEvent`] transition is not valid in the current state of
+/// the event.
+///
+/// [`AuditEvent`]: crate::services::audit::chained::audit_event::AuditEvent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditTransitionError {
+    /// The event is final and cannot be modified.
+    AlreadyFinal,
+
+    /// The token entry of the given kind is already recorded.
+    DuplicateToken(&'static str),
+
+    /// The token entry of the given kind is not recorded.
+    MissingToken(&'static str),
+
+    /// The event is expected to be empty, e.g. when the token could not be extracted.
+    NotEmpty,
+}
+
+impl Display for AuditTransitionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuditTransitionError::AlreadyFinal => write!(f, "Final audit event cannot be modified"),
+            AuditTransitionError::DuplicateToken(kind) => write!(f, "The {} token is already recorded", kind),
+            AuditTransitionError::MissingToken(kind) => write!(f, "The {} token is not recorded", kind),
+            AuditTransitionError::NotEmpty => write!(f, "Audit event is not empty"),
+        }
+    }
+}
+
+impl Error for AuditTransitionError {}
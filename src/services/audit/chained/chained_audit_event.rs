@@ -1,27 +1,69 @@
+use crate::services::audit::chained::audit_chain_link::AuditChainLink;
+use crate::services::audit::chained::audit_event::AuditEvent;
+use crate::services::audit::chained::request_metadata::RequestMetadata;
 use crate::services::audit::chained::token_audit_event::TokenAuditEvent;
 use crate::services::audit::events::authorization_audit_event::Reason;
 use cedar_policy::Decision;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 /// [`ChainedAuditEvent`] represents the information collected during the processing of a
 /// request that is relevant for auditing purposes. It includes details about the external and
 /// internal token validation, the action being performed, the actor, the resource, the
-/// decision made by the authorization engine, and any reasons for that decision.
+/// decision made by the authorization engine, any reasons for that decision and the custom
+/// attributes set by the request handlers. Events resumed
+/// from the signed audit chain header of the caller carry the link to the caller event. The
+/// metadata of the HTTP request is added by the audit recorder when the response completes.
+///
+/// Every chain is identified by a UUID chain id that is kept when the chain is resumed from a
+/// v2 token or an audit chain header, so the records of a call tree can be deduplicated and
+/// ordered by the chain id and the sequence number. Timestamps are milliseconds since the Unix epoch.
+///
+/// The fields are read-only outside of the crate and are modified through the transitions of the
+/// [`AuditEvent`].
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChainedAuditEvent {
-    pub external_token: Option<TokenAuditEvent>,
-    pub internal_token: Option<TokenAuditEvent>,
-
-    pub action: Option<String>,
-    pub actor: Option<String>,
-    pub resource: Option<String>,
-    pub decision: Option<Decision>,
-    pub reason: Option<Reason>,
+    pub(crate) external_token: Option<TokenAuditEvent>,
+    pub(crate) internal_token: Option<TokenAuditEvent>,
+
+    pub(crate) action: Option<String>,
+    pub(crate) actor: Option<String>,
+    pub(crate) resource: Option<String>,
+    pub(crate) decision: Option<Decision>,
+    pub(crate) reason: Option<Reason>,
+
+    /// The custom attributes set by the request handlers.
+    #[serde(default)]
+    pub(crate) attributes: BTreeMap<String, String>,
+
+    #[serde(default)]
+    pub(crate) parent: Option<AuditChainLink>,
+
+    #[serde(default)]
+    pub(crate) request: Option<RequestMetadata>,
+
+    #[serde(default)]
+    pub(crate) chain_id: Option<String>,
+
+    #[serde(default)]
+    pub(crate) created_at: Option<u64>,
+
+    #[serde(default)]
+    pub(crate) finalized_at: Option<u64>,
+
+    /// The number of intermediate writes of the chain.
+    #[serde(default)]
+    pub(crate) sequence: u64,
+
+    /// The unique id of the written record, assigned on every write of the event.
+    #[serde(default)]
+    pub(crate) event_id: Option<String>,
 }
 
 impl ChainedAuditEvent {
     /// Creates a new empty `ChainedAuditEvent` with all fields set to `None`.
-    pub fn empty() -> ChainedAuditEvent {
+    pub(crate) fn empty() -> ChainedAuditEvent {
         ChainedAuditEvent {
             external_token: None,
             internal_token: None,
@@ -30,10 +72,34 @@ impl ChainedAuditEvent {
             resource: None,
             decision: None,
             reason: None,
+            attributes: BTreeMap::new(),
+            parent: None,
+            request: None,
+            chain_id: None,
+            created_at: None,
+            finalized_at: None,
+            sequence: 0,
+            event_id: None,
+        }
+    }
+
+    /// Starts a new audit chain: an empty event with a new chain id and the creation time.
+    pub fn begin() -> ChainedAuditEvent {
+        ChainedAuditEvent {
+            chain_id: Some(uuid::Uuid::new_v4().to_string()),
+            created_at: Some(now_millis()),
+            ..ChainedAuditEvent::empty()
         }
     }
 
-    /// Checks if the `ChainedAuditEvent` is empty
+    /// Sets the finalization time and returns the event as `AuditEvent::Final`.
+    pub(crate) fn finalize(mut self) -> AuditEvent {
+        self.finalized_at = Some(now_millis());
+        AuditEvent::Final(self)
+    }
+
+    /// Checks if the `ChainedAuditEvent` is empty. The chain identity, timestamps, the sequence
+    /// number and the event id are not taken into account.
     pub fn is_empty(&self) -> bool {
         self.external_token.is_none()
             && self.internal_token.is_none()
@@ -42,5 +108,75 @@ impl ChainedAuditEvent {
             && self.resource.is_none()
             && self.decision.is_none()
             && self.reason.is_none()
+            && self.attributes.is_empty()
+            && self.parent.is_none()
+            && self.request.is_none()
+    }
+
+    pub fn external_token(&self) -> Option<&TokenAuditEvent> {
+        self.external_token.as_ref()
+    }
+
+    pub fn internal_token(&self) -> Option<&TokenAuditEvent> {
+        self.internal_token.as_ref()
+    }
+
+    pub fn action(&self) -> Option<&str> {
+        self.action.as_deref()
     }
+
+    pub fn actor(&self) -> Option<&str> {
+        self.actor.as_deref()
+    }
+
+    pub fn resource(&self) -> Option<&str> {
+        self.resource.as_deref()
+    }
+
+    pub fn decision(&self) -> Option<Decision> {
+        self.decision
+    }
+
+    pub fn reason(&self) -> Option<&Reason> {
+        self.reason.as_ref()
+    }
+
+    pub fn attributes(&self) -> &BTreeMap<String, String> {
+        &self.attributes
+    }
+
+    pub fn parent(&self) -> Option<&AuditChainLink> {
+        self.parent.as_ref()
+    }
+
+    pub fn request(&self) -> Option<&RequestMetadata> {
+        self.request.as_ref()
+    }
+
+    pub fn chain_id(&self) -> Option<&str> {
+        self.chain_id.as_deref()
+    }
+
+    pub fn created_at(&self) -> Option<u64> {
+        self.created_at
+    }
+
+    pub fn finalized_at(&self) -> Option<u64> {
+        self.finalized_at
+    }
+
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    pub fn event_id(&self) -> Option<&str> {
+        self.event_id.as_deref()
+    }
+}
+
+pub(crate) fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
 }
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+/// [`RequestMetadata`] describes the HTTP request and response of an audited call. Each field is
+/// recorded only when it is enabled in the audit recorder settings, so it is `None` otherwise.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequestMetadata {
+    pub method: Option<String>,
+    pub path: Option<String>,
+    pub status_code: Option<u16>,
+    pub latency_ms: Option<u64>,
+    pub client_ip: Option<String>,
+    pub user_agent: Option<String>,
+}
@@ -1,16 +1,23 @@
 use crate::services::audit::events::token_validation_event::TokenValidationResult;
+use crate::services::audit::token_id_strategy::TokenIdStrategy;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
 /// [`TokenAuditEvent`] represents the audit information related to a token validation,
 /// including the token's ID, the result of the validation, any errors that occurred during
-/// validation, and the type of token (internal or external).
+/// validation, the type of token (internal or external) and the source the token was read from
+/// when its extraction failed. The reason errors are fixed codes, while the free-text description
+/// of the failure, if any, is recorded in the reason detail.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenAuditEvent {
     pub token_id: Option<String>,
     pub result: Option<TokenValidationResult>,
     pub reason_errors: HashSet<String>,
     pub token_type: Option<String>,
+    #[serde(default)]
+    pub token_source: Option<String>,
+    #[serde(default)]
+    pub reason_detail: Option<String>,
 }
 
 impl TokenAuditEvent {
@@ -21,13 +28,23 @@ impl TokenAuditEvent {
             result: None,
             reason_errors: HashSet::new(),
             token_type: Some("external".into()),
+            token_source: None,
+            reason_detail: None,
         }
     }
 
-    /// Adds a token ID to the TokenAuditEvent by computing the MD5 hash of the provided token string.
-    pub fn with_token_id(mut self, token: &str) -> Self {
-        let token_hash = md5::compute(token);
-        self.token_id = Some(format!("md5:{:x}", token_hash));
+    /// Creates a new TokenAuditEvent for an internal token validation, with no token ID or errors.
+    pub fn internal() -> Self {
+        Self {
+            token_type: Some("internal".into()),
+            ..Self::external()
+        }
+    }
+
+    /// Adds a token ID to the TokenAuditEvent by deriving it from the raw token with the provided
+    /// strategy.
+    pub fn with_token_id(mut self, token: &str, strategy: &TokenIdStrategy) -> Self {
+        self.token_id = Some(strategy.token_id(token));
         self
     }
 
@@ -0,0 +1,61 @@
+pub mod audit_filter;
+pub mod composed_audit_error;
+#[cfg(test)]
+mod tests;
+
+use crate::services::audit::AuditService;
+use crate::services::audit::audit_record::AuditRecord;
+use crate::services::audit::composed_audit_service::audit_filter::AuditFilter;
+use crate::services::audit::composed_audit_service::composed_audit_error::ComposedAuditError;
+use anyhow::Result;
+use std::sync::Arc;
+
+/// [`ComposedAuditService`] fans every audit record out to several sinks, similar to the
+/// `ComposedLogger`. Each sink receives the records matching its [`AuditFilter`].
+///
+/// The sinks are isolated from each other: every matching sink receives the record even if
+/// another sink fails, and the failures are reported after all sinks were called.
+#[derive(Default)]
+pub struct ComposedAuditService {
+    sinks: Vec<ComposedSink>,
+}
+
+struct ComposedSink {
+    filter: AuditFilter,
+    service: Arc<dyn AuditService>,
+}
+
+impl ComposedAuditService {
+    pub fn new() -> Self {
+        Self { sinks: Vec::new() }
+    }
+
+    /// Adds a sink receiving all records.
+    pub fn with_sink(self, sink: Arc<dyn AuditService>) -> Self {
+        self.with_filtered_sink(sink, AuditFilter::new())
+    }
+
+    /// Adds a sink receiving the records matching the filter.
+    pub fn with_filtered_sink(mut self, sink: Arc<dyn AuditService>, filter: AuditFilter) -> Self {
+        self.sinks.push(ComposedSink { filter, service: sink });
+        self
+    }
+}
+
+/// The failures of the sinks are combined into a [`ComposedAuditError`]. A record written by the
+/// audit recorder is rejected if any of the sinks rejects it.
+impl AuditService for ComposedAuditService {
+    fn record(&self, record: AuditRecord) -> Result<()> {
+        let errors: Vec<anyhow::Error> = self
+            .sinks
+            .iter()
+            .filter(|sink| sink.filter.matches(&record))
+            .filter_map(|sink| sink.service.record(record.clone()).err())
+            .collect();
+
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(ComposedAuditError::new(errors).into()),
+        }
+    }
+}
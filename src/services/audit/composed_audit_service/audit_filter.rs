@@ -0,0 +1,57 @@
+use crate::services::audit::audit_record::{AuditRecord, AuditRecordKind};
+use cedar_policy::Decision;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+type AuditPredicate = Arc<dyn Fn(&AuditRecord) -> bool + Send + Sync>;
+
+/// [`AuditFilter`] selects the audit records delivered to a sink of the [`ComposedAuditService`].
+/// The default filter accepts all records, and every configured condition must match.
+///
+/// [`ComposedAuditService`]: crate::services::audit::composed_audit_service::ComposedAuditService
+#[derive(Clone, Default)]
+pub struct AuditFilter {
+    kinds: Option<HashSet<AuditRecordKind>>,
+    decision: Option<Decision>,
+    final_only: bool,
+    predicate: Option<AuditPredicate>,
+}
+
+impl AuditFilter {
+    /// Creates a filter that accepts all records.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accepts only the records of the given kinds.
+    pub fn with_kinds(mut self, kinds: impl IntoIterator<Item = AuditRecordKind>) -> Self {
+        self.kinds = Some(kinds.into_iter().collect());
+        self
+    }
+
+    /// Accepts only the records with the given decision. Records without a decision are rejected.
+    pub fn with_decision(mut self, decision: Decision) -> Self {
+        self.decision = Some(decision);
+        self
+    }
+
+    /// Rejects the intermediate events of the audit chain.
+    pub fn final_only(mut self) -> Self {
+        self.final_only = true;
+        self
+    }
+
+    /// Accepts only the records matching the predicate.
+    pub fn with_predicate(mut self, predicate: impl Fn(&AuditRecord) -> bool + Send + Sync + 'static) -> Self {
+        self.predicate = Some(Arc::new(predicate));
+        self
+    }
+
+    /// Checks if the record matches the filter.
+    pub fn matches(&self, record: &AuditRecord) -> bool {
+        self.kinds.as_ref().is_none_or(|kinds| kinds.contains(&record.kind()))
+            && self.decision.is_none_or(|decision| record.decision() == Some(decision))
+            && (!self.final_only || record.is_final())
+            && self.predicate.as_ref().is_none_or(|predicate| predicate(record))
+    }
+}
@@ -0,0 +1,42 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// The error returned by the [`ComposedAuditService`] when some of the sinks fail to record an
+/// event. The errors of the sinks are kept as they were returned, so they can be downcast.
+///
+/// [`ComposedAuditService`]: crate::services::audit::composed_audit_service::ComposedAuditService
+#[derive(Debug)]
+pub struct ComposedAuditError {
+    errors: Vec<anyhow::Error>,
+}
+
+impl ComposedAuditError {
+    pub(super) fn new(errors: Vec<anyhow::Error>) -> Self {
+        ComposedAuditError { errors }
+    }
+
+    /// Returns the errors of the failed sinks in the order of the sinks.
+    pub fn errors(&self) -> &[anyhow::Error] {
+        &self.errors
+    }
+}
+
+impl Display for ComposedAuditError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} audit sinks failed", self.errors.len())?;
+        for (index, error) in self.errors.iter().enumerate() {
+            let separator = if index == 0 { ": " } else { "; " };
+            write!(f, "{}{:#}", separator, error)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ComposedAuditError {
+    /// Returns the error of the first failed sink.
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.errors
+            .first()
+            .map(|error| error.as_ref() as &(dyn Error + 'static))
+    }
+}
@@ -0,0 +1,243 @@
+use crate::http::middleware::audit::audit_recorder::audit_write_error::AuditWriteError;
+use crate::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
+use crate::services::audit::AuditService;
+use crate::services::audit::audit_record::{AuditRecord, AuditRecordEvent, AuditRecordKind};
+use crate::services::audit::chained::audit_event::AuditEvent;
+use crate::services::audit::chained::chained_audit_event::ChainedAuditEvent;
+use crate::services::audit::composed_audit_service::ComposedAuditService;
+use crate::services::audit::composed_audit_service::audit_filter::AuditFilter;
+use crate::services::audit::composed_audit_service::composed_audit_error::ComposedAuditError;
+use crate::services::audit::events::resource_delete_audit_event::ResourceDeleteAuditEvent;
+use crate::testing::audit_events::AuthorizationAuditEventBuilder;
+use anyhow::{Result, anyhow};
+use cedar_policy::Decision;
+use mockall::mock;
+use pretty_assertions::assert_eq;
+use std::sync::Arc;
+
+#[test]
+fn test_fan_out_to_all_sinks() {
+    // Arrange
+    let mut first = MockAuditSink::new();
+    first.expect_record().times(2).returning(|_| Ok(()));
+    let mut second = MockAuditSink::new();
+    second.expect_record().times(2).returning(|_| Ok(()));
+    let service = ComposedAuditService::new()
+        .with_sink(Arc::new(first))
+        .with_sink(Arc::new(second));
+
+    // Act
+    let result = service.record(AuthorizationAuditEventBuilder::default().record());
+    service.write(AuditEvent::Intermediate(ChainedAuditEvent::begin()));
+
+    // Assert
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_filter_by_kind() {
+    // Arrange
+    let mut authorization_only = MockAuditSink::new();
+    authorization_only
+        .expect_record()
+        .withf(|record| record.kind() == AuditRecordKind::Authorization)
+        .times(1)
+        .returning(|_| Ok(()));
+    let service = ComposedAuditService::new().with_filtered_sink(
+        Arc::new(authorization_only),
+        AuditFilter::new().with_kinds([AuditRecordKind::Authorization]),
+    );
+
+    // Act
+    let result = service.record(AuthorizationAuditEventBuilder::default().record());
+    service.write(AuditEvent::Final(ChainedAuditEvent::begin()));
+
+    // Assert
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_filter_by_decision() {
+    // Arrange
+    let mut denies_only = MockAuditSink::new();
+    denies_only
+        .expect_record()
+        .withf(|record| record.decision() == Some(Decision::Deny))
+        .times(1)
+        .returning(|_| Ok(()));
+    let service = ComposedAuditService::new()
+        .with_filtered_sink(Arc::new(denies_only), AuditFilter::new().with_decision(Decision::Deny));
+
+    // Act
+    let allowed = service.record(AuthorizationAuditEventBuilder::default().record());
+    let denied = service.record(
+        AuthorizationAuditEventBuilder::default()
+            .with_decision(Decision::Deny)
+            .record(),
+    );
+    let deleted = service.record(AuditRecord::from(ResourceDeleteAuditEvent::new(
+        "id".to_string(),
+        "schema".to_string(),
+        true,
+    )));
+
+    // Assert
+    assert!(allowed.is_ok() && denied.is_ok() && deleted.is_ok());
+}
+
+#[test]
+fn test_filter_final_only() {
+    // Arrange
+    let mut final_only = MockAuditSink::new();
+    final_only
+        .expect_record()
+        .withf(|record| record.is_final())
+        .times(1)
+        .returning(|_| Ok(()));
+    let service = ComposedAuditService::new().with_filtered_sink(Arc::new(final_only), AuditFilter::new().final_only());
+
+    // Act
+    service.write(AuditEvent::Intermediate(ChainedAuditEvent::begin()));
+    service.write(AuditEvent::Final(ChainedAuditEvent::begin()));
+
+    // Assert
+    // Expectations are verified automatically when the sink is dropped at the end of the scope.
+}
+
+#[test]
+fn test_filter_by_predicate() {
+    // Arrange
+    let mut alice_only = MockAuditSink::new();
+    alice_only
+        .expect_record()
+        .withf(|record| matches!(record.event(), AuditRecordEvent::Authorization(event) if event.actor == "alice"))
+        .times(1)
+        .returning(|_| Ok(()));
+    let filter = AuditFilter::new().with_predicate(
+        |record| matches!(record.event(), AuditRecordEvent::Authorization(event) if event.actor == "alice"),
+    );
+    let service = ComposedAuditService::new().with_filtered_sink(Arc::new(alice_only), filter);
+
+    // Act
+    let alice_result = service.record(AuthorizationAuditEventBuilder::default().record());
+    let bob_result = service.record(AuthorizationAuditEventBuilder::default().with_actor("bob").record());
+
+    // Assert
+    assert!(alice_result.is_ok() && bob_result.is_ok());
+}
+
+#[test]
+fn test_failing_sink_does_not_suppress_others() {
+    // Arrange
+    let mut failing = MockAuditSink::new();
+    failing
+        .expect_record()
+        .times(1)
+        .returning(|_| Err(anyhow!("sink is unavailable")));
+    let mut healthy = MockAuditSink::new();
+    healthy.expect_record().times(1).returning(|_| Ok(()));
+    let service = ComposedAuditService::new()
+        .with_sink(Arc::new(failing))
+        .with_sink(Arc::new(healthy));
+
+    // Act
+    let result = service.record(AuthorizationAuditEventBuilder::default().record());
+
+    // Assert
+    let error = result.expect_err("failure should be reported");
+    assert!(error.to_string().contains("sink is unavailable"), "{}", error);
+}
+
+#[test]
+fn test_sink_errors_are_preserved() {
+    // Arrange
+    let mut failing = MockAuditSink::new();
+    failing
+        .expect_record()
+        .times(1)
+        .returning(|_| Err(anyhow!("sink is unavailable")));
+    let service = ComposedAuditService::new()
+        .with_sink(Arc::new(FullAuditSink))
+        .with_sink(Arc::new(failing));
+
+    // Act
+    let result = service.record(AuthorizationAuditEventBuilder::default().record());
+
+    // Assert
+    let error = result.expect_err("failure should be reported");
+    let composed = error
+        .downcast_ref::<ComposedAuditError>()
+        .expect("failures should be composed");
+    assert_eq!(composed.errors().len(), 2);
+    assert_eq!(
+        composed.errors()[0].downcast_ref::<AuditWriteError>(),
+        Some(&AuditWriteError::QueueFull)
+    );
+    assert_eq!(composed.errors()[1].to_string(), "sink is unavailable");
+}
+
+#[test]
+fn test_rejected_write_does_not_suppress_others() {
+    // Arrange
+    let mut healthy = MockAuditSink::new();
+    healthy.expect_record().times(1).returning(|_| Ok(()));
+    let service = ComposedAuditService::new()
+        .with_sink(Arc::new(FullAuditSink))
+        .with_sink(Arc::new(healthy));
+
+    // Act
+    let result = service.try_write(AuditEvent::Final(ChainedAuditEvent::begin()));
+
+    // Assert
+    assert_eq!(result, Err(AuditWriteError::QueueFull));
+}
+
+#[test]
+fn test_compose_dyn_sinks() {
+    // Arrange
+    let mut sink = MockAuditSink::new();
+    sink.expect_record().times(2).returning(|_| Ok(()));
+    let inner: Arc<dyn AuditService> = Arc::new(ComposedAuditService::new().with_sink(Arc::new(sink)));
+    let service = ComposedAuditService::new().with_sink(inner);
+
+    // Act
+    let result = service.record(AuthorizationAuditEventBuilder::default().record());
+    service.write(AuditEvent::Intermediate(ChainedAuditEvent::begin()));
+
+    // Assert
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_failed_write_is_not_rejected() {
+    // Arrange
+    let mut failing = MockAuditSink::new();
+    failing
+        .expect_record()
+        .times(1)
+        .returning(|_| Err(anyhow!("sink is unavailable")));
+    let service = ComposedAuditService::new().with_sink(Arc::new(failing));
+
+    // Act
+    let result = service.try_write(AuditEvent::Final(ChainedAuditEvent::begin()));
+
+    // Assert
+    assert_eq!(result, Ok(()));
+}
+
+mock! {
+    pub AuditSink {}
+
+    impl AuditService for AuditSink {
+        fn record(&self, record: AuditRecord) -> Result<()>;
+    }
+}
+
+/// Rejects every record as an asynchronous sink with a full buffer.
+struct FullAuditSink;
+
+impl AuditService for FullAuditSink {
+    fn record(&self, _record: AuditRecord) -> Result<()> {
+        Err(AuditWriteError::QueueFull.into())
+    }
+}
@@ -1,7 +1,7 @@
 use crate::services::audit::audit_facade::to_audit_record::ToAuditRecord;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ResourceModificationAuditEvent {
     pub id: String,
     pub resource_type: String,
@@ -18,7 +18,7 @@ impl ResourceModificationAuditEvent {
     }
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum ModificationResult {
     Success(String),
     Failure,
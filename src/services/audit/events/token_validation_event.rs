@@ -1,6 +1,10 @@
+use crate::services::audit::token_id_strategy::TokenIdStrategy;
+use josekit::jwt::JwtPayload;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
+use std::time::{SystemTime, UNIX_EPOCH};
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TokenValidationEvent {
     pub token_id: String,
     pub result: TokenValidationResult,
@@ -10,20 +14,40 @@ pub struct TokenValidationEvent {
 }
 
 #[allow(dead_code)]
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct TokenMetadata {
     exp: Option<u64>,
     nbf: Option<u64>,
-    sub: Option<String>,
-    iss: Option<String>,
-    aud: Option<String>,
+    pub(crate) sub: Option<String>,
+    pub(crate) iss: Option<String>,
+    pub(crate) aud: Option<String>,
+}
+
+impl TokenMetadata {
+    /// Returns the subject of the token.
+    pub fn subject(&self) -> Option<&str> {
+        self.sub.as_deref()
+    }
+}
+
+/// Builds the token metadata from claims that were verified by an external token validator
+impl From<&JwtPayload> for TokenMetadata {
+    fn from(claims: &JwtPayload) -> Self {
+        let seconds = |time: SystemTime| time.duration_since(UNIX_EPOCH).ok().map(|d| d.as_secs());
+        Self {
+            exp: claims.expires_at().and_then(seconds),
+            nbf: claims.not_before().and_then(seconds),
+            sub: claims.subject().map(String::from),
+            iss: claims.issuer().map(String::from),
+            aud: claims.audience().map(|aud| aud.join(" ")),
+        }
+    }
 }
 
 impl TokenValidationEvent {
-    pub fn internal(token: &str, is_successful: bool, details: HashSet<String>) -> Self {
-        let token_hash = md5::compute(token);
+    pub fn internal(token: &str, strategy: &TokenIdStrategy, is_successful: bool, details: HashSet<String>) -> Self {
         Self {
-            token_id: format!("md5:{:x}", token_hash),
+            token_id: strategy.token_id(token),
             result: make_result(is_successful),
             reason_errors: details,
             token_type: "internal".to_string(),
@@ -31,15 +55,21 @@ impl TokenValidationEvent {
         }
     }
 
-    pub fn external(token: &str, is_successful: bool, details: HashSet<String>) -> Self {
-        let metadata = jsonwebtoken::dangerous::insecure_decode::<TokenMetadata>(token);
-        let token_hash = md5::compute(token);
+    /// Creates a validation event for an external token. The metadata should only be provided
+    /// for tokens that passed validation, since the claims of unverified tokens cannot be trusted.
+    pub fn external(
+        token: &str,
+        strategy: &TokenIdStrategy,
+        is_successful: bool,
+        details: HashSet<String>,
+        metadata: Option<TokenMetadata>,
+    ) -> Self {
         Self {
-            token_id: format!("md5:{:x}", token_hash),
+            token_id: strategy.token_id(token),
             result: make_result(is_successful),
             reason_errors: details,
             token_type: "external".to_string(),
-            token_metadata: metadata.ok().map(|data| data.claims),
+            token_metadata: metadata,
         }
     }
 
@@ -0,0 +1,204 @@
+pub mod file_audit_settings;
+#[cfg(test)]
+mod tests;
+
+use crate::services::audit::AuditService;
+use crate::services::audit::audit_format::audit_formatter::AuditFormatter;
+use crate::services::audit::audit_record::AuditRecord;
+use crate::services::audit::audit_sink::AuditSink;
+use crate::services::audit::audit_sink::partial_batch_error::PartialBatchError;
+use crate::services::audit::file_audit_service::file_audit_settings::{Durability, FileAuditSettings};
+use crate::services::audit::hash_chained_audit_service::hash_chained_record::HashChainedRecord;
+use crate::services::audit::hash_chained_audit_service::hash_chained_record_writer::HashChainedRecordWriter;
+use anyhow::Result;
+use async_trait::async_trait;
+use log::warn;
+use serde_json::Value;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// [`FileAuditService`] writes the audit records to local files, one record per line. By default
+/// the records are written as the JSON envelopes of the [`AuditRecord`], and the other formats are
+/// selected with the [`AuditFormat`] setting. The files are meant to be picked
+/// up by a separate log shipper, so the records are not mixed with the application logs.
+///
+/// The active file is rotated when the next record would exceed the maximum file size or when the
+/// file is older than the maximum age. The file names contain the creation time and a counter, so
+/// they sort in the order of creation. The files are synchronized to the disk according to the
+/// [`Durability`] setting. A record that fails to be written is removed from the file, so the
+/// next record does not continue a torn line.
+///
+/// The files are written on the calling thread by the `AuditService` and `HashChainedRecordWriter`
+/// implementations, so they must not be called from the actix workers
+/// directly. Register the service as the [`AuditSink`] of an [`AuditDispatcher`] instead, which
+/// writes the batches on the blocking thread pool.
+///
+/// [`AuditFormat`]: crate::services::audit::audit_format::AuditFormat
+/// [`AuditDispatcher`]: crate::services::audit::audit_dispatcher::AuditDispatcher
+pub struct FileAuditService {
+    log: Arc<FileAuditLog>,
+}
+
+/// The files of the [`FileAuditService`], shared with the blocking tasks writing the batches.
+struct FileAuditLog {
+    settings: FileAuditSettings,
+    formatter: Arc<dyn AuditFormatter>,
+    state: Mutex<FileState>,
+}
+
+struct FileState {
+    active: Option<ActiveFile>,
+    opened_files: u64,
+}
+
+struct ActiveFile {
+    file: File,
+    size: u64,
+    opened_at: Instant,
+}
+
+impl FileAuditService {
+    /// Creates the service and the directory of the audit files. The first file is created with
+    /// the first record.
+    pub fn new(settings: FileAuditSettings) -> Result<Self> {
+        std::fs::create_dir_all(&settings.directory)?;
+        Ok(FileAuditService {
+            log: Arc::new(FileAuditLog {
+                formatter: settings.format.formatter(),
+                settings,
+                state: Mutex::new(FileState {
+                    active: None,
+                    opened_files: 0,
+                }),
+            }),
+        })
+    }
+
+    /// Appends the record to the active file, rotating it if needed.
+    pub fn append(&self, record: &AuditRecord) -> Result<()> {
+        self.log.append(record)
+    }
+
+    /// Appends the JSON value as a line of the active file, rotating it if needed.
+    pub fn append_value(&self, value: &Value) -> Result<()> {
+        self.log.append_line(serde_json::to_string(value)?)
+    }
+}
+
+impl FileAuditLog {
+    fn append(&self, record: &AuditRecord) -> Result<()> {
+        self.append_line(self.formatter.format(record)?)
+    }
+
+    fn append_line(&self, line: String) -> Result<()> {
+        let mut line = line.into_bytes();
+        line.push(b'\n');
+        let length = line.len() as u64;
+
+        let mut state = self.state();
+        if state
+            .active
+            .as_ref()
+            .is_some_and(|active| self.is_rotation_due(active, length))
+            && let Some(active) = state.active.take()
+        {
+            self.close(active)?;
+        }
+        if state.active.is_none() {
+            state.active = Some(self.open(state.opened_files)?);
+            state.opened_files += 1;
+        }
+
+        let active = state.active.as_mut().expect("Active audit file is opened above");
+        if let Err(error) = active.file.write_all(&line) {
+            // Removes the partially written line, or starts a new file if it cannot be removed
+            if active.file.set_len(active.size).is_err() {
+                state.active = None;
+            }
+            return Err(error.into());
+        }
+        active.size += length;
+        if self.settings.durability == Durability::SyncEveryRecord {
+            active.file.sync_data()?;
+        }
+        Ok(())
+    }
+
+    fn is_rotation_due(&self, active: &ActiveFile, length: u64) -> bool {
+        let max_age = self.settings.max_file_age.map(Duration::from);
+        active.size > 0
+            && (active.size + length > self.settings.max_file_size
+                || max_age.is_some_and(|max_age| active.opened_at.elapsed() >= max_age))
+    }
+
+    fn open(&self, index: u64) -> Result<ActiveFile> {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let name = format!("{}-{:013}-{:06}.jsonl", self.settings.file_prefix, created_at, index);
+        let file = OpenOptions::new()
+            .create_new(true)
+            .append(true)
+            .open(self.settings.directory.join(name))?;
+        Ok(ActiveFile {
+            file,
+            size: 0,
+            opened_at: Instant::now(),
+        })
+    }
+
+    fn close(&self, active: ActiveFile) -> Result<()> {
+        if self.settings.durability == Durability::SyncOnRotation {
+            active.file.sync_all()?;
+        }
+        Ok(())
+    }
+
+    fn state(&self) -> MutexGuard<'_, FileState> {
+        self.state.lock().expect("Audit file lock is poisoned")
+    }
+}
+
+impl Drop for FileAuditLog {
+    fn drop(&mut self) {
+        if let Some(active) = self.state().active.take()
+            && let Err(error) = self.close(active)
+        {
+            warn!("Failed to synchronize the audit file: {:?}", error);
+        }
+    }
+}
+
+/// Appends the record to the active file on the calling thread.
+impl AuditService for FileAuditService {
+    fn record(&self, record: AuditRecord) -> Result<()> {
+        self.append(&record)
+    }
+}
+
+/// The batches are written on the blocking thread pool. A failed batch reports the records written
+/// before the failure with a [`PartialBatchError`], so they are not counted as dropped.
+#[async_trait]
+impl AuditSink for FileAuditService {
+    async fn write_batch(&self, records: Vec<AuditRecord>) -> Result<()> {
+        let log = Arc::clone(&self.log);
+        tokio::task::spawn_blocking(move || {
+            for (written, record) in records.iter().enumerate() {
+                log.append(record)
+                    .map_err(|error| PartialBatchError::new(written, error))?;
+            }
+            Ok(())
+        })
+        .await?
+    }
+}
+
+impl HashChainedRecordWriter for FileAuditService {
+    /// Appends the hash-chained record as a line of the active file.
+    fn write_chained(&self, record: &HashChainedRecord) -> Result<()> {
+        self.append_value(&serde_json::to_value(record)?)
+    }
+}
@@ -0,0 +1,51 @@
+use crate::services::audit::audit_format::AuditFormat;
+use duration_string::DurationString;
+use serde::Deserialize;
+use std::path::PathBuf;
+
+/// The settings of the [`FileAuditService`].
+///
+/// [`FileAuditService`]: crate::services::audit::file_audit_service::FileAuditService
+#[derive(Debug, Clone, Deserialize)]
+pub struct FileAuditSettings {
+    /// The directory of the audit files. It is created if it does not exist.
+    pub directory: PathBuf,
+
+    /// The prefix of the audit file names.
+    #[serde(default = "default_file_prefix")]
+    pub file_prefix: String,
+
+    /// The size in bytes after which the audit file is rotated.
+    pub max_file_size: u64,
+
+    /// The age after which the audit file is rotated, if set.
+    #[serde(default)]
+    pub max_file_age: Option<DurationString>,
+
+    #[serde(default)]
+    pub durability: Durability,
+
+    /// The format of the written records.
+    #[serde(default)]
+    pub format: AuditFormat,
+}
+
+/// Selects when the written records are synchronized to the disk.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Durability {
+    /// The records are left to the operating system, which is the fastest but may lose the last
+    /// records if the node fails.
+    #[default]
+    Buffered,
+
+    /// The audit file is synchronized when it is rotated.
+    SyncOnRotation,
+
+    /// Every record is synchronized before the write returns.
+    SyncEveryRecord,
+}
+
+fn default_file_prefix() -> String {
+    "audit".to_string()
+}
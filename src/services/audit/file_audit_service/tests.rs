@@ -0,0 +1,258 @@
+use crate::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
+use crate::services::audit::AuditService;
+use crate::services::audit::audit_format::AuditFormat;
+use crate::services::audit::audit_format::audit_formatter::AuditFormatter;
+use crate::services::audit::audit_format::native_formatter::NativeFormatter;
+use crate::services::audit::audit_record::{AuditRecord, AuditRecordKind};
+use crate::services::audit::audit_sink::AuditSink;
+use crate::services::audit::audit_sink::partial_batch_error::PartialBatchError;
+use crate::services::audit::chained::audit_event::AuditEvent;
+use crate::services::audit::chained::chained_audit_event::ChainedAuditEvent;
+use crate::services::audit::events::resource_delete_audit_event::ResourceDeleteAuditEvent;
+use crate::services::audit::events::resource_modification_audit_event::{
+    ModificationResult, ResourceModificationAuditEvent,
+};
+use crate::services::audit::events::token_validation_event::TokenValidationEvent;
+use crate::services::audit::file_audit_service::FileAuditService;
+use crate::services::audit::file_audit_service::file_audit_settings::{Durability, FileAuditSettings};
+use crate::testing::audit_events::AuthorizationAuditEventBuilder;
+use anyhow::{Result, anyhow};
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[rstest]
+#[case(Durability::Buffered)]
+#[case(Durability::SyncOnRotation)]
+#[case(Durability::SyncEveryRecord)]
+fn test_write_json_lines(#[case] durability: Durability) {
+    // Arrange
+    let directory = make_directory();
+    let service = FileAuditService::new(FileAuditSettings {
+        durability,
+        ..make_settings(&directory)
+    })
+    .expect("service should be created");
+
+    // Act
+    service.write(AuditEvent::Final(ChainedAuditEvent {
+        actor: Some("alice".to_string()),
+        ..ChainedAuditEvent::begin()
+    }));
+    service
+        .record(AuthorizationAuditEventBuilder::default().record())
+        .expect("event should be written");
+    service
+        .record(AuditRecord::from(ResourceDeleteAuditEvent::new(
+            "id".to_string(),
+            "schema".to_string(),
+            true,
+        )))
+        .expect("event should be written");
+    service
+        .record(AuditRecord::from(ResourceModificationAuditEvent::new(
+            "id".to_string(),
+            "schema".to_string(),
+            ModificationResult::Failure,
+        )))
+        .expect("event should be written");
+    service
+        .record(AuditRecord::from(TokenValidationEvent::external_empty(
+            false,
+            HashSet::new(),
+        )))
+        .expect("event should be written");
+    drop(service);
+
+    // Assert
+    let records = read_records(&directory);
+    let kinds: Vec<&str> = records.iter().map(|r| r["kind"].as_str().unwrap()).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            "chained",
+            "authorization",
+            "resource_deletion",
+            "resource_modification",
+            "token_validation"
+        ]
+    );
+    assert_eq!(records[0]["is_final"], Value::Bool(true));
+    assert_eq!(records[0]["event"]["actor"], Value::String("alice".to_string()));
+    assert_eq!(
+        records[1]["event"]["resource"],
+        Value::String("documents/1".to_string())
+    );
+    assert_eq!(files_in(&directory).len(), 1);
+    std::fs::remove_dir_all(&directory).expect("directory should be removed");
+}
+
+#[test]
+fn test_rotate_by_size() {
+    // Arrange
+    let directory = make_directory();
+    let service = FileAuditService::new(FileAuditSettings {
+        max_file_size: 1,
+        ..make_settings(&directory)
+    })
+    .expect("service should be created");
+
+    // Act
+    for _ in 0..3 {
+        service
+            .record(AuthorizationAuditEventBuilder::default().record())
+            .expect("event should be written");
+    }
+    drop(service);
+
+    // Assert
+    let files = files_in(&directory);
+    assert_eq!(files.len(), 3);
+    assert!(files.iter().all(|file| read_lines(file).len() == 1));
+    std::fs::remove_dir_all(&directory).expect("directory should be removed");
+}
+
+#[test]
+fn test_rotate_by_age() {
+    // Arrange
+    let directory = make_directory();
+    let service = FileAuditService::new(FileAuditSettings {
+        max_file_age: Some(Duration::from_millis(50).into()),
+        ..make_settings(&directory)
+    })
+    .expect("service should be created");
+
+    // Act
+    service
+        .record(AuthorizationAuditEventBuilder::default().record())
+        .expect("event should be written");
+    service
+        .record(AuthorizationAuditEventBuilder::default().record())
+        .expect("event should be written");
+    std::thread::sleep(Duration::from_millis(60));
+    service
+        .record(AuthorizationAuditEventBuilder::default().record())
+        .expect("event should be written");
+    drop(service);
+
+    // Assert
+    let files = files_in(&directory);
+    let lines: Vec<usize> = files.iter().map(|file| read_lines(file).len()).collect();
+    assert_eq!(lines, vec![2, 1]);
+    std::fs::remove_dir_all(&directory).expect("directory should be removed");
+}
+
+#[tokio::test]
+async fn test_write_batch() {
+    // Arrange
+    let directory = make_directory();
+    let service = FileAuditService::new(make_settings(&directory)).expect("service should be created");
+    let records = ["alice", "bob"]
+        .into_iter()
+        .map(|actor| AuthorizationAuditEventBuilder::default().with_actor(actor).record())
+        .collect();
+
+    // Act
+    service.write_batch(records).await.expect("batch should be written");
+    drop(service);
+
+    // Assert
+    let actors: Vec<Value> = read_records(&directory)
+        .into_iter()
+        .map(|record| record["event"]["actor"].clone())
+        .collect();
+    assert_eq!(actors, vec!["alice", "bob"]);
+    std::fs::remove_dir_all(&directory).expect("directory should be removed");
+}
+
+#[tokio::test]
+async fn test_write_batch_reports_written_records() {
+    // Arrange
+    let directory = make_directory();
+    let mut service = FileAuditService::new(make_settings(&directory)).expect("service should be created");
+    Arc::get_mut(&mut service.log)
+        .expect("log should not be shared")
+        .formatter = Arc::new(DeletionFailingFormatter);
+    let records = vec![
+        AuthorizationAuditEventBuilder::default().with_actor("alice").record(),
+        AuditRecord::from(ResourceDeleteAuditEvent::new(
+            "id".to_string(),
+            "schema".to_string(),
+            true,
+        )),
+        AuthorizationAuditEventBuilder::default().with_actor("bob").record(),
+    ];
+
+    // Act
+    let result = service.write_batch(records).await;
+    drop(service);
+
+    // Assert
+    let error = result.expect_err("batch should fail");
+    let partial = error
+        .downcast_ref::<PartialBatchError>()
+        .expect("written records should be reported");
+    assert_eq!(partial.written(), 1);
+    let actors: Vec<Value> = read_records(&directory)
+        .into_iter()
+        .map(|record| record["event"]["actor"].clone())
+        .collect();
+    assert_eq!(actors, vec!["alice"]);
+    std::fs::remove_dir_all(&directory).expect("directory should be removed");
+}
+
+/// Writes the records as the native formatter, but fails on the resource deletions.
+struct DeletionFailingFormatter;
+
+impl AuditFormatter for DeletionFailingFormatter {
+    fn format(&self, record: &AuditRecord) -> Result<String> {
+        match record.kind() {
+            AuditRecordKind::ResourceDeletion => Err(anyhow!("unsupported record")),
+            _ => NativeFormatter.format(record),
+        }
+    }
+}
+
+fn make_directory() -> PathBuf {
+    std::env::temp_dir().join(format!("audit-{}", uuid::Uuid::new_v4()))
+}
+
+fn make_settings(directory: &Path) -> FileAuditSettings {
+    FileAuditSettings {
+        directory: directory.to_path_buf(),
+        file_prefix: "audit".to_string(),
+        max_file_size: 1024 * 1024,
+        max_file_age: None,
+        durability: Durability::Buffered,
+        format: AuditFormat::Native,
+    }
+}
+
+fn files_in(directory: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(directory)
+        .expect("directory should exist")
+        .map(|entry| entry.expect("entry should be readable").path())
+        .collect();
+    files.sort();
+    files
+}
+
+fn read_lines(file: &Path) -> Vec<String> {
+    std::fs::read_to_string(file)
+        .expect("file should be readable")
+        .lines()
+        .map(String::from)
+        .collect()
+}
+
+fn read_records(directory: &Path) -> Vec<Value> {
+    files_in(directory)
+        .iter()
+        .flat_map(|file| read_lines(file))
+        .map(|line| serde_json::from_str(&line).expect("line should be a JSON object"))
+        .collect()
+}
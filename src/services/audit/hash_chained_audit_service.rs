@@ -0,0 +1,163 @@
+pub mod audit_signing_key;
+pub mod audit_verification_keys;
+pub mod hash_chain_verification;
+pub mod hash_chained_record;
+pub mod hash_chained_record_writer;
+#[cfg(test)]
+mod tests;
+
+use crate::services::audit::AuditService;
+use crate::services::audit::audit_record::AuditRecord;
+use crate::services::audit::audit_sink::AuditSink;
+use crate::services::audit::hash_chained_audit_service::audit_signing_key::AuditSigningKey;
+use crate::services::audit::hash_chained_audit_service::hash_chain_verification::{
+    CHAIN_ID_CLAIM, CLOSED_CLAIM, HASH_CLAIM, INTERVAL_CLAIM, SEQUENCE_CLAIM,
+};
+use crate::services::audit::hash_chained_audit_service::hash_chained_record::{GENESIS_HASH, HashChainedRecord};
+use crate::services::audit::hash_chained_audit_service::hash_chained_record_writer::HashChainedRecordWriter;
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use josekit::jwt::JwtPayload;
+use log::warn;
+use serde_json::Value;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::SystemTime;
+
+/// [`HashChainedAuditService`] makes the stored audit log tamper-evident. Every record is written
+/// to the [`HashChainedRecordWriter`] with a sequence number and the hash of the previous record,
+/// so a modified, removed or inserted record is detected by `verify_hash_chain`.
+///
+/// Every instance starts a new chain with a random id. If checkpoints are enabled, the first record
+/// and every n-th record carry a compact JWS signed with the [`AuditSigningKey`], so a chain
+/// rewritten from the start is detected as well. The checkpoint of the first record also signs the
+/// checkpoint interval, so the verifier can require every checkpoint of the chain.
+///
+/// The chain is closed on `close`, which is also called when the service is dropped. With
+/// checkpoints enabled, a closing record without an audit record is written with a signed
+/// checkpoint, so the removal of the records at the end of the chain is detected.
+pub struct HashChainedAuditService {
+    writer: Arc<dyn HashChainedRecordWriter>,
+    chain_id: String,
+    checkpoints: Option<Checkpoints>,
+    state: Mutex<ChainState>,
+}
+
+struct Checkpoints {
+    key: AuditSigningKey,
+    interval: u64,
+}
+
+struct ChainState {
+    sequence: u64,
+    previous_hash: String,
+    closed: bool,
+}
+
+impl HashChainedAuditService {
+    /// Creates the service starting a new chain.
+    pub fn new(writer: Arc<dyn HashChainedRecordWriter>) -> Self {
+        HashChainedAuditService {
+            writer,
+            chain_id: uuid::Uuid::new_v4().to_string(),
+            checkpoints: None,
+            state: Mutex::new(ChainState {
+                sequence: 0,
+                previous_hash: GENESIS_HASH.to_string(),
+                closed: false,
+            }),
+        }
+    }
+
+    /// Signs the first record and a checkpoint every `interval` records with the audit signing key.
+    pub fn with_checkpoints(mut self, key: AuditSigningKey, interval: u64) -> Self {
+        self.checkpoints = Some(Checkpoints {
+            key,
+            interval: interval.max(1),
+        });
+        self
+    }
+
+    /// Returns the id of the chain written by this service.
+    pub fn chain_id(&self) -> &str {
+        &self.chain_id
+    }
+
+    /// Links the record to the chain and writes it. The chain is not advanced if the write fails.
+    pub fn append(&self, record: &AuditRecord) -> Result<()> {
+        self.link(record.to_json()?, false)
+    }
+
+    /// Closes the chain. With checkpoints enabled, the closing record is written. Records cannot
+    /// be appended to a closed chain.
+    pub fn close(&self) -> Result<()> {
+        if self.state().closed {
+            return Ok(());
+        }
+        if self.checkpoints.is_some() {
+            self.link(Value::Null, true)?;
+        }
+        self.state().closed = true;
+        Ok(())
+    }
+
+    fn link(&self, value: Value, closing: bool) -> Result<()> {
+        let mut state = self.state();
+        if state.closed {
+            bail!("Audit hash chain {} is closed", self.chain_id);
+        }
+        let mut chained = HashChainedRecord::new(&self.chain_id, state.sequence, &state.previous_hash, value)?;
+        chained.checkpoint = self.checkpoint(&chained, closing)?;
+        self.writer.write_chained(&chained)?;
+
+        state.sequence += 1;
+        state.previous_hash = chained.hash;
+        Ok(())
+    }
+
+    fn checkpoint(&self, record: &HashChainedRecord, closing: bool) -> Result<Option<String>> {
+        let Some(checkpoints) = &self.checkpoints else {
+            return Ok(None);
+        };
+        let starting = record.sequence == 0;
+        if !(starting || closing || (record.sequence + 1).is_multiple_of(checkpoints.interval)) {
+            return Ok(None);
+        }
+        let mut payload = JwtPayload::new();
+        payload.set_issued_at(&SystemTime::now());
+        payload.set_claim(CHAIN_ID_CLAIM, Some(Value::String(record.chain_id.clone())))?;
+        payload.set_claim(SEQUENCE_CLAIM, Some(Value::from(record.sequence)))?;
+        payload.set_claim(HASH_CLAIM, Some(Value::String(record.hash.clone())))?;
+        if starting {
+            payload.set_claim(INTERVAL_CLAIM, Some(Value::from(checkpoints.interval)))?;
+        }
+        if closing {
+            payload.set_claim(CLOSED_CLAIM, Some(Value::Bool(true)))?;
+        }
+        Ok(Some(checkpoints.key.sign(&payload)?))
+    }
+
+    fn state(&self) -> MutexGuard<'_, ChainState> {
+        self.state.lock().expect("Audit hash chain lock is poisoned")
+    }
+}
+
+impl Drop for HashChainedAuditService {
+    fn drop(&mut self) {
+        if let Err(error) = self.close() {
+            warn!("Failed to close the audit hash chain: {:?}", error);
+        }
+    }
+}
+
+impl AuditService for HashChainedAuditService {
+    fn record(&self, record: AuditRecord) -> Result<()> {
+        self.append(&record)
+    }
+}
+
+#[async_trait]
+impl AuditSink for HashChainedAuditService {
+    async fn write_batch(&self, records: Vec<AuditRecord>) -> Result<()> {
+        records.iter().try_for_each(|record| self.append(record))
+    }
+}
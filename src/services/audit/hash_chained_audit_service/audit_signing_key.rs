@@ -0,0 +1,47 @@
+use crate::services::key_management::signing_key::{KeyStatus, SigningKey};
+use anyhow::Result;
+use josekit::jwk::Jwk;
+use josekit::jws::JwsHeader;
+use josekit::jwt;
+use josekit::jwt::JwtPayload;
+
+/// [`AuditSigningKey`] signs the checkpoints of the hash-chained audit log.
+///
+/// The audit signing key is dedicated to the audit log and is not taken from the token key set of
+/// the `KeyManager`: retired token keys are no longer accepted for validation, while an audit log
+/// must stay verifiable with every key that signed it. The public key must be added to the
+/// [`AuditVerificationKeys`] of the verifiers before it is used, and kept there after it is
+/// replaced.
+///
+/// [`AuditVerificationKeys`]: crate::services::audit::hash_chained_audit_service::audit_verification_keys::AuditVerificationKeys
+#[derive(Debug, Clone)]
+pub struct AuditSigningKey {
+    key: SigningKey,
+}
+
+impl AuditSigningKey {
+    /// Creates the key from a private JWK. The JWK must declare the signing algorithm in its
+    /// `alg` parameter.
+    pub fn new(kid: impl Into<String>, jwk: Jwk) -> Result<Self> {
+        Ok(AuditSigningKey {
+            key: SigningKey::new(kid, KeyStatus::Active, jwk)?,
+        })
+    }
+
+    pub fn kid(&self) -> &str {
+        self.key.kid()
+    }
+
+    /// Returns the public part of the key, to be retained in the verification key history.
+    pub fn public_jwk(&self) -> &Jwk {
+        self.key.public_jwk()
+    }
+
+    /// Signs the checkpoint claims. The `kid` is written to the JWS header.
+    pub(crate) fn sign(&self, payload: &JwtPayload) -> Result<String> {
+        let mut header = JwsHeader::new();
+        header.set_token_type("JWT");
+        header.set_key_id(self.key.kid());
+        Ok(jwt::encode_with_signer(payload, &header, self.key.signer())?)
+    }
+}
@@ -0,0 +1,53 @@
+use crate::services::key_management::jws_algorithms::verifier_from_jwk;
+use anyhow::{Result, anyhow, bail};
+use josekit::JoseError;
+use josekit::jwk::Jwk;
+use josekit::jws::JwsVerifier;
+use josekit::jwt;
+use josekit::jwt::JwtPayload;
+use std::collections::HashMap;
+
+/// [`AuditVerificationKeys`] is the history of the public keys of the [`AuditSigningKey`]s. The
+/// keys are never retired, so the checkpoints signed before a key rotation can still be verified.
+///
+/// [`AuditSigningKey`]: crate::services::audit::hash_chained_audit_service::audit_signing_key::AuditSigningKey
+#[derive(Debug)]
+pub struct AuditVerificationKeys {
+    verifiers: HashMap<String, Box<dyn JwsVerifier>>,
+}
+
+impl AuditVerificationKeys {
+    /// Creates the key history from the public JWKs. Every key must declare its `kid` and its
+    /// signing algorithm, and the `kid`s must be unique.
+    pub fn new(keys: impl IntoIterator<Item = Jwk>) -> Result<Self> {
+        let mut verifiers = HashMap::new();
+        for jwk in keys {
+            let kid = jwk
+                .key_id()
+                .ok_or_else(|| anyhow!("Audit verification key does not declare a key id"))?
+                .to_string();
+            let verifier = verifier_from_jwk(&jwk, None)?;
+            if verifiers.insert(kid.clone(), verifier).is_some() {
+                bail!("Duplicate audit verification key id: {}", kid);
+            }
+        }
+        Ok(AuditVerificationKeys { verifiers })
+    }
+
+    /// Verifies the signature of the checkpoint with the key referenced by the `kid` header and
+    /// returns its claims.
+    pub(crate) fn verify(&self, checkpoint: &str) -> Result<JwtPayload, String> {
+        let (payload, _) = jwt::decode_with_verifier_selector(checkpoint, |header| {
+            let kid = header.key_id().ok_or(JoseError::InvalidJwsFormat(anyhow!(
+                "Checkpoint header does not contain a key id"
+            )))?;
+            let verifier = self
+                .verifiers
+                .get(kid)
+                .ok_or(JoseError::InvalidJwsFormat(anyhow!("Unknown key id: {}", kid)))?;
+            Ok(Some(verifier.as_ref()))
+        })
+        .map_err(|e| e.to_string())?;
+        Ok(payload)
+    }
+}
@@ -0,0 +1,281 @@
+use crate::services::audit::hash_chained_audit_service::audit_verification_keys::AuditVerificationKeys;
+use crate::services::audit::hash_chained_audit_service::hash_chained_record::{GENESIS_HASH, HashChainedRecord};
+use josekit::jwt::JwtPayload;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io::BufRead;
+
+pub(crate) const CHAIN_ID_CLAIM: &str = "chain_id";
+pub(crate) const SEQUENCE_CLAIM: &str = "sequence";
+pub(crate) const HASH_CLAIM: &str = "hash";
+pub(crate) const INTERVAL_CLAIM: &str = "interval";
+pub(crate) const CLOSED_CLAIM: &str = "closed";
+
+/// The violation found while verifying a hash-chained audit log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HashChainViolation {
+    /// The line of the log is not a hash-chained record.
+    Malformed { line: usize, reason: String },
+
+    /// The record does not match its hash.
+    Modified { chain_id: String, sequence: u64 },
+
+    /// Records are missing before the record with the `found` sequence number.
+    Gap {
+        chain_id: String,
+        expected: u64,
+        found: u64,
+    },
+
+    /// The previous hash of the record does not match the hash of the preceding record, so the
+    /// preceding record was replaced.
+    BrokenLink { chain_id: String, sequence: u64 },
+
+    /// The checkpoint of the record cannot be verified or does not match the record.
+    InvalidCheckpoint {
+        chain_id: String,
+        sequence: u64,
+        reason: String,
+    },
+
+    /// The record is due a checkpoint, but has none.
+    MissingCheckpoint { chain_id: String, sequence: u64 },
+
+    /// The chain appears again after the log has switched to another chain, e.g. because its
+    /// records were replayed.
+    Reappeared { chain_id: String, sequence: u64 },
+
+    /// The chain is followed by another chain without its closing record, so the records at its
+    /// end were removed.
+    Truncated { chain_id: String, sequence: u64 },
+
+    /// The record follows the closing record of its chain.
+    AfterClose { chain_id: String, sequence: u64 },
+}
+
+impl Display for HashChainViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HashChainViolation::Malformed { line, reason } => {
+                write!(f, "Malformed audit record at line {}: {}", line, reason)
+            }
+            HashChainViolation::Modified { chain_id, sequence } => {
+                write!(f, "Audit record {} of chain {} was modified", sequence, chain_id)
+            }
+            HashChainViolation::Gap {
+                chain_id,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Audit records of chain {} are missing: expected {}, found {}",
+                chain_id, expected, found
+            ),
+            HashChainViolation::BrokenLink { chain_id, sequence } => write!(
+                f,
+                "Audit record {} of chain {} is not linked to the previous record",
+                sequence, chain_id
+            ),
+            HashChainViolation::InvalidCheckpoint {
+                chain_id,
+                sequence,
+                reason,
+            } => write!(
+                f,
+                "Invalid checkpoint of audit record {} of chain {}: {}",
+                sequence, chain_id, reason
+            ),
+            HashChainViolation::MissingCheckpoint { chain_id, sequence } => {
+                write!(f, "Audit record {} of chain {} has no checkpoint", sequence, chain_id)
+            }
+            HashChainViolation::Reappeared { chain_id, sequence } => write!(
+                f,
+                "Audit record {} of chain {} appears after the log switched to another chain",
+                sequence, chain_id
+            ),
+            HashChainViolation::Truncated { chain_id, sequence } => write!(
+                f,
+                "Audit chain {} ends at record {} without the closing record",
+                chain_id, sequence
+            ),
+            HashChainViolation::AfterClose { chain_id, sequence } => write!(
+                f,
+                "Audit record {} of chain {} follows the closing record",
+                sequence, chain_id
+            ),
+        }
+    }
+}
+
+impl Error for HashChainViolation {}
+
+/// The result of a successful verification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct HashChainSummary {
+    /// The number of verified records.
+    pub records: u64,
+
+    /// The number of verified checkpoints. Checkpoints are not verified without the verification keys.
+    pub checkpoints: u64,
+
+    /// Whether the last chain of the log has no closing record. The chain of a running service is
+    /// open, so the records written after its last checkpoint cannot be protected. An open last
+    /// chain of a log whose service was shut down indicates that the end of the log was removed.
+    pub open: bool,
+}
+
+/// Verifies the records in the order they were written. Every chain must start with the first
+/// record, the records of a chain must be contiguous, and a chain must not appear again once the
+/// log has switched to another chain.
+///
+/// With the verification keys, the checkpoints are verified and required: the first record of
+/// every chain must carry the signed checkpoint interval, every record due a checkpoint must carry
+/// one, and every chain followed by another chain must end with its signed closing record.
+pub fn verify_hash_chain(
+    records: impl IntoIterator<Item = HashChainedRecord>,
+    keys: Option<&AuditVerificationKeys>,
+) -> Result<HashChainSummary, HashChainViolation> {
+    let mut summary = HashChainSummary::default();
+    let mut previous: Option<HashChainedRecord> = None;
+    let mut chain = ChainState::default();
+    let mut seen_chains = HashSet::new();
+    for record in records {
+        if !record.is_intact() {
+            return Err(HashChainViolation::Modified {
+                chain_id: record.chain_id,
+                sequence: record.sequence,
+            });
+        }
+
+        let (expected, previous_hash) = match &previous {
+            Some(previous) if previous.chain_id == record.chain_id => {
+                if chain.closed {
+                    return Err(HashChainViolation::AfterClose {
+                        chain_id: record.chain_id,
+                        sequence: record.sequence,
+                    });
+                }
+                (previous.sequence + 1, previous.hash.as_str())
+            }
+            _ => {
+                if let Some(previous) = &previous
+                    && keys.is_some()
+                    && !chain.closed
+                {
+                    return Err(HashChainViolation::Truncated {
+                        chain_id: previous.chain_id.clone(),
+                        sequence: previous.sequence,
+                    });
+                }
+                if !seen_chains.insert(record.chain_id.clone()) {
+                    return Err(HashChainViolation::Reappeared {
+                        chain_id: record.chain_id,
+                        sequence: record.sequence,
+                    });
+                }
+                chain = ChainState::default();
+                (0, GENESIS_HASH)
+            }
+        };
+        if record.sequence != expected {
+            return Err(HashChainViolation::Gap {
+                chain_id: record.chain_id,
+                expected,
+                found: record.sequence,
+            });
+        }
+        if record.previous_hash != previous_hash {
+            return Err(HashChainViolation::BrokenLink {
+                chain_id: record.chain_id,
+                sequence: record.sequence,
+            });
+        }
+
+        if let Some(keys) = keys {
+            verify_checkpoint(&record, keys, &mut chain)?;
+            summary.checkpoints += u64::from(record.checkpoint.is_some());
+        }
+        summary.records += 1;
+        previous = Some(record);
+    }
+    summary.open = previous.is_some() && !chain.closed;
+    Ok(summary)
+}
+
+/// Verifies a log stored as JSON lines, one hash-chained record per line. Rotated files should be
+/// read in the order of creation. Empty lines are ignored.
+pub fn verify_json_lines(
+    reader: impl BufRead,
+    keys: Option<&AuditVerificationKeys>,
+) -> Result<HashChainSummary, HashChainViolation> {
+    let mut records = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let malformed = |reason: String| HashChainViolation::Malformed {
+            line: index + 1,
+            reason,
+        };
+        let line = line.map_err(|e| malformed(e.to_string()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        records.push(serde_json::from_str(&line).map_err(|e| malformed(e.to_string()))?);
+    }
+    verify_hash_chain(records, keys)
+}
+
+/// The checkpoint state of the chain being verified.
+#[derive(Default)]
+struct ChainState {
+    interval: u64,
+    closed: bool,
+}
+
+/// Verifies the checkpoint of the record and requires it if the record is due a checkpoint.
+fn verify_checkpoint(
+    record: &HashChainedRecord,
+    keys: &AuditVerificationKeys,
+    chain: &mut ChainState,
+) -> Result<(), HashChainViolation> {
+    let invalid = |reason: &str| HashChainViolation::InvalidCheckpoint {
+        chain_id: record.chain_id.clone(),
+        sequence: record.sequence,
+        reason: reason.to_string(),
+    };
+    let Some(checkpoint) = &record.checkpoint else {
+        let due = record.sequence == 0 || (record.sequence + 1).is_multiple_of(chain.interval);
+        return match due {
+            true => Err(HashChainViolation::MissingCheckpoint {
+                chain_id: record.chain_id.clone(),
+                sequence: record.sequence,
+            }),
+            false => Ok(()),
+        };
+    };
+
+    let payload = keys.verify(checkpoint).map_err(|reason| invalid(&reason))?;
+    let matches = payload.claim(CHAIN_ID_CLAIM).and_then(|v| v.as_str()) == Some(record.chain_id.as_str())
+        && payload.claim(SEQUENCE_CLAIM).and_then(|v| v.as_u64()) == Some(record.sequence)
+        && payload.claim(HASH_CLAIM).and_then(|v| v.as_str()) == Some(record.hash.as_str());
+    if !matches {
+        return Err(invalid("the checkpoint was issued for another record"));
+    }
+    if record.sequence == 0 {
+        chain.interval =
+            read_interval(&payload).ok_or_else(|| invalid("the chain start has no checkpoint interval"))?;
+    }
+    if payload.claim(CLOSED_CLAIM).and_then(|v| v.as_bool()) == Some(true) {
+        if !record.record.is_null() {
+            return Err(invalid("the closing checkpoint signs an audit record"));
+        }
+        chain.closed = true;
+    }
+    Ok(())
+}
+
+fn read_interval(payload: &JwtPayload) -> Option<u64> {
+    payload
+        .claim(INTERVAL_CLAIM)
+        .and_then(|v| v.as_u64())
+        .filter(|interval| *interval > 0)
+}
@@ -0,0 +1,67 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// The previous hash of the first record of a chain.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// [`HashChainedRecord`] is an audit record linked to the previous record of the same chain.
+///
+/// The hash covers the chain id, the sequence number, the previous hash and the record, so a
+/// modified record does not match its hash, and a record rehashed after a modification no longer
+/// matches the previous hash of the next record.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HashChainedRecord {
+    /// The id of the chain, unique for every instance of the hash-chained audit service.
+    pub chain_id: String,
+
+    /// The position of the record in the chain, starting from 0.
+    pub sequence: u64,
+
+    /// The hash of the previous record of the chain, or [`GENESIS_HASH`] for the first record.
+    pub previous_hash: String,
+
+    /// The SHA-256 hash of the record, hex encoded.
+    pub hash: String,
+
+    /// The audit record, as serialized by the audit sinks, or `null` for the record closing the chain.
+    pub record: Value,
+
+    /// The compact JWS signing the chain id, the sequence number and the hash of this record.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checkpoint: Option<String>,
+}
+
+impl HashChainedRecord {
+    /// Creates the record linked to the previous hash. The checkpoint is not set.
+    pub fn new(chain_id: &str, sequence: u64, previous_hash: &str, record: Value) -> Result<Self> {
+        let hash = compute_hash(chain_id, sequence, previous_hash, &record)?;
+        Ok(HashChainedRecord {
+            chain_id: chain_id.to_string(),
+            sequence,
+            previous_hash: previous_hash.to_string(),
+            hash,
+            record,
+            checkpoint: None,
+        })
+    }
+
+    /// Checks if the hash of the record matches its content.
+    pub fn is_intact(&self) -> bool {
+        compute_hash(&self.chain_id, self.sequence, &self.previous_hash, &self.record)
+            .is_ok_and(|hash| hash == self.hash)
+    }
+}
+
+fn compute_hash(chain_id: &str, sequence: u64, previous_hash: &str, record: &Value) -> Result<String> {
+    let mut hasher = Sha256::new();
+    hasher.update(chain_id.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(sequence.to_string().as_bytes());
+    hasher.update(b"\n");
+    hasher.update(previous_hash.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(serde_json::to_vec(record)?);
+    Ok(format!("{:x}", hasher.finalize()))
+}
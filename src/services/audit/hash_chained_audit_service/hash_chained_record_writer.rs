@@ -0,0 +1,8 @@
+use crate::services::audit::hash_chained_audit_service::hash_chained_record::HashChainedRecord;
+use anyhow::Result;
+
+/// [`HashChainedRecordWriter`] stores the records produced by the `HashChainedAuditService`.
+pub trait HashChainedRecordWriter: Send + Sync + 'static {
+    /// Stores the record. The record is not considered a part of the chain if this method fails.
+    fn write_chained(&self, record: &HashChainedRecord) -> Result<()>;
+}
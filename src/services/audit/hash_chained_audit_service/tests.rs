@@ -0,0 +1,430 @@
+use crate::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
+use crate::services::audit::AuditService;
+use crate::services::audit::audit_format::AuditFormat;
+use crate::services::audit::audit_record::AuditRecord;
+use crate::services::audit::chained::audit_event::AuditEvent;
+use crate::services::audit::chained::chained_audit_event::ChainedAuditEvent;
+use crate::services::audit::file_audit_service::FileAuditService;
+use crate::services::audit::file_audit_service::file_audit_settings::{Durability, FileAuditSettings};
+use crate::services::audit::hash_chained_audit_service::HashChainedAuditService;
+use crate::services::audit::hash_chained_audit_service::audit_signing_key::AuditSigningKey;
+use crate::services::audit::hash_chained_audit_service::audit_verification_keys::AuditVerificationKeys;
+use crate::services::audit::hash_chained_audit_service::hash_chain_verification::{
+    HashChainSummary, HashChainViolation, verify_hash_chain, verify_json_lines,
+};
+use crate::services::audit::hash_chained_audit_service::hash_chained_record::{GENESIS_HASH, HashChainedRecord};
+use crate::services::audit::hash_chained_audit_service::hash_chained_record_writer::HashChainedRecordWriter;
+use crate::testing::audit_events::AuthorizationAuditEventBuilder;
+use crate::testing::signing_keys::make_jwk;
+use anyhow::{Result, anyhow};
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use serde_json::Value;
+use std::io::BufReader;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_records_are_chained() {
+    // Arrange
+    let writer = Arc::new(RecordingWriter::default());
+    let service = HashChainedAuditService::new(writer.clone());
+
+    // Act
+    service.write(AuditEvent::Final(ChainedAuditEvent::begin()));
+    service
+        .record(AuthorizationAuditEventBuilder::default().record())
+        .expect("event should be written");
+    service
+        .record(AuthorizationAuditEventBuilder::default().with_actor("bob").record())
+        .expect("event should be written");
+
+    // Assert
+    let records = writer.records();
+    let sequences: Vec<u64> = records.iter().map(|r| r.sequence).collect();
+    assert_eq!(sequences, vec![0, 1, 2]);
+    assert_eq!(records[0].previous_hash, GENESIS_HASH);
+    assert_eq!(records[1].previous_hash, records[0].hash);
+    assert_eq!(records[2].previous_hash, records[1].hash);
+    assert!(records.iter().all(|r| r.chain_id == service.chain_id()));
+    assert_eq!(records[2].record["event"]["actor"], Value::String("bob".to_string()));
+    assert_eq!(
+        verify_hash_chain(records, None),
+        Ok(HashChainSummary {
+            records: 3,
+            checkpoints: 0,
+            open: true,
+        })
+    );
+}
+
+#[test]
+fn test_failed_write_does_not_advance_chain() {
+    // Arrange
+    let writer = Arc::new(RecordingWriter::failing_once());
+    let service = HashChainedAuditService::new(writer.clone());
+
+    // Act
+    let failed = service.record(AuthorizationAuditEventBuilder::default().record());
+    service
+        .record(AuthorizationAuditEventBuilder::default().with_actor("bob").record())
+        .expect("event should be written");
+
+    // Assert
+    assert!(failed.is_err());
+    let records = writer.records();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].sequence, 0);
+    assert!(verify_hash_chain(records, None).is_ok());
+}
+
+#[test]
+fn test_verify_detects_modification() {
+    // Arrange
+    let mut records = make_chain(3);
+    records[1].record["event"]["actor"] = Value::String("mallory".to_string());
+
+    // Act
+    let result = verify_hash_chain(records, None);
+
+    // Assert
+    assert_matches!(result, Err(HashChainViolation::Modified { sequence: 1, .. }));
+}
+
+#[test]
+fn test_verify_detects_rehashed_record() {
+    // Arrange
+    let mut records = make_chain(3);
+    let mut forged = records[1].record.clone();
+    forged["event"]["actor"] = Value::String("mallory".to_string());
+    records[1] = HashChainedRecord::new(&records[1].chain_id, 1, &records[1].previous_hash, forged)
+        .expect("record should be hashed");
+
+    // Act
+    let result = verify_hash_chain(records, None);
+
+    // Assert
+    assert_matches!(result, Err(HashChainViolation::BrokenLink { sequence: 2, .. }));
+}
+
+#[test]
+fn test_verify_detects_gap() {
+    // Arrange
+    let mut records = make_chain(3);
+    records.remove(1);
+
+    // Act
+    let result = verify_hash_chain(records, None);
+
+    // Assert
+    assert_matches!(
+        result,
+        Err(HashChainViolation::Gap {
+            expected: 1,
+            found: 2,
+            ..
+        })
+    );
+}
+
+#[test]
+fn test_verify_detects_truncated_start() {
+    // Arrange
+    let mut records = make_chain(3);
+    records.remove(0);
+
+    // Act
+    let result = verify_hash_chain(records, None);
+
+    // Assert
+    assert_matches!(
+        result,
+        Err(HashChainViolation::Gap {
+            expected: 0,
+            found: 1,
+            ..
+        })
+    );
+}
+
+#[test]
+fn test_signed_checkpoints() {
+    // Arrange
+    let (key, keys) = make_keys("audit-1");
+    let writer = Arc::new(RecordingWriter::default());
+    let service = HashChainedAuditService::new(writer.clone()).with_checkpoints(key, 2);
+    for actor in ["a", "b", "c", "d", "e"] {
+        service
+            .record(AuthorizationAuditEventBuilder::default().with_actor(actor).record())
+            .expect("event should be written");
+    }
+
+    // Act
+    let records = writer.records();
+    let result = verify_hash_chain(records.clone(), Some(&keys));
+
+    // Assert
+    let checkpoints: Vec<bool> = records.iter().map(|r| r.checkpoint.is_some()).collect();
+    assert_eq!(checkpoints, vec![true, true, false, true, false]);
+    assert_eq!(
+        result,
+        Ok(HashChainSummary {
+            records: 5,
+            checkpoints: 3,
+            open: true,
+        })
+    );
+}
+
+#[test]
+fn test_close_writes_signed_closing_record() {
+    // Arrange
+    let (key, keys) = make_keys("audit-1");
+    let writer = Arc::new(RecordingWriter::default());
+    let service = HashChainedAuditService::new(writer.clone()).with_checkpoints(key, 2);
+    service
+        .record(AuthorizationAuditEventBuilder::default().record())
+        .expect("event should be written");
+
+    // Act
+    service.close().expect("chain should be closed");
+    let appended = service.record(AuthorizationAuditEventBuilder::default().record());
+
+    // Assert
+    assert!(appended.is_err());
+    let records = writer.records();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[1].record, Value::Null);
+    assert_eq!(
+        verify_hash_chain(records, Some(&keys)),
+        Ok(HashChainSummary {
+            records: 2,
+            checkpoints: 2,
+            open: false,
+        })
+    );
+}
+
+#[test]
+fn test_verify_with_retained_key_after_rotation() {
+    // Arrange
+    let (old_key, _) = make_keys("audit-1");
+    let (new_key, _) = make_keys("audit-2");
+    let keys = AuditVerificationKeys::new(vec![old_key.public_jwk().clone(), new_key.public_jwk().clone()])
+        .expect("keys should be valid");
+    let mut records = make_signed_chain(old_key, 2, true);
+    records.extend(make_signed_chain(new_key, 2, false));
+
+    // Act
+    let result = verify_hash_chain(records, Some(&keys));
+
+    // Assert
+    assert_eq!(result.map(|summary| summary.records), Ok(5));
+}
+
+#[test]
+fn test_verify_detects_invalid_checkpoint() {
+    // Arrange
+    let (key, keys) = make_keys("audit-1");
+    let (_, other_keys) = make_keys("audit-1");
+    let records = make_signed_chain(key, 2, false);
+    let mut moved = records.clone();
+    moved[1].checkpoint = moved[0].checkpoint.clone();
+
+    // Act
+    let foreign_key = verify_hash_chain(records, Some(&other_keys));
+    let moved_checkpoint = verify_hash_chain(moved, Some(&keys));
+
+    // Assert
+    assert_matches!(
+        foreign_key,
+        Err(HashChainViolation::InvalidCheckpoint { sequence: 0, .. })
+    );
+    assert_matches!(
+        moved_checkpoint,
+        Err(HashChainViolation::InvalidCheckpoint { sequence: 1, .. })
+    );
+}
+
+#[rstest]
+#[case(0)]
+#[case(1)]
+fn test_verify_detects_missing_checkpoint(#[case] stripped: usize) {
+    // Arrange
+    let (key, keys) = make_keys("audit-1");
+    let mut records = make_signed_chain(key, 2, false);
+    records[stripped].checkpoint = None;
+
+    // Act
+    let result = verify_hash_chain(records, Some(&keys));
+
+    // Assert
+    assert_matches!(
+        result,
+        Err(HashChainViolation::MissingCheckpoint { sequence, .. }) if sequence == stripped as u64
+    );
+}
+
+#[test]
+fn test_verify_detects_truncated_tail() {
+    // Arrange
+    let (first_key, _) = make_keys("audit-1");
+    let (second_key, _) = make_keys("audit-2");
+    let keys = AuditVerificationKeys::new(vec![first_key.public_jwk().clone(), second_key.public_jwk().clone()])
+        .expect("keys should be valid");
+    let mut records = make_signed_chain(first_key, 4, true);
+    records.truncate(2);
+    records.extend(make_signed_chain(second_key, 1, false));
+
+    // Act
+    let result = verify_hash_chain(records, Some(&keys));
+
+    // Assert
+    assert_matches!(result, Err(HashChainViolation::Truncated { sequence: 1, .. }));
+}
+
+#[test]
+fn test_verify_detects_record_after_close() {
+    // Arrange
+    let (key, keys) = make_keys("audit-1");
+    let mut records = make_signed_chain(key, 1, true);
+    let closing = records.last().expect("chain should be closed").clone();
+    let appended = HashChainedRecord::new(&closing.chain_id, closing.sequence + 1, &closing.hash, Value::Null)
+        .expect("record should be hashed");
+    records.push(appended);
+
+    // Act
+    let result = verify_hash_chain(records, Some(&keys));
+
+    // Assert
+    assert_matches!(result, Err(HashChainViolation::AfterClose { sequence: 2, .. }));
+}
+
+#[test]
+fn test_verify_detects_reappeared_chain() {
+    // Arrange
+    let first = make_chain(2);
+    let second = make_chain(1);
+    let records: Vec<HashChainedRecord> = first.iter().chain(&second).chain(&first).cloned().collect();
+
+    // Act
+    let result = verify_hash_chain(records, None);
+
+    // Assert
+    assert_matches!(
+        result,
+        Err(HashChainViolation::Reappeared { chain_id, sequence: 0 }) if chain_id == first[0].chain_id
+    );
+}
+
+#[test]
+fn test_verify_file_log() {
+    // Arrange
+    let directory = std::env::temp_dir().join(format!("audit-{}", uuid::Uuid::new_v4()));
+    let file = FileAuditService::new(FileAuditSettings {
+        directory: directory.clone(),
+        file_prefix: "audit".to_string(),
+        max_file_size: 1024 * 1024,
+        max_file_age: None,
+        durability: Durability::Buffered,
+        format: AuditFormat::Native,
+    })
+    .expect("service should be created");
+    let service = HashChainedAuditService::new(Arc::new(file));
+    for actor in ["a", "b", "c"] {
+        service
+            .record(AuthorizationAuditEventBuilder::default().with_actor(actor).record())
+            .expect("event should be written");
+    }
+    drop(service);
+    let path = std::fs::read_dir(&directory)
+        .expect("directory should exist")
+        .next()
+        .expect("file should be created")
+        .expect("entry should be readable")
+        .path();
+    let content = std::fs::read_to_string(&path).expect("file should be readable");
+    let tampered = content.replacen("\"actor\":\"b\"", "\"actor\":\"mallory\"", 1);
+
+    // Act
+    let intact = verify_json_lines(BufReader::new(content.as_bytes()), None);
+    let modified = verify_json_lines(BufReader::new(tampered.as_bytes()), None);
+    let malformed = verify_json_lines(BufReader::new("not a record\n".as_bytes()), None);
+
+    // Assert
+    assert_eq!(intact.map(|summary| summary.records), Ok(3));
+    assert_matches!(modified, Err(HashChainViolation::Modified { sequence: 1, .. }));
+    assert_matches!(malformed, Err(HashChainViolation::Malformed { line: 1, .. }));
+    std::fs::remove_dir_all(&directory).expect("directory should be removed");
+}
+
+#[derive(Default)]
+struct RecordingWriter {
+    records: Mutex<Vec<HashChainedRecord>>,
+    fail_next: Mutex<bool>,
+}
+
+impl RecordingWriter {
+    fn failing_once() -> Self {
+        RecordingWriter {
+            records: Mutex::new(Vec::new()),
+            fail_next: Mutex::new(true),
+        }
+    }
+
+    fn records(&self) -> Vec<HashChainedRecord> {
+        self.records.lock().unwrap().clone()
+    }
+}
+
+impl HashChainedRecordWriter for RecordingWriter {
+    fn write_chained(&self, record: &HashChainedRecord) -> Result<()> {
+        let mut fail_next = self.fail_next.lock().unwrap();
+        if *fail_next {
+            *fail_next = false;
+            return Err(anyhow!("writer is unavailable"));
+        }
+        self.records.lock().unwrap().push(record.clone());
+        Ok(())
+    }
+}
+
+fn make_keys(kid: &str) -> (AuditSigningKey, AuditVerificationKeys) {
+    let key = AuditSigningKey::new(kid, make_jwk()).expect("key should be valid");
+    let keys = AuditVerificationKeys::new(vec![key.public_jwk().clone()]).expect("keys should be valid");
+    (key, keys)
+}
+
+/// Writes a chain of `length` records signed with the key and an interval of 2, closed on request.
+fn make_signed_chain(key: AuditSigningKey, length: usize, closed: bool) -> Vec<HashChainedRecord> {
+    let writer = Arc::new(RecordingWriter::default());
+    let service = HashChainedAuditService::new(writer.clone()).with_checkpoints(key, 2);
+    for index in 0..length {
+        service
+            .record(AuditRecord::from(
+                AuthorizationAuditEventBuilder::default()
+                    .with_actor(&format!("actor-{}", index))
+                    .build(),
+            ))
+            .expect("event should be written");
+    }
+    if closed {
+        service.close().expect("chain should be closed");
+    }
+    writer.records()
+}
+
+fn make_chain(length: usize) -> Vec<HashChainedRecord> {
+    let writer = Arc::new(RecordingWriter::default());
+    let service = HashChainedAuditService::new(writer.clone());
+    for index in 0..length {
+        service
+            .record(AuditRecord::from(
+                AuthorizationAuditEventBuilder::default()
+                    .with_actor(&format!("actor-{}", index))
+                    .build(),
+            ))
+            .expect("event should be written");
+    }
+    writer.records()
+}
@@ -0,0 +1,97 @@
+mod event_rate_limiter;
+pub mod kubernetes_event_audit_settings;
+pub mod kubernetes_event_publisher;
+pub mod kubernetes_event_sink;
+#[cfg(test)]
+mod tests;
+
+use crate::services::audit::AuditService;
+use crate::services::audit::audit_dispatcher::AuditDispatcher;
+use crate::services::audit::audit_record::{AuditRecord, AuditRecordEvent};
+use crate::services::audit::kubernetes_event_audit_service::kubernetes_event_audit_settings::KubernetesEventAuditSettings;
+use crate::services::audit::kubernetes_event_audit_service::kubernetes_event_publisher::{
+    KubernetesEventPublisher, SchemaDocumentEventPublisher,
+};
+use crate::services::audit::kubernetes_event_audit_service::kubernetes_event_sink::{
+    KubernetesEventSink, is_schema_resource,
+};
+use crate::services::backends::kubernetes::kubernetes_resource_manager::KubernetesResourceManagerConfig;
+use crate::services::observability::open_telemetry::metrics::metric_recorders::audit_events_dropped::AuditEventsDroppedMetric;
+use crate::services::observability::open_telemetry::metrics::metric_recorders::audit_queue_depth::AuditQueueDepthMetric;
+use anyhow::Result;
+use kube::Client;
+use kube::runtime::events::Reporter;
+use std::sync::Arc;
+
+/// [`KubernetesEventAuditService`] makes the schema changes visible to the cluster operators with
+/// `kubectl describe`. The modifications and deletions of the schemas recorded by the `AuditFacade`
+/// are published as Kubernetes events attached to the affected `SchemaDocument`.
+///
+/// The events are queued by an [`AuditDispatcher`] and published by the [`KubernetesEventSink`],
+/// which aggregates the repeated events and limits the rate of the requests to the Kubernetes API.
+/// The other audit events are ignored.
+pub struct KubernetesEventAuditService {
+    dispatcher: AuditDispatcher,
+}
+
+impl KubernetesEventAuditService {
+    /// Starts the service publishing the events in the namespace of the resource manager, with the
+    /// same client configuration. Must be called from within a tokio runtime.
+    pub fn start(
+        config: &KubernetesResourceManagerConfig,
+        settings: KubernetesEventAuditSettings,
+        queue_depth: Arc<dyn AuditQueueDepthMetric>,
+        events_dropped: Arc<dyn AuditEventsDroppedMetric>,
+    ) -> Result<Self> {
+        let client = Client::try_from(config.kubeconfig.clone())?;
+        let reporter = Reporter {
+            controller: settings.controller.clone(),
+            instance: settings.instance.clone(),
+        };
+        let publisher = Arc::new(SchemaDocumentEventPublisher::new(
+            client,
+            config.namespace.clone(),
+            reporter,
+        ));
+        Self::with_publisher(publisher, &config.namespace, settings, queue_depth, events_dropped)
+    }
+
+    /// Starts the service publishing the events of the documents in the namespace with the publisher.
+    pub fn with_publisher(
+        publisher: Arc<dyn KubernetesEventPublisher>,
+        namespace: &str,
+        settings: KubernetesEventAuditSettings,
+        queue_depth: Arc<dyn AuditQueueDepthMetric>,
+        events_dropped: Arc<dyn AuditEventsDroppedMetric>,
+    ) -> Result<Self> {
+        let sink = Arc::new(KubernetesEventSink::new(
+            publisher,
+            namespace,
+            settings.events_per_second,
+            settings.burst,
+        ));
+        let dispatcher = AuditDispatcher::start(sink, settings.dispatcher, queue_depth, events_dropped)?;
+        Ok(KubernetesEventAuditService { dispatcher })
+    }
+
+    /// Stops accepting events and waits until the queued events are published.
+    pub fn shutdown(&self) {
+        self.dispatcher.shutdown();
+    }
+}
+
+/// Only the modifications and deletions of the schemas are published, so the service can be
+/// composed with the sinks recording the other records.
+impl AuditService for KubernetesEventAuditService {
+    fn record(&self, record: AuditRecord) -> Result<()> {
+        let is_schema_record = match record.event() {
+            AuditRecordEvent::ResourceDeletion(event) => is_schema_resource(&event.resource_type),
+            AuditRecordEvent::ResourceModification(event) => is_schema_resource(&event.resource_type),
+            _ => false,
+        };
+        if !is_schema_record {
+            return Ok(());
+        }
+        Ok(self.dispatcher.dispatch(record)?)
+    }
+}
@@ -0,0 +1,45 @@
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// [`EventRateLimiter`] is a token bucket limiting the number of published events. The bucket
+/// holds up to `burst` tokens and is refilled with `rate` tokens per second.
+pub(crate) struct EventRateLimiter {
+    burst: f64,
+    rate: f64,
+    state: Mutex<Bucket>,
+}
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl EventRateLimiter {
+    pub(crate) fn new(rate: u32, burst: u32) -> Self {
+        let burst = f64::from(burst.max(1));
+        EventRateLimiter {
+            burst,
+            rate: f64::from(rate.max(1)),
+            state: Mutex::new(Bucket {
+                tokens: burst,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Waits until a token is available and takes it.
+    pub(crate) async fn acquire(&self) {
+        let mut bucket = self.state.lock().await;
+        loop {
+            let now = Instant::now();
+            let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+            bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.burst);
+            bucket.refilled_at = now;
+            if bucket.tokens >= 1.0 {
+                bucket.tokens -= 1.0;
+                return;
+            }
+            tokio::time::sleep(Duration::from_secs_f64((1.0 - bucket.tokens) / self.rate)).await;
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use crate::services::audit::audit_dispatcher::audit_dispatcher_settings::AuditDispatcherSettings;
+use serde::Deserialize;
+
+/// The settings of the [`KubernetesEventAuditService`].
+///
+/// [`KubernetesEventAuditService`]: crate::services::audit::kubernetes_event_audit_service::KubernetesEventAuditService
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct KubernetesEventAuditSettings {
+    /// The name of the reporting controller shown in the events.
+    pub controller: String,
+
+    /// The id of the reporting instance, usually the name of the pod.
+    pub instance: Option<String>,
+
+    /// The maximum number of events published per second.
+    pub events_per_second: u32,
+
+    /// The number of events that can be published at once before the rate limit applies.
+    pub burst: u32,
+
+    /// The settings of the queue in front of the Kubernetes API.
+    pub dispatcher: AuditDispatcherSettings,
+}
+
+impl Default for KubernetesEventAuditSettings {
+    fn default() -> Self {
+        KubernetesEventAuditSettings {
+            controller: "boxer".to_string(),
+            instance: None,
+            events_per_second: 5,
+            burst: 10,
+            dispatcher: AuditDispatcherSettings::default(),
+        }
+    }
+}
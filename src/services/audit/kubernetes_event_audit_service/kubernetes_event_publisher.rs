@@ -0,0 +1,58 @@
+use crate::services::backends::kubernetes::kubernetes_repository::schema_repository::schema_document::SchemaDocument;
+use anyhow::Result;
+use async_trait::async_trait;
+use k8s_openapi::api::core::v1::ObjectReference;
+use kube::runtime::events::{Event, Recorder, Reporter};
+use kube::{Api, Client, Resource};
+
+/// [`KubernetesEventPublisher`] publishes the Kubernetes events attached to the schema documents.
+#[async_trait]
+pub trait KubernetesEventPublisher: Send + Sync + 'static {
+    /// Publishes the event attached to the schema document with the name.
+    async fn publish(&self, event: &Event, name: &str) -> Result<()>;
+}
+
+/// Publishes the events with the [`Recorder`], so repeated events of a document are aggregated
+/// into an event series by the Kubernetes API.
+pub struct SchemaDocumentEventPublisher {
+    recorder: Recorder,
+    api: Api<SchemaDocument>,
+    namespace: String,
+}
+
+impl SchemaDocumentEventPublisher {
+    pub fn new(client: Client, namespace: impl Into<String>, reporter: Reporter) -> Self {
+        let namespace = namespace.into();
+        SchemaDocumentEventPublisher {
+            recorder: Recorder::new(client.clone(), reporter),
+            api: Api::namespaced(client, &namespace),
+            namespace,
+        }
+    }
+
+    /// Returns the reference of the document. The reference of a document that does not exist
+    /// anymore has no uid, so the event is kept but not shown by `kubectl describe`.
+    async fn reference(&self, name: &str) -> Result<ObjectReference> {
+        Ok(match self.api.get_opt(name).await? {
+            Some(document) => document.object_ref(&()),
+            None => ObjectReference {
+                api_version: Some(SchemaDocument::api_version(&()).to_string()),
+                kind: Some(SchemaDocument::kind(&()).to_string()),
+                name: Some(name.to_string()),
+                namespace: Some(self.namespace.clone()),
+                ..ObjectReference::default()
+            },
+        })
+    }
+}
+
+#[async_trait]
+impl KubernetesEventPublisher for SchemaDocumentEventPublisher {
+    // COVERAGE: disabled since the event recorder only talks to the Kubernetes API; the events it publishes
+    // are built and tested in the sink
+    #[cfg_attr(coverage, coverage(off))]
+    async fn publish(&self, event: &Event, name: &str) -> Result<()> {
+        let reference = self.reference(name).await?;
+        Ok(self.recorder.publish(event, &reference).await?)
+    }
+}
@@ -0,0 +1,180 @@
+use crate::services::audit::audit_record::{AuditRecord, AuditRecordEvent};
+use crate::services::audit::audit_sink::AuditSink;
+use crate::services::audit::events::resource_modification_audit_event::ModificationResult;
+use crate::services::audit::kubernetes_event_audit_service::event_rate_limiter::EventRateLimiter;
+use crate::services::audit::kubernetes_event_audit_service::kubernetes_event_publisher::KubernetesEventPublisher;
+use crate::services::backends::kubernetes::kubernetes_repository::schema_repository::schema_document::SchemaDocument;
+use crate::services::backends::kubernetes::kubernetes_repository::try_into_object_ref::TryIntoObjectRef;
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use cedar_policy::SchemaFragment;
+use kube::runtime::events::{Event, EventType};
+use log::warn;
+use std::sync::Arc;
+
+/// [`KubernetesEventSink`] publishes the modifications and deletions of the schemas as Kubernetes
+/// events attached to the schema documents. Other records are ignored. The documents are named
+/// from the keys of the records as they are stored by the schema repository.
+///
+/// The same event of a document recorded several times in a row is published once with the
+/// number of occurrences, and the events are published at the rate allowed by the rate limiter.
+pub struct KubernetesEventSink {
+    publisher: Arc<dyn KubernetesEventPublisher>,
+    namespace: String,
+    rate_limiter: EventRateLimiter,
+}
+
+/// The event of a schema document with the number of its occurrences.
+struct SchemaEvent {
+    name: String,
+    type_: EventType,
+    reason: &'static str,
+    action: &'static str,
+    note: String,
+    count: u32,
+}
+
+impl KubernetesEventSink {
+    /// Creates the sink publishing the events of the documents in the namespace, up to
+    /// `events_per_second` events, with bursts of up to `burst` events.
+    pub fn new(
+        publisher: Arc<dyn KubernetesEventPublisher>,
+        namespace: impl Into<String>,
+        events_per_second: u32,
+        burst: u32,
+    ) -> Self {
+        KubernetesEventSink {
+            publisher,
+            namespace: namespace.into(),
+            rate_limiter: EventRateLimiter::new(events_per_second, burst),
+        }
+    }
+}
+
+#[async_trait]
+impl AuditSink for KubernetesEventSink {
+    /// Publishes the events of the batch. A failed event does not stop the others, and the
+    /// failures are reported once all events are published.
+    async fn write_batch(&self, records: Vec<AuditRecord>) -> Result<()> {
+        let events = aggregate(
+            records
+                .iter()
+                .filter_map(|record| SchemaEvent::from_record(record, &self.namespace)),
+        );
+        let total = events.len();
+        let mut failed = 0;
+        for event in events {
+            self.rate_limiter.acquire().await;
+            if let Err(error) = self.publisher.publish(&event.to_event(), &event.name).await {
+                warn!(
+                    "Failed to publish the Kubernetes event for schema {}: {:?}",
+                    event.name, error
+                );
+                failed += 1;
+            }
+        }
+        if failed > 0 {
+            bail!("{} of {} Kubernetes events failed", failed, total);
+        }
+        Ok(())
+    }
+}
+
+/// Checks if the records of the resource type are published as Kubernetes events.
+pub(crate) fn is_schema_resource(resource_type: &str) -> bool {
+    resource_type == std::any::type_name::<SchemaFragment>()
+}
+
+/// Returns the name of the schema document stored under the key by the schema repository.
+fn document_name(key: &str, namespace: &str) -> Result<String> {
+    let reference = TryIntoObjectRef::<SchemaDocument>::try_into_object_ref(key.to_string(), namespace.to_string())?;
+    Ok(reference.name)
+}
+
+/// Collapses runs of consecutive identical events into a single event with a count, so that the
+/// published events keep the order in which the records were written.
+fn aggregate(events: impl Iterator<Item = SchemaEvent>) -> Vec<SchemaEvent> {
+    let mut aggregated: Vec<SchemaEvent> = Vec::new();
+    for event in events {
+        match aggregated.last_mut() {
+            Some(last) if last.is_repeated_by(&event) => last.count += 1,
+            _ => aggregated.push(event),
+        }
+    }
+    aggregated
+}
+
+impl SchemaEvent {
+    fn is_repeated_by(&self, other: &SchemaEvent) -> bool {
+        self.name == other.name
+            && self.type_ == other.type_
+            && self.reason == other.reason
+            && self.action == other.action
+            && self.note == other.note
+    }
+
+    fn from_record(record: &AuditRecord, namespace: &str) -> Option<SchemaEvent> {
+        let (name, type_, reason, action, note) = match record.event() {
+            AuditRecordEvent::ResourceModification(event) if is_schema_resource(&event.resource_type) => {
+                let (type_, reason, note) = match event.modification_result {
+                    ModificationResult::Success(_) => (
+                        EventType::Normal,
+                        "SchemaModified",
+                        format!("Schema {} was modified", event.id),
+                    ),
+                    ModificationResult::Failure => (
+                        EventType::Warning,
+                        "SchemaModificationFailed",
+                        format!("Modification of schema {} failed", event.id),
+                    ),
+                };
+                (&event.id, type_, reason, "Modify", note)
+            }
+            AuditRecordEvent::ResourceDeletion(event) if is_schema_resource(&event.resource_type) => {
+                let (type_, reason, note) = match event.successful {
+                    true => (
+                        EventType::Normal,
+                        "SchemaDeleted",
+                        format!("Schema {} was deleted", event.id),
+                    ),
+                    false => (
+                        EventType::Warning,
+                        "SchemaDeletionFailed",
+                        format!("Deletion of schema {} failed", event.id),
+                    ),
+                };
+                (&event.id, type_, reason, "Delete", note)
+            }
+            _ => return None,
+        };
+        let name = match document_name(name, namespace) {
+            Ok(name) => name,
+            Err(error) => {
+                warn!("Failed to name the schema document {}: {:?}", name, error);
+                return None;
+            }
+        };
+        Some(SchemaEvent {
+            name,
+            type_,
+            reason,
+            action,
+            note,
+            count: 1,
+        })
+    }
+
+    fn to_event(&self) -> Event {
+        let note = match self.count {
+            1 => self.note.clone(),
+            count => format!("{} ({} times)", self.note, count),
+        };
+        Event {
+            type_: self.type_,
+            reason: self.reason.to_string(),
+            note: Some(note),
+            action: self.action.to_string(),
+            secondary: None,
+        }
+    }
+}
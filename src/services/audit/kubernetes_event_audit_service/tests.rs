@@ -0,0 +1,299 @@
+use crate::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
+use crate::services::audit::AuditService;
+use crate::services::audit::audit_record::AuditRecord;
+use crate::services::audit::audit_sink::AuditSink;
+use crate::services::audit::chained::audit_event::AuditEvent;
+use crate::services::audit::chained::chained_audit_event::ChainedAuditEvent;
+use crate::services::audit::events::authorization_audit_event::{AuthorizationAuditEvent, Reason};
+use crate::services::audit::events::resource_delete_audit_event::ResourceDeleteAuditEvent;
+use crate::services::audit::events::resource_modification_audit_event::{
+    ModificationResult, ResourceModificationAuditEvent,
+};
+use crate::services::audit::kubernetes_event_audit_service::KubernetesEventAuditService;
+use crate::services::audit::kubernetes_event_audit_service::kubernetes_event_audit_settings::KubernetesEventAuditSettings;
+use crate::services::audit::kubernetes_event_audit_service::kubernetes_event_publisher::KubernetesEventPublisher;
+use crate::services::audit::kubernetes_event_audit_service::kubernetes_event_sink::KubernetesEventSink;
+use crate::testing::audit_metrics::{MockAuditEventsDroppedMetric, queue_depth_metric};
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use cedar_policy::{Decision, SchemaFragment};
+use kube::runtime::events::{Event, EventType};
+use pretty_assertions::assert_eq;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const NAMESPACE: &str = "default";
+
+#[test]
+fn test_publish_schema_events() {
+    // Arrange
+    let publisher = Arc::new(RecordingPublisher::default());
+    let service = KubernetesEventAuditService::with_publisher(
+        publisher.clone(),
+        NAMESPACE,
+        KubernetesEventAuditSettings::default(),
+        Arc::new(queue_depth_metric()),
+        Arc::new(MockAuditEventsDroppedMetric::new()),
+    )
+    .expect("service should start");
+
+    // Act
+    service
+        .record(AuditRecord::from(make_modification(
+            "schema-a",
+            ModificationResult::Success("{}".to_string()),
+        )))
+        .expect("event should be queued");
+    service
+        .record(AuditRecord::from(make_modification(
+            "schema-b",
+            ModificationResult::Failure,
+        )))
+        .expect("event should be queued");
+    service
+        .record(AuditRecord::from(make_deletion("schema-a", true)))
+        .expect("event should be queued");
+    service
+        .record(AuditRecord::from(make_deletion("schema-b", false)))
+        .expect("event should be queued");
+    service.shutdown();
+
+    // Assert
+    assert_eq!(
+        publisher.published(),
+        vec![
+            published(
+                "schema-a",
+                EventType::Normal,
+                "SchemaModified",
+                "Schema schema-a was modified"
+            ),
+            published(
+                "schema-b",
+                EventType::Warning,
+                "SchemaModificationFailed",
+                "Modification of schema schema-b failed"
+            ),
+            published(
+                "schema-a",
+                EventType::Normal,
+                "SchemaDeleted",
+                "Schema schema-a was deleted"
+            ),
+            published(
+                "schema-b",
+                EventType::Warning,
+                "SchemaDeletionFailed",
+                "Deletion of schema schema-b failed"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_ignore_other_events() {
+    // Arrange
+    let publisher = Arc::new(RecordingPublisher::default());
+    let service = KubernetesEventAuditService::with_publisher(
+        publisher.clone(),
+        NAMESPACE,
+        KubernetesEventAuditSettings::default(),
+        Arc::new(queue_depth_metric()),
+        Arc::new(MockAuditEventsDroppedMetric::new()),
+    )
+    .expect("service should start");
+    let other_resource = ResourceDeleteAuditEvent::new("id".to_string(), "other".to_string(), true);
+
+    // Act
+    service
+        .record(AuditRecord::from(other_resource))
+        .expect("event should be ignored");
+    service
+        .record(AuditRecord::from(AuthorizationAuditEvent {
+            action: "read".to_string(),
+            actor: "alice".to_string(),
+            resource: "documents/1".to_string(),
+            decision: Decision::Allow,
+            reason: Reason {
+                policies: HashSet::new(),
+                errors: HashSet::new(),
+            },
+        }))
+        .expect("event should be ignored");
+    service.write(AuditEvent::Final(ChainedAuditEvent::begin()));
+    service.shutdown();
+
+    // Assert
+    assert_eq!(publisher.published(), vec![]);
+}
+
+#[tokio::test]
+async fn test_aggregate_consecutive_repeated_events() {
+    // Arrange
+    let publisher = Arc::new(RecordingPublisher::default());
+    let sink = KubernetesEventSink::new(publisher.clone(), NAMESPACE, 100, 10);
+    let records = vec![
+        modification_record("schema-a"),
+        modification_record("schema-b"),
+        modification_record("schema-a"),
+        modification_record("schema-a"),
+    ];
+
+    // Act
+    let result = sink.write_batch(records).await;
+
+    // Assert
+    assert!(result.is_ok(), "{:?}", result.err());
+    assert_eq!(
+        publisher.published(),
+        vec![
+            published(
+                "schema-a",
+                EventType::Normal,
+                "SchemaModified",
+                "Schema schema-a was modified"
+            ),
+            published(
+                "schema-b",
+                EventType::Normal,
+                "SchemaModified",
+                "Schema schema-b was modified"
+            ),
+            published(
+                "schema-a",
+                EventType::Normal,
+                "SchemaModified",
+                "Schema schema-a was modified (2 times)"
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_rate_limit_events() {
+    // Arrange
+    let publisher = Arc::new(RecordingPublisher::default());
+    let sink = KubernetesEventSink::new(publisher.clone(), NAMESPACE, 50, 1);
+    let records = vec![
+        modification_record("schema-a"),
+        modification_record("schema-b"),
+        modification_record("schema-c"),
+    ];
+    let started = Instant::now();
+
+    // Act
+    let result = sink.write_batch(records).await;
+
+    // Assert
+    assert!(result.is_ok(), "{:?}", result.err());
+    assert_eq!(publisher.published().len(), 3);
+    assert!(
+        started.elapsed() >= Duration::from_millis(35),
+        "{:?}",
+        started.elapsed()
+    );
+}
+
+#[tokio::test]
+async fn test_failed_event_does_not_stop_others() {
+    // Arrange
+    let publisher = Arc::new(RecordingPublisher {
+        failing: Some("schema-a".to_string()),
+        ..RecordingPublisher::default()
+    });
+    let sink = KubernetesEventSink::new(publisher.clone(), NAMESPACE, 100, 10);
+
+    // Act
+    let result = sink
+        .write_batch(vec![modification_record("schema-a"), modification_record("schema-b")])
+        .await;
+
+    // Assert
+    let error = result.expect_err("failure should be reported");
+    assert_eq!(error.to_string(), "1 of 2 Kubernetes events failed");
+    assert_eq!(publisher.published().len(), 1);
+}
+
+#[tokio::test]
+async fn test_publish_to_stored_document_name() {
+    // Arrange
+    let publisher = Arc::new(RecordingPublisher::default());
+    let sink = KubernetesEventSink::new(publisher.clone(), NAMESPACE, 100, 10);
+
+    // Act
+    let result = sink.write_batch(vec![modification_record("Orders_Schema.v1")]).await;
+
+    // Assert
+    assert!(result.is_ok(), "{:?}", result.err());
+    assert_eq!(
+        publisher.published(),
+        vec![published(
+            "orders-schema-v1",
+            EventType::Normal,
+            "SchemaModified",
+            "Schema Orders_Schema.v1 was modified"
+        )]
+    );
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct PublishedEvent {
+    name: String,
+    type_: EventType,
+    reason: String,
+    note: Option<String>,
+}
+
+#[derive(Default)]
+struct RecordingPublisher {
+    published: Mutex<Vec<PublishedEvent>>,
+    failing: Option<String>,
+}
+
+impl RecordingPublisher {
+    fn published(&self) -> Vec<PublishedEvent> {
+        self.published.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl KubernetesEventPublisher for RecordingPublisher {
+    async fn publish(&self, event: &Event, name: &str) -> Result<()> {
+        if self.failing.as_deref() == Some(name) {
+            return Err(anyhow!("Kubernetes API is unavailable"));
+        }
+        self.published.lock().unwrap().push(PublishedEvent {
+            name: name.to_string(),
+            type_: event.type_,
+            reason: event.reason.clone(),
+            note: event.note.clone(),
+        });
+        Ok(())
+    }
+}
+
+fn published(name: &str, type_: EventType, reason: &str, note: &str) -> PublishedEvent {
+    PublishedEvent {
+        name: name.to_string(),
+        type_,
+        reason: reason.to_string(),
+        note: Some(note.to_string()),
+    }
+}
+
+fn schema_type() -> String {
+    std::any::type_name::<SchemaFragment>().to_string()
+}
+
+fn make_modification(name: &str, result: ModificationResult) -> ResourceModificationAuditEvent {
+    ResourceModificationAuditEvent::new(name.to_string(), schema_type(), result)
+}
+
+fn make_deletion(name: &str, successful: bool) -> ResourceDeleteAuditEvent {
+    ResourceDeleteAuditEvent::new(name.to_string(), schema_type(), successful)
+}
+
+fn modification_record(name: &str) -> AuditRecord {
+    AuditRecord::from(make_modification(name, ModificationResult::Success("{}".to_string())))
+}
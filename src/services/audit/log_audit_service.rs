@@ -1,5 +1,8 @@
-use crate::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
 use crate::services::audit::AuditService;
+use crate::services::audit::audit_format::AuditFormat;
+use crate::services::audit::audit_format::audit_formatter::AuditFormatter;
+use crate::services::audit::audit_record::{AuditRecord, AuditRecordEvent};
+use crate::services::audit::audit_sink::AuditSink;
 use crate::services::audit::chained::audit_event::AuditEvent;
 use crate::services::audit::events::authorization_audit_event::AuthorizationAuditEvent;
 use crate::services::audit::events::resource_delete_audit_event::ResourceDeleteAuditEvent;
@@ -7,142 +10,252 @@ use crate::services::audit::events::resource_modification_audit_event::{
     ModificationResult, ResourceModificationAuditEvent,
 };
 use crate::services::audit::events::token_validation_event::TokenValidationEvent;
+use crate::services::audit::hash_chained_audit_service::hash_chained_record::HashChainedRecord;
+use crate::services::audit::hash_chained_audit_service::hash_chained_record_writer::HashChainedRecordWriter;
 use anyhow::Result;
+use async_trait::async_trait;
 use std::collections::HashSet;
+use std::sync::Arc;
 
-pub struct LogAuditService;
+#[cfg(test)]
+mod tests;
+
+pub struct LogAuditService {
+    layout: LogLayout,
+}
+
+/// The layout of the records logged by the [`LogAuditService`].
+enum LogLayout {
+    /// The fields of the event as separate structured log fields.
+    Fields,
+
+    /// The versioned envelope of the record as a single structured log field.
+    Envelope,
+
+    /// A single line written by the formatter.
+    Formatted(Arc<dyn AuditFormatter>),
+}
 
 impl LogAuditService {
+    /// Creates the service logging the fields of every event as separate structured log fields.
     pub fn new() -> Self {
-        Self {}
+        Self {
+            layout: LogLayout::Fields,
+        }
     }
-}
 
-impl AuditService for LogAuditService {
-    // COVERAGE: disabled since this should be tested in integration tests only
-    #[cfg_attr(coverage, coverage(off))]
-    fn record_authorization(&self, event: AuthorizationAuditEvent) -> Result<()> {
-        log::info!(
-            // Indicates the audit events for easier filtering in log aggregation systems
-            log_type = "audit",
+    /// Logs every record as the versioned envelope of the record, instead of the fields of the event.
+    pub fn with_envelope(mut self) -> Self {
+        self.layout = LogLayout::Envelope;
+        self
+    }
 
-            // The event decomposition for structured logging
-            action = event.action.as_str(),
-            actor = event.actor.as_str(),
-            resource = event.resource.as_str(),
-            result:serde = event.decision,
-            reason_policies:serde = event.reason.policies,
-            reason_errors:serde = event.reason.errors;
+    /// Logs every record as a single line in the format, instead of the structured log entries.
+    pub fn with_format(self, format: AuditFormat) -> Self {
+        self.with_formatter(format.formatter())
+    }
 
-            // The log message
-            "Authorization to access the resource: {:?}", event.resource());
+    /// Logs every record as a single line written by the formatter.
+    pub fn with_formatter(mut self, formatter: Arc<dyn AuditFormatter>) -> Self {
+        self.layout = LogLayout::Formatted(formatter);
+        self
+    }
+}
 
-        Ok(())
+impl Default for LogAuditService {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    // COVERAGE: disabled since this should be tested in integration tests only
-    #[cfg_attr(coverage, coverage(off))]
-    fn record_resource_deletion(&self, event: ResourceDeleteAuditEvent) -> Result<()> {
-        log::info!(
-            // Indicates the audit events for easier filtering in log aggregation systems
-            log_type = "audit",
+/// Logs the record as a single line in the format of the formatter.
+fn log_formatted(formatter: &dyn AuditFormatter, record: &AuditRecord) -> Result<()> {
+    let line = formatter.format(record)?;
+    log::info!(
+        // Indicates the audit events for easier filtering in log aggregation systems
+        log_type = "audit";
 
-            // The event decomposition for structured logging
-            id = event.id.as_str(),
-            resource_type = event.resource_type.as_str(),
-            successfull = event.successful;
+        // The log message
+        "{}", line);
 
-            // The log message
-            "Boxer resource deleted: {:?}/{:?}", event.resource_type, event.id);
+    Ok(())
+}
 
-        Ok(())
-    }
+/// Logs the record as a structured log entry holding the versioned envelope of the record.
+fn log_envelope(record: &AuditRecord) -> Result<()> {
+    log::info!(
+        // Indicates the audit events for easier filtering in log aggregation systems
+        log_type = "audit",
 
-    // COVERAGE: disabled since this should be tested in integration tests only
-    #[cfg_attr(coverage, coverage(off))]
-    fn record_resource_modification(&self, event: ResourceModificationAuditEvent) -> Result<()> {
-        if let ModificationResult::Success(result) = &event.modification_result {
-            log::info!(
-            // Indicates the audit events for easier filtering in log aggregation systems
-            log_type = "audit",
+        // The envelope of the record for structured logging
+        kind = record.kind().name(),
+        record:serde = record;
 
-            // The event decomposition for structured logging
-            id = event.id.as_str(),
-            resource_type = event.resource_type.as_str(),
-            successfull = result;
+        // The log message
+        "Boxer audit record of kind {:?}", record.kind().name());
 
-            // The log message
-            "Boxer resource modified: {:?}/{:?}", event.resource_type, event.id);
-        } else {
-            log::info!(
-            // Indicates the audit events for easier filtering in log aggregation systems
-            log_type = "audit",
+    Ok(())
+}
 
-            // The event decomposition for structured logging
-            id = event.id.as_str(),
-            resource_type = event.resource_type.as_str(),
-            failure:serde = event.modification_result;
+fn log_authorization(event: &AuthorizationAuditEvent) {
+    log::info!(
+        // Indicates the audit events for easier filtering in log aggregation systems
+        log_type = "audit",
 
-            // The log message
-            "Boxer resource modified: {:?}/{:?}", event.resource_type, event.id);
-        }
+        // The event decomposition for structured logging
+        action = event.action.as_str(),
+        actor = event.actor.as_str(),
+        resource = event.resource.as_str(),
+        result:serde = event.decision,
+        reason_policies:serde = event.reason.policies,
+        reason_errors:serde = event.reason.errors;
 
-        Ok(())
-    }
+        // The log message
+        "Authorization to access the resource: {:?}", event.resource());
+}
+
+fn log_resource_deletion(event: &ResourceDeleteAuditEvent) {
+    log::info!(
+        // Indicates the audit events for easier filtering in log aggregation systems
+        log_type = "audit",
 
-    // COVERAGE: disabled since this should be tested in integration tests only
-    #[cfg_attr(coverage, coverage(off))]
-    fn record_token_validation(&self, event: TokenValidationEvent) -> Result<()> {
+        // The event decomposition for structured logging
+        id = event.id.as_str(),
+        resource_type = event.resource_type.as_str(),
+        successfull = event.successful;
+
+        // The log message
+        "Boxer resource deleted: {:?}/{:?}", event.resource_type, event.id);
+}
+
+fn log_resource_modification(event: &ResourceModificationAuditEvent) {
+    if let ModificationResult::Success(result) = &event.modification_result {
         log::info!(
-            // Indicates the audit events for easier filtering in log aggregation systems
-            log_type = "audit",
+        // Indicates the audit events for easier filtering in log aggregation systems
+        log_type = "audit",
 
-            // The event decomposition for structured logging
-            id = event.token_id.as_str(),
-            result:serde = event.result,
-            token_type = event.token_type.as_str(),
-            reason_errors:serde = event.reason_errors,
-            metadata:serde = event.token_metadata;
+        // The event decomposition for structured logging
+        id = event.id.as_str(),
+        resource_type = event.resource_type.as_str(),
+        successfull = result;
 
-            // The log message
-            "Boxer token validation: {:?}/{:?}", event.token_type, event.token_id);
+        // The log message
+        "Boxer resource modified: {:?}/{:?}", event.resource_type, event.id);
+    } else {
+        log::info!(
+        // Indicates the audit events for easier filtering in log aggregation systems
+        log_type = "audit",
+
+        // The event decomposition for structured logging
+        id = event.id.as_str(),
+        resource_type = event.resource_type.as_str(),
+        failure:serde = event.modification_result;
+
+        // The log message
+        "Boxer resource modified: {:?}/{:?}", event.resource_type, event.id);
+    }
+}
+
+fn log_token_validation(event: &TokenValidationEvent) {
+    log::info!(
+        // Indicates the audit events for easier filtering in log aggregation systems
+        log_type = "audit",
+
+        // The event decomposition for structured logging
+        id = event.token_id.as_str(),
+        result:serde = event.result,
+        token_type = event.token_type.as_str(),
+        reason_errors:serde = event.reason_errors,
+        metadata:serde = event.token_metadata;
+
+        // The log message
+        "Boxer token validation: {:?}/{:?}", event.token_type, event.token_id);
+}
+
+/// Logs the event of the audit chain. Both intermediate and final events are normalized into a
+/// single payload shape, flagged with `is_final`.
+fn log_chained(event: &AuditEvent) {
+    let (payload, is_final) = match event {
+        AuditEvent::Final(e) => (e, true),
+        AuditEvent::Intermediate(e) => (e, false),
+    };
+
+    log::info!(
+        // Indicates the audit events for easier filtering in log aggregation systems
+        log_type = "audit",
+
+        // The event decomposition for structured logging
+        is_final = is_final,
+        chain_id = payload.chain_id,
+        sequence = payload.sequence,
+        created_at = payload.created_at,
+        finalized_at = payload.finalized_at,
+        action = payload.action,
+        actor = payload.actor,
+        resource = payload.resource,
+        decision:serde = payload.decision,
+        reason_policies:serde = payload.reason.as_ref().map_or(HashSet::new(), |r| r.policies.clone()),
+        reason_errors:serde = payload.reason.as_ref().map_or(HashSet::new(), |r| r.errors.clone()),
+        attributes:serde = payload.attributes,
+        external_token_id = payload.external_token.as_ref().and_then(|t| t.token_id.as_deref()),
+        internal_token_id = payload.internal_token.as_ref().and_then(|t| t.token_id.as_deref()),
+        parent_id = payload.parent.as_ref().map(|p| p.id.as_str()),
+        parent_issuer = payload.parent.as_ref().map(|p| p.issuer.as_str()),
+        parent_event:serde = payload.parent.as_ref().and_then(|p| p.event.as_ref()),
+        request:serde = payload.request;
+
+        // The log message
+        "Boxer audit event recorded with decision: {:?}", payload.decision
+    );
+}
+
+impl AuditService for LogAuditService {
+    /// Logs the fields of the event as a structured log entry, or the record in the layout the
+    /// service was configured with.
+    fn record(&self, record: AuditRecord) -> Result<()> {
+        match &self.layout {
+            LogLayout::Formatted(formatter) => return log_formatted(formatter.as_ref(), &record),
+            LogLayout::Envelope => return log_envelope(&record),
+            LogLayout::Fields => {}
+        }
+        match record.event() {
+            AuditRecordEvent::Chained(event) => log_chained(event),
+            AuditRecordEvent::Authorization(event) => log_authorization(event),
+            AuditRecordEvent::ResourceDeletion(event) => log_resource_deletion(event),
+            AuditRecordEvent::ResourceModification(event) => log_resource_modification(event),
+            AuditRecordEvent::TokenValidation(event) => log_token_validation(event),
+        }
 
         Ok(())
     }
 }
 
-impl AuditWriter for LogAuditService {
-    /// Writes an audit event as a structured log entry.
-    ///
-    /// Both intermediate and final events are normalized into a single payload
-    /// shape and emitted with `log_type = "audit"` so downstream log systems can
-    /// reliably filter and index audit records.
-
-    // COVERAGE: disabled since this should be tested in integration tests only
-    #[cfg_attr(coverage, coverage(off))]
-    fn write(&self, event: AuditEvent) {
-        let (payload, is_final) = match event {
-            AuditEvent::Final(e) => (e, true),
-            AuditEvent::Intermediate(e) => (e, false),
-        };
+#[async_trait]
+impl AuditSink for LogAuditService {
+    async fn write_batch(&self, records: Vec<AuditRecord>) -> Result<()> {
+        records.into_iter().try_for_each(|record| self.record(record))
+    }
+}
 
+impl HashChainedRecordWriter for LogAuditService {
+    /// Writes the hash-chained record as a structured log entry. The record is logged as a single
+    /// value, so it can be verified in the same form as it was hashed.
+    fn write_chained(&self, record: &HashChainedRecord) -> Result<()> {
         log::info!(
             // Indicates the audit events for easier filtering in log aggregation systems
             log_type = "audit",
 
-            // The event decomposition for structured logging
-            is_final = is_final,
-            action = payload.action,
-            actor = payload.actor,
-            resource = payload.resource,
-            decision:serde = payload.decision,
-            reason_policies:serde = payload.reason.clone().map_or(HashSet::new(), |r| r.policies),
-            reason_errors:serde = payload.reason.map(|r| r.errors).unwrap_or(HashSet::new()),
-            external_token_id = payload.external_token.or(None).map(|t| t.token_id),
-            internal_token_id = payload.internal_token.or(None).map(|t| t.token_id);
+            // The hash chain of the record
+            hash_chain_id = record.chain_id.as_str(),
+            hash_chain_sequence = record.sequence,
+            previous_hash = record.previous_hash.as_str(),
+            hash = record.hash.as_str(),
+            checkpoint = record.checkpoint.as_deref(),
+            record:serde = record.record;
 
             // The log message
-            "Boxer audit event recorded with decision: {:?}", payload.decision
-        );
+            "Boxer audit record {:?} of hash chain {:?}", record.sequence, record.chain_id);
+
+        Ok(())
     }
 }
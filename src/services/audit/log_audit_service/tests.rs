@@ -0,0 +1,179 @@
+use crate::services::audit::AuditService;
+use crate::services::audit::audit_format::AuditFormat;
+use crate::services::audit::audit_format::audit_formatter::AuditFormatter;
+use crate::services::audit::audit_record::AuditRecord;
+use crate::services::audit::chained::audit_event::AuditEvent;
+use crate::services::audit::chained::chained_audit_event::ChainedAuditEvent;
+use crate::services::audit::events::resource_delete_audit_event::ResourceDeleteAuditEvent;
+use crate::services::audit::hash_chained_audit_service::hash_chained_record::{GENESIS_HASH, HashChainedRecord};
+use crate::services::audit::hash_chained_audit_service::hash_chained_record_writer::HashChainedRecordWriter;
+use crate::services::audit::log_audit_service::LogAuditService;
+use crate::testing::audit_events::AuthorizationAuditEventBuilder;
+use anyhow::{Result, anyhow};
+use log::kv::{self, Key, Value, VisitSource};
+use log::{LevelFilter, Log, Metadata, Record};
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use serde_json::json;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread::{self, ThreadId};
+
+#[rstest]
+#[case(AuditFormat::Native)]
+#[case(AuditFormat::CloudEvents)]
+#[case(AuditFormat::Ocsf)]
+#[case(AuditFormat::Cef)]
+fn test_log_formatted_record(#[case] format: AuditFormat) {
+    // Arrange
+    let service = LogAuditService::new().with_format(format);
+
+    // Act
+    let result = service.record(AuditRecord::from(make_deletion()));
+
+    // Assert
+    assert!(result.is_ok(), "{:?}", result.err());
+}
+
+#[rstest]
+#[case(
+    AuthorizationAuditEventBuilder::default().record(),
+    vec!["log_type", "action", "actor", "resource", "result", "reason_policies", "reason_errors"],
+)]
+#[case(
+    AuditRecord::from(make_deletion()),
+    vec!["log_type", "id", "resource_type", "successfull"],
+)]
+#[case(
+    AuditRecord::from(AuditEvent::Final(ChainedAuditEvent::begin())),
+    vec![
+        "log_type",
+        "is_final",
+        "chain_id",
+        "sequence",
+        "created_at",
+        "finalized_at",
+        "action",
+        "actor",
+        "resource",
+        "decision",
+        "reason_policies",
+        "reason_errors",
+        "attributes",
+        "external_token_id",
+        "internal_token_id",
+        "parent_id",
+        "parent_issuer",
+        "parent_event",
+        "request",
+    ],
+)]
+fn test_log_event_fields(#[case] record: AuditRecord, #[case] fields: Vec<&str>) {
+    // Arrange
+    let service = LogAuditService::new();
+
+    // Act
+    let (result, logged) = capture(|| service.record(record));
+
+    // Assert
+    assert!(result.is_ok(), "{:?}", result.err());
+    assert_eq!(logged, vec![fields]);
+}
+
+#[test]
+fn test_log_record_envelope() {
+    // Arrange
+    let service = LogAuditService::new().with_envelope();
+
+    // Act
+    let (result, logged) = capture(|| service.record(AuditRecord::from(make_deletion())));
+
+    // Assert
+    assert!(result.is_ok(), "{:?}", result.err());
+    assert_eq!(logged, vec![vec!["log_type", "kind", "record"]]);
+}
+
+#[test]
+fn test_propagate_format_error() {
+    // Arrange
+    let service = LogAuditService::default().with_formatter(Arc::new(FailingFormatter));
+
+    // Act
+    let result = service.record(AuditRecord::from(make_deletion()));
+
+    // Assert
+    let error = result.expect_err("format error should be returned");
+    assert_eq!(error.to_string(), "unsupported record");
+}
+
+#[test]
+fn test_write_chained_record() {
+    // Arrange
+    let record = HashChainedRecord::new("chain-1", 0, GENESIS_HASH, json!({ "actor": "alice" })).unwrap();
+
+    // Act
+    let result = LogAuditService::new().write_chained(&record);
+
+    // Assert
+    assert!(result.is_ok(), "{:?}", result.err());
+}
+
+struct FailingFormatter;
+
+impl AuditFormatter for FailingFormatter {
+    fn format(&self, _record: &AuditRecord) -> Result<String> {
+        Err(anyhow!("unsupported record"))
+    }
+}
+
+/// Captures the keys of the structured fields logged by every thread.
+struct CapturingLogger {
+    captured: Mutex<Vec<(ThreadId, Vec<String>)>>,
+}
+
+impl Log for CapturingLogger {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        let mut keys = KeyCollector(Vec::new());
+        record.key_values().visit(&mut keys).expect("fields should be visited");
+        self.captured.lock().unwrap().push((thread::current().id(), keys.0));
+    }
+
+    fn flush(&self) {}
+}
+
+struct KeyCollector(Vec<String>);
+
+impl<'kvs> VisitSource<'kvs> for KeyCollector {
+    fn visit_pair(&mut self, key: Key<'kvs>, _value: Value<'kvs>) -> Result<(), kv::Error> {
+        self.0.push(key.as_str().to_string());
+        Ok(())
+    }
+}
+
+/// Runs the action and returns the keys of the fields it logged on the current thread.
+fn capture<T>(action: impl FnOnce() -> T) -> (T, Vec<Vec<String>>) {
+    static LOGGER: OnceLock<&'static CapturingLogger> = OnceLock::new();
+    let logger = *LOGGER.get_or_init(|| {
+        let logger: &'static CapturingLogger = Box::leak(Box::new(CapturingLogger {
+            captured: Mutex::new(Vec::new()),
+        }));
+        log::set_logger(logger).expect("logger should be installed once");
+        log::set_max_level(LevelFilter::Info);
+        logger
+    });
+
+    let result = action();
+
+    let thread_id = thread::current().id();
+    let mut captured = logger.captured.lock().unwrap();
+    let (logged, others): (Vec<_>, Vec<_>) = captured.drain(..).partition(|(id, _)| *id == thread_id);
+    *captured = others;
+    (result, logged.into_iter().map(|(_, keys)| keys).collect())
+}
+
+fn make_deletion() -> ResourceDeleteAuditEvent {
+    ResourceDeleteAuditEvent::new("schema-a".to_string(), "schema".to_string(), true)
+}
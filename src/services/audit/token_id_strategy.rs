@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod tests;
+
+use hmac::{Hmac, Mac};
+use josekit::jwt::JwtPayload;
+use serde::Deserialize;
+use sha2::Sha256;
+use std::fmt::{Debug, Formatter};
+
+/// [`TokenIdStrategy`] defines how token identifiers written to audit records are derived from
+/// raw token values.
+///
+/// Identifiers allow correlating audit records that refer to the same token. They should not
+/// allow confirming which token a record refers to, so the keyed [`TokenIdStrategy::HmacSha256`]
+/// strategy should be preferred. [`TokenIdStrategy::Md5`] is the default for compatibility with
+/// the identifiers of existing audit records.
+#[derive(Clone, Default, Deserialize)]
+#[serde(tag = "strategy", rename_all = "kebab-case")]
+pub enum TokenIdStrategy {
+    /// HMAC-SHA256 of the token keyed with the secret: `hmac-sha256:<hex>`.
+    HmacSha256 { secret: String },
+
+    /// The `jti` claim of a validated token prefixed with its issuer: `jti:<issuer>#<value>`.
+    /// Tokens are identified with the fallback strategy until their signature is validated, and
+    /// tokens without a `jti` claim, e.g. encrypted or opaque tokens, keep the fallback id.
+    Jti { fallback: Box<TokenIdStrategy> },
+
+    /// Unkeyed MD5 hash of the token: `md5:<hex>`.
+    #[default]
+    Md5,
+}
+
+impl TokenIdStrategy {
+    pub fn hmac_sha256(secret: impl Into<String>) -> Self {
+        TokenIdStrategy::HmacSha256 { secret: secret.into() }
+    }
+
+    pub fn jti(fallback: TokenIdStrategy) -> Self {
+        TokenIdStrategy::Jti {
+            fallback: Box::new(fallback),
+        }
+    }
+
+    /// Returns the identifier of the raw token value. The claims of the raw token are not trusted,
+    /// so the [`TokenIdStrategy::Jti`] strategy identifies it with the fallback strategy.
+    pub fn token_id(&self, token: &str) -> String {
+        match self {
+            TokenIdStrategy::HmacSha256 { secret } => {
+                let mut mac =
+                    Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+                mac.update(token.as_bytes());
+                format!("hmac-sha256:{:x}", mac.finalize().into_bytes())
+            }
+            TokenIdStrategy::Jti { fallback } => fallback.token_id(token),
+            TokenIdStrategy::Md5 => format!("md5:{:x}", md5::compute(token)),
+        }
+    }
+
+    /// Returns the identifier of a token from its validated claims, if the strategy identifies
+    /// validated tokens differently from the raw token value.
+    pub fn validated_token_id(&self, issuer: &str, claims: &JwtPayload) -> Option<String> {
+        match self {
+            TokenIdStrategy::Jti { .. } => claims
+                .jwt_id()
+                .filter(|jti| !jti.is_empty())
+                .map(|jti| format!("jti:{}#{}", issuer, jti)),
+            TokenIdStrategy::HmacSha256 { .. } | TokenIdStrategy::Md5 => None,
+        }
+    }
+}
+
+/// The secret is never written to logs.
+impl Debug for TokenIdStrategy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenIdStrategy::HmacSha256 { .. } => write!(f, "HmacSha256 {{ secret: <redacted> }}"),
+            TokenIdStrategy::Jti { fallback } => write!(f, "Jti {{ fallback: {:?} }}", fallback),
+            TokenIdStrategy::Md5 => write!(f, "Md5"),
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use crate::services::audit::token_id_strategy::TokenIdStrategy;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use josekit::jwt::JwtPayload;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use serde_json::json;
+
+#[test]
+fn test_md5_is_compatible() {
+    // Act
+    let id = TokenIdStrategy::Md5.token_id("token");
+
+    // Assert
+    assert_eq!(id, "md5:94a08da1fecbb6e8b46990538c7b50b2");
+}
+
+#[test]
+fn test_hmac_sha256() {
+    // Act: test case 2 of RFC 4231
+    let id = TokenIdStrategy::hmac_sha256("Jefe").token_id("what do ya want for nothing?");
+
+    // Assert
+    assert_eq!(
+        id,
+        "hmac-sha256:5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+    );
+}
+
+#[test]
+fn test_hmac_sha256_depends_on_secret() {
+    // Act
+    let first = TokenIdStrategy::hmac_sha256("first").token_id("token");
+    let second = TokenIdStrategy::hmac_sha256("second").token_id("token");
+
+    // Assert
+    assert_ne!(first, second);
+}
+
+#[test]
+fn test_jti() {
+    // Arrange
+    let claims = make_claims(json!({"sub": "alice", "jti": "token-1"}));
+
+    // Act
+    let id = TokenIdStrategy::jti(TokenIdStrategy::Md5).validated_token_id("https://idp.example.com", &claims);
+
+    // Assert
+    assert_eq!(id.as_deref(), Some("jti:https://idp.example.com#token-1"));
+}
+
+#[rstest]
+#[case(TokenIdStrategy::jti(TokenIdStrategy::Md5), json!({"sub": "alice"}))]
+#[case(TokenIdStrategy::jti(TokenIdStrategy::Md5), json!({"jti": ""}))]
+#[case(TokenIdStrategy::Md5, json!({"jti": "token-1"}))]
+#[case(TokenIdStrategy::hmac_sha256("secret"), json!({"jti": "token-1"}))]
+fn test_no_validated_token_id(#[case] strategy: TokenIdStrategy, #[case] claims: serde_json::Value) {
+    // Act
+    let id = strategy.validated_token_id("https://idp.example.com", &make_claims(claims));
+
+    // Assert
+    assert_eq!(id, None);
+}
+
+#[rstest]
+#[case(make_jws(json!({"sub": "alice", "jti": "token-1"})))]
+#[case(make_jws(json!({"sub": "alice"})))]
+#[case("opaque-token".to_string())]
+#[case("a.b.c.d.e".to_string())]
+fn test_jti_fallback(#[case] token: String) {
+    // Arrange
+    let strategy = TokenIdStrategy::jti(TokenIdStrategy::hmac_sha256("secret"));
+
+    // Act
+    let id = strategy.token_id(&token);
+
+    // Assert
+    assert_eq!(id, TokenIdStrategy::hmac_sha256("secret").token_id(&token));
+}
+
+#[rstest]
+#[case(json!({"strategy": "md5"}), "md5:")]
+#[case(json!({"strategy": "hmac-sha256", "secret": "secret"}), "hmac-sha256:")]
+#[case(json!({"strategy": "jti", "fallback": {"strategy": "md5"}}), "md5:")]
+fn test_deserialize(#[case] settings: serde_json::Value, #[case] prefix: &str) {
+    // Act
+    let strategy: TokenIdStrategy = serde_json::from_value(settings).unwrap();
+
+    // Assert
+    assert!(strategy.token_id("token").starts_with(prefix));
+}
+
+#[test]
+fn test_debug_hides_secret() {
+    // Act
+    let debug = format!(
+        "{:?}",
+        TokenIdStrategy::jti(TokenIdStrategy::hmac_sha256("s3cr3t-value"))
+    );
+
+    // Assert
+    assert!(!debug.contains("s3cr3t-value"), "{}", debug);
+    assert_eq!(debug, "Jti { fallback: HmacSha256 { secret: <redacted> } }");
+}
+
+fn make_claims(claims: serde_json::Value) -> JwtPayload {
+    JwtPayload::from_map(claims.as_object().unwrap().clone()).unwrap()
+}
+
+fn make_jws(claims: serde_json::Value) -> String {
+    format!(
+        "{}.{}.c2lnbmF0dXJl",
+        URL_SAFE_NO_PAD.encode(json!({"alg": "ES256"}).to_string()),
+        URL_SAFE_NO_PAD.encode(claims.to_string())
+    )
+}
@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod tests;
+pub mod webhook_audit_settings;
+pub mod webhook_audit_sink;
+
+use crate::services::audit::AuditService;
+use crate::services::audit::audit_dispatcher::AuditDispatcher;
+use crate::services::audit::audit_record::AuditRecord;
+use crate::services::audit::webhook_audit_service::webhook_audit_settings::WebhookAuditSettings;
+use crate::services::audit::webhook_audit_service::webhook_audit_sink::WebhookAuditSink;
+use crate::services::observability::open_telemetry::metrics::metric_recorders::audit_events_dropped::AuditEventsDroppedMetric;
+use crate::services::observability::open_telemetry::metrics::metric_recorders::audit_queue_depth::AuditQueueDepthMetric;
+use anyhow::Result;
+use std::sync::Arc;
+
+/// [`WebhookAuditService`] forwards the audit records to an external collector over HTTP.
+///
+/// The records are queued by an [`AuditDispatcher`] and posted in batches by the
+/// [`WebhookAuditSink`], so the requests are not delayed by the collector. The batches that cannot
+/// be delivered are written to the dead-letter file.
+pub struct WebhookAuditService {
+    dispatcher: AuditDispatcher,
+}
+
+impl WebhookAuditService {
+    /// Starts the delivery task and returns the service.
+    pub fn start(
+        settings: WebhookAuditSettings,
+        queue_depth: Arc<dyn AuditQueueDepthMetric>,
+        events_dropped: Arc<dyn AuditEventsDroppedMetric>,
+    ) -> Result<Self> {
+        let dispatcher_settings = settings.dispatcher.clone();
+        let sink = Arc::new(WebhookAuditSink::new(settings)?);
+        let dispatcher = AuditDispatcher::start(sink, dispatcher_settings, queue_depth, events_dropped)?;
+        Ok(WebhookAuditService { dispatcher })
+    }
+
+    /// Stops accepting records and waits until the queued records are delivered or dead-lettered.
+    pub fn shutdown(&self) {
+        self.dispatcher.shutdown();
+    }
+}
+
+impl AuditService for WebhookAuditService {
+    fn record(&self, record: AuditRecord) -> Result<()> {
+        self.dispatcher.record(record)
+    }
+}
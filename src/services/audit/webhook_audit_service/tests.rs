@@ -0,0 +1,266 @@
+use crate::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
+use crate::services::audit::AuditService;
+use crate::services::audit::audit_dispatcher::audit_dispatcher_settings::AuditDispatcherSettings;
+use crate::services::audit::audit_format::AuditFormat;
+use crate::services::audit::audit_format::audit_formatter::AuditFormatter;
+use crate::services::audit::audit_record::{AuditRecord, AuditRecordEvent};
+use crate::services::audit::audit_sink::AuditSink;
+use crate::services::audit::chained::audit_event::AuditEvent;
+use crate::services::audit::chained::chained_audit_event::ChainedAuditEvent;
+use crate::services::audit::webhook_audit_service::WebhookAuditService;
+use crate::services::audit::webhook_audit_service::webhook_audit_settings::WebhookAuditSettings;
+use crate::services::audit::webhook_audit_service::webhook_audit_sink::{
+    WEBHOOK_BATCH_ID_HEADER, WEBHOOK_SIGNATURE_HEADER, WEBHOOK_TIMESTAMP_HEADER, WebhookAuditSink, sign,
+};
+use crate::testing::audit_events::AuthorizationAuditEventBuilder;
+use crate::testing::audit_metrics::{MockAuditEventsDroppedMetric, queue_depth_metric};
+use crate::testing::stub_webhook_receiver::StubWebhookReceiver;
+use actix_web::http::StatusCode;
+use anyhow::anyhow;
+use pretty_assertions::{assert_eq, assert_ne};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[actix_web::test]
+async fn test_post_signed_batch() {
+    // Arrange
+    let receiver = StubWebhookReceiver::start(vec![]).unwrap();
+    let settings = make_settings(&receiver);
+    let sink = WebhookAuditSink::new(settings.clone()).unwrap();
+
+    // Act
+    let result = sink.write_batch(vec![make_record("alice"), make_record("bob")]).await;
+
+    // Assert
+    assert!(result.is_ok(), "{:?}", result.err());
+    let received = receiver.received();
+    assert_eq!(received.len(), 1);
+    let actors: Vec<Value> = received[0]
+        .json()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|record| record["event"]["actor"].clone())
+        .collect();
+    assert_eq!(actors, vec![Value::from("alice"), Value::from("bob")]);
+    let timestamp = received[0]
+        .header(WEBHOOK_TIMESTAMP_HEADER)
+        .expect("timestamp should be sent");
+    let batch_id = received[0]
+        .header(WEBHOOK_BATCH_ID_HEADER)
+        .expect("batch id should be sent");
+    assert_eq!(
+        received[0].header(WEBHOOK_SIGNATURE_HEADER),
+        Some(sign("secret", timestamp, batch_id, &received[0].body).as_str())
+    );
+    assert_ne!(
+        received[0].header(WEBHOOK_SIGNATURE_HEADER),
+        Some(sign("secret", "0", batch_id, &received[0].body).as_str())
+    );
+    assert!(!settings.dead_letter_file.exists());
+    receiver.stop().await;
+}
+
+#[actix_web::test]
+async fn test_post_batch_in_format() {
+    // Arrange
+    let receiver = StubWebhookReceiver::start(vec![]).unwrap();
+    let settings = WebhookAuditSettings {
+        format: AuditFormat::Cef,
+        ..make_settings(&receiver)
+    };
+    let sink = WebhookAuditSink::new(settings.clone()).unwrap();
+
+    // Act
+    let result = sink.write_batch(vec![make_record("alice"), make_record("bob")]).await;
+
+    // Assert
+    assert!(result.is_ok(), "{:?}", result.err());
+    let received = receiver.received();
+    assert_eq!(received[0].header("content-type"), Some("text/plain"));
+    let body = String::from_utf8(received[0].body.clone()).unwrap();
+    let lines: Vec<&str> = body.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines.iter().all(|line| line.starts_with("CEF:0|")), "{}", body);
+    assert!(lines[1].contains("suser=bob"), "{}", body);
+    receiver.stop().await;
+}
+
+#[actix_web::test]
+async fn test_retry_until_delivered() {
+    // Arrange
+    let receiver =
+        StubWebhookReceiver::start(vec![StatusCode::SERVICE_UNAVAILABLE, StatusCode::TOO_MANY_REQUESTS]).unwrap();
+    let settings = make_settings(&receiver);
+    let sink = WebhookAuditSink::new(settings.clone()).unwrap();
+
+    // Act
+    let result = sink.write_batch(vec![make_record("alice")]).await;
+
+    // Assert
+    assert!(result.is_ok(), "{:?}", result.err());
+    let batch_ids: Vec<Option<String>> = receiver
+        .received()
+        .iter()
+        .map(|request| request.header(WEBHOOK_BATCH_ID_HEADER).map(String::from))
+        .collect();
+    assert_eq!(batch_ids.len(), 3);
+    assert!(batch_ids.iter().all(|id| id.is_some() && *id == batch_ids[0]));
+    assert!(!settings.dead_letter_file.exists());
+    receiver.stop().await;
+}
+
+#[actix_web::test]
+async fn test_dead_letter_after_retries_are_exhausted() {
+    // Arrange
+    let receiver = StubWebhookReceiver::start(vec![StatusCode::BAD_GATEWAY; 3]).unwrap();
+    let settings = WebhookAuditSettings {
+        max_retries: 1,
+        ..make_settings(&receiver)
+    };
+    let sink = WebhookAuditSink::new(settings.clone()).unwrap();
+
+    // Act
+    let result = sink.write_batch(vec![make_record("alice"), make_record("bob")]).await;
+
+    // Assert
+    assert!(result.is_ok(), "{:?}", result.err());
+    assert_eq!(receiver.received().len(), 2);
+    let dead_letters = read_dead_letters(&settings.dead_letter_file);
+    assert_eq!(dead_letters.len(), 2);
+    assert_eq!(dead_letters[1]["record"]["event"]["actor"], Value::from("bob"));
+    assert!(dead_letters[0]["error"].as_str().unwrap().contains("502"));
+    receiver.stop().await;
+    std::fs::remove_file(&settings.dead_letter_file).unwrap();
+}
+
+#[actix_web::test]
+async fn test_rejected_batch_is_not_retried() {
+    // Arrange
+    let receiver = StubWebhookReceiver::start(vec![StatusCode::BAD_REQUEST]).unwrap();
+    let settings = make_settings(&receiver);
+    let sink = WebhookAuditSink::new(settings.clone()).unwrap();
+
+    // Act
+    let result = sink.write_batch(vec![make_record("alice")]).await;
+
+    // Assert
+    assert!(result.is_ok(), "{:?}", result.err());
+    assert_eq!(receiver.received().len(), 1);
+    assert_eq!(read_dead_letters(&settings.dead_letter_file).len(), 1);
+    receiver.stop().await;
+    std::fs::remove_file(&settings.dead_letter_file).unwrap();
+}
+
+#[actix_web::test]
+async fn test_dead_letter_unformatted_records() {
+    // Arrange
+    let receiver = StubWebhookReceiver::start(vec![]).unwrap();
+    let settings = make_settings(&receiver);
+    let sink = WebhookAuditSink::new(settings.clone())
+        .unwrap()
+        .with_formatter(Arc::new(RejectingFormatter("mallory")));
+
+    // Act
+    let result = sink
+        .write_batch(vec![make_record("alice"), make_record("mallory"), make_record("bob")])
+        .await;
+
+    // Assert
+    assert!(result.is_ok(), "{:?}", result.err());
+    let actors: Vec<Value> = receiver.received()[0]
+        .json()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|record| record["event"]["actor"].clone())
+        .collect();
+    assert_eq!(actors, vec![Value::from("alice"), Value::from("bob")]);
+    let dead_letters = read_dead_letters(&settings.dead_letter_file);
+    assert_eq!(dead_letters.len(), 1);
+    assert_eq!(dead_letters[0]["record"]["event"]["actor"], Value::from("mallory"));
+    assert!(dead_letters[0]["error"].as_str().unwrap().contains("mallory"));
+    receiver.stop().await;
+    std::fs::remove_file(&settings.dead_letter_file).unwrap();
+}
+
+#[actix_web::test]
+async fn test_service_delivers_queued_events() {
+    // Arrange
+    let receiver = StubWebhookReceiver::start(vec![]).unwrap();
+    let service = Arc::new(
+        WebhookAuditService::start(
+            make_settings(&receiver),
+            Arc::new(queue_depth_metric()),
+            Arc::new(MockAuditEventsDroppedMetric::new()),
+        )
+        .unwrap(),
+    );
+
+    // Act
+    service.write(AuditEvent::Final(ChainedAuditEvent {
+        actor: Some("alice".to_string()),
+        ..ChainedAuditEvent::begin()
+    }));
+    service
+        .record(AuthorizationAuditEventBuilder::default().with_actor("bob").record())
+        .expect("event should be queued");
+    let shutdown = Arc::clone(&service);
+    tokio::task::spawn_blocking(move || shutdown.shutdown()).await.unwrap();
+
+    // Assert
+    let actors: Vec<Value> = receiver
+        .received()
+        .iter()
+        .flat_map(|request| request.json().as_array().cloned().unwrap_or_default())
+        .map(|record| record["event"]["actor"].clone())
+        .collect();
+    assert_eq!(actors, vec![Value::from("alice"), Value::from("bob")]);
+    receiver.stop().await;
+}
+
+fn make_settings(receiver: &StubWebhookReceiver) -> WebhookAuditSettings {
+    WebhookAuditSettings {
+        url: receiver.url().to_string(),
+        secret: Some("secret".to_string()),
+        timeout: Duration::from_secs(5).into(),
+        max_retries: 3,
+        initial_backoff: Duration::from_millis(1).into(),
+        max_backoff: Duration::from_millis(5).into(),
+        format: AuditFormat::Native,
+        dead_letter_file: make_dead_letter_file(),
+        dispatcher: AuditDispatcherSettings::default(),
+    }
+}
+
+fn make_dead_letter_file() -> PathBuf {
+    std::env::temp_dir().join(format!("audit-dead-letters-{}.jsonl", uuid::Uuid::new_v4()))
+}
+
+fn read_dead_letters(file: &Path) -> Vec<Value> {
+    std::fs::read_to_string(file)
+        .expect("dead-letter file should be readable")
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("line should be a JSON object"))
+        .collect()
+}
+
+/// Formats the records in the native format, except for the records of the actor.
+struct RejectingFormatter(&'static str);
+
+impl AuditFormatter for RejectingFormatter {
+    fn format(&self, record: &AuditRecord) -> anyhow::Result<String> {
+        match record.event() {
+            AuditRecordEvent::Authorization(event) if event.actor == self.0 => {
+                Err(anyhow!("Cannot format the record of {}", event.actor))
+            }
+            _ => Ok(serde_json::to_string(record)?),
+        }
+    }
+}
+
+fn make_record(actor: &str) -> AuditRecord {
+    AuthorizationAuditEventBuilder::default().with_actor(actor).record()
+}
@@ -0,0 +1,81 @@
+use crate::services::audit::audit_dispatcher::audit_dispatcher_settings::AuditDispatcherSettings;
+use crate::services::audit::audit_format::AuditFormat;
+use duration_string::DurationString;
+use serde::Deserialize;
+use std::fmt::{Debug, Formatter};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// The settings of the [`WebhookAuditService`].
+///
+/// [`WebhookAuditService`]: crate::services::audit::webhook_audit_service::WebhookAuditService
+#[derive(Clone, Deserialize)]
+pub struct WebhookAuditSettings {
+    /// The URL of the collector receiving the audit records.
+    pub url: String,
+
+    /// The secret of the HMAC-SHA256 signature of the request timestamp, batch id and body. The
+    /// requests are not signed if it is not set.
+    #[serde(default)]
+    pub secret: Option<String>,
+
+    /// The timeout of a single delivery attempt.
+    #[serde(default = "default_timeout")]
+    pub timeout: DurationString,
+
+    /// The number of retries after the first failed attempt.
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+
+    /// The delay before the first retry. The delay is doubled for every next retry.
+    #[serde(default = "default_initial_backoff")]
+    pub initial_backoff: DurationString,
+
+    /// The maximum delay between the retries.
+    #[serde(default = "default_max_backoff")]
+    pub max_backoff: DurationString,
+
+    /// The format of the delivered records. The dead-letter file is always written in the native
+    /// format.
+    #[serde(default)]
+    pub format: AuditFormat,
+
+    /// The file receiving the records that could not be delivered, as JSON lines.
+    pub dead_letter_file: PathBuf,
+
+    /// The settings of the queue in front of the collector.
+    #[serde(default)]
+    pub dispatcher: AuditDispatcherSettings,
+}
+
+impl Debug for WebhookAuditSettings {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookAuditSettings")
+            .field("url", &self.url)
+            .field("secret", &self.secret.as_ref().map(|_| "<redacted>"))
+            .field("timeout", &self.timeout)
+            .field("max_retries", &self.max_retries)
+            .field("initial_backoff", &self.initial_backoff)
+            .field("max_backoff", &self.max_backoff)
+            .field("format", &self.format)
+            .field("dead_letter_file", &self.dead_letter_file)
+            .field("dispatcher", &self.dispatcher)
+            .finish()
+    }
+}
+
+fn default_timeout() -> DurationString {
+    Duration::from_secs(10).into()
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+fn default_initial_backoff() -> DurationString {
+    Duration::from_millis(200).into()
+}
+
+fn default_max_backoff() -> DurationString {
+    Duration::from_secs(10).into()
+}
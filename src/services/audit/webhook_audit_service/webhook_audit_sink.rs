@@ -0,0 +1,185 @@
+use crate::services::audit::audit_format::audit_formatter::AuditFormatter;
+use crate::services::audit::audit_record::AuditRecord;
+use crate::services::audit::audit_sink::AuditSink;
+use crate::services::audit::webhook_audit_service::webhook_audit_settings::WebhookAuditSettings;
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use log::warn;
+use serde_json::json;
+use sha2::Sha256;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The header carrying the HMAC-SHA256 signature of the timestamp, the batch id and the request
+/// body, joined with dots: `sha256=<hex>`.
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "X-Audit-Signature";
+
+/// The header carrying the time of the delivery attempt in seconds since the Unix epoch. The
+/// timestamp is signed, so the collector can reject the requests replayed outside of its tolerance.
+pub const WEBHOOK_TIMESTAMP_HEADER: &str = "X-Audit-Timestamp";
+
+/// The header carrying the id of the batch. The id is the same for all attempts to deliver a
+/// batch, so the collector can discard the duplicates.
+pub const WEBHOOK_BATCH_ID_HEADER: &str = "X-Audit-Batch-Id";
+
+/// [`WebhookAuditSink`] posts every batch of audit records to the collector, as a JSON array in
+/// the native format or as a batch of the configured [`AuditFormat`].
+///
+/// Failed attempts are retried with an exponential backoff if the collector is unreachable or
+/// responds with `429` or a server error. A batch that exhausts the retries or is rejected by the
+/// collector is appended to the dead-letter file, as well as every record that cannot be formatted.
+///
+/// [`AuditFormat`]: crate::services::audit::audit_format::AuditFormat
+pub struct WebhookAuditSink {
+    settings: WebhookAuditSettings,
+    formatter: Arc<dyn AuditFormatter>,
+    client: reqwest::Client,
+    dead_letters: Mutex<()>,
+}
+
+enum Attempt {
+    Delivered,
+    Retry(anyhow::Error),
+    Reject(anyhow::Error),
+}
+
+impl WebhookAuditSink {
+    pub fn new(settings: WebhookAuditSettings) -> Result<Self> {
+        let client = reqwest::Client::builder().timeout(settings.timeout.into()).build()?;
+        Ok(WebhookAuditSink {
+            formatter: settings.format.formatter(),
+            settings,
+            client,
+            dead_letters: Mutex::new(()),
+        })
+    }
+
+    /// Replaces the formatter selected by the format setting, e.g. with a custom formatter.
+    pub fn with_formatter(mut self, formatter: Arc<dyn AuditFormatter>) -> Self {
+        self.formatter = formatter;
+        self
+    }
+
+    /// Posts the body until it is delivered, rejected or the retries are exhausted.
+    async fn deliver(&self, body: Vec<u8>) -> Result<()> {
+        let batch_id = uuid::Uuid::new_v4().to_string();
+        let mut backoff: Duration = self.settings.initial_backoff.into();
+        let mut retries = 0;
+        loop {
+            match self.attempt(&body, &batch_id).await {
+                Attempt::Delivered => return Ok(()),
+                Attempt::Reject(error) => return Err(error),
+                Attempt::Retry(error) if retries >= self.settings.max_retries => return Err(error),
+                Attempt::Retry(error) => {
+                    warn!("Failed to deliver the audit batch {}, retrying: {:?}", batch_id, error);
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(self.settings.max_backoff.into());
+                    retries += 1;
+                }
+            }
+        }
+    }
+
+    async fn attempt(&self, body: &[u8], batch_id: &str) -> Attempt {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default()
+            .to_string();
+        let mut request = self
+            .client
+            .post(&self.settings.url)
+            .header(reqwest::header::CONTENT_TYPE, self.formatter.content_type())
+            .header(WEBHOOK_BATCH_ID_HEADER, batch_id)
+            .header(WEBHOOK_TIMESTAMP_HEADER, &timestamp)
+            .body(body.to_vec());
+        if let Some(secret) = &self.settings.secret {
+            request = request.header(WEBHOOK_SIGNATURE_HEADER, sign(secret, &timestamp, batch_id, body));
+        }
+
+        match request.send().await {
+            Ok(response) if response.status().is_success() => Attempt::Delivered,
+            Ok(response) if response.status().is_server_error() || response.status().as_u16() == 429 => {
+                Attempt::Retry(anyhow!("Collector responded with {}", response.status()))
+            }
+            Ok(response) => Attempt::Reject(anyhow!("Collector rejected the batch with {}", response.status())),
+            Err(error) => Attempt::Retry(error.into()),
+        }
+    }
+
+    /// Appends the records to the dead-letter file, one JSON object per line with the error of the
+    /// record.
+    fn dead_letter<'a>(&self, letters: impl IntoIterator<Item = (&'a AuditRecord, String)>) -> Result<()> {
+        let _guard = self.dead_letters.lock().expect("Dead-letter file lock is poisoned");
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.settings.dead_letter_file)?;
+        for (record, error) in letters {
+            let mut line = serde_json::to_vec(&json!({
+                "error": error,
+                "record": record,
+            }))?;
+            line.push(b'\n');
+            file.write_all(&line)?;
+        }
+        Ok(file.sync_data()?)
+    }
+}
+
+#[async_trait]
+impl AuditSink for WebhookAuditSink {
+    /// Delivers the batch to the collector. The records that cannot be formatted and the batch that
+    /// cannot be delivered are dead-lettered, and an error is returned only if the dead-letter file
+    /// cannot be written.
+    async fn write_batch(&self, records: Vec<AuditRecord>) -> Result<()> {
+        let mut lines = Vec::with_capacity(records.len());
+        let mut formatted = Vec::with_capacity(records.len());
+        let mut unformatted = Vec::new();
+        for record in &records {
+            match self.formatter.format(record) {
+                Ok(line) => {
+                    lines.push(line);
+                    formatted.push(record);
+                }
+                Err(error) => unformatted.push((record, format!("Failed to format the record: {}", error))),
+            }
+        }
+        if !unformatted.is_empty() {
+            warn!(
+                "Failed to format {} audit records, writing them to the dead-letter file",
+                unformatted.len()
+            );
+            self.dead_letter(unformatted)?;
+        }
+        if lines.is_empty() {
+            return Ok(());
+        }
+
+        let body = self.formatter.batch(lines).into_bytes();
+        if let Err(error) = self.deliver(body).await {
+            warn!(
+                "Failed to deliver {} audit records, writing them to the dead-letter file: {:?}",
+                formatted.len(),
+                error
+            );
+            self.dead_letter(formatted.into_iter().map(|record| (record, error.to_string())))?;
+        }
+        Ok(())
+    }
+}
+
+/// Computes the value of the [`WEBHOOK_SIGNATURE_HEADER`] for the timestamp, the batch id and the
+/// body of the request.
+pub fn sign(secret: &str, timestamp: &str, batch_id: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(batch_id.as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("sha256={:x}", mac.finalize().into_bytes())
+}
@@ -0,0 +1,11 @@
+pub mod external_token_validation_error;
+pub mod external_token_validator;
+pub mod identity_provider_registry;
+pub mod internal_token_decoder;
+pub mod jwks_cache;
+pub mod mapped_principal;
+pub mod oidc_token_validator;
+pub mod principal_mapping;
+pub mod principal_mapping_error;
+pub mod settings;
+pub mod validated_external_token;
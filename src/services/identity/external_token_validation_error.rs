@@ -0,0 +1,84 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// The error type returned by [`ExternalTokenValidator`] implementations.
+///
+/// Each variant maps to a stable reason code that is written to audit events.
+///
+/// [`ExternalTokenValidator`]: crate::services::identity::external_token_validator::ExternalTokenValidator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExternalTokenValidationError {
+    /// The token is not a well-formed JWS compact serialization or lacks a required claim.
+    Malformed(String),
+
+    /// The token issuer is not one of the configured identity providers.
+    UnknownIssuer(String),
+
+    /// The issuer JWKS does not contain the key referenced by the token `kid` header.
+    UnknownKey(String),
+
+    /// The token signature does not match the key published by the issuer.
+    InvalidSignature(String),
+
+    /// The token `exp` claim is in the past.
+    Expired,
+
+    /// The token `nbf` claim is in the future.
+    NotYetValid,
+
+    /// The token `aud` claim does not contain any of the audiences accepted for the issuer.
+    InvalidAudience,
+
+    /// The issuer JWKS could not be fetched.
+    JwksUnavailable(String),
+
+    /// The token is bound to a key with the `cnf.jkt` claim, but the request carries no `DPoP`
+    /// proof of possession.
+    ProofRequired,
+
+    /// The `DPoP` proof is not signed with the key the token is bound to.
+    KeyBindingMismatch,
+}
+
+impl ExternalTokenValidationError {
+    /// Returns the reason code recorded in audit events for this error.
+    pub fn reason_code(&self) -> &'static str {
+        match self {
+            ExternalTokenValidationError::Malformed(_) => "malformed",
+            ExternalTokenValidationError::UnknownIssuer(_) => "unknown-issuer",
+            ExternalTokenValidationError::UnknownKey(_) => "unknown-key",
+            ExternalTokenValidationError::InvalidSignature(_) => "invalid-signature",
+            ExternalTokenValidationError::Expired => "expired",
+            ExternalTokenValidationError::NotYetValid => "not-yet-valid",
+            ExternalTokenValidationError::InvalidAudience => "invalid-audience",
+            ExternalTokenValidationError::JwksUnavailable(_) => "jwks-unavailable",
+            ExternalTokenValidationError::ProofRequired => "dpop-proof-required",
+            ExternalTokenValidationError::KeyBindingMismatch => "dpop-key-mismatch",
+        }
+    }
+}
+
+impl Display for ExternalTokenValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExternalTokenValidationError::Malformed(details) => write!(f, "Malformed token: {}", details),
+            ExternalTokenValidationError::UnknownIssuer(issuer) => write!(f, "Unknown token issuer: {}", issuer),
+            ExternalTokenValidationError::UnknownKey(kid) => write!(f, "Unknown signing key: {}", kid),
+            ExternalTokenValidationError::InvalidSignature(details) => {
+                write!(f, "Invalid token signature: {}", details)
+            }
+            ExternalTokenValidationError::Expired => write!(f, "Token has expired"),
+            ExternalTokenValidationError::NotYetValid => write!(f, "Token is not yet valid"),
+            ExternalTokenValidationError::InvalidAudience => write!(f, "Token audience is not accepted"),
+            ExternalTokenValidationError::JwksUnavailable(details) => {
+                write!(f, "Issuer JWKS is unavailable: {}", details)
+            }
+            ExternalTokenValidationError::ProofRequired => write!(f, "Token requires a DPoP proof"),
+            ExternalTokenValidationError::KeyBindingMismatch => {
+                write!(f, "DPoP proof key does not match the token confirmation")
+            }
+        }
+    }
+}
+
+impl Error for ExternalTokenValidationError {}
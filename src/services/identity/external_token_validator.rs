@@ -0,0 +1,11 @@
+use crate::models::external_token::ExternalToken;
+use crate::services::identity::external_token_validation_error::ExternalTokenValidationError;
+use crate::services::identity::validated_external_token::ValidatedExternalToken;
+use async_trait::async_trait;
+
+/// Validates external tokens issued by the trusted identity providers.
+#[async_trait]
+pub trait ExternalTokenValidator: Send + Sync {
+    /// Verifies the token signature, issuer, audience and lifetime and returns the verified claims.
+    async fn validate(&self, token: &ExternalToken) -> Result<ValidatedExternalToken, ExternalTokenValidationError>;
+}
@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod tests;
+
+use crate::services::identity::mapped_principal::MappedPrincipal;
+use crate::services::identity::principal_mapping::PrincipalMapping;
+use crate::services::identity::principal_mapping_error::PrincipalMappingError;
+use crate::services::identity::settings::ExternalTokenValidationSettings;
+use crate::services::identity::validated_external_token::ValidatedExternalToken;
+use cedar_policy::{Entity, Schema};
+use std::collections::HashMap;
+
+/// A registered identity provider and the mapping of its claims to the principal.
+#[derive(Debug, Clone)]
+pub struct RegisteredIdentityProvider {
+    pub name: String,
+    pub issuer: String,
+    pub principal: PrincipalMapping,
+}
+
+/// [`IdentityProviderRegistry`] maps validated external tokens to principal entities using the
+/// mapping registered for the token issuer.
+#[derive(Debug, Clone, Default)]
+pub struct IdentityProviderRegistry {
+    providers: HashMap<String, RegisteredIdentityProvider>,
+}
+
+impl IdentityProviderRegistry {
+    pub fn new() -> Self {
+        IdentityProviderRegistry::default()
+    }
+
+    /// Registers the provider. A provider previously registered for the same issuer is replaced.
+    pub fn with_provider(mut self, provider: RegisteredIdentityProvider) -> Self {
+        self.providers.insert(provider.issuer.clone(), provider);
+        self
+    }
+
+    /// Returns the provider registered for the issuer.
+    pub fn find(&self, issuer: &str) -> Option<&RegisteredIdentityProvider> {
+        self.providers.get(issuer)
+    }
+
+    /// Builds the principal of the validated token and validates it against the schema.
+    pub fn map_principal(
+        &self,
+        token: &ValidatedExternalToken,
+        schema: &Schema,
+    ) -> Result<MappedPrincipal, PrincipalMappingError> {
+        let provider = self
+            .find(&token.issuer)
+            .ok_or(PrincipalMappingError::UnknownIssuer(token.issuer.clone()))?;
+
+        let user_id = provider.principal.principal_id(&token.claims)?;
+        let entity_json = provider.principal.to_entity_json(&token.claims)?;
+        let principal = Entity::from_json_value(entity_json, Some(schema))
+            .map_err(|e| PrincipalMappingError::InvalidEntity(e.to_string()))?;
+
+        Ok(MappedPrincipal {
+            identity_provider: provider.name.clone(),
+            user_id,
+            principal,
+        })
+    }
+}
+
+impl From<&ExternalTokenValidationSettings> for IdentityProviderRegistry {
+    fn from(settings: &ExternalTokenValidationSettings) -> Self {
+        settings
+            .identity_providers
+            .iter()
+            .fold(IdentityProviderRegistry::new(), |registry, provider| {
+                registry.with_provider(RegisteredIdentityProvider {
+                    name: provider.name.clone(),
+                    issuer: provider.issuer.clone(),
+                    principal: provider.principal.clone(),
+                })
+            })
+    }
+}
@@ -0,0 +1,131 @@
+use crate::services::identity::identity_provider_registry::{IdentityProviderRegistry, RegisteredIdentityProvider};
+use crate::services::identity::principal_mapping::{ParentMapping, PrincipalMapping};
+use crate::services::identity::principal_mapping_error::PrincipalMappingError;
+use crate::services::identity::validated_external_token::ValidatedExternalToken;
+use assert_matches::assert_matches;
+use cedar_policy::{EntityUid, Schema};
+use josekit::jwt::JwtPayload;
+use maplit::hashmap;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use serde_json::{Value, json};
+use std::collections::HashSet;
+
+const ISSUER: &str = "https://idp.example.com";
+
+#[test]
+fn test_map_principal() {
+    // Arrange
+    let registry = make_registry();
+    let token = make_token(json!({
+        "iss": ISSUER,
+        "preferred_username": "alice",
+        "email": "alice@example.com",
+        "realm_access": {"roles": ["admins", "editors"]}
+    }));
+
+    // Act
+    let result = registry.map_principal(&token, &make_schema());
+
+    // Assert
+    assert_matches!(result, Ok(mapped) => {
+        assert_eq!(mapped.identity_provider, "keycloak");
+        assert_eq!(mapped.user_id, "alice");
+        assert_eq!(mapped.principal.uid().to_string(), r#"PhotoApp::User::"alice""#);
+        let parents: HashSet<EntityUid> = mapped.principal.into_inner().2;
+        assert_eq!(parents, HashSet::from([
+            r#"PhotoApp::Group::"admins""#.parse().unwrap(),
+            r#"PhotoApp::Group::"editors""#.parse().unwrap(),
+        ]));
+    });
+}
+
+#[rstest]
+#[case::unknown_issuer(json!({"iss": "https://other.example.com", "preferred_username": "alice"}), "No identity provider")]
+#[case::missing_id(json!({"iss": ISSUER, "email": "alice@example.com"}), "Missing claim: preferred_username")]
+#[case::invalid_id(json!({"iss": ISSUER, "preferred_username": 42}), "Invalid claim preferred_username")]
+#[case::invalid_parents(
+    json!({"iss": ISSUER, "preferred_username": "alice", "email": "alice@example.com", "realm_access": {"roles": [1]}}),
+    "Invalid claim /realm_access/roles"
+)]
+#[case::missing_required_attribute(json!({"iss": ISSUER, "preferred_username": "alice"}), "Invalid principal")]
+#[case::invalid_attribute_type(json!({"iss": ISSUER, "preferred_username": "alice", "email": true}), "Invalid principal")]
+fn test_map_principal_errors(#[case] claims: Value, #[case] expected: &str) {
+    // Arrange
+    let registry = make_registry();
+    let token = make_token(claims);
+
+    // Act
+    let result = registry.map_principal(&token, &make_schema());
+
+    // Assert
+    assert_matches!(result, Err(error) => {
+        assert!(error.to_string().starts_with(expected), "{}", error);
+    });
+}
+
+#[test]
+fn test_entity_type_not_in_schema() {
+    // Arrange
+    let mut provider = make_provider();
+    provider.principal.entity_type = "PhotoApp::Robot".to_string();
+    let registry = IdentityProviderRegistry::new().with_provider(provider);
+    let token = make_token(json!({"iss": ISSUER, "preferred_username": "alice", "email": "a@b.c"}));
+
+    // Act
+    let result = registry.map_principal(&token, &make_schema());
+
+    // Assert
+    assert_matches!(result, Err(PrincipalMappingError::InvalidEntity(_)));
+}
+
+fn make_registry() -> IdentityProviderRegistry {
+    IdentityProviderRegistry::new().with_provider(make_provider())
+}
+
+fn make_provider() -> RegisteredIdentityProvider {
+    RegisteredIdentityProvider {
+        name: "keycloak".to_string(),
+        issuer: ISSUER.to_string(),
+        principal: PrincipalMapping {
+            entity_type: "PhotoApp::User".to_string(),
+            id_claim: "preferred_username".to_string(),
+            attributes: hashmap! {
+                "email".to_string() => "email".to_string(),
+            },
+            parents: vec![ParentMapping {
+                entity_type: "PhotoApp::Group".to_string(),
+                claim: "/realm_access/roles".to_string(),
+            }],
+        },
+    }
+}
+
+fn make_token(claims: Value) -> ValidatedExternalToken {
+    let claims = JwtPayload::from_map(claims.as_object().unwrap().clone()).unwrap();
+    ValidatedExternalToken {
+        issuer: claims.issuer().unwrap().to_string(),
+        claims,
+    }
+}
+
+fn make_schema() -> Schema {
+    Schema::from_json_value(json!({
+        "PhotoApp": {
+            "entityTypes": {
+                "User": {
+                    "memberOfTypes": ["Group"],
+                    "shape": {
+                        "type": "Record",
+                        "attributes": {
+                            "email": {"type": "String", "required": true}
+                        }
+                    }
+                },
+                "Group": {}
+            },
+            "actions": {}
+        }
+    }))
+    .unwrap()
+}
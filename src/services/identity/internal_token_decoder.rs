@@ -0,0 +1,9 @@
+use crate::contracts::dynamic_claims_collection::DynamicClaimsCollection;
+use crate::contracts::internal_token::encrypted_token::EncryptedToken;
+
+/// [`InternalTokenDecoder`] decrypts an internal token issued by Boxer and verifies its
+/// signature. It is implemented by the validator services that hold the token keys.
+pub trait InternalTokenDecoder: Send + Sync {
+    /// Returns the verified claims of the token.
+    fn decode(&self, token: &EncryptedToken) -> anyhow::Result<DynamicClaimsCollection>;
+}
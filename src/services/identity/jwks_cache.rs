@@ -0,0 +1,110 @@
+use crate::services::identity::external_token_validation_error::ExternalTokenValidationError;
+use josekit::jwk::{Jwk, JwkSet};
+use log::info;
+use serde_json::{Map, Value};
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, RwLock};
+
+/// [`JwksCache`] keeps the JWKS of an identity provider in memory.
+///
+/// The key set is fetched on first use and refreshed once it is older than the refresh interval.
+/// A token signed with an unknown `kid` triggers an early refresh, so keys rotated by the
+/// identity provider are picked up without waiting for the next scheduled refresh. The early
+/// refreshes are throttled by the minimum refresh interval and concurrent refreshes are merged
+/// into a single fetch, so tokens with made-up `kid`s cannot flood the identity provider.
+pub struct JwksCache {
+    jwks_uri: String,
+    refresh_interval: Duration,
+    min_refresh_interval: Duration,
+    client: reqwest::Client,
+    state: RwLock<Option<CachedJwkSet>>,
+    refresh_lock: Mutex<()>,
+}
+
+struct CachedJwkSet {
+    jwk_set: JwkSet,
+    fetched_at: Instant,
+}
+
+impl JwksCache {
+    pub fn new(jwks_uri: impl Into<String>, refresh_interval: Duration, min_refresh_interval: Duration) -> Self {
+        JwksCache {
+            jwks_uri: jwks_uri.into(),
+            refresh_interval,
+            min_refresh_interval,
+            client: reqwest::Client::new(),
+            state: RwLock::new(None),
+            refresh_lock: Mutex::new(()),
+        }
+    }
+
+    /// Returns the key with the given `kid`, fetching the JWKS if the cache is stale or does not
+    /// contain the key. An unknown key is reported without fetching the JWKS if it was fetched less
+    /// than the minimum refresh interval ago.
+    pub async fn find(&self, kid: &str) -> Result<Jwk, ExternalTokenValidationError> {
+        if let Some(jwk) = self.find_cached(kid, self.refresh_interval).await? {
+            return Ok(jwk);
+        }
+
+        {
+            // Only one caller fetches the JWKS, the others wait and use the key set it fetched.
+            let _refresh = self.refresh_lock.lock().await;
+            if self.find_cached(kid, self.min_refresh_interval).await?.is_none() {
+                self.refresh().await?;
+            }
+        }
+
+        let state = self.state.read().await;
+        state
+            .as_ref()
+            .and_then(|cached| cached.find(kid))
+            .ok_or(ExternalTokenValidationError::UnknownKey(kid.to_string()))
+    }
+
+    /// Returns the cached key if the key set was fetched within the max age. A key missing from a
+    /// key set fetched within the minimum refresh interval is reported as unknown.
+    async fn find_cached(&self, kid: &str, max_age: Duration) -> Result<Option<Jwk>, ExternalTokenValidationError> {
+        let state = self.state.read().await;
+        let Some(cached) = state.as_ref() else {
+            return Ok(None);
+        };
+        let age = cached.fetched_at.elapsed();
+        match cached.find(kid) {
+            Some(jwk) if age < max_age => Ok(Some(jwk)),
+            None if age < self.min_refresh_interval => Err(ExternalTokenValidationError::UnknownKey(kid.to_string())),
+            _ => Ok(None),
+        }
+    }
+
+    /// Fetches the JWKS from the identity provider and replaces the cached key set.
+    pub async fn refresh(&self) -> Result<(), ExternalTokenValidationError> {
+        let jwk_set = self.fetch().await?;
+        info!("Fetched {} keys from {}", jwk_set.keys().len(), self.jwks_uri);
+        *self.state.write().await = Some(CachedJwkSet {
+            jwk_set,
+            fetched_at: Instant::now(),
+        });
+        Ok(())
+    }
+
+    async fn fetch(&self) -> Result<JwkSet, ExternalTokenValidationError> {
+        let unavailable = |e: &dyn std::fmt::Display| ExternalTokenValidationError::JwksUnavailable(e.to_string());
+        let document = self
+            .client
+            .get(&self.jwks_uri)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| unavailable(&e))?
+            .json::<Map<String, Value>>()
+            .await
+            .map_err(|e| unavailable(&e))?;
+        JwkSet::from_map(document).map_err(|e| unavailable(&e))
+    }
+}
+
+impl CachedJwkSet {
+    fn find(&self, kid: &str) -> Option<Jwk> {
+        self.jwk_set.get(kid).first().map(|jwk| (*jwk).clone())
+    }
+}
@@ -0,0 +1,16 @@
+use cedar_policy::Entity;
+
+/// The principal built from an external token by the [`IdentityProviderRegistry`].
+///
+/// [`IdentityProviderRegistry`]: crate::services::identity::identity_provider_registry::IdentityProviderRegistry
+#[derive(Debug, Clone)]
+pub struct MappedPrincipal {
+    /// The name of the identity provider that issued the external token.
+    pub identity_provider: String,
+
+    /// The principal id taken from the external token.
+    pub user_id: String,
+
+    /// The principal entity validated against the schema.
+    pub principal: Entity,
+}
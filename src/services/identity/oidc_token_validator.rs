@@ -0,0 +1,172 @@
+#[cfg(test)]
+mod tests;
+
+use crate::models::external_token::ExternalToken;
+use crate::services::identity::external_token_validation_error::ExternalTokenValidationError;
+use crate::services::identity::external_token_validator::ExternalTokenValidator;
+use crate::services::identity::jwks_cache::JwksCache;
+use crate::services::identity::settings::{ExternalTokenValidationSettings, IdentityProviderSettings};
+use crate::services::identity::validated_external_token::ValidatedExternalToken;
+use crate::services::key_management::jws_algorithms::verifier_from_jwk;
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use josekit::jwt;
+use josekit::jwt::JwtPayload;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+/// An identity provider trusted to issue external tokens.
+pub struct IdentityProvider {
+    issuer: String,
+    audiences: Vec<String>,
+    jwks: JwksCache,
+}
+
+impl IdentityProvider {
+    pub fn new(issuer: impl Into<String>, audiences: Vec<String>, jwks: JwksCache) -> Self {
+        IdentityProvider {
+            issuer: issuer.into(),
+            audiences,
+            jwks,
+        }
+    }
+
+    pub fn issuer(&self) -> &str {
+        &self.issuer
+    }
+}
+
+impl From<&IdentityProviderSettings> for IdentityProvider {
+    fn from(settings: &IdentityProviderSettings) -> Self {
+        IdentityProvider::new(
+            settings.issuer.clone(),
+            settings.audiences.clone(),
+            JwksCache::new(
+                settings.jwks_uri.clone(),
+                settings.jwks_refresh_interval.into(),
+                settings.jwks_min_refresh_interval.into(),
+            ),
+        )
+    }
+}
+
+/// [`OidcTokenValidator`] validates external tokens against the JWKS published by the
+/// identity provider that issued them. The provider is selected by the `iss` claim, the key
+/// by the `kid` header.
+pub struct OidcTokenValidator {
+    providers: HashMap<String, IdentityProvider>,
+    clock_skew: Duration,
+}
+
+impl OidcTokenValidator {
+    pub fn new(providers: Vec<IdentityProvider>, clock_skew: Duration) -> Self {
+        OidcTokenValidator {
+            providers: providers.into_iter().map(|p| (p.issuer.clone(), p)).collect(),
+            clock_skew,
+        }
+    }
+
+    fn validate_lifetime(&self, claims: &JwtPayload) -> Result<(), ExternalTokenValidationError> {
+        let now = SystemTime::now();
+        match claims.expires_at() {
+            None => {
+                return Err(ExternalTokenValidationError::Malformed(
+                    "Token does not contain the exp claim".to_string(),
+                ));
+            }
+            Some(expires_at) if expires_at + self.clock_skew <= now => {
+                return Err(ExternalTokenValidationError::Expired);
+            }
+            Some(_) => {}
+        }
+        match claims.not_before() {
+            Some(not_before) if not_before > now + self.clock_skew => Err(ExternalTokenValidationError::NotYetValid),
+            _ => Ok(()),
+        }
+    }
+
+    fn validate_audience(provider: &IdentityProvider, claims: &JwtPayload) -> Result<(), ExternalTokenValidationError> {
+        let accepted = claims
+            .audience()
+            .unwrap_or_default()
+            .iter()
+            .any(|aud| provider.audiences.iter().any(|accepted| accepted == aud));
+        if accepted {
+            Ok(())
+        } else {
+            Err(ExternalTokenValidationError::InvalidAudience)
+        }
+    }
+}
+
+impl From<&ExternalTokenValidationSettings> for OidcTokenValidator {
+    fn from(settings: &ExternalTokenValidationSettings) -> Self {
+        OidcTokenValidator::new(
+            settings.identity_providers.iter().map(IdentityProvider::from).collect(),
+            settings.clock_skew.into(),
+        )
+    }
+}
+
+#[async_trait]
+impl ExternalTokenValidator for OidcTokenValidator {
+    async fn validate(&self, token: &ExternalToken) -> Result<ValidatedExternalToken, ExternalTokenValidationError> {
+        let token = token.as_ref();
+        let (header, unverified_claims) = decode_unverified(token)?;
+
+        let issuer =
+            unverified_claims
+                .get("iss")
+                .and_then(Value::as_str)
+                .ok_or(ExternalTokenValidationError::Malformed(
+                    "Token does not contain the iss claim".to_string(),
+                ))?;
+        let provider = self
+            .providers
+            .get(issuer)
+            .ok_or(ExternalTokenValidationError::UnknownIssuer(issuer.to_string()))?;
+
+        let kid = header
+            .get("kid")
+            .and_then(Value::as_str)
+            .ok_or(ExternalTokenValidationError::Malformed(
+                "Token header does not contain a key id".to_string(),
+            ))?;
+        let jwk = provider.jwks.find(kid).await?;
+        let verifier = verifier_from_jwk(&jwk, header.get("alg").and_then(Value::as_str))
+            .map_err(|e| ExternalTokenValidationError::InvalidSignature(e.to_string()))?;
+        let (claims, _) = jwt::decode_with_verifier(token, verifier.as_ref())
+            .map_err(|e| ExternalTokenValidationError::InvalidSignature(e.to_string()))?;
+
+        self.validate_lifetime(&claims)?;
+        Self::validate_audience(provider, &claims)?;
+
+        Ok(ValidatedExternalToken {
+            issuer: provider.issuer.clone(),
+            claims,
+        })
+    }
+}
+
+pub(crate) type JsonObject = Map<String, Value>;
+
+/// Decodes the header and the claims of a JWS compact serialization without verifying the
+/// signature. The result is only used to select the identity provider and the key.
+pub(crate) fn decode_unverified(token: &str) -> Result<(JsonObject, JsonObject), ExternalTokenValidationError> {
+    let parts: Vec<&str> = token.split('.').collect();
+    if parts.len() != 3 {
+        return Err(ExternalTokenValidationError::Malformed(
+            "Token is not a JWS compact serialization".to_string(),
+        ));
+    }
+    Ok((decode_part(parts[0])?, decode_part(parts[1])?))
+}
+
+fn decode_part(part: &str) -> Result<JsonObject, ExternalTokenValidationError> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(part)
+        .map_err(|e| ExternalTokenValidationError::Malformed(e.to_string()))?;
+    serde_json::from_slice(&bytes).map_err(|e| ExternalTokenValidationError::Malformed(e.to_string()))
+}
@@ -0,0 +1,256 @@
+use crate::models::external_token::ExternalToken;
+use crate::services::identity::external_token_validation_error::ExternalTokenValidationError;
+use crate::services::identity::external_token_validator::ExternalTokenValidator;
+use crate::services::identity::jwks_cache::JwksCache;
+use crate::services::identity::oidc_token_validator::{IdentityProvider, OidcTokenValidator};
+use crate::testing::signing_keys::make_jwk;
+use crate::testing::stub_jwks_server::StubJwksServer;
+use assert_matches::assert_matches;
+use josekit::jwk::Jwk;
+use josekit::jws::{ES256, JwsHeader};
+use josekit::jwt;
+use josekit::jwt::JwtPayload;
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use std::time::{Duration, SystemTime};
+
+const ISSUER: &str = "https://idp.example.com";
+const AUDIENCE: &str = "boxer";
+
+#[actix_web::test]
+async fn test_valid_token() {
+    // Arrange
+    let key = make_key("key-1");
+    let server = StubJwksServer::start(vec![key.clone()]).unwrap();
+    let validator = make_validator(&server, Duration::from_secs(60), Duration::ZERO);
+
+    // Act
+    let result = validator.validate(&sign(&key, make_claims())).await;
+
+    // Assert
+    assert_matches!(result, Ok(validated) => {
+        assert_eq!(validated.issuer, ISSUER);
+        assert_eq!(validated.claims.subject(), Some("alice"));
+    });
+    server.stop().await;
+}
+
+#[rstest]
+#[case::expired(|c: &mut JwtPayload| c.set_expires_at(&(SystemTime::now() - Duration::from_secs(600))), "expired")]
+#[case::not_yet_valid(|c: &mut JwtPayload| c.set_not_before(&(SystemTime::now() + Duration::from_secs(600))), "not-yet-valid")]
+#[case::wrong_audience(|c: &mut JwtPayload| c.set_audience(vec!["other"]), "invalid-audience")]
+#[case::unknown_issuer(|c: &mut JwtPayload| c.set_issuer("https://other.example.com"), "unknown-issuer")]
+#[case::missing_expiry(|c: &mut JwtPayload| *c = without_claim(c, "exp"), "malformed")]
+#[actix_web::test]
+async fn test_invalid_claims(#[case] modify: fn(&mut JwtPayload), #[case] reason_code: &str) {
+    // Arrange
+    let key = make_key("key-1");
+    let server = StubJwksServer::start(vec![key.clone()]).unwrap();
+    let validator = make_validator(&server, Duration::from_secs(60), Duration::ZERO);
+    let mut claims = make_claims();
+    modify(&mut claims);
+
+    // Act
+    let result = validator.validate(&sign(&key, claims)).await;
+
+    // Assert
+    assert_matches!(result, Err(error) => assert_eq!(error.reason_code(), reason_code));
+    server.stop().await;
+}
+
+#[actix_web::test]
+async fn test_invalid_signature() {
+    // Arrange
+    let published = make_key("key-1");
+    let forged = make_key("key-1");
+    let server = StubJwksServer::start(vec![published]).unwrap();
+    let validator = make_validator(&server, Duration::from_secs(60), Duration::ZERO);
+
+    // Act
+    let result = validator.validate(&sign(&forged, make_claims())).await;
+
+    // Assert
+    assert_matches!(result, Err(ExternalTokenValidationError::InvalidSignature(_)));
+    server.stop().await;
+}
+
+#[actix_web::test]
+async fn test_unknown_key() {
+    // Arrange
+    let server = StubJwksServer::start(vec![make_key("key-1")]).unwrap();
+    let validator = make_validator(&server, Duration::from_secs(60), Duration::ZERO);
+
+    // Act
+    let result = validator.validate(&sign(&make_key("key-2"), make_claims())).await;
+
+    // Assert
+    assert_matches!(result, Err(ExternalTokenValidationError::UnknownKey(kid)) => assert_eq!(kid, "key-2"));
+    server.stop().await;
+}
+
+#[rstest]
+#[case("not-a-token")]
+#[case("a.b.c")]
+#[actix_web::test]
+async fn test_malformed_token(#[case] token: &str) {
+    // Arrange
+    let server = StubJwksServer::start(vec![make_key("key-1")]).unwrap();
+    let validator = make_validator(&server, Duration::from_secs(60), Duration::ZERO);
+
+    // Act
+    let result = validator.validate(&ExternalToken::from(token.to_string())).await;
+
+    // Assert
+    assert_matches!(result, Err(ExternalTokenValidationError::Malformed(_)));
+    assert_eq!(server.request_count(), 0);
+    server.stop().await;
+}
+
+#[actix_web::test]
+async fn test_jwks_unavailable() {
+    // Arrange
+    let key = make_key("key-1");
+    let server = StubJwksServer::start(vec![key.clone()]).unwrap();
+    let validator = make_validator(&server, Duration::from_secs(60), Duration::ZERO);
+    server.stop().await;
+
+    // Act
+    let result = validator.validate(&sign(&key, make_claims())).await;
+
+    // Assert
+    assert_matches!(result, Err(ExternalTokenValidationError::JwksUnavailable(_)));
+}
+
+#[actix_web::test]
+async fn test_jwks_is_cached() {
+    // Arrange
+    let key = make_key("key-1");
+    let server = StubJwksServer::start(vec![key.clone()]).unwrap();
+    let validator = make_validator(&server, Duration::from_secs(60), Duration::ZERO);
+
+    // Act
+    for _ in 0..3 {
+        validator.validate(&sign(&key, make_claims())).await.unwrap();
+    }
+
+    // Assert
+    assert_eq!(server.request_count(), 1);
+    server.stop().await;
+}
+
+#[actix_web::test]
+async fn test_jwks_is_refreshed_after_interval() {
+    // Arrange
+    let key = make_key("key-1");
+    let server = StubJwksServer::start(vec![key.clone()]).unwrap();
+    let validator = make_validator(&server, Duration::ZERO, Duration::ZERO);
+
+    // Act
+    for _ in 0..2 {
+        validator.validate(&sign(&key, make_claims())).await.unwrap();
+    }
+
+    // Assert
+    assert_eq!(server.request_count(), 2);
+    server.stop().await;
+}
+
+#[actix_web::test]
+async fn test_jwks_is_refreshed_on_key_rotation() {
+    // Arrange
+    let old_key = make_key("key-1");
+    let new_key = make_key("key-2");
+    let server = StubJwksServer::start(vec![old_key.clone()]).unwrap();
+    let validator = make_validator(&server, Duration::from_secs(60), Duration::ZERO);
+    validator.validate(&sign(&old_key, make_claims())).await.unwrap();
+
+    // Act
+    server.set_keys(vec![old_key, new_key.clone()]);
+    let result = validator.validate(&sign(&new_key, make_claims())).await;
+
+    // Assert
+    assert!(result.is_ok(), "{:?}", result.err());
+    assert_eq!(server.request_count(), 2);
+    server.stop().await;
+}
+
+#[actix_web::test]
+async fn test_unknown_key_refresh_is_throttled() {
+    // Arrange
+    let key = make_key("key-1");
+    let server = StubJwksServer::start(vec![key.clone()]).unwrap();
+    let validator = make_validator(&server, Duration::from_secs(60), Duration::from_secs(60));
+    validator.validate(&sign(&key, make_claims())).await.unwrap();
+
+    // Act
+    let mut results = Vec::new();
+    for kid in ["key-2", "key-3", "key-4"] {
+        results.push(validator.validate(&sign(&make_key(kid), make_claims())).await);
+    }
+
+    // Assert
+    for result in results {
+        assert_matches!(result, Err(ExternalTokenValidationError::UnknownKey(_)));
+    }
+    assert_eq!(server.request_count(), 1);
+    server.stop().await;
+}
+
+#[actix_web::test]
+async fn test_concurrent_refreshes_are_merged() {
+    // Arrange
+    let key = make_key("key-1");
+    let server = StubJwksServer::start(vec![key.clone()]).unwrap();
+    let validator = make_validator(&server, Duration::from_secs(60), Duration::from_secs(60));
+    let tokens: Vec<_> = (0..5).map(|_| sign(&key, make_claims())).collect();
+
+    // Act
+    let results = futures::future::join_all(tokens.iter().map(|token| validator.validate(token))).await;
+
+    // Assert
+    assert!(results.iter().all(|result| result.is_ok()));
+    assert_eq!(server.request_count(), 1);
+    server.stop().await;
+}
+
+fn make_validator(
+    server: &StubJwksServer,
+    refresh_interval: Duration,
+    min_refresh_interval: Duration,
+) -> OidcTokenValidator {
+    let provider = IdentityProvider::new(
+        ISSUER,
+        vec![AUDIENCE.to_string()],
+        JwksCache::new(server.jwks_uri(), refresh_interval, min_refresh_interval),
+    );
+    OidcTokenValidator::new(vec![provider], Duration::from_secs(5))
+}
+
+fn make_key(kid: &str) -> Jwk {
+    let mut jwk = make_jwk();
+    jwk.set_key_id(kid);
+    jwk
+}
+
+fn make_claims() -> JwtPayload {
+    let mut claims = JwtPayload::new();
+    claims.set_issuer(ISSUER);
+    claims.set_subject("alice");
+    claims.set_audience(vec![AUDIENCE]);
+    claims.set_expires_at(&(SystemTime::now() + Duration::from_secs(600)));
+    claims
+}
+
+fn without_claim(claims: &JwtPayload, name: &str) -> JwtPayload {
+    let mut map = claims.claims_set().clone();
+    map.remove(name);
+    JwtPayload::from_map(map).unwrap()
+}
+
+fn sign(key: &Jwk, claims: JwtPayload) -> ExternalToken {
+    let mut header = JwsHeader::new();
+    header.set_token_type("JWT");
+    header.set_key_id(key.key_id().unwrap());
+    let signer = ES256.signer_from_jwk(key).unwrap();
+    ExternalToken::from(jwt::encode_with_signer(&claims, &header, &signer).unwrap())
+}
@@ -0,0 +1,98 @@
+use crate::services::identity::principal_mapping_error::PrincipalMappingError;
+use josekit::jwt::JwtPayload;
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+
+/// Describes how the claims of an external token become the principal Cedar entity.
+///
+/// Claims are referenced by name, or by a JSON pointer (e.g. `/realm_access/roles`) for
+/// nested claims.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PrincipalMapping {
+    /// The Cedar entity type of the principal, e.g. `PhotoApp::User`.
+    pub entity_type: String,
+
+    /// The claim holding the principal id. The claim must be a string.
+    pub id_claim: String,
+
+    /// Maps the principal attribute names to the claims holding their values. Attributes with
+    /// missing claims are omitted.
+    #[serde(default)]
+    pub attributes: HashMap<String, String>,
+
+    /// Parent groups of the principal taken from the claims.
+    #[serde(default)]
+    pub parents: Vec<ParentMapping>,
+}
+
+/// Maps a string or string array claim to parent entities of the given type.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ParentMapping {
+    pub entity_type: String,
+    pub claim: String,
+}
+
+impl PrincipalMapping {
+    /// Returns the principal id taken from the claims.
+    pub fn principal_id(&self, claims: &JwtPayload) -> Result<String, PrincipalMappingError> {
+        match find_claim(claims, &self.id_claim) {
+            Some(Value::String(id)) => Ok(id.clone()),
+            Some(_) => Err(PrincipalMappingError::InvalidClaim {
+                claim: self.id_claim.clone(),
+                details: "expected a string".to_string(),
+            }),
+            None => Err(PrincipalMappingError::MissingClaim(self.id_claim.clone())),
+        }
+    }
+
+    /// Builds the principal in the Cedar entity JSON format. The attribute values are copied
+    /// from the claims as is, so their types are checked when the entity is parsed with a schema.
+    pub fn to_entity_json(&self, claims: &JwtPayload) -> Result<Value, PrincipalMappingError> {
+        let id = self.principal_id(claims)?;
+
+        let mut attrs = Map::new();
+        for (attribute, claim) in &self.attributes {
+            if let Some(value) = find_claim(claims, claim) {
+                attrs.insert(attribute.clone(), value.clone());
+            }
+        }
+
+        let mut parents = Vec::new();
+        for parent in &self.parents {
+            let ids = match find_claim(claims, &parent.claim) {
+                None => vec![],
+                Some(Value::String(id)) => vec![id.as_str()],
+                Some(Value::Array(values)) => values.iter().map(|v| v.as_str()).collect::<Option<Vec<&str>>>().ok_or(
+                    PrincipalMappingError::InvalidClaim {
+                        claim: parent.claim.clone(),
+                        details: "expected an array of strings".to_string(),
+                    },
+                )?,
+                Some(_) => {
+                    return Err(PrincipalMappingError::InvalidClaim {
+                        claim: parent.claim.clone(),
+                        details: "expected a string or an array of strings".to_string(),
+                    });
+                }
+            };
+            parents.extend(ids.into_iter().map(|id| json!({"type": parent.entity_type, "id": id})));
+        }
+
+        Ok(json!({
+            "uid": {"type": self.entity_type, "id": id},
+            "attrs": attrs,
+            "parents": parents,
+        }))
+    }
+}
+
+fn find_claim<'a>(claims: &'a JwtPayload, claim: &str) -> Option<&'a Value> {
+    match claim.strip_prefix('/') {
+        Some(path) => match path.split_once('/') {
+            Some((name, pointer)) => claims.claim(name)?.pointer(&format!("/{}", pointer)),
+            None => claims.claim(path),
+        },
+        None => claims.claim(claim),
+    }
+}
@@ -0,0 +1,35 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// The error type returned when the claims of an external token cannot be mapped to a principal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrincipalMappingError {
+    /// No identity provider is registered for the token issuer.
+    UnknownIssuer(String),
+
+    /// A claim required by the mapping is missing from the token.
+    MissingClaim(String),
+
+    /// A claim has a value that cannot be used by the mapping.
+    InvalidClaim { claim: String, details: String },
+
+    /// The mapped principal does not conform to the schema.
+    InvalidEntity(String),
+}
+
+impl Display for PrincipalMappingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrincipalMappingError::UnknownIssuer(issuer) => {
+                write!(f, "No identity provider is registered for issuer: {}", issuer)
+            }
+            PrincipalMappingError::MissingClaim(claim) => write!(f, "Missing claim: {}", claim),
+            PrincipalMappingError::InvalidClaim { claim, details } => {
+                write!(f, "Invalid claim {}: {}", claim, details)
+            }
+            PrincipalMappingError::InvalidEntity(details) => write!(f, "Invalid principal: {}", details),
+        }
+    }
+}
+
+impl Error for PrincipalMappingError {}
@@ -0,0 +1,29 @@
+use crate::services::identity::principal_mapping::PrincipalMapping;
+use duration_string::DurationString;
+use serde::Deserialize;
+use std::time::Duration;
+
+#[derive(Debug, Deserialize)]
+pub struct ExternalTokenValidationSettings {
+    pub identity_providers: Vec<IdentityProviderSettings>,
+    pub clock_skew: DurationString,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IdentityProviderSettings {
+    pub name: String,
+    pub issuer: String,
+    pub jwks_uri: String,
+    pub audiences: Vec<String>,
+    pub jwks_refresh_interval: DurationString,
+
+    /// The minimum delay between two fetches of the JWKS. Tokens with an unknown `kid` are rejected
+    /// without fetching the JWKS until the delay has passed since the last fetch.
+    #[serde(default = "default_jwks_min_refresh_interval")]
+    pub jwks_min_refresh_interval: DurationString,
+    pub principal: PrincipalMapping,
+}
+
+fn default_jwks_min_refresh_interval() -> DurationString {
+    Duration::from_secs(10).into()
+}
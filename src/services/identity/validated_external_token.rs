@@ -0,0 +1,8 @@
+use josekit::jwt::JwtPayload;
+
+/// An external token that passed signature, issuer, audience and lifetime validation.
+#[derive(Debug, Clone)]
+pub struct ValidatedExternalToken {
+    pub issuer: String,
+    pub claims: JwtPayload,
+}
@@ -0,0 +1,8 @@
+pub(crate) mod jti_cache;
+pub mod jws_algorithms;
+pub mod key_manager;
+pub mod key_set;
+pub mod key_set_document;
+pub mod key_set_loader;
+pub mod settings;
+pub mod signing_key;
@@ -0,0 +1,62 @@
+use std::collections::{BTreeSet, HashMap};
+use std::time::SystemTime;
+
+/// Remembers the `jti`s of the accepted single-use JWTs, e.g. `DPoP` proofs or audit chain
+/// headers, until they expire.
+///
+/// The entries are ordered by expiry, so expired entries are dropped from the front without
+/// scanning the whole cache. The cache holds at most `capacity` entries and refuses new ones when
+/// it is full, since evicting a live entry would allow its JWT to be replayed.
+#[derive(Debug)]
+pub(crate) struct JtiCache {
+    capacity: usize,
+    expires_at: HashMap<String, SystemTime>,
+    by_expiry: BTreeSet<(SystemTime, String)>,
+}
+
+/// The reason a `jti` was not registered.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum JtiCacheError {
+    Replayed,
+    Full,
+}
+
+impl JtiCache {
+    pub fn new(capacity: usize) -> Self {
+        JtiCache {
+            capacity,
+            expires_at: HashMap::new(),
+            by_expiry: BTreeSet::new(),
+        }
+    }
+
+    /// Registers the `jti` until it expires. Fails if the `jti` is still registered or the cache
+    /// is full of live entries.
+    pub fn register(&mut self, jti: &str, expires_at: SystemTime, now: SystemTime) -> Result<(), JtiCacheError> {
+        self.evict_expired(now);
+        if self.expires_at.contains_key(jti) {
+            return Err(JtiCacheError::Replayed);
+        }
+        if self.expires_at.len() >= self.capacity {
+            return Err(JtiCacheError::Full);
+        }
+        self.expires_at.insert(jti.to_string(), expires_at);
+        self.by_expiry.insert((expires_at, jti.to_string()));
+        Ok(())
+    }
+
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.expires_at.len()
+    }
+
+    fn evict_expired(&mut self, now: SystemTime) {
+        while let Some((expires_at, _)) = self.by_expiry.first()
+            && *expires_at < now
+        {
+            if let Some((_, jti)) = self.by_expiry.pop_first() {
+                self.expires_at.remove(&jti);
+            }
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use anyhow::{Result, bail};
+use josekit::jwk::Jwk;
+use josekit::jws::{ES256, ES384, ES512, EdDSA, JwsSigner, JwsVerifier, PS256, PS384, PS512, RS256, RS384, RS512};
+
+/// Creates a JWS signer for the algorithm declared in the `alg` parameter of the private `jwk`.
+pub fn signer_from_jwk(jwk: &Jwk) -> Result<Box<dyn JwsSigner>> {
+    let signer: Box<dyn JwsSigner> = match jwk.algorithm() {
+        Some("RS256") => Box::new(RS256.signer_from_jwk(jwk)?),
+        Some("RS384") => Box::new(RS384.signer_from_jwk(jwk)?),
+        Some("RS512") => Box::new(RS512.signer_from_jwk(jwk)?),
+        Some("PS256") => Box::new(PS256.signer_from_jwk(jwk)?),
+        Some("PS384") => Box::new(PS384.signer_from_jwk(jwk)?),
+        Some("PS512") => Box::new(PS512.signer_from_jwk(jwk)?),
+        Some("ES256") => Box::new(ES256.signer_from_jwk(jwk)?),
+        Some("ES384") => Box::new(ES384.signer_from_jwk(jwk)?),
+        Some("ES512") => Box::new(ES512.signer_from_jwk(jwk)?),
+        Some("EdDSA") => Box::new(EdDSA.signer_from_jwk(jwk)?),
+        Some(other) => bail!("Unsupported signing algorithm: {}", other),
+        None => bail!("Key does not declare the signing algorithm"),
+    };
+    Ok(signer)
+}
+
+/// Creates a JWS verifier for the algorithm declared in the `alg` parameter of the `jwk`.
+/// When the key does not declare the algorithm, the `fallback_algorithm` (usually taken from
+/// the JWS header) is used instead.
+pub fn verifier_from_jwk(jwk: &Jwk, fallback_algorithm: Option<&str>) -> Result<Box<dyn JwsVerifier>> {
+    let verifier: Box<dyn JwsVerifier> = match jwk.algorithm().or(fallback_algorithm) {
+        Some("RS256") => Box::new(RS256.verifier_from_jwk(jwk)?),
+        Some("RS384") => Box::new(RS384.verifier_from_jwk(jwk)?),
+        Some("RS512") => Box::new(RS512.verifier_from_jwk(jwk)?),
+        Some("PS256") => Box::new(PS256.verifier_from_jwk(jwk)?),
+        Some("PS384") => Box::new(PS384.verifier_from_jwk(jwk)?),
+        Some("PS512") => Box::new(PS512.verifier_from_jwk(jwk)?),
+        Some("ES256") => Box::new(ES256.verifier_from_jwk(jwk)?),
+        Some("ES384") => Box::new(ES384.verifier_from_jwk(jwk)?),
+        Some("ES512") => Box::new(ES512.verifier_from_jwk(jwk)?),
+        Some("EdDSA") => Box::new(EdDSA.verifier_from_jwk(jwk)?),
+        Some(other) => bail!("Unsupported signing algorithm: {}", other),
+        None => bail!("Key does not declare the signing algorithm"),
+    };
+    Ok(verifier)
+}
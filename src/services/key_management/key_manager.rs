@@ -0,0 +1,54 @@
+use crate::services::key_management::key_set::KeySet;
+use crate::services::key_management::key_set_loader::KeySetLoader;
+use log::{info, warn};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+#[cfg(test)]
+mod tests;
+
+/// [`KeyManager`] keeps the current [`KeySet`] and reloads it from the configured
+/// [`KeySetLoader`], so a rotation performed in the key source is picked up without a restart.
+pub struct KeyManager {
+    loader: Arc<dyn KeySetLoader>,
+    current: RwLock<Arc<KeySet>>,
+}
+
+impl KeyManager {
+    /// Loads the initial key set and creates the manager.
+    pub async fn start(loader: Arc<dyn KeySetLoader>) -> anyhow::Result<Arc<Self>> {
+        let key_set = loader.load().await?;
+        Ok(Arc::new(KeyManager {
+            loader,
+            current: RwLock::new(Arc::new(key_set)),
+        }))
+    }
+
+    /// Returns a snapshot of the current key set.
+    pub fn current(&self) -> Arc<KeySet> {
+        self.current.read().expect("Key set lock is poisoned").clone()
+    }
+
+    /// Reloads the key set from the loader. The current key set is kept if loading fails.
+    pub async fn reload(&self) -> anyhow::Result<()> {
+        let key_set = self.loader.load().await?;
+        info!("Loaded key set with active key: {:?}", key_set.active().kid());
+        *self.current.write().expect("Key set lock is poisoned") = Arc::new(key_set);
+        Ok(())
+    }
+
+    /// Spawns a background task that reloads the key set with the given interval.
+    pub fn start_refresh(self: &Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let manager = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                if let Err(error) = manager.reload().await {
+                    warn!("Failed to reload the key set, keeping the current one: {:?}", error);
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,88 @@
+use crate::services::key_management::key_manager::KeyManager;
+use crate::services::key_management::key_set::KeySet;
+use crate::services::key_management::key_set_loader::KeySetLoader;
+use crate::services::key_management::signing_key::KeyStatus;
+use crate::testing::signing_keys::make_signing_key;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use pretty_assertions::assert_eq;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_reload_rotated_key_set() {
+    // Arrange
+    let manager = KeyManager::start(RotatingKeySetLoader::new(&[Some("first"), Some("second")]))
+        .await
+        .unwrap();
+
+    // Act
+    let result = manager.reload().await;
+
+    // Assert
+    assert!(result.is_ok(), "{:?}", result.err());
+    assert_eq!(manager.current().active().kid(), "second");
+}
+
+#[tokio::test]
+async fn test_keep_current_key_set_when_reload_fails() {
+    // Arrange
+    let manager = KeyManager::start(RotatingKeySetLoader::new(&[Some("first"), None]))
+        .await
+        .unwrap();
+
+    // Act
+    let result = manager.reload().await;
+
+    // Assert
+    assert!(result.is_err());
+    assert_eq!(manager.current().active().kid(), "first");
+}
+
+#[tokio::test]
+async fn test_refresh_in_background() {
+    // Arrange
+    let manager = KeyManager::start(RotatingKeySetLoader::new(&[Some("first"), None, Some("second")]))
+        .await
+        .unwrap();
+
+    // Act
+    let refresh = manager.start_refresh(Duration::from_millis(10));
+    for _ in 0..100 {
+        if manager.current().active().kid() == "second" {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    refresh.abort();
+
+    // Assert
+    assert_eq!(manager.current().active().kid(), "second");
+}
+
+/// Loads the active keys in order, failing on `None` and repeating the last key set once exhausted.
+struct RotatingKeySetLoader {
+    kids: Mutex<VecDeque<Option<&'static str>>>,
+}
+
+impl RotatingKeySetLoader {
+    fn new(kids: &[Option<&'static str>]) -> Arc<Self> {
+        Arc::new(Self {
+            kids: Mutex::new(kids.iter().copied().collect()),
+        })
+    }
+}
+
+#[async_trait]
+impl KeySetLoader for RotatingKeySetLoader {
+    async fn load(&self) -> anyhow::Result<KeySet> {
+        let mut kids = self.kids.lock().unwrap();
+        let kid = match kids.len() {
+            1 => kids[0],
+            _ => kids.pop_front().flatten(),
+        };
+        let kid = kid.ok_or(anyhow!("key source is unavailable"))?;
+        KeySet::new(vec![make_signing_key(kid, KeyStatus::Active)])
+    }
+}
@@ -0,0 +1,139 @@
+#[cfg(test)]
+mod tests;
+
+use crate::services::key_management::key_set_document::KeySetDocument;
+use crate::services::key_management::signing_key::{KeyStatus, SigningKey};
+use anyhow::{Result, anyhow, bail};
+use josekit::JoseError;
+use josekit::jwk::JwkSet;
+use josekit::jws::JwsHeader;
+use josekit::jwt;
+use josekit::jwt::JwtPayload;
+use std::collections::HashSet;
+
+/// [`KeySet`] holds the signing keys of the service. A valid key set contains exactly one
+/// active key, at most one next key and any number of retired keys, all with unique `kid`s.
+///
+/// Tokens are always signed with the active key, while signatures made with any non-retired
+/// key are accepted.
+#[derive(Debug, Clone)]
+pub struct KeySet {
+    keys: Vec<SigningKey>,
+}
+
+impl KeySet {
+    /// Creates a key set and validates its invariants.
+    pub fn new(keys: Vec<SigningKey>) -> Result<Self> {
+        let mut kids = HashSet::new();
+        for key in &keys {
+            if !kids.insert(key.kid()) {
+                bail!("Duplicate key id in key set: {}", key.kid());
+            }
+        }
+
+        let active = keys.iter().filter(|k| k.status() == KeyStatus::Active).count();
+        if active != 1 {
+            bail!("Key set must contain exactly one active key, found {}", active);
+        }
+
+        let next = keys.iter().filter(|k| k.status() == KeyStatus::Next).count();
+        if next > 1 {
+            bail!("Key set must contain at most one next key, found {}", next);
+        }
+
+        Ok(KeySet { keys })
+    }
+
+    /// Returns the key used to sign newly issued tokens.
+    pub fn active(&self) -> &SigningKey {
+        self.keys
+            .iter()
+            .find(|k| k.status() == KeyStatus::Active)
+            .expect("Key set invariant: exactly one active key")
+    }
+
+    /// Returns the key that becomes active on the next rotation, if any.
+    pub fn next(&self) -> Option<&SigningKey> {
+        self.keys.iter().find(|k| k.status() == KeyStatus::Next)
+    }
+
+    /// Returns the key with the given `kid` regardless of its status.
+    pub fn find(&self, kid: &str) -> Option<&SigningKey> {
+        self.keys.iter().find(|k| k.kid() == kid)
+    }
+
+    /// Returns the keys accepted for signature validation, i.e. all non-retired keys.
+    pub fn validation_keys(&self) -> impl Iterator<Item = &SigningKey> {
+        self.keys.iter().filter(|k| k.status() != KeyStatus::Retired)
+    }
+
+    /// Returns the public JWK set that should be published at `/.well-known/jwks.json`.
+    pub fn to_jwk_set(&self) -> JwkSet {
+        let mut jwk_set = JwkSet::new();
+        for key in self.validation_keys() {
+            jwk_set.push_key(key.public_jwk().clone());
+        }
+        jwk_set
+    }
+
+    /// Signs the payload with the active key. The `kid` of the active key is written to the
+    /// JWS header so verifiers can select the matching public key.
+    pub fn sign(&self, payload: &JwtPayload) -> Result<String> {
+        let active = self.active();
+        let mut header = JwsHeader::new();
+        header.set_token_type("JWT");
+        header.set_key_id(active.kid());
+        Ok(jwt::encode_with_signer(payload, &header, active.signer())?)
+    }
+
+    /// Verifies the signature of the token with the key referenced by the `kid` header.
+    /// Tokens signed with retired or unknown keys are rejected. This method does not
+    /// validate the token claims.
+    pub fn verify(&self, token: &str) -> Result<(JwtPayload, JwsHeader)> {
+        let result = jwt::decode_with_verifier_selector(token, |header| {
+            let kid = header.key_id().ok_or(JoseError::InvalidJwsFormat(anyhow!(
+                "Token header does not contain a key id"
+            )))?;
+            let key = self
+                .find(kid)
+                .ok_or(JoseError::InvalidJwsFormat(anyhow!("Unknown key id: {}", kid)))?;
+            if key.status() == KeyStatus::Retired {
+                return Err(JoseError::InvalidJwsFormat(anyhow!("Key {} is retired", kid)));
+            }
+            Ok(Some(key.verifier()))
+        })?;
+        Ok(result)
+    }
+
+    /// Promotes the next key to active and retires the currently active key.
+    ///
+    /// Returns an error if the key set does not contain a next key.
+    pub fn rotate(self) -> Result<KeySet> {
+        if self.next().is_none() {
+            bail!("Cannot rotate the key set without a next key");
+        }
+        let keys = self
+            .keys
+            .into_iter()
+            .map(|key| match key.status() {
+                KeyStatus::Active => key.with_status(KeyStatus::Retired),
+                KeyStatus::Next => key.with_status(KeyStatus::Active),
+                KeyStatus::Retired => key,
+            })
+            .collect();
+        KeySet::new(keys)
+    }
+}
+
+impl TryFrom<KeySetDocument> for KeySet {
+    type Error = anyhow::Error;
+
+    fn try_from(document: KeySetDocument) -> Result<Self, Self::Error> {
+        let keys = document
+            .keys
+            .into_iter()
+            .map(|k| k.try_into())
+            .collect::<Result<Vec<SigningKey>>>()?;
+        KeySet::new(keys)
+    }
+}
@@ -0,0 +1,198 @@
+use crate::services::key_management::key_set::KeySet;
+use crate::services::key_management::key_set_document::{KeyDocument, KeySetDocument};
+use crate::services::key_management::key_set_loader::{FileKeySetLoader, KeySetLoader, KubernetesSecretKeySetLoader};
+use crate::services::key_management::signing_key::KeyStatus;
+use crate::testing::signing_keys::{make_jwk, make_signing_key};
+use crate::testing::temp_namespace_context::TempNamespaceContext;
+use josekit::jwt::JwtPayload;
+use k8s_openapi::api::core::v1::Secret;
+use kube::api::PostParams;
+use kube::{Api, Client};
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use serde_json::json;
+use test_context::test_context;
+
+#[test]
+fn test_sign_uses_active_key() {
+    // Arrange
+    let key_set = make_key_set();
+
+    // Act
+    let token = key_set.sign(&make_payload()).expect("token should be signed");
+    let (payload, header) = key_set.verify(&token).expect("token should be valid");
+
+    // Assert
+    assert_eq!(header.key_id(), Some("active"));
+    assert_eq!(payload.subject(), Some("alice"));
+}
+
+#[test]
+fn test_next_key_is_accepted_for_validation() {
+    // Arrange
+    let key_set = make_key_set();
+    let rotated = key_set.clone().rotate().expect("key set should rotate");
+
+    // Act
+    let token = rotated.sign(&make_payload()).expect("token should be signed");
+    let result = key_set.verify(&token);
+
+    // Assert
+    assert!(result.is_ok(), "{:?}", result.err());
+}
+
+#[test]
+fn test_retired_key_is_rejected() {
+    // Arrange
+    let key_set = make_key_set();
+    let token = key_set.sign(&make_payload()).expect("token should be signed");
+    let rotated = key_set.rotate().expect("key set should rotate");
+
+    // Act
+    let result = rotated.verify(&token);
+
+    // Assert
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_unknown_key_is_rejected() {
+    // Arrange
+    let other = KeySet::new(vec![make_signing_key("other", KeyStatus::Active)]).unwrap();
+    let token = other.sign(&make_payload()).expect("token should be signed");
+
+    // Act
+    let result = make_key_set().verify(&token);
+
+    // Assert
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_jwk_set_publishes_public_non_retired_keys() {
+    // Arrange
+    let key_set = KeySet::new(vec![
+        make_signing_key("active", KeyStatus::Active),
+        make_signing_key("next", KeyStatus::Next),
+        make_signing_key("retired", KeyStatus::Retired),
+    ])
+    .unwrap();
+
+    // Act
+    let jwk_set = key_set.to_jwk_set();
+
+    // Assert
+    let kids: Vec<&str> = jwk_set.keys().iter().filter_map(|k| k.key_id()).collect();
+    assert_eq!(kids, vec!["active", "next"]);
+    assert!(jwk_set.keys().iter().all(|k| k.parameter("d").is_none()));
+    assert!(jwk_set.keys().iter().all(|k| k.key_use() == Some("sig")));
+}
+
+#[test]
+fn test_rotation_without_next_key_fails() {
+    // Arrange
+    let key_set = KeySet::new(vec![make_signing_key("active", KeyStatus::Active)]).unwrap();
+
+    // Act
+    let result = key_set.rotate();
+
+    // Assert
+    assert!(result.is_err());
+}
+
+#[rstest]
+#[case(vec![("a", KeyStatus::Next)], "exactly one active key")]
+#[case(vec![("a", KeyStatus::Active), ("b", KeyStatus::Active)], "exactly one active key")]
+#[case(vec![("a", KeyStatus::Active), ("b", KeyStatus::Next), ("c", KeyStatus::Next)], "at most one next key")]
+#[case(vec![("a", KeyStatus::Active), ("a", KeyStatus::Retired)], "Duplicate key id")]
+fn test_invalid_key_sets(#[case] keys: Vec<(&str, KeyStatus)>, #[case] message: &str) {
+    // Arrange
+    let keys = keys
+        .into_iter()
+        .map(|(kid, status)| make_signing_key(kid, status))
+        .collect();
+
+    // Act
+    let result = KeySet::new(keys);
+
+    // Assert
+    let error = result.expect_err("key set should be invalid");
+    assert!(error.to_string().contains(message), "{}", error);
+}
+
+#[tokio::test]
+async fn test_file_loader() {
+    // Arrange
+    let document = KeySetDocument {
+        keys: vec![
+            make_document("active", KeyStatus::Active),
+            make_document("next", KeyStatus::Next),
+        ],
+    };
+    let path = std::env::temp_dir().join(format!("{}.json", uuid::Uuid::new_v4()));
+    std::fs::write(&path, serde_json::to_vec(&document).unwrap()).unwrap();
+
+    // Act
+    let key_set = FileKeySetLoader::new(&path).load().await;
+    std::fs::remove_file(&path).unwrap();
+
+    // Assert
+    let key_set = key_set.expect("key set should be loaded");
+    assert_eq!(key_set.active().kid(), "active");
+    assert_eq!(key_set.next().map(|k| k.kid()), Some("next"));
+}
+
+#[test_context(TempNamespaceContext)]
+#[tokio::test]
+async fn test_kubernetes_secret_loader(ctx: &mut TempNamespaceContext) {
+    // Arrange
+    let document = KeySetDocument {
+        keys: vec![make_document("active", KeyStatus::Active)],
+    };
+    let secret: Secret = serde_json::from_value(json!({
+        "metadata": { "name": "signing-keys" },
+        "stringData": { "keys.json": serde_json::to_string(&document).unwrap() },
+    }))
+    .unwrap();
+    let client = Client::try_from(ctx.config.clone()).unwrap();
+    Api::<Secret>::namespaced(client, &ctx.namespace)
+        .create(&PostParams::default(), &secret)
+        .await
+        .expect("Failed to create secret");
+    let loader = KubernetesSecretKeySetLoader::new(
+        ctx.config.clone(),
+        &ctx.namespace,
+        "signing-keys".to_string(),
+        "keys.json".to_string(),
+    )
+    .unwrap();
+
+    // Act
+    let key_set = loader.load().await;
+
+    // Assert
+    let key_set = key_set.expect("key set should be loaded");
+    assert_eq!(key_set.active().kid(), "active");
+}
+
+fn make_key_set() -> KeySet {
+    KeySet::new(vec![
+        make_signing_key("active", KeyStatus::Active),
+        make_signing_key("next", KeyStatus::Next),
+    ])
+    .unwrap()
+}
+
+fn make_document(kid: &str, status: KeyStatus) -> KeyDocument {
+    KeyDocument {
+        kid: kid.to_string(),
+        status,
+        jwk: make_jwk().into(),
+    }
+}
+
+fn make_payload() -> JwtPayload {
+    let mut payload = JwtPayload::new();
+    payload.set_subject("alice");
+    payload
+}
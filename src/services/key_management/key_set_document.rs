@@ -0,0 +1,44 @@
+use crate::services::key_management::signing_key::{KeyStatus, SigningKey};
+use josekit::jwk::Jwk;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// The serialized form of a key set as stored in a file or a Kubernetes Secret:
+///
+/// ```json
+/// {
+///   "keys": [
+///     { "kid": "2025-06", "status": "active", "jwk": { "kty": "EC", "alg": "ES256", ... } },
+///     { "kid": "2025-07", "status": "next", "jwk": { ... } }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeySetDocument {
+    pub keys: Vec<KeyDocument>,
+}
+
+/// A single private key entry of the [`KeySetDocument`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyDocument {
+    pub kid: String,
+    pub status: KeyStatus,
+    pub jwk: Map<String, Value>,
+}
+
+impl TryInto<SigningKey> for KeyDocument {
+    type Error = anyhow::Error;
+
+    fn try_into(self) -> Result<SigningKey, Self::Error> {
+        let jwk = Jwk::from_map(self.jwk)?;
+        SigningKey::new(self.kid, self.status, jwk)
+    }
+}
+
+impl TryFrom<&[u8]> for KeySetDocument {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Ok(serde_json::from_slice(value)?)
+    }
+}
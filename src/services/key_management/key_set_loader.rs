@@ -0,0 +1,69 @@
+use crate::services::key_management::key_set::KeySet;
+use crate::services::key_management::key_set_document::KeySetDocument;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use k8s_openapi::api::core::v1::Secret;
+use kube::{Api, Client};
+use log::info;
+use std::path::PathBuf;
+
+/// Loads the [`KeySet`] from its source of truth. The loader is invoked on startup and on every
+/// key set refresh, so it should always read the most recent state of the source.
+#[async_trait]
+pub trait KeySetLoader: Send + Sync {
+    async fn load(&self) -> anyhow::Result<KeySet>;
+}
+
+/// Loads the key set from a JSON file containing a [`KeySetDocument`].
+pub struct FileKeySetLoader {
+    path: PathBuf,
+}
+
+impl FileKeySetLoader {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        FileKeySetLoader { path: path.into() }
+    }
+}
+
+#[async_trait]
+impl KeySetLoader for FileKeySetLoader {
+    async fn load(&self) -> anyhow::Result<KeySet> {
+        info!("Loading signing keys from file: {:?}", self.path);
+        let content = tokio::fs::read(&self.path).await?;
+        KeySetDocument::try_from(content.as_slice())?.try_into()
+    }
+}
+
+/// Loads the key set from a data entry of a Kubernetes Secret containing a [`KeySetDocument`].
+pub struct KubernetesSecretKeySetLoader {
+    api: Api<Secret>,
+    name: String,
+    key: String,
+}
+
+impl KubernetesSecretKeySetLoader {
+    pub fn new(kubeconfig: kube::Config, namespace: &str, name: String, key: String) -> anyhow::Result<Self> {
+        let client = Client::try_from(kubeconfig)?;
+        Ok(KubernetesSecretKeySetLoader {
+            api: Api::namespaced(client, namespace),
+            name,
+            key,
+        })
+    }
+}
+
+#[async_trait]
+impl KeySetLoader for KubernetesSecretKeySetLoader {
+    // COVERAGE: disabled since this should be tested in integration tests only
+    #[cfg_attr(coverage, coverage(off))]
+    async fn load(&self) -> anyhow::Result<KeySet> {
+        info!("Loading signing keys from secret: {:?}/{:?}", self.name, self.key);
+        let secret = self.api.get(&self.name).await?;
+        let data = secret.data.and_then(|mut data| data.remove(&self.key)).ok_or(anyhow!(
+            "Secret {} does not contain the key {}",
+            self.name,
+            self.key
+        ))?;
+        KeySetDocument::try_from(data.0.as_slice())?.try_into()
+    }
+}
@@ -0,0 +1,21 @@
+use duration_string::DurationString;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct KeyManagementSettings {
+    pub source: KeySetSource,
+    pub refresh_interval: DurationString,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeySetSource {
+    File {
+        path: String,
+    },
+    KubernetesSecret {
+        namespace: String,
+        name: String,
+        key: String,
+    },
+}
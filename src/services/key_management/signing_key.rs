@@ -0,0 +1,87 @@
+use crate::services::key_management::jws_algorithms::{signer_from_jwk, verifier_from_jwk};
+use anyhow::{Result, bail};
+use josekit::jwk::Jwk;
+use josekit::jws::{JwsSigner, JwsVerifier};
+use serde::{Deserialize, Serialize};
+
+/// The lifecycle stage of a signing key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KeyStatus {
+    /// The key used to sign newly issued tokens. Exactly one key in a key set is active.
+    Active,
+
+    /// The key that becomes active on the next rotation. It is published and accepted for
+    /// validation in advance, so verifiers can cache it before any token is signed with it.
+    Next,
+
+    /// The key was active before the last rotation. It is neither published nor accepted
+    /// for validation.
+    Retired,
+}
+
+/// [`SigningKey`] is a private key identified by its `kid` together with its lifecycle status.
+#[derive(Debug, Clone)]
+pub struct SigningKey {
+    kid: String,
+    status: KeyStatus,
+    public_jwk: Jwk,
+    signer: Box<dyn JwsSigner>,
+    verifier: Box<dyn JwsVerifier>,
+}
+
+impl SigningKey {
+    /// Creates a signing key from a private JWK. The `kid` overrides the key id stored in the
+    /// JWK, and the JWK must declare the signing algorithm in its `alg` parameter.
+    pub fn new(kid: impl Into<String>, status: KeyStatus, mut jwk: Jwk) -> Result<Self> {
+        let kid = kid.into();
+        if kid.is_empty() {
+            bail!("Signing key id must not be empty");
+        }
+        jwk.set_key_id(kid.clone());
+
+        let signer = signer_from_jwk(&jwk)?;
+        let mut public_jwk = jwk.to_public_key()?;
+        public_jwk.set_key_id(kid.clone());
+        public_jwk.set_key_use("sig");
+        if let Some(algorithm) = jwk.algorithm() {
+            public_jwk.set_algorithm(algorithm);
+        }
+        let verifier = verifier_from_jwk(&public_jwk, None)?;
+
+        Ok(SigningKey {
+            kid,
+            status,
+            public_jwk,
+            signer,
+            verifier,
+        })
+    }
+
+    pub fn kid(&self) -> &str {
+        &self.kid
+    }
+
+    pub fn status(&self) -> KeyStatus {
+        self.status
+    }
+
+    /// Returns the public part of the key in the form published in the JWKS document.
+    pub fn public_jwk(&self) -> &Jwk {
+        &self.public_jwk
+    }
+
+    pub fn signer(&self) -> &dyn JwsSigner {
+        self.signer.as_ref()
+    }
+
+    pub fn verifier(&self) -> &dyn JwsVerifier {
+        self.verifier.as_ref()
+    }
+
+    /// Returns a copy of the key with the given status.
+    pub fn with_status(mut self, status: KeyStatus) -> Self {
+        self.status = status;
+        self
+    }
+}
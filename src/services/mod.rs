@@ -1,5 +1,7 @@
 pub mod audit;
 pub mod backends;
 pub mod base;
+pub mod identity;
+pub mod key_management;
 pub mod observability;
 pub mod service_provider;
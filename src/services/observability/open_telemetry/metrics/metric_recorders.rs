@@ -1,3 +1,7 @@
+pub mod audit_events_dropped;
+pub mod audit_events_skipped;
+pub mod audit_pipeline_failure;
+pub mod audit_queue_depth;
 pub mod token_accepted;
 pub mod token_attempt;
 pub mod token_forbidden;
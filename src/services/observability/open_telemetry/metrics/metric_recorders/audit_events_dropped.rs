@@ -0,0 +1,33 @@
+use opentelemetry::metrics::Counter;
+use opentelemetry::{KeyValue, global};
+
+#[derive(Clone)]
+pub struct AuditEventsDropped(Counter<u64>, String);
+
+impl AuditEventsDropped {
+    pub(crate) fn new(app_name: &'static str, instance_id: String) -> AuditEventsDropped {
+        let meter = global::meter(app_name);
+        let counter = meter
+            .u64_counter(format!("{}.{}", app_name, "audit_events_dropped"))
+            .with_description("Count of audit events dropped by the audit dispatcher")
+            .with_unit("events")
+            .build();
+        Self(counter, instance_id)
+    }
+}
+
+pub trait AuditEventsDroppedMetric: Send + Sync {
+    fn increment(&self, reason: &str, count: u64);
+}
+
+impl AuditEventsDroppedMetric for AuditEventsDropped {
+    fn increment(&self, reason: &str, count: u64) {
+        self.0.add(
+            count,
+            &[
+                KeyValue::new("reason", reason.to_string()),
+                KeyValue::new("instance_id", self.1.clone()),
+            ],
+        );
+    }
+}
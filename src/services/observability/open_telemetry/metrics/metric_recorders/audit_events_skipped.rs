@@ -0,0 +1,33 @@
+use opentelemetry::metrics::Counter;
+use opentelemetry::{KeyValue, global};
+
+#[derive(Clone)]
+pub struct AuditEventsSkipped(Counter<u64>, String);
+
+impl AuditEventsSkipped {
+    pub(crate) fn new(app_name: &'static str, instance_id: String) -> AuditEventsSkipped {
+        let meter = global::meter(app_name);
+        let counter = meter
+            .u64_counter(format!("{}.{}", app_name, "audit_events_skipped"))
+            .with_description("Count of audit events skipped by the audit sampling rules")
+            .with_unit("events")
+            .build();
+        Self(counter, instance_id)
+    }
+}
+
+pub trait AuditEventsSkippedMetric: Send + Sync {
+    fn increment(&self, reason: &str);
+}
+
+impl AuditEventsSkippedMetric for AuditEventsSkipped {
+    fn increment(&self, reason: &str) {
+        self.0.add(
+            1,
+            &[
+                KeyValue::new("reason", reason.to_string()),
+                KeyValue::new("instance_id", self.1.clone()),
+            ],
+        );
+    }
+}
@@ -0,0 +1,33 @@
+use opentelemetry::metrics::Counter;
+use opentelemetry::{KeyValue, global};
+
+#[derive(Clone)]
+pub struct AuditPipelineFailure(Counter<u64>, String);
+
+impl AuditPipelineFailure {
+    pub(crate) fn new(app_name: &'static str, instance_id: String) -> AuditPipelineFailure {
+        let meter = global::meter(app_name);
+        let counter = meter
+            .u64_counter(format!("{}.{}", app_name, "audit_pipeline_failure"))
+            .with_description("Count of requests that violated the audit middleware invariants")
+            .with_unit("requests")
+            .build();
+        Self(counter, instance_id)
+    }
+}
+
+pub trait AuditPipelineFailureMetric {
+    fn increment(&self, reason_code: &str);
+}
+
+impl AuditPipelineFailureMetric for AuditPipelineFailure {
+    fn increment(&self, reason_code: &str) {
+        self.0.add(
+            1,
+            &[
+                KeyValue::new("reason_code", reason_code.to_string()),
+                KeyValue::new("instance_id", self.1.clone()),
+            ],
+        );
+    }
+}
@@ -0,0 +1,27 @@
+use opentelemetry::metrics::Gauge;
+use opentelemetry::{KeyValue, global};
+
+#[derive(Clone)]
+pub struct AuditQueueDepth(Gauge<u64>, String);
+
+impl AuditQueueDepth {
+    pub(crate) fn new(app_name: &'static str, instance_id: String) -> AuditQueueDepth {
+        let meter = global::meter(app_name);
+        let gauge = meter
+            .u64_gauge(format!("{}.{}", app_name, "audit_queue_depth"))
+            .with_description("The number of audit events waiting in the queue of the audit dispatcher")
+            .with_unit("events")
+            .build();
+        Self(gauge, instance_id)
+    }
+}
+
+pub trait AuditQueueDepthMetric: Send + Sync {
+    fn record(&self, depth: u64);
+}
+
+impl AuditQueueDepthMetric for AuditQueueDepth {
+    fn record(&self, depth: u64) {
+        self.0.record(depth, &[KeyValue::new("instance_id", self.1.clone())]);
+    }
+}
@@ -1,3 +1,7 @@
+use crate::services::observability::open_telemetry::metrics::metric_recorders::audit_events_dropped::AuditEventsDropped;
+use crate::services::observability::open_telemetry::metrics::metric_recorders::audit_events_skipped::AuditEventsSkipped;
+use crate::services::observability::open_telemetry::metrics::metric_recorders::audit_pipeline_failure::AuditPipelineFailure;
+use crate::services::observability::open_telemetry::metrics::metric_recorders::audit_queue_depth::AuditQueueDepth;
 use crate::services::observability::open_telemetry::metrics::metric_recorders::token_accepted::TokenAccepted;
 use crate::services::observability::open_telemetry::metrics::metric_recorders::token_attempt::TokenAttempt;
 use crate::services::observability::open_telemetry::metrics::metric_recorders::token_forbidden::TokenForbidden;
@@ -15,6 +19,10 @@ pub struct MetricsProvider {
     token_lifetime: TokenLifetime,
     token_accepted: TokenAccepted,
     token_rejected: TokenRejected,
+    audit_pipeline_failure: AuditPipelineFailure,
+    audit_queue_depth: AuditQueueDepth,
+    audit_events_dropped: AuditEventsDropped,
+    audit_events_skipped: AuditEventsSkipped,
 }
 
 impl MetricsProvider {
@@ -28,7 +36,11 @@ impl MetricsProvider {
             token_attempt: TokenAttempt::new(root_metrics_namespace, instance_id.clone()),
             token_lifetime: TokenLifetime::new(root_metrics_namespace, instance_id.clone()),
             token_accepted: TokenAccepted::new(root_metrics_namespace, instance_id.clone()),
-            token_rejected: TokenRejected::new(root_metrics_namespace, instance_id),
+            token_rejected: TokenRejected::new(root_metrics_namespace, instance_id.clone()),
+            audit_pipeline_failure: AuditPipelineFailure::new(root_metrics_namespace, instance_id.clone()),
+            audit_queue_depth: AuditQueueDepth::new(root_metrics_namespace, instance_id.clone()),
+            audit_events_dropped: AuditEventsDropped::new(root_metrics_namespace, instance_id.clone()),
+            audit_events_skipped: AuditEventsSkipped::new(root_metrics_namespace, instance_id),
         }
     }
 }
@@ -88,3 +100,35 @@ impl ServiceProvider<TokenRejected> for MetricsProvider {
         self.token_rejected.clone()
     }
 }
+
+// COVERAGE: Disable since the function is trivial
+#[cfg_attr(coverage, coverage(off))]
+impl ServiceProvider<AuditPipelineFailure> for MetricsProvider {
+    fn get(&self) -> AuditPipelineFailure {
+        self.audit_pipeline_failure.clone()
+    }
+}
+
+// COVERAGE: Disable since the function is trivial
+#[cfg_attr(coverage, coverage(off))]
+impl ServiceProvider<AuditQueueDepth> for MetricsProvider {
+    fn get(&self) -> AuditQueueDepth {
+        self.audit_queue_depth.clone()
+    }
+}
+
+// COVERAGE: Disable since the function is trivial
+#[cfg_attr(coverage, coverage(off))]
+impl ServiceProvider<AuditEventsDropped> for MetricsProvider {
+    fn get(&self) -> AuditEventsDropped {
+        self.audit_events_dropped.clone()
+    }
+}
+
+// COVERAGE: Disable since the function is trivial
+#[cfg_attr(coverage, coverage(off))]
+impl ServiceProvider<AuditEventsSkipped> for MetricsProvider {
+    fn get(&self) -> AuditEventsSkipped {
+        self.audit_events_skipped.clone()
+    }
+}
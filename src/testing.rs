@@ -10,7 +10,13 @@ use uuid::Uuid;
 //#[cfg(feature = "testing")]
 pub mod api_client_context;
 pub mod api_extensions;
+pub mod audit_events;
+#[cfg(test)]
+pub mod audit_metrics;
+pub mod signing_keys;
 pub mod spin_lock_kubernetes_resource_manager_context;
+pub mod stub_jwks_server;
+pub mod stub_webhook_receiver;
 pub mod temp_namespace_context;
 
 /// COVERAGE: disabled since this is a testing helper
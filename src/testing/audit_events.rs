@@ -0,0 +1,54 @@
+use crate::services::audit::audit_record::AuditRecord;
+use crate::services::audit::events::authorization_audit_event::{AuthorizationAuditEvent, Reason};
+use cedar_policy::Decision;
+use std::collections::HashSet;
+
+/// Builds the authorization audit events used in the audit tests. By default, `alice` is allowed
+/// to `read` the `documents/1` resource without any matching policy.
+pub struct AuthorizationAuditEventBuilder {
+    event: AuthorizationAuditEvent,
+}
+
+impl Default for AuthorizationAuditEventBuilder {
+    fn default() -> Self {
+        AuthorizationAuditEventBuilder {
+            event: AuthorizationAuditEvent {
+                action: "read".to_string(),
+                actor: "alice".to_string(),
+                resource: "documents/1".to_string(),
+                decision: Decision::Allow,
+                reason: Reason {
+                    policies: HashSet::new(),
+                    errors: HashSet::new(),
+                },
+            },
+        }
+    }
+}
+
+impl AuthorizationAuditEventBuilder {
+    pub fn with_actor(mut self, actor: &str) -> Self {
+        self.event.actor = actor.to_string();
+        self
+    }
+
+    pub fn with_decision(mut self, decision: Decision) -> Self {
+        self.event.decision = decision;
+        self
+    }
+
+    /// Adds a policy to the reason of the decision.
+    pub fn with_policy(mut self, policy: &str) -> Self {
+        self.event.reason.policies.insert(policy.to_string());
+        self
+    }
+
+    pub fn build(self) -> AuthorizationAuditEvent {
+        self.event
+    }
+
+    /// Builds the event wrapped in an [`AuditRecord`].
+    pub fn record(self) -> AuditRecord {
+        AuditRecord::from(self.event)
+    }
+}
@@ -0,0 +1,26 @@
+use crate::services::observability::open_telemetry::metrics::metric_recorders::audit_events_dropped::AuditEventsDroppedMetric;
+use crate::services::observability::open_telemetry::metrics::metric_recorders::audit_queue_depth::AuditQueueDepthMetric;
+use mockall::mock;
+
+mock! {
+    pub AuditQueueDepthMetric {}
+
+    impl AuditQueueDepthMetric for AuditQueueDepthMetric {
+        fn record(&self, depth: u64);
+    }
+}
+
+mock! {
+    pub AuditEventsDroppedMetric {}
+
+    impl AuditEventsDroppedMetric for AuditEventsDroppedMetric {
+        fn increment(&self, reason: &str, count: u64);
+    }
+}
+
+/// Creates the queue depth metric accepting any number of recordings.
+pub fn queue_depth_metric() -> MockAuditQueueDepthMetric {
+    let mut metric = MockAuditQueueDepthMetric::new();
+    metric.expect_record().returning(|_| ());
+    metric
+}
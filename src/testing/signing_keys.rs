@@ -0,0 +1,37 @@
+use crate::services::key_management::key_manager::KeyManager;
+use crate::services::key_management::key_set::KeySet;
+use crate::services::key_management::key_set_loader::KeySetLoader;
+use crate::services::key_management::signing_key::{KeyStatus, SigningKey};
+use async_trait::async_trait;
+use josekit::jwk::Jwk;
+use josekit::jwk::alg::ec::EcCurve;
+use std::sync::Arc;
+
+/// A [`KeySetLoader`] returning a fixed set of keys, used in place of the key stores in tests.
+pub struct StaticKeySetLoader(pub Vec<SigningKey>);
+
+#[async_trait]
+impl KeySetLoader for StaticKeySetLoader {
+    async fn load(&self) -> anyhow::Result<KeySet> {
+        KeySet::new(self.0.clone())
+    }
+}
+
+/// Generates an EC P-256 key for the ES256 algorithm.
+pub fn make_jwk() -> Jwk {
+    let mut jwk = Jwk::generate_ec_key(EcCurve::P256).expect("EC key should be generated");
+    jwk.set_algorithm("ES256");
+    jwk
+}
+
+/// Generates an ES256 signing key with the key id and the status.
+pub fn make_signing_key(kid: &str, status: KeyStatus) -> SigningKey {
+    SigningKey::new(kid, status, make_jwk()).expect("key should be valid")
+}
+
+/// Starts a [`KeyManager`] serving the keys.
+pub async fn make_key_manager(keys: Vec<SigningKey>) -> Arc<KeyManager> {
+    KeyManager::start(Arc::new(StaticKeySetLoader(keys)))
+        .await
+        .expect("key manager should start")
+}
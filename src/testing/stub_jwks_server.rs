@@ -0,0 +1,88 @@
+use actix_web::dev::ServerHandle;
+use actix_web::{App, HttpResponse, HttpServer, web};
+use josekit::jwk::{Jwk, JwkSet};
+use serde_json::{Map, Value};
+use std::net::TcpListener;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A local HTTP server publishing a JWKS document, used in place of an identity provider
+/// in tests.
+pub struct StubJwksServer {
+    jwks_uri: String,
+    state: web::Data<StubJwksState>,
+    handle: ServerHandle,
+}
+
+struct StubJwksState {
+    keys: RwLock<Vec<Jwk>>,
+    requests: AtomicUsize,
+}
+
+impl StubJwksServer {
+    /// Starts the server on a random local port, publishing the public part of the provided keys.
+    /// Must be called from within an actix runtime.
+    pub fn start(keys: Vec<Jwk>) -> anyhow::Result<Self> {
+        let state = web::Data::new(StubJwksState {
+            keys: RwLock::new(keys),
+            requests: AtomicUsize::new(0),
+        });
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let jwks_uri = format!("http://{}/jwks.json", listener.local_addr()?);
+
+        let app_state = state.clone();
+        let server = HttpServer::new(move || {
+            App::new()
+                .app_data(app_state.clone())
+                .route("/jwks.json", web::get().to(jwks))
+        })
+        .workers(1)
+        .listen(listener)?
+        .run();
+        let handle = server.handle();
+        actix_web::rt::spawn(server);
+
+        Ok(StubJwksServer {
+            jwks_uri,
+            state,
+            handle,
+        })
+    }
+
+    /// Returns the URI of the JWKS document.
+    pub fn jwks_uri(&self) -> &str {
+        &self.jwks_uri
+    }
+
+    /// Replaces the published keys, simulating a key rotation at the identity provider.
+    pub fn set_keys(&self, keys: Vec<Jwk>) {
+        *self.state.keys.write().expect("Stub JWKS lock is poisoned") = keys;
+    }
+
+    /// Returns the number of JWKS requests served so far.
+    pub fn request_count(&self) -> usize {
+        self.state.requests.load(Ordering::SeqCst)
+    }
+
+    /// Stops the server.
+    pub async fn stop(self) {
+        self.handle.stop(false).await;
+    }
+}
+
+async fn jwks(state: web::Data<StubJwksState>) -> HttpResponse {
+    state.requests.fetch_add(1, Ordering::SeqCst);
+    let mut jwk_set = JwkSet::new();
+    for key in state.keys.read().expect("Stub JWKS lock is poisoned").iter() {
+        let mut public_key = key.to_public_key().unwrap_or_else(|_| key.clone());
+        if let Some(kid) = key.key_id() {
+            public_key.set_key_id(kid);
+        }
+        if let Some(algorithm) = key.algorithm() {
+            public_key.set_algorithm(algorithm);
+        }
+        jwk_set.push_key(public_key);
+    }
+    let document: Map<String, Value> = jwk_set.into();
+    HttpResponse::Ok().json(document)
+}
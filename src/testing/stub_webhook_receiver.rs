@@ -0,0 +1,111 @@
+use actix_web::dev::ServerHandle;
+use actix_web::http::StatusCode;
+use actix_web::{App, HttpRequest, HttpResponse, HttpServer, web};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::net::TcpListener;
+use std::sync::Mutex;
+
+/// A local HTTP server receiving webhook requests, used in place of an audit collector in tests.
+pub struct StubWebhookReceiver {
+    url: String,
+    state: web::Data<StubWebhookState>,
+    handle: ServerHandle,
+}
+
+/// A request received by the [`StubWebhookReceiver`].
+#[derive(Debug, Clone)]
+pub struct ReceivedWebhook {
+    pub body: Vec<u8>,
+    pub headers: Vec<(String, String)>,
+}
+
+impl ReceivedWebhook {
+    /// Returns the value of the header, if present.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Parses the body as JSON.
+    pub fn json(&self) -> Value {
+        serde_json::from_slice(&self.body).unwrap_or(Value::Null)
+    }
+}
+
+struct StubWebhookState {
+    responses: Mutex<VecDeque<StatusCode>>,
+    received: Mutex<Vec<ReceivedWebhook>>,
+}
+
+impl StubWebhookReceiver {
+    /// Starts the server on a random local port. The requests are answered with the provided
+    /// statuses in order, and with `200 OK` once they are exhausted. Must be called from within an
+    /// actix runtime.
+    pub fn start(responses: Vec<StatusCode>) -> anyhow::Result<Self> {
+        let state = web::Data::new(StubWebhookState {
+            responses: Mutex::new(responses.into()),
+            received: Mutex::new(Vec::new()),
+        });
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}/audit", listener.local_addr()?);
+
+        let app_state = state.clone();
+        let server = HttpServer::new(move || {
+            App::new()
+                .app_data(app_state.clone())
+                .route("/audit", web::post().to(receive))
+        })
+        .workers(1)
+        .listen(listener)?
+        .run();
+        let handle = server.handle();
+        actix_web::rt::spawn(server);
+
+        Ok(StubWebhookReceiver { url, state, handle })
+    }
+
+    /// Returns the URL accepting the webhook requests.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Returns the requests received so far, including the rejected ones.
+    pub fn received(&self) -> Vec<ReceivedWebhook> {
+        self.state
+            .received
+            .lock()
+            .expect("Stub webhook lock is poisoned")
+            .clone()
+    }
+
+    /// Stops the server.
+    pub async fn stop(self) {
+        self.handle.stop(false).await;
+    }
+}
+
+async fn receive(state: web::Data<StubWebhookState>, request: HttpRequest, body: web::Bytes) -> HttpResponse {
+    let headers = request
+        .headers()
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_str().unwrap_or_default().to_string()))
+        .collect();
+    state
+        .received
+        .lock()
+        .expect("Stub webhook lock is poisoned")
+        .push(ReceivedWebhook {
+            body: body.to_vec(),
+            headers,
+        });
+    let status = state
+        .responses
+        .lock()
+        .expect("Stub webhook lock is poisoned")
+        .pop_front()
+        .unwrap_or(StatusCode::OK);
+    HttpResponse::build(status).finish()
+}